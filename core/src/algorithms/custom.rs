@@ -0,0 +1,411 @@
+// Custom Pattern Schemes - user-defined multi-pass overwrites
+//
+// Contractual schemes (BSI VSITR, Schneier, RCMP TSSIT OPS-II, ...) are
+// described in a TOML or JSON file and executed through the same resumable
+// OptimizedIO path as the built-in algorithms:
+//
+//   name = "RCMP TSSIT OPS-II"
+//
+//   [[passes]]
+//   type = "bytes"
+//   bytes = [0x00]
+//
+//   [[passes]]
+//   type = "complement"
+//
+//   [[passes]]
+//   type = "random"
+//   verify = true
+
+use anyhow::{Result, bail, Context};
+use crate::algorithms::verify::{ExpectedData, PassVerifier};
+use crate::crypto::keystream::Keystream;
+use crate::crypto::random_pipeline::RandomSource;
+use crate::ui::progress::ProgressBar;
+use crate::io::{Checkpointer, OptimizedIO, IOConfig, IOHandle};
+use crate::algorithms::OverwriteOptions;
+use crate::DriveType;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A named sequence of overwrite passes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PatternScheme {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub passes: Vec<PassSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PassSpec {
+    #[serde(flatten)]
+    pub pattern: PassPattern,
+    #[serde(default)]
+    pub verify: bool,  // Always read back this pass, whatever the verification policy
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PassPattern {
+    /// Fixed byte sequence repeated across the drive
+    Bytes { bytes: Vec<u8> },
+    /// Cryptographically secure random data
+    Random,
+    /// Bitwise complement of the previous fixed pattern
+    Complement,
+}
+
+impl PatternScheme {
+    /// Load a scheme from `.json`, or TOML for any other extension
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pattern scheme {}", path.display()))?;
+
+        let scheme = if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&text)
+        } else {
+            Self::from_toml(&text)
+        }
+        .with_context(|| format!("Invalid pattern scheme {}", path.display()))?;
+
+        Ok(scheme)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let scheme: Self = toml::from_str(text)?;
+        scheme.validate()?;
+        Ok(scheme)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let scheme: Self = serde_json::from_str(text)?;
+        scheme.validate()?;
+        Ok(scheme)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("Scheme name must not be empty");
+        }
+        if self.passes.is_empty() {
+            bail!("Scheme '{}' has no passes", self.name);
+        }
+
+        for index in 0..self.passes.len() {
+            match &self.passes[index].pattern {
+                PassPattern::Bytes { bytes } if bytes.is_empty() => {
+                    bail!("Pass {} has an empty byte pattern", index + 1);
+                }
+                // A random pass cannot be complemented without storing it
+                PassPattern::Complement if self.fixed_pattern(index).is_none() => {
+                    bail!("Pass {} complements a pass without a fixed pattern", index + 1);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The concrete bytes written by pass `index`, or None for random passes
+    pub fn fixed_pattern(&self, index: usize) -> Option<Vec<u8>> {
+        match &self.passes.get(index)?.pattern {
+            PassPattern::Bytes { bytes } => Some(bytes.clone()),
+            PassPattern::Random => None,
+            PassPattern::Complement => {
+                let previous = self.fixed_pattern(index.checked_sub(1)?)?;
+                Some(previous.iter().map(|b| !b).collect())
+            }
+        }
+    }
+
+    /// Short human-readable description of pass `index`
+    pub fn describe_pass(&self, index: usize) -> String {
+        match self.fixed_pattern(index) {
+            Some(bytes) => bytes.iter()
+                .map(|b| format!("0x{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => "random data".to_string(),
+        }
+    }
+}
+
+pub struct CustomWipe;
+
+impl CustomWipe {
+    /// Run every pass of `scheme`, resuming from `checkpoint` like the
+    /// built-in algorithms. Random passes use the keystream in `options` when
+    /// given. Passes marked `verify` are read back even if the verification
+    /// policy skips them.
+    pub fn wipe_drive(
+        device_path: &str,
+        size: u64,
+        drive_type: DriveType,
+        scheme: &PatternScheme,
+        options: &OverwriteOptions,
+        checkpoint: &mut Checkpointer,
+    ) -> Result<()> {
+        scheme.validate()?;
+        let total = scheme.passes.len();
+        println!("Starting custom scheme '{}' ({} passes) on {}", scheme.name, total, device_path);
+
+        // Progress from every pass is attributed to this wipe
+        let _events = options.enter(device_path);
+
+        // Configure I/O based on drive type
+        let io_config = match drive_type {
+            DriveType::NVMe => IOConfig::nvme_optimized(),
+            DriveType::SSD => IOConfig::sata_ssd_optimized(),
+            DriveType::HDD => IOConfig::hdd_optimized(),
+            _ => IOConfig::default(),
+        };
+
+        // Open device with optimized I/O
+        let mut io_handle = options.open(device_path, io_config)?;
+
+        for (pass, spec) in scheme.passes.iter().enumerate() {
+            if checkpoint.is_pass_complete(pass) {
+                continue;
+            }
+
+            println!("\n🔄 Pass {}/{}: Writing {}", pass + 1, total, scheme.describe_pass(pass));
+
+            let pattern = scheme.fixed_pattern(pass);
+            let start = checkpoint.resume_offset(pass);
+            let expected = match &pattern {
+                Some(pattern) => {
+                    Self::write_pattern(&mut io_handle, size, pattern, checkpoint, pass)?;
+                    ExpectedData::Pattern(pattern)
+                }
+                None => {
+                    Self::write_random(&mut io_handle, size, options.keystream, checkpoint, pass)?;
+                    ExpectedData::random(options.keystream, pass, start)
+                }
+            };
+
+            if spec.verify || options.verification.covers(pass, total) {
+                PassVerifier::verify_pass(device_path, size, pass, &expected, options, &checkpoint.bad_sectors())?;
+            }
+        }
+
+        // Final sync
+        io_handle.sync()?;
+
+        // Print performance report
+        OptimizedIO::print_performance_report(&io_handle, None);
+
+        checkpoint.finish();
+
+        println!("\n✅ Custom scheme '{}' completed successfully", scheme.name);
+        Ok(())
+    }
+
+    /// Write a repeating pattern, phased by absolute offset so a resumed pass
+    /// lines up with what was already written
+    fn write_pattern(
+        io_handle: &mut IOHandle,
+        size: u64,
+        pattern: &[u8],
+        checkpoint: &mut Checkpointer,
+        pass: usize,
+    ) -> Result<()> {
+        let mut bytes_written = checkpoint.resume_offset(pass);
+        let mut bar = ProgressBar::new(48);
+
+        OptimizedIO::sequential_write_resumable(io_handle, size, checkpoint, pass, |buffer| {
+            let buf = buffer.as_mut_slice();
+            let phase = (bytes_written % pattern.len() as u64) as usize;
+            for (i, byte) in buf.iter_mut().enumerate() {
+                *byte = pattern[(phase + i) % pattern.len()];
+            }
+
+            bytes_written += buf.len() as u64;
+
+            if bytes_written.is_multiple_of(50 * 1024 * 1024) || bytes_written >= size {
+                let progress = (bytes_written as f64 / size as f64) * 100.0;
+                bar.render(progress, Some(bytes_written), Some(size));
+            }
+
+            Ok(())
+        })?;
+
+        bar.render(100.0, Some(size), Some(size));
+        Ok(())
+    }
+
+    /// Write random data, from the keystream if there is one
+    fn write_random(
+        io_handle: &mut IOHandle,
+        size: u64,
+        keystream: Option<&Keystream>,
+        checkpoint: &mut Checkpointer,
+        pass: usize,
+    ) -> Result<()> {
+        let mut bytes_written = checkpoint.resume_random(pass)?;
+        let mut bar = ProgressBar::new(48);
+        let mut random = RandomSource::start(keystream, pass, bytes_written, io_handle.buffer_size())?;
+
+        OptimizedIO::sequential_write_resumable(io_handle, size, checkpoint, pass, |buffer| {
+            let buf = buffer.as_mut_slice();
+            random.fill(buf)?;
+
+            bytes_written += buf.len() as u64;
+
+            if bytes_written.is_multiple_of(50 * 1024 * 1024) || bytes_written >= size {
+                let progress = (bytes_written as f64 / size as f64) * 100.0;
+                bar.render(progress, Some(bytes_written), Some(size));
+            }
+
+            Ok(())
+        })?;
+
+        bar.render(100.0, Some(size), Some(size));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_drive;
+    use crate::{DriveInfo, VerifyCoverage};
+    use tempfile::{NamedTempFile, TempDir};
+
+    const OPS_II: &str = r#"
+        name = "RCMP TSSIT OPS-II"
+
+        [[passes]]
+        type = "bytes"
+        bytes = [0x00]
+
+        [[passes]]
+        type = "complement"
+
+        [[passes]]
+        type = "bytes"
+        bytes = [0x92, 0x49, 0x24]
+        verify = true
+
+        [[passes]]
+        type = "complement"
+        verify = true
+    "#;
+
+    #[test]
+    fn test_parse_toml_and_json() {
+        let scheme = PatternScheme::from_toml(OPS_II).unwrap();
+        assert_eq!(scheme.passes.len(), 4);
+        assert_eq!(scheme.fixed_pattern(1), Some(vec![0xFF]));
+        assert_eq!(scheme.fixed_pattern(3), Some(vec![0x6D, 0xB6, 0xDB]));
+        assert!(scheme.passes[2].verify);
+
+        let json = serde_json::to_string(&scheme).unwrap();
+        assert_eq!(PatternScheme::from_json(&json).unwrap(), scheme);
+    }
+
+    #[test]
+    fn test_rejects_invalid_schemes() {
+        assert!(PatternScheme::from_toml("name = \"empty\"\npasses = []").is_err());
+        assert!(PatternScheme::from_toml(
+            "name = \"x\"\n[[passes]]\ntype = \"random\"\n[[passes]]\ntype = \"complement\""
+        ).is_err());
+        assert!(PatternScheme::from_toml(
+            "name = \"x\"\n[[passes]]\ntype = \"bytes\"\nbytes = []"
+        ).is_err());
+        assert!(PatternScheme::from_toml(
+            "name = \"x\"\n[[passes]]\ntype = \"complement\""
+        ).is_err());
+    }
+
+    #[test]
+    fn test_bundled_schemes_parse() {
+        for text in [
+            include_str!("../../schemes/bsi-vsitr.toml"),
+            include_str!("../../schemes/schneier.toml"),
+            include_str!("../../schemes/rcmp-tssit-ops-ii.toml"),
+            include_str!("../../schemes/random-verify-zero.toml"),
+        ] {
+            PatternScheme::from_toml(text).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_custom_wipe_writes_final_pattern() {
+        let temp = NamedTempFile::new().unwrap();
+        let size = 256 * 1024u64;
+        std::fs::write(temp.path(), vec![0x11; size as usize]).unwrap();
+        let path = temp.path().to_str().unwrap();
+
+        let drive = DriveInfo { device_path: path.to_string(), size, ..test_drive("CUSTOM-TEST") };
+
+        let dir = TempDir::new().unwrap();
+        let scheme = PatternScheme::from_toml(OPS_II).unwrap();
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Custom", false).unwrap();
+
+        // Buffered I/O: regular files do not support O_DIRECT everywhere
+        let io_config = IOConfig { use_direct_io: false, initial_buffer_size: 64 * 1024, ..Default::default() };
+        let mut handle = OptimizedIO::open(path, io_config).unwrap();
+
+        for pass in 0..scheme.passes.len() {
+            let pattern = scheme.fixed_pattern(pass).unwrap();
+            CustomWipe::write_pattern(&mut handle, size, &pattern, &mut checkpoint, pass).unwrap();
+        }
+        let check = |handle: &mut IOHandle, pattern: &[u8]| {
+            PassVerifier::check(handle, size, &ExpectedData::Pattern(pattern), VerifyCoverage::Full, &[]).unwrap()
+        };
+        assert!(check(&mut handle, &[0x6D, 0xB6, 0xDB]).is_clean());
+        assert!(!check(&mut handle, &[0x00]).is_clean());
+    }
+
+    #[test]
+    fn test_keystream_pass_verifies_across_resume() {
+        let temp = NamedTempFile::new().unwrap();
+        let size = 512 * 1024u64;
+        std::fs::write(temp.path(), vec![0x11; size as usize]).unwrap();
+        let path = temp.path().to_str().unwrap();
+        let drive = DriveInfo { device_path: path.to_string(), size, ..test_drive("KEYSTREAM-TEST") };
+        let dir = TempDir::new().unwrap();
+        let io_config = IOConfig { use_direct_io: false, initial_buffer_size: 64 * 1024, ..Default::default() };
+
+        // First run stops partway, and its key is gone with it
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Custom", false).unwrap();
+        let mut handle = OptimizedIO::open(path, io_config.clone()).unwrap();
+        {
+            let first = Keystream::generate().unwrap();
+            let mut random = RandomSource::start(Some(&first), 0, 0, handle.buffer_size()).unwrap();
+            let mut calls = 0;
+            let result = OptimizedIO::sequential_write_resumable(&mut handle, size, &mut checkpoint, 0, |buffer| {
+                calls += 1;
+                if calls > 3 {
+                    return Err(crate::io::IOError::Interrupted);
+                }
+                Ok(random.fill(buffer.as_mut_slice())?)
+            });
+            assert!(result.is_err());
+        }
+        drop(checkpoint);
+
+        // The resumed run writes the rest under a new key, which verifies from
+        // its resume point onward
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Custom", true).unwrap();
+        let start = checkpoint.resume_offset(0);
+        assert_eq!(start, 3 * 64 * 1024);
+        let keystream = Keystream::generate().unwrap();
+        let mut handle = OptimizedIO::open(path, io_config.clone()).unwrap();
+        CustomWipe::write_random(&mut handle, size, Some(&keystream), &mut checkpoint, 0).unwrap();
+
+        let mut io_config = IOConfig::verification_optimized();
+        io_config.use_direct_io = false;
+        let mut reader = OptimizedIO::open(path, io_config).unwrap();
+        let resumed = ExpectedData::Keystream { keystream: &keystream, pass: 0, start };
+        let report = PassVerifier::check(&mut reader, size, &resumed, VerifyCoverage::Full, &[]).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.bytes_checked, size - start);
+        let whole = ExpectedData::Keystream { keystream: &keystream, pass: 0, start: 0 };
+        let report = PassVerifier::check(&mut reader, size, &whole, VerifyCoverage::Full, &[]).unwrap();
+        assert_eq!(report.mismatched_ranges, vec![(0, start / 512 - 1)]);
+    }
+}
@@ -1,15 +1,30 @@
 use anyhow::Result;
-use crate::crypto::secure_rng::secure_random_bytes;
+use crate::crypto::keystream::Keystream;
+use crate::crypto::random_pipeline::RandomSource;
+use crate::algorithms::OverwriteOptions;
+use crate::algorithms::verify::{ExpectedData, PassVerifier};
 use crate::ui::progress::ProgressBar;
-use crate::io::{OptimizedIO, IOConfig, IOHandle};
+use crate::io::{Checkpointer, OptimizedIO, IOConfig, IOHandle};
 use crate::DriveType;
 
 pub struct DoDWipe;
 
 impl DoDWipe {
-    pub fn wipe_drive(device_path: &str, size: u64, drive_type: DriveType) -> Result<()> {
+    /// Run the three passes, skipping any that `checkpoint` already recorded
+    /// as complete and resuming the interrupted one at its saved offset. Each
+    /// pass the verification policy covers is read back before the next one.
+    pub fn wipe_drive(
+        device_path: &str,
+        size: u64,
+        drive_type: DriveType,
+        options: &OverwriteOptions,
+        checkpoint: &mut Checkpointer,
+    ) -> Result<()> {
         println!("Starting DoD 5220.22-M 3-pass wipe on {}", device_path);
 
+        // Progress from every pass is attributed to this wipe
+        let _events = options.enter(device_path);
+
         // Configure I/O based on drive type
         let io_config = match drive_type {
             DriveType::NVMe => IOConfig::nvme_optimized(),
@@ -19,19 +34,30 @@ impl DoDWipe {
         };
 
         // Open device with optimized I/O
-        let mut io_handle = OptimizedIO::open(device_path, io_config)?;
+        let mut io_handle = options.open(device_path, io_config)?;
 
         // Pass 1: Write 0x00
-        println!("\n🔄 Pass 1/3: Writing 0x00");
-        Self::write_pattern(&mut io_handle, size, 0x00)?;
+        if !checkpoint.is_pass_complete(0) {
+            println!("\n🔄 Pass 1/3: Writing 0x00");
+            Self::write_pattern(&mut io_handle, size, 0x00, checkpoint, 0)?;
+            PassVerifier::after_pass(device_path, size, 0, 3, &ExpectedData::Pattern(&[0x00]), options, checkpoint)?;
+        }
 
         // Pass 2: Write 0xFF
-        println!("\n🔄 Pass 2/3: Writing 0xFF");
-        Self::write_pattern(&mut io_handle, size, 0xFF)?;
+        if !checkpoint.is_pass_complete(1) {
+            println!("\n🔄 Pass 2/3: Writing 0xFF");
+            Self::write_pattern(&mut io_handle, size, 0xFF, checkpoint, 1)?;
+            PassVerifier::after_pass(device_path, size, 1, 3, &ExpectedData::Pattern(&[0xFF]), options, checkpoint)?;
+        }
 
         // Pass 3: Write random data
-        println!("\n🔄 Pass 3/3: Writing random data");
-        Self::write_random(&mut io_handle, size)?;
+        if !checkpoint.is_pass_complete(2) {
+            println!("\n🔄 Pass 3/3: Writing random data");
+            let start = checkpoint.resume_offset(2);
+            Self::write_random(&mut io_handle, size, options.keystream, checkpoint, 2)?;
+            let expected = ExpectedData::random(options.keystream, 2, start);
+            PassVerifier::after_pass(device_path, size, 2, 3, &expected, options, checkpoint)?;
+        }
 
         // Final sync
         io_handle.sync()?;
@@ -39,15 +65,23 @@ impl DoDWipe {
         // Print performance report
         OptimizedIO::print_performance_report(&io_handle, None);
 
+        checkpoint.finish();
+
         println!("\n✅ DoD wipe completed successfully");
         Ok(())
     }
 
-    fn write_pattern(io_handle: &mut IOHandle, size: u64, pattern_byte: u8) -> Result<()> {
-        let mut bytes_written = 0u64;
+    fn write_pattern(
+        io_handle: &mut IOHandle,
+        size: u64,
+        pattern_byte: u8,
+        checkpoint: &mut Checkpointer,
+        pass: usize,
+    ) -> Result<()> {
+        let mut bytes_written = checkpoint.resume_offset(pass);
         let mut bar = ProgressBar::new(48);
 
-        OptimizedIO::sequential_write(io_handle, size, |buffer| {
+        OptimizedIO::sequential_write_resumable(io_handle, size, checkpoint, pass, |buffer| {
             // Fill buffer with pattern
             let buf = buffer.as_mut_slice();
             buf.fill(pattern_byte);
@@ -66,14 +100,21 @@ impl DoDWipe {
         Ok(())
     }
 
-    fn write_random(io_handle: &mut IOHandle, size: u64) -> Result<()> {
-        let mut bytes_written = 0u64;
+    fn write_random(
+        io_handle: &mut IOHandle,
+        size: u64,
+        keystream: Option<&Keystream>,
+        checkpoint: &mut Checkpointer,
+        pass: usize,
+    ) -> Result<()> {
+        let mut bytes_written = checkpoint.resume_random(pass)?;
         let mut bar = ProgressBar::new(48);
+        let mut random = RandomSource::start(keystream, pass, bytes_written, io_handle.buffer_size())?;
 
-        OptimizedIO::sequential_write(io_handle, size, |buffer| {
+        OptimizedIO::sequential_write_resumable(io_handle, size, checkpoint, pass, |buffer| {
             // Fill buffer with cryptographically secure random data
             let buf = buffer.as_mut_slice();
-            secure_random_bytes(buf)?;
+            random.fill(buf)?;
 
             bytes_written += buf.len() as u64;
 
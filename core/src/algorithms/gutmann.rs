@@ -1,11 +1,12 @@
-use anyhow::{Result, anyhow};
-use crate::crypto::secure_rng::secure_random_bytes;
-use std::collections::HashMap;
+use anyhow::Result;
+use crate::algorithms::verify::{ExpectedData, PassVerifier};
+use crate::crypto::keystream::Keystream;
+use crate::crypto::random_pipeline::RandomSource;
 use std::time::Instant;
 use crate::ui::progress::ProgressBar;
-use crate::io::{OptimizedIO, IOConfig, IOHandle};
+use crate::io::{Checkpointer, OptimizedIO, IOConfig, IOHandle};
+use crate::algorithms::OverwriteOptions;
 use crate::DriveType;
-use serde::{Serialize, Deserialize};
 
 /// Drive encoding types that affect pattern selection
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -16,17 +17,6 @@ pub enum DriveEncoding {
     Unknown,  // Default to most comprehensive patterns
 }
 
-/// Checkpoint for resume capability
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GutmannCheckpoint {
-    pub device_path: String,
-    pub current_pass: usize,
-    pub bytes_written: u64,
-    pub total_size: u64,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub encoding: String,
-}
-
 pub struct GutmannWipe;
 
 impl GutmannWipe {
@@ -76,8 +66,15 @@ impl GutmannWipe {
         (None, "Random Pass 35"),
     ];
 
-    /// Perform the complete 35-pass Gutmann wipe with verification
-    pub fn wipe_drive(device_path: &str, size: u64, drive_type: DriveType) -> Result<()> {
+    /// Perform the complete 35-pass Gutmann wipe, reading back the passes
+    /// verification policy covers
+    pub fn wipe_drive(
+        device_path: &str,
+        size: u64,
+        drive_type: DriveType,
+        options: &OverwriteOptions,
+        checkpoint: &mut Checkpointer,
+    ) -> Result<()> {
         println!("Starting Gutmann 35-pass secure wipe on {}", device_path);
         println!("Drive size: {} bytes ({} GB)", size, size / (1024 * 1024 * 1024));
 
@@ -85,14 +82,8 @@ impl GutmannWipe {
         let encoding = Self::detect_drive_encoding(device_path)?;
         println!("Detected drive encoding: {:?}", encoding);
 
-        // Check for existing checkpoint
-        let checkpoint = Self::load_checkpoint(device_path);
-        let start_pass = checkpoint.as_ref().map(|c| c.current_pass).unwrap_or(0);
-
-        if let Some(cp) = &checkpoint {
-            println!("Resuming from pass {} (checkpoint found from {})",
-                     cp.current_pass + 1, cp.timestamp);
-        }
+        // Progress from every pass is attributed to this wipe
+        let _events = options.enter(device_path);
 
         // Configure I/O based on drive type
         let io_config = match drive_type {
@@ -103,12 +94,12 @@ impl GutmannWipe {
         };
 
         // Open device with optimized I/O
-        let mut io_handle = OptimizedIO::open(device_path, io_config)?;
+        let mut io_handle = options.open(device_path, io_config)?;
 
         // Perform each pass
         for (pass_num, (pattern, description)) in Self::GUTMANN_PATTERNS.iter().enumerate() {
             // Skip completed passes if resuming
-            if pass_num < start_pass {
+            if checkpoint.is_pass_complete(pass_num) {
                 continue;
             }
 
@@ -118,17 +109,19 @@ impl GutmannWipe {
 
             // Write the pattern
             if let Some(pattern_bytes) = pattern {
-                Self::write_pattern_with_verification(&mut io_handle, size, pattern_bytes, pass_num)?;
+                Self::write_pattern(&mut io_handle, size, pattern_bytes, pass_num, checkpoint)?;
+                let expected = ExpectedData::Pattern(pattern_bytes);
+                PassVerifier::after_pass(device_path, size, pass_num, 35, &expected, options, checkpoint)?;
             } else {
-                Self::write_random_with_verification(&mut io_handle, size, pass_num)?;
+                let start = checkpoint.resume_offset(pass_num);
+                Self::write_random(&mut io_handle, size, pass_num, options.keystream, checkpoint)?;
+                let expected = ExpectedData::random(options.keystream, pass_num, start);
+                PassVerifier::after_pass(device_path, size, pass_num, 35, &expected, options, checkpoint)?;
             }
 
             let pass_duration = pass_start.elapsed();
-            println!("  ✅ Pass {} completed and verified in {:.2}s",
+            println!("  ✅ Pass {} completed in {:.2}s",
                      pass_num + 1, pass_duration.as_secs_f64());
-
-            // Save checkpoint after each pass
-            Self::save_checkpoint(device_path, pass_num + 1, size, &encoding)?;
         }
 
         // Final sync
@@ -138,10 +131,10 @@ impl GutmannWipe {
         OptimizedIO::print_performance_report(&io_handle, None);
 
         // Clean up checkpoint
-        Self::delete_checkpoint(device_path);
+        checkpoint.finish();
 
         println!("\n✅ Gutmann 35-pass wipe completed successfully!");
-        println!("All data has been securely overwritten and verified.");
+        println!("All data has been securely overwritten.");
 
         Ok(())
     }
@@ -182,184 +175,74 @@ impl GutmannWipe {
         Ok(DriveEncoding::Unknown)
     }
 
-    /// Write a specific pattern and verify it was written correctly
-    pub(crate) fn write_pattern_with_verification(
+    /// Write a repeating pattern, phased by absolute offset so a resumed pass
+    /// lines up with what was already written
+    pub(crate) fn write_pattern(
         io_handle: &mut IOHandle,
         size: u64,
         pattern: &[u8],
-        pass_num: usize
+        pass_num: usize,
+        checkpoint: &mut Checkpointer,
     ) -> Result<()> {
-        let mut bytes_written = 0u64;
+        let mut bytes_written = checkpoint.resume_offset(pass_num);
         let mut bar = ProgressBar::new(48);
 
-        // Write phase using OptimizedIO
-        OptimizedIO::sequential_write(io_handle, size, |buffer| {
+        OptimizedIO::sequential_write_resumable(io_handle, size, checkpoint, pass_num, |buffer| {
             // Fill buffer with repeating pattern
             let buf = buffer.as_mut_slice();
+            let phase = (bytes_written % pattern.len() as u64) as usize;
             for (i, byte) in buf.iter_mut().enumerate() {
-                *byte = pattern[i % pattern.len()];
+                *byte = pattern[(phase + i) % pattern.len()];
             }
 
             bytes_written += buf.len() as u64;
 
             // Update progress every 100MB
             if bytes_written % (100 * 1024 * 1024) == 0 || bytes_written >= size {
-                let progress = (bytes_written as f64 / size as f64) * 50.0; // First 50% for writing
+                let progress = (bytes_written as f64 / size as f64) * 100.0;
                 bar.render(progress, Some(bytes_written), Some(size));
             }
 
             Ok(())
         })?;
 
-        // Verification phase
-        println!("\n  🔍 Verifying pass {} pattern...", pass_num + 1);
-        Self::verify_pattern_from_device(&io_handle.device_path, size, pattern, &mut bar)?;
-
         bar.render(100.0, Some(size), Some(size));
         Ok(())
     }
 
-    /// Write cryptographically secure random data and verify
-    fn write_random_with_verification(
+    /// Write cryptographically secure random data, from the keystream if
+    /// there is one
+    fn write_random(
         io_handle: &mut IOHandle,
         size: u64,
-        pass_num: usize
+        pass_num: usize,
+        keystream: Option<&Keystream>,
+        checkpoint: &mut Checkpointer,
     ) -> Result<()> {
-        let mut bytes_written = 0u64;
+        let mut bytes_written = checkpoint.resume_random(pass_num)?;
         let mut bar = ProgressBar::new(48);
+        let mut random = RandomSource::start(keystream, pass_num, bytes_written, io_handle.buffer_size())?;
 
-        // Store chunks for verification (sample every 100MB)
-        let mut verification_samples: HashMap<u64, Vec<u8>> = HashMap::new();
-
-        // Write phase using OptimizedIO
-        OptimizedIO::sequential_write(io_handle, size, |buffer| {
+        OptimizedIO::sequential_write_resumable(io_handle, size, checkpoint, pass_num, |buffer| {
             // Fill buffer with cryptographically secure random data
             let buf = buffer.as_mut_slice();
-            secure_random_bytes(buf)?;
-
-            // Store sample for verification (first 4KB of every 100MB)
-            if bytes_written % (100 * 1024 * 1024) == 0 {
-                let sample_size = std::cmp::min(4096, buf.len());
-                verification_samples.insert(
-                    bytes_written,
-                    buf[..sample_size].to_vec()
-                );
-            }
+            random.fill(buf)?;
 
             bytes_written += buf.len() as u64;
 
             // Update progress
             if bytes_written % (100 * 1024 * 1024) == 0 || bytes_written >= size {
-                let progress = (bytes_written as f64 / size as f64) * 50.0; // First 50% for writing
+                let progress = (bytes_written as f64 / size as f64) * 100.0;
                 bar.render(progress, Some(bytes_written), Some(size));
             }
 
             Ok(())
         })?;
 
-        // Verification phase - verify random data has high entropy
-        println!("\n  🔍 Verifying pass {} randomness...", pass_num + 1);
-        Self::verify_random_entropy_from_device(&io_handle.device_path, size, &verification_samples, &mut bar)?;
-
         bar.render(100.0, Some(size), Some(size));
         Ok(())
     }
 
-    /// Verify that a pattern was written correctly (uses separate file handle for reading)
-    pub(crate) fn verify_pattern_from_device(
-        device_path: &str,
-        size: u64,
-        expected_pattern: &[u8],
-        bar: &mut ProgressBar
-    ) -> Result<()> {
-        const SAMPLE_SIZE: usize = 4096;
-
-        // Open device for reading with optimized I/O
-        let config = IOConfig::verification_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
-
-        // Verify samples throughout the drive
-        let num_samples = std::cmp::min(1000, (size / SAMPLE_SIZE as u64) as usize);
-        let sample_interval = size / num_samples as u64;
-
-        for i in 0..num_samples {
-            let offset = i as u64 * sample_interval;
-            let read_size = std::cmp::min(SAMPLE_SIZE, (size - offset) as usize);
-
-            let buffer = OptimizedIO::read_range(&mut handle, offset, read_size)?;
-
-            // Check pattern matches
-            for (j, byte) in buffer.iter().enumerate() {
-                let expected = expected_pattern[j % expected_pattern.len()];
-                if *byte != expected {
-                    return Err(anyhow!(
-                        "Verification failed at offset {}: expected 0x{:02x}, got 0x{:02x}",
-                        offset + j as u64, expected, byte
-                    ));
-                }
-            }
-
-            // Update progress (50-100% range for verification)
-            let progress = 50.0 + ((i as f64 / num_samples as f64) * 50.0);
-            bar.render(progress, None, None);
-        }
-
-        Ok(())
-    }
-
-    /// Verify random data has sufficient entropy (uses separate file handle for reading)
-    pub(crate) fn verify_random_entropy_from_device(
-        device_path: &str,
-        size: u64,
-        samples: &HashMap<u64, Vec<u8>>,
-        bar: &mut ProgressBar
-    ) -> Result<()> {
-        // Open device for reading with optimized I/O
-        let config = IOConfig::verification_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
-
-        // Verify stored samples match what's on disk
-        let num_samples = samples.len();
-        let mut verified = 0;
-
-        for (offset, expected_data) in samples {
-            let buffer = OptimizedIO::read_range(&mut handle, *offset, expected_data.len())?;
-
-            if buffer != *expected_data {
-                // Check entropy instead of exact match (drive might have done something)
-                let entropy = Self::calculate_entropy(&buffer);
-                if entropy < 7.5 {
-                    return Err(anyhow!(
-                        "Low entropy detected at offset {}: {:.2} bits/byte",
-                        offset, entropy
-                    ));
-                }
-            }
-
-            verified += 1;
-            let progress = 50.0 + ((verified as f64 / num_samples as f64) * 50.0);
-            bar.render(progress, None, None);
-        }
-
-        // Additionally check overall entropy at random positions
-        for _ in 0..100 {
-            // Generate random offset using secure RNG
-            let mut offset_bytes = [0u8; 8];
-            secure_random_bytes(&mut offset_bytes)?;
-            let offset = u64::from_le_bytes(offset_bytes) % size.saturating_sub(4096);
-
-            if let Ok(buffer) = OptimizedIO::read_range(&mut handle, offset, 4096) {
-                let entropy = Self::calculate_entropy(&buffer);
-                if entropy < 7.0 {
-                    println!("  ⚠️  Warning: Lower entropy at offset {}: {:.2} bits/byte",
-                             offset, entropy);
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     /// Calculate Shannon entropy of data
     pub(crate) fn calculate_entropy(data: &[u8]) -> f64 {
         let mut counts = [0u64; 256];
@@ -380,58 +263,6 @@ impl GutmannWipe {
         entropy
     }
 
-    /// Save checkpoint for resume capability
-    pub(crate) fn save_checkpoint(
-        device_path: &str,
-        current_pass: usize,
-        total_size: u64,
-        encoding: &DriveEncoding
-    ) -> Result<()> {
-        let checkpoint = GutmannCheckpoint {
-            device_path: device_path.to_string(),
-            current_pass,
-            bytes_written: 0,
-            total_size,
-            timestamp: chrono::Utc::now(),
-            encoding: format!("{:?}", encoding),
-        };
-
-        let checkpoint_file = Self::checkpoint_filename(device_path);
-        let json = serde_json::to_string_pretty(&checkpoint)?;
-        std::fs::write(checkpoint_file, json)?;
-
-        Ok(())
-    }
-
-    /// Load checkpoint if it exists
-    pub(crate) fn load_checkpoint(device_path: &str) -> Option<GutmannCheckpoint> {
-        let checkpoint_file = Self::checkpoint_filename(device_path);
-
-        if let Ok(json) = std::fs::read_to_string(&checkpoint_file) {
-            if let Ok(checkpoint) = serde_json::from_str::<GutmannCheckpoint>(&json) {
-                // Check if checkpoint is recent (within 24 hours)
-                let age = chrono::Utc::now() - checkpoint.timestamp;
-                if age.num_hours() < 24 {
-                    return Some(checkpoint);
-                }
-            }
-        }
-
-        None
-    }
-
-    /// Delete checkpoint after successful completion
-    pub(crate) fn delete_checkpoint(device_path: &str) {
-        let checkpoint_file = Self::checkpoint_filename(device_path);
-        let _ = std::fs::remove_file(checkpoint_file);
-    }
-
-    /// Generate checkpoint filename for a device
-    fn checkpoint_filename(device_path: &str) -> String {
-        let safe_name = device_path.replace('/', "_");
-        format!("/tmp/gutmann_checkpoint{}.json", safe_name)
-    }
-
     /// Select optimal patterns based on drive encoding
     pub fn get_optimized_patterns(encoding: DriveEncoding) -> Vec<usize> {
         match encoding {
@@ -9,6 +9,8 @@ mod tests {
     use std::io::{Write, Read, Seek, SeekFrom};
     use crate::algorithms::gutmann::DriveEncoding;
     use crate::ui::progress::ProgressBar;
+    use crate::io::Checkpointer;
+    use crate::test_support::test_drive;
 
     /// Test that patterns match the original Gutmann specification
     #[test]
@@ -117,37 +119,38 @@ mod tests {
         assert!(entropy > 7.5, "Random data should have high entropy");
     }
 
+    fn checkpoint_drive(serial: &str, size: u64) -> crate::DriveInfo {
+        crate::DriveInfo { device_path: "/dev/test_device".to_string(), size, ..test_drive(serial) }
+    }
+
     /// Test checkpoint save and load
     #[test]
     fn test_checkpoint_operations() -> Result<()> {
-        let test_device = "/dev/test_device";
-        let test_pass = 15;
-        let test_size = 1024 * 1024 * 1024; // 1GB
-        let encoding = DriveEncoding::PRML;
-
-        // Save checkpoint
-        GutmannWipe::save_checkpoint(test_device, test_pass, test_size, &encoding)?;
-
-        // Load checkpoint
-        let loaded = GutmannWipe::load_checkpoint(test_device);
-        assert!(loaded.is_some(), "Checkpoint should be loaded");
-
-        let checkpoint = loaded.unwrap();
-        assert_eq!(checkpoint.device_path, test_device);
-        assert_eq!(checkpoint.current_pass, test_pass);
-        assert_eq!(checkpoint.total_size, test_size);
-        assert_eq!(checkpoint.encoding, "PRML");
+        let dir = tempfile::TempDir::new()?;
+        let drive = checkpoint_drive("GUT-CP", 1024 * 1024 * 1024); // 1GB
+
+        // Simulate a run that finished pass 15
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Gutmann", false)?;
+        checkpoint.complete_pass(14)?;
+        assert!(checkpoint.path().exists());
+
+        // Resume picks up at the next pass
+        let mut resumed = Checkpointer::open_in(dir.path(), &drive, "Gutmann", true)?;
+        let state = resumed.checkpoint().unwrap().clone();
+        assert_eq!(state.current_pass, 15);
+        assert_eq!(state.total_size, drive.size);
+        assert_eq!(state.algorithm, "Gutmann");
+        assert!(resumed.is_pass_complete(14));
+        assert!(!resumed.is_pass_complete(15));
 
         // Verify timestamp is recent
-        let age = chrono::Utc::now() - checkpoint.timestamp;
+        let age = chrono::Utc::now() - state.updated_at;
         assert!(age.num_seconds() < 5, "Checkpoint should be recent");
 
         // Clean up
-        GutmannWipe::delete_checkpoint(test_device);
-
-        // Verify deletion
-        let deleted = GutmannWipe::load_checkpoint(test_device);
-        assert!(deleted.is_none(), "Checkpoint should be deleted");
+        let path = resumed.path().to_path_buf();
+        resumed.finish();
+        assert!(!path.exists(), "Checkpoint should be deleted");
 
         Ok(())
     }
@@ -284,22 +287,19 @@ mod tests {
     /// Integration test for resume functionality
     #[test]
     fn test_resume_after_interruption() -> Result<()> {
-        let test_device = "/dev/test_resume";
-        let test_size = 100 * 1024 * 1024; // 100MB
-        let encoding = DriveEncoding::PRML;
+        let dir = tempfile::TempDir::new()?;
+        let drive = checkpoint_drive("GUT-RESUME", 100 * 1024 * 1024); // 100MB
 
         // Simulate interruption at pass 10
-        GutmannWipe::save_checkpoint(test_device, 10, test_size, &encoding)?;
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Gutmann", false)?;
+        checkpoint.complete_pass(9)?;
 
         // Load and verify resume point
-        let checkpoint = GutmannWipe::load_checkpoint(test_device);
-        assert!(checkpoint.is_some());
+        let resumed = Checkpointer::open_in(dir.path(), &drive, "Gutmann", true)?;
+        assert_eq!(resumed.checkpoint().unwrap().current_pass, 10, "Should resume from pass 10");
 
-        let cp = checkpoint.unwrap();
-        assert_eq!(cp.current_pass, 10, "Should resume from pass 10");
-
-        // Clean up
-        GutmannWipe::delete_checkpoint(test_device);
+        // A different algorithm must not continue this checkpoint
+        assert!(Checkpointer::open_in(dir.path(), &drive, "DoD5220", true).is_err());
 
         Ok(())
     }
@@ -2,6 +2,16 @@ pub mod gutmann;
 pub mod dod;
 pub mod random;
 pub mod zero;
+pub mod custom;
+pub mod verify;
+
+use anyhow::{bail, Result};
+use crate::control::{OperationContext, QuietHours};
+use crate::crypto::keystream::Keystream;
+use crate::drives::backend::BackendIO;
+use crate::events::DeviceScope;
+use crate::io::{Checkpointer, Extent, IOConfig, IOHandle, OptimizedIO};
+use crate::{Algorithm, DriveType, PassVerification};
 
 #[cfg(test)]
 mod gutmann_test;
@@ -11,3 +21,72 @@ pub use dod::DoDWipe;
 pub use gutmann::GutmannWipe;
 pub use random::RandomWipe;
 pub use zero::ZeroWipe;
+pub use custom::{CustomWipe, PatternScheme};
+pub use verify::{ExpectedData, PassVerificationReport, PassVerifier};
+
+/// Settings shared by the overwrite algorithms
+#[derive(Clone, Copy, Default)]
+pub struct OverwriteOptions<'a> {
+    pub keystream: Option<&'a Keystream>,  // Random passes use this keystream instead of the DRBG
+    pub verification: PassVerification,    // Which passes are read back
+    pub extent: Option<Extent>,            // Part of the device to overwrite; None for all of it
+    pub context: Option<&'a OperationContext>,  // Cancellation, pause and observer of this wipe
+    pub quiet_hours: Option<QuietHours>,   // Daily window with a write bandwidth ceiling
+    pub max_bytes_per_sec: Option<u64>,    // Write bandwidth ceiling for this drive
+    pub max_iops: Option<u64>,             // Write operations per second ceiling for this drive
+    pub backend: Option<&'a BackendIO>,    // Reach the drive through its backend instead of the device node
+}
+
+/// Run overwrite `algorithm` on `device_path` with its implementation above
+pub fn overwrite(
+    algorithm: &Algorithm,
+    device_path: &str,
+    size: u64,
+    drive_type: DriveType,
+    options: &OverwriteOptions,
+    checkpoint: &mut Checkpointer,
+) -> Result<()> {
+    match algorithm {
+        Algorithm::DoD5220 => DoDWipe::wipe_drive(device_path, size, drive_type, options, checkpoint),
+        Algorithm::Gutmann => GutmannWipe::wipe_drive(device_path, size, drive_type, options, checkpoint),
+        Algorithm::Random => RandomWipe::wipe_drive(device_path, size, drive_type, options, checkpoint),
+        Algorithm::Zero => ZeroWipe::wipe_drive(device_path, size, drive_type, options, checkpoint),
+        Algorithm::Custom(scheme) => CustomWipe::wipe_drive(device_path, size, drive_type, scheme, options, checkpoint),
+        other => bail!("{} is not an overwrite method", other.name()),
+    }
+}
+
+impl OverwriteOptions<'_> {
+    /// Open `device_path`, confined to the extent if there is one. Offsets
+    /// on the handle are then relative to the start of the extent.
+    pub fn open(&self, device_path: &str, config: IOConfig) -> Result<IOHandle> {
+        let config = IOConfig {
+            max_bytes_per_sec: self.max_bytes_per_sec.or(config.max_bytes_per_sec),
+            max_iops: self.max_iops.or(config.max_iops),
+            ..config
+        };
+        let mut handle = match self.backend {
+            Some(backend) => OptimizedIO::open_with(device_path, config, Box::new(backend.clone()))?,
+            None => OptimizedIO::open(device_path, config)?,
+        };
+        if let Some(extent) = self.extent {
+            handle.restrict_to(extent)?;
+        }
+        if let Some(context) = self.context {
+            handle.set_cancellation(context.cancel.clone());
+            handle.set_pause(context.pause.clone());
+            handle.set_shared_limiter(context.rate_limiter.clone());
+        }
+        handle.set_quiet_hours(self.quiet_hours);
+        Ok(handle)
+    }
+
+    /// Attribute progress on this thread to `device_path` and the wipe's
+    /// observer until the returned scope is dropped
+    pub fn enter(&self, device_path: &str) -> DeviceScope {
+        match self.context {
+            Some(context) => context.enter(device_path),
+            None => DeviceScope::enter(device_path, None),
+        }
+    }
+}
@@ -1,15 +1,27 @@
 use anyhow::Result;
 use crate::ui::progress::ProgressBar;
-use crate::crypto::secure_rng::get_secure_rng;
-use crate::io::{OptimizedIO, IOConfig, IOHandle};
+use crate::crypto::keystream::Keystream;
+use crate::crypto::random_pipeline::RandomSource;
+use crate::algorithms::OverwriteOptions;
+use crate::algorithms::verify::{ExpectedData, PassVerifier};
+use crate::io::{Checkpointer, OptimizedIO, IOConfig, IOHandle};
 use crate::DriveType;
 
 pub struct RandomWipe;
 
 impl RandomWipe {
-    pub fn wipe_drive(device_path: &str, size: u64, drive_type: DriveType) -> Result<()> {
+    pub fn wipe_drive(
+        device_path: &str,
+        size: u64,
+        drive_type: DriveType,
+        options: &OverwriteOptions,
+        checkpoint: &mut Checkpointer,
+    ) -> Result<()> {
         println!("Starting single-pass random wipe on {}", device_path);
 
+        // Progress from every pass is attributed to this wipe
+        let _events = options.enter(device_path);
+
         // Configure I/O based on drive type
         let io_config = match drive_type {
             DriveType::NVMe => IOConfig::nvme_optimized(),
@@ -19,9 +31,12 @@ impl RandomWipe {
         };
 
         // Open device with optimized I/O
-        let mut io_handle = OptimizedIO::open(device_path, io_config)?;
+        let mut io_handle = options.open(device_path, io_config)?;
 
-        Self::write_random(&mut io_handle, size)?;
+        let start = checkpoint.resume_offset(0);
+        Self::write_random(&mut io_handle, size, options.keystream, checkpoint)?;
+        let expected = ExpectedData::random(options.keystream, 0, start);
+        PassVerifier::after_pass(device_path, size, 0, 1, &expected, options, checkpoint)?;
 
         // Final sync
         io_handle.sync()?;
@@ -29,19 +44,26 @@ impl RandomWipe {
         // Print performance report
         OptimizedIO::print_performance_report(&io_handle, None);
 
+        checkpoint.finish();
+
         println!("\n✅ Random wipe completed successfully");
         Ok(())
     }
 
-    fn write_random(io_handle: &mut IOHandle, size: u64) -> Result<()> {
-        let rng = get_secure_rng();
-        let mut bytes_written = 0u64;
+    fn write_random(
+        io_handle: &mut IOHandle,
+        size: u64,
+        keystream: Option<&Keystream>,
+        checkpoint: &mut Checkpointer,
+    ) -> Result<()> {
+        let mut bytes_written = checkpoint.resume_random(0)?;
+        let mut random = RandomSource::start(keystream, 0, bytes_written, io_handle.buffer_size())?;
         let mut bar = ProgressBar::new(48);
 
-        OptimizedIO::sequential_write(io_handle, size, |buffer| {
+        OptimizedIO::sequential_write_resumable(io_handle, size, checkpoint, 0, |buffer| {
             // Fill buffer with cryptographically secure random data
             let buf = buffer.as_mut_slice();
-            rng.fill_bytes(buf)?;
+            random.fill(buf)?;
 
             bytes_written += buf.len() as u64;
 
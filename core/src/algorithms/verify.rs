@@ -0,0 +1,454 @@
+// Read-back verification of overwrite passes
+//
+// `PassVerification` decides which passes are read back and how much of each.
+// Fixed patterns and keystream passes have exactly known contents, so every
+// byte read is compared with what should be there and differing sectors are
+// reported as LBA ranges. DRBG passes are unknown to everyone, including us,
+// so for those each 4 KiB block only has to look random.
+
+use anyhow::{Result, anyhow};
+use crate::algorithms::gutmann::GutmannWipe;
+use crate::crypto::keystream::Keystream;
+use crate::io::{Checkpointer, IOConfig, IOHandle};
+use crate::io::buffer_pool::PooledBuffer;
+use crate::ui::progress::ProgressBar;
+use crate::algorithms::OverwriteOptions;
+use crate::VerifyCoverage;
+
+/// Bytes read per sample when verifying a percentage of the drive. Large
+/// enough that a 1% check of an HDD is dominated by transfer, not seeks.
+const SAMPLE_SIZE: u64 = 1024 * 1024;
+
+/// Bytes read at a time when verifying the whole pass
+const CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Blocks checked for entropy in DRBG passes
+const ENTROPY_BLOCK: usize = 4096;
+
+/// Minimum Shannon entropy of a 4 KiB random block (ideal is about 7.95)
+const MIN_ENTROPY: f64 = 7.5;
+
+/// Ranges listed in a verification error before the rest are elided
+const MAX_LISTED_RANGES: usize = 20;
+
+/// What a pass should have left on the drive
+pub enum ExpectedData<'a> {
+    /// A repeating pattern, phased by absolute offset
+    Pattern(&'a [u8]),
+    /// The keystream of `pass`. Only `[start, size)` was written under this
+    /// key; anything before a resume used a key that no longer exists.
+    Keystream { keystream: &'a Keystream, pass: usize, start: u64 },
+    /// DRBG output: checked for entropy only
+    Random,
+}
+
+impl<'a> ExpectedData<'a> {
+    /// A random pass of `pass` that started writing at `start`
+    pub fn random(keystream: Option<&'a Keystream>, pass: usize, start: u64) -> Self {
+        match keystream {
+            Some(keystream) => ExpectedData::Keystream { keystream, pass, start },
+            None => ExpectedData::Random,
+        }
+    }
+}
+
+/// Outcome of reading back one pass
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PassVerificationReport {
+    pub bytes_checked: u64,
+    pub mismatched_sectors: u64,
+    pub mismatched_ranges: Vec<(u64, u64)>,  // Inclusive LBA runs
+}
+
+impl PassVerificationReport {
+    pub fn is_clean(&self) -> bool {
+        self.mismatched_sectors == 0
+    }
+
+    /// "10-12, 4000", eliding all but the first `limit` runs
+    pub fn describe_ranges(&self, limit: usize) -> String {
+        let mut listed: Vec<String> = self.mismatched_ranges.iter()
+            .take(limit)
+            .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+            .collect();
+        if self.mismatched_ranges.len() > limit {
+            listed.push(format!("... {} more", self.mismatched_ranges.len() - limit));
+        }
+        listed.join(", ")
+    }
+
+    fn record_mismatch(&mut self, first: u64, last: u64) {
+        self.mismatched_sectors += last - first + 1;
+        match self.mismatched_ranges.last_mut() {
+            Some((_, end)) if *end + 1 >= first => *end = (*end).max(last),
+            _ => self.mismatched_ranges.push((first, last)),
+        }
+    }
+}
+
+pub struct PassVerifier;
+
+impl PassVerifier {
+    /// Verify pass `pass` of `total` if the policy in `options` covers it,
+    /// skipping the sectors `checkpoint` recorded as unwritable
+    pub fn after_pass(
+        device_path: &str,
+        size: u64,
+        pass: usize,
+        total: usize,
+        expected: &ExpectedData,
+        options: &OverwriteOptions,
+        checkpoint: &Checkpointer,
+    ) -> Result<()> {
+        if options.verification.covers(pass, total) {
+            Self::verify_pass(device_path, size, pass, expected, options, &checkpoint.bad_sectors())?;
+        }
+        Ok(())
+    }
+
+    /// Read back pass `pass` with a fresh handle, confined to the same extent,
+    /// and fail, listing the differing LBA ranges, if anything other than
+    /// `unwritable` sectors does not hold `expected`
+    pub fn verify_pass(
+        device_path: &str,
+        size: u64,
+        pass: usize,
+        expected: &ExpectedData,
+        options: &OverwriteOptions,
+        unwritable: &[u64],
+    ) -> Result<PassVerificationReport> {
+        let coverage = options.verification.coverage;
+        let scope = match coverage {
+            VerifyCoverage::Sample(percent) => format!("{}% sampled", percent),
+            VerifyCoverage::Full => "full read".to_string(),
+        };
+        let method = match expected {
+            ExpectedData::Random => "entropy",
+            _ => "exact",
+        };
+        println!("\n  🔍 Verifying pass {} ({}, {})...", pass + 1, scope, method);
+
+        let mut handle = options.open(device_path, IOConfig::verification_optimized())?;
+        let report = Self::check(&mut handle, size, expected, coverage, unwritable)?;
+
+        if !report.is_clean() {
+            return Err(anyhow!(
+                "Pass {} verification failed: {} sectors do not hold the expected data (LBAs {})",
+                pass + 1, report.mismatched_sectors, report.describe_ranges(MAX_LISTED_RANGES)
+            ));
+        }
+
+        println!("  ✓ {} MB read back and verified", report.bytes_checked / (1024 * 1024));
+        Ok(report)
+    }
+
+    /// Compare the regions selected by `coverage` with `expected`
+    pub(crate) fn check(
+        handle: &mut IOHandle,
+        size: u64,
+        expected: &ExpectedData,
+        coverage: VerifyCoverage,
+        unwritable: &[u64],
+    ) -> Result<PassVerificationReport> {
+        let start = match expected {
+            ExpectedData::Keystream { start, .. } => *start,
+            _ => 0,
+        };
+        let sector_size = handle.sector_size();
+        // Pooled buffers are aligned for direct I/O, unlike `read_range`'s
+        let mut buffer = handle.acquire_buffer()?;
+        let align = sector_size.max(ENTROPY_BLOCK as u64);
+        let regions = Self::regions(start, size, coverage, align, buffer.size() as u64);
+        let total: u64 = regions.iter().map(|&(_, len)| len).sum();
+
+        let mut report = PassVerificationReport::default();
+        let mut want = Vec::new();
+        let mut bar = ProgressBar::new(48);
+
+        for (offset, len) in regions {
+            let data = Self::read_region(handle, &mut buffer, offset, len as usize)?;
+
+            match expected {
+                ExpectedData::Pattern(pattern) => {
+                    want.resize(data.len(), 0);
+                    for (i, byte) in want.iter_mut().enumerate() {
+                        *byte = pattern[((offset + i as u64) % pattern.len() as u64) as usize];
+                    }
+                    Self::compare_sectors(data, &want, handle.device_offset(offset), sector_size, unwritable, &mut report);
+                }
+                ExpectedData::Keystream { keystream, pass, .. } => {
+                    want.resize(data.len(), 0);
+                    keystream.fill_at(*pass, offset, &mut want)?;
+                    Self::compare_sectors(data, &want, handle.device_offset(offset), sector_size, unwritable, &mut report);
+                }
+                ExpectedData::Random => {
+                    Self::check_entropy(data, handle.device_offset(offset), sector_size, unwritable, &mut report);
+                }
+            }
+
+            report.bytes_checked += len;
+            bar.render(report.bytes_checked as f64 / total as f64 * 100.0, Some(report.bytes_checked), Some(total));
+        }
+
+        Ok(report)
+    }
+
+    /// Aligned `(offset, len)` regions of `[start, size)` to read, none
+    /// longer than `max_len`
+    fn regions(start: u64, size: u64, coverage: VerifyCoverage, align: u64, max_len: u64) -> Vec<(u64, u64)> {
+        if start >= size {
+            return Vec::new();
+        }
+        let span = size - start;
+
+        match coverage {
+            VerifyCoverage::Full => {
+                let chunk = CHUNK_SIZE.min(max_len);
+                (start..size)
+                    .step_by(chunk as usize)
+                    .map(|offset| (offset, chunk.min(size - offset)))
+                    .collect()
+            }
+            VerifyCoverage::Sample(percent) => {
+                let sample = SAMPLE_SIZE.min(max_len);
+                let wanted = (span as f64 * percent / 100.0) as u64;
+                let count = wanted.div_ceil(sample).clamp(1, span.div_ceil(sample));
+                let interval = span / count;
+
+                (0..count)
+                    .map(|i| {
+                        let offset = (start + i * interval) / align * align;
+                        let offset = offset.max(start);
+                        (offset, sample.min(size - offset))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn read_region<'b>(handle: &mut IOHandle, buffer: &'b mut PooledBuffer, offset: u64, len: usize) -> Result<&'b [u8]> {
+        let data = &mut buffer.as_mut_slice()[..len];
+        let mut done = 0;
+        while done < len {
+            let read = handle.read_at(&mut data[done..], offset + done as u64)?;
+            if read == 0 {
+                return Err(anyhow!("Short read verifying offset {}: {} of {} bytes", offset, done, len));
+            }
+            done += read;
+        }
+        Ok(data)
+    }
+
+    fn compare_sectors(
+        data: &[u8],
+        want: &[u8],
+        offset: u64,
+        sector_size: u64,
+        unwritable: &[u64],
+        report: &mut PassVerificationReport,
+    ) {
+        let first_lba = offset / sector_size;
+        for (i, (got, want)) in data.chunks(sector_size as usize).zip(want.chunks(sector_size as usize)).enumerate() {
+            let lba = first_lba + i as u64;
+            if got != want && unwritable.binary_search(&lba).is_err() {
+                report.record_mismatch(lba, lba);
+            }
+        }
+    }
+
+    fn check_entropy(
+        data: &[u8],
+        offset: u64,
+        sector_size: u64,
+        unwritable: &[u64],
+        report: &mut PassVerificationReport,
+    ) {
+        for (i, block) in data.chunks(ENTROPY_BLOCK).enumerate() {
+            // Too few bytes for a meaningful estimate
+            if block.len() < ENTROPY_BLOCK {
+                continue;
+            }
+
+            let block_offset = offset + (i * ENTROPY_BLOCK) as u64;
+            let first = block_offset / sector_size;
+            let last = (block_offset + ENTROPY_BLOCK as u64 - 1) / sector_size;
+            if unwritable.iter().any(|lba| (first..=last).contains(lba)) {
+                continue;
+            }
+            if GutmannWipe::calculate_entropy(block) < MIN_ENTROPY {
+                report.record_mismatch(first, last);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::secure_rng::secure_random_bytes;
+    use crate::io::{Extent, OptimizedIO};
+    use crate::{PassVerification, VerifyPasses};
+    use tempfile::NamedTempFile;
+
+    fn reader(temp: &NamedTempFile) -> IOHandle {
+        let mut config = IOConfig::verification_optimized();
+        config.use_direct_io = false;
+        OptimizedIO::open(temp.path().to_str().unwrap(), config).unwrap()
+    }
+
+    fn pattern_file(pattern: &[u8], size: usize) -> (NamedTempFile, Vec<u8>) {
+        let data: Vec<u8> = (0..size).map(|i| pattern[i % pattern.len()]).collect();
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), &data).unwrap();
+        (temp, data)
+    }
+
+    #[test]
+    fn test_policy_selects_passes() {
+        use crate::VerifyPasses;
+
+        let last = PassVerification { passes: VerifyPasses::parse("last").unwrap(), coverage: VerifyCoverage::Full };
+        assert!(!last.covers(1, 3));
+        assert!(last.covers(2, 3));
+        assert!(PassVerification { passes: VerifyPasses::EveryPass, ..Default::default() }.covers(0, 3));
+        // Library callers only get read-back when they ask for it
+        assert!(!PassVerification::default().covers(2, 3));
+        assert!(!crate::WipeConfig::default().pass_verification.covers(0, 1));
+
+        assert_eq!(VerifyCoverage::parse("2.5%").unwrap(), VerifyCoverage::Sample(2.5));
+        assert_eq!(VerifyCoverage::parse("100").unwrap(), VerifyCoverage::Full);
+        assert_eq!(VerifyCoverage::parse("FULL").unwrap(), VerifyCoverage::Full);
+        assert!(VerifyCoverage::parse("0").is_err());
+        assert!(VerifyCoverage::parse("150").is_err());
+        assert!(VerifyPasses::parse("some").is_err());
+    }
+
+    #[test]
+    fn test_regions_cover_requested_share() {
+        let size = 1000 * SAMPLE_SIZE;
+        let regions = PassVerifier::regions(0, size, VerifyCoverage::Sample(1.0), 4096, CHUNK_SIZE);
+        assert_eq!(regions.len(), 10);
+        assert!(regions.iter().all(|&(offset, len)| offset % 4096 == 0 && len == SAMPLE_SIZE));
+
+        let full = PassVerifier::regions(0, size + 100, VerifyCoverage::Full, 4096, CHUNK_SIZE);
+        assert_eq!(full.iter().map(|&(_, len)| len).sum::<u64>(), size + 100);
+
+        // Small drives still get one sample, resumed keystream passes none
+        // before their start
+        assert_eq!(PassVerifier::regions(0, 8192, VerifyCoverage::Sample(0.1), 4096, CHUNK_SIZE), vec![(0, 8192)]);
+        assert!(PassVerifier::regions(8192, 8192, VerifyCoverage::Full, 4096, CHUNK_SIZE).is_empty());
+        let resumed = PassVerifier::regions(3 * SAMPLE_SIZE, size, VerifyCoverage::Sample(50.0), 4096, CHUNK_SIZE);
+        assert!(resumed.iter().all(|&(offset, _)| offset >= 3 * SAMPLE_SIZE));
+    }
+
+    #[test]
+    fn test_pattern_mismatches_reported_as_lba_ranges() {
+        let pattern = [0x92, 0x49, 0x24];
+        let size = 2 * SAMPLE_SIZE as usize + 1000;
+        let (temp, mut data) = pattern_file(&pattern, size);
+
+        let mut handle = reader(&temp);
+        let report = PassVerifier::check(&mut handle, size as u64, &ExpectedData::Pattern(&pattern), VerifyCoverage::Full, &[]).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.bytes_checked, size as u64);
+
+        // Damage sectors 10-12 and 4000
+        data[10 * 512..13 * 512 - 1].fill(0);
+        data[4000 * 512 + 7] ^= 0xFF;
+        std::fs::write(temp.path(), &data).unwrap();
+        let mut handle = reader(&temp);
+        let sector_size = handle.sector_size();
+        assert_eq!(sector_size, 512);
+
+        let report = PassVerifier::check(&mut handle, size as u64, &ExpectedData::Pattern(&pattern), VerifyCoverage::Full, &[]).unwrap();
+        assert_eq!(report.mismatched_sectors, 4);
+        assert_eq!(report.mismatched_ranges, vec![(10, 12), (4000, 4000)]);
+        assert_eq!(report.describe_ranges(20), "10-12, 4000");
+        assert_eq!(report.describe_ranges(1), "10-12, ... 1 more");
+
+        // Sectors the wipe already recorded as unwritable are not mismatches
+        let report = PassVerifier::check(&mut handle, size as u64, &ExpectedData::Pattern(&pattern), VerifyCoverage::Full, &[10, 11, 12, 4000]).unwrap();
+        assert!(report.is_clean());
+
+        // A 1% sample reads one block at the start and finds the first range
+        let report = PassVerifier::check(&mut handle, size as u64, &ExpectedData::Pattern(&pattern), VerifyCoverage::Sample(1.0), &[]).unwrap();
+        assert_eq!(report.bytes_checked, SAMPLE_SIZE);
+        assert_eq!(report.mismatched_ranges, vec![(10, 12)]);
+    }
+
+    #[test]
+    fn test_keystream_pass_checked_from_its_start() {
+        let keystream = Keystream::generate().unwrap();
+        let size = 256 * 1024 + 100;
+        let mut data = vec![0u8; size];
+        keystream.fill_at(1, 0, &mut data).unwrap();
+        data[..4096].fill(0x11);  // Written under an earlier key
+
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), &data).unwrap();
+        let mut handle = reader(&temp);
+
+        let expected = ExpectedData::Keystream { keystream: &keystream, pass: 1, start: 4096 };
+        let report = PassVerifier::check(&mut handle, size as u64, &expected, VerifyCoverage::Full, &[]).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.bytes_checked, size as u64 - 4096);
+
+        let expected = ExpectedData::Keystream { keystream: &keystream, pass: 1, start: 0 };
+        let report = PassVerifier::check(&mut handle, size as u64, &expected, VerifyCoverage::Full, &[]).unwrap();
+        assert_eq!(report.mismatched_ranges, vec![(0, 7)]);
+
+        // Another pass's keystream matches nowhere
+        let expected = ExpectedData::Keystream { keystream: &keystream, pass: 2, start: 4096 };
+        let report = PassVerifier::check(&mut handle, size as u64, &expected, VerifyCoverage::Full, &[]).unwrap();
+        assert_eq!(report.mismatched_sectors, (size as u64 - 4096).div_ceil(512));
+    }
+
+    #[test]
+    fn test_random_pass_flags_low_entropy_blocks() {
+        let mut data = vec![0u8; 64 * 1024];
+        secure_random_bytes(&mut data).unwrap();
+        data[8192..12288].fill(0);
+
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), &data).unwrap();
+        let mut handle = reader(&temp);
+
+        let report = PassVerifier::check(&mut handle, data.len() as u64, &ExpectedData::Random, VerifyCoverage::Full, &[]).unwrap();
+        assert_eq!(report.mismatched_ranges, vec![(16, 23)]);
+    }
+
+    #[test]
+    fn test_verify_pass_fails_with_ranges() {
+        let (temp, mut data) = pattern_file(&[0x00], 64 * 1024);
+        let path = temp.path().to_str().unwrap();
+        data[512] = 1;
+        std::fs::write(temp.path(), &data).unwrap();
+
+        let options = OverwriteOptions {
+            verification: PassVerification { passes: VerifyPasses::EveryPass, coverage: VerifyCoverage::Full },
+            ..Default::default()
+        };
+        let err = PassVerifier::verify_pass(path, data.len() as u64, 2, &ExpectedData::Pattern(&[0x00]), &options, &[])
+            .unwrap_err();
+        assert!(err.to_string().contains("Pass 3 verification failed: 1 sectors"), "{}", err);
+        assert!(err.to_string().contains("LBAs 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_extent_mismatches_reported_as_device_lbas() {
+        let (temp, mut data) = pattern_file(&[0xFF], 64 * 1024);
+        // Outside the extent: not our business
+        data[..4096].fill(0);
+        data[40 * 512] = 0;
+        std::fs::write(temp.path(), &data).unwrap();
+
+        let mut handle = reader(&temp);
+        handle.restrict_to(Extent { offset: 8192, length: 48 * 1024 }).unwrap();
+        let report = PassVerifier::check(&mut handle, 48 * 1024, &ExpectedData::Pattern(&[0xFF]), VerifyCoverage::Full, &[40]).unwrap();
+        assert!(report.is_clean());
+        assert_eq!(report.bytes_checked, 48 * 1024);
+
+        let report = PassVerifier::check(&mut handle, 48 * 1024, &ExpectedData::Pattern(&[0xFF]), VerifyCoverage::Full, &[]).unwrap();
+        assert_eq!(report.mismatched_ranges, vec![(40, 40)]);
+    }
+}
@@ -8,17 +8,28 @@
 
 use anyhow::Result;
 use crate::ui::progress::ProgressBar;
-use crate::io::{OptimizedIO, IOConfig, IOHandle};
+use crate::io::{Checkpointer, OptimizedIO, IOConfig, IOHandle};
+use crate::algorithms::OverwriteOptions;
+use crate::algorithms::verify::{ExpectedData, PassVerifier};
 use crate::DriveType;
 
 pub struct ZeroWipe;
 
 impl ZeroWipe {
     /// Perform a single-pass zero wipe
-    pub fn wipe_drive(device_path: &str, size: u64, drive_type: DriveType) -> Result<()> {
+    pub fn wipe_drive(
+        device_path: &str,
+        size: u64,
+        drive_type: DriveType,
+        options: &OverwriteOptions,
+        checkpoint: &mut Checkpointer,
+    ) -> Result<()> {
         println!("Starting single-pass zero wipe on {}", device_path);
         println!("Drive size: {} bytes ({} GB)", size, size / (1024 * 1024 * 1024));
 
+        // Progress from every pass is attributed to this wipe
+        let _events = options.enter(device_path);
+
         // Configure I/O based on drive type
         let io_config = match drive_type {
             DriveType::NVMe => IOConfig::nvme_optimized(),
@@ -28,10 +39,11 @@ impl ZeroWipe {
         };
 
         // Open device with optimized I/O
-        let mut io_handle = OptimizedIO::open(device_path, io_config)?;
+        let mut io_handle = options.open(device_path, io_config)?;
 
         println!("\n🔄 Writing zeros to entire drive");
-        Self::write_zeros(&mut io_handle, size)?;
+        Self::write_zeros(&mut io_handle, size, checkpoint)?;
+        PassVerifier::after_pass(device_path, size, 0, 1, &ExpectedData::Pattern(&[0x00]), options, checkpoint)?;
 
         // Final sync
         io_handle.sync()?;
@@ -39,6 +51,8 @@ impl ZeroWipe {
         // Print performance report
         OptimizedIO::print_performance_report(&io_handle, None);
 
+        checkpoint.finish();
+
         println!("\n✅ Zero wipe completed successfully");
         println!("All sectors have been overwritten with zeros.");
 
@@ -46,11 +60,11 @@ impl ZeroWipe {
     }
 
     /// Write zeros to the entire drive
-    fn write_zeros(io_handle: &mut IOHandle, size: u64) -> Result<()> {
-        let mut bytes_written = 0u64;
+    fn write_zeros(io_handle: &mut IOHandle, size: u64, checkpoint: &mut Checkpointer) -> Result<()> {
+        let mut bytes_written = checkpoint.resume_offset(0);
         let mut bar = ProgressBar::new(48);
 
-        OptimizedIO::sequential_write(io_handle, size, |buffer| {
+        OptimizedIO::sequential_write_resumable(io_handle, size, checkpoint, 0, |buffer| {
             // Fill buffer with zeros
             let buf = buffer.as_mut_slice();
             buf.fill(0x00);
@@ -0,0 +1,225 @@
+// Per-operation control for wipes
+//
+// Each wipe runs under its own `CancellationToken` and `PauseToken` and may
+// carry its own `WipeObserver`, so a batch or an embedding application can
+// stop, pause or watch a single drive without affecting the others. Tokens
+// form a tree: cancelling or pausing a parent (the CLI's root on SIGINT,
+// SIGUSR1) applies to every child, while a child on its own leaves its
+// parent and siblings running.
+//
+// `QuietHours` is the daily window in which overwrites are throttled. A
+// context's `rate_limiter` is shared by all its children, capping a whole
+// batch in aggregate.
+
+use crate::events::{DeviceScope, WipeObserver};
+use crate::io::RateLimiter;
+use anyhow::{anyhow, Result};
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Cheaply cloneable cancellation flag; clones share the same state
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    parent: Option<CancellationToken>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that is cancelled with this one, but can also be cancelled on
+    /// its own
+    pub fn child(&self) -> Self {
+        Self {
+            state: Arc::new(TokenState { cancelled: AtomicBool::new(false), parent: Some(self.clone()) }),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+            || self.state.parent.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+/// Pause flag checked between writes; clones share the same state. A child
+/// is paused while it or any ancestor is, so resuming a child does not undo
+/// a pause of its parent.
+#[derive(Debug, Clone, Default)]
+pub struct PauseToken {
+    state: Arc<PauseState>,
+}
+
+#[derive(Debug, Default)]
+struct PauseState {
+    paused: AtomicBool,
+    parent: Option<PauseToken>,
+}
+
+impl PauseToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn child(&self) -> Self {
+        Self {
+            state: Arc::new(PauseState { paused: AtomicBool::new(false), parent: Some(self.clone()) }),
+        }
+    }
+
+    pub fn pause(&self) {
+        self.state.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.state.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::SeqCst)
+            || self.state.parent.as_ref().is_some_and(PauseToken::is_paused)
+    }
+}
+
+/// Everything one wipe operation is controlled and observed through
+#[derive(Clone, Default)]
+pub struct OperationContext {
+    pub cancel: CancellationToken,
+    pub pause: PauseToken,
+    pub observer: Option<Arc<dyn WipeObserver>>,  // Receives this operation's events only
+    pub rate_limiter: Option<Arc<RateLimiter>>,  // Shared with every child
+}
+
+impl OperationContext {
+    /// A context cancelled, paused and rate limited together with this one,
+    /// without its observer
+    pub fn child(&self) -> Self {
+        Self {
+            cancel: self.cancel.child(),
+            pause: self.pause.child(),
+            observer: None,
+            rate_limiter: self.rate_limiter.clone(),
+        }
+    }
+
+    /// Attribute events from the current thread to `device` and this
+    /// operation's observer until the returned scope is dropped
+    pub fn enter(&self, device: &str) -> DeviceScope {
+        DeviceScope::enter(device, self.observer.clone())
+    }
+}
+
+/// Daily window, in local time, during which overwrites are throttled to a
+/// bandwidth ceiling. A window whose end is before its start runs past
+/// midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub max_mb_per_sec: u64,
+}
+
+impl QuietHours {
+    /// Parse a window such as "08:00-18:00"
+    pub fn parse(window: &str, max_mb_per_sec: u64) -> Result<Self> {
+        let time = |s: &str| {
+            NaiveTime::parse_from_str(s.trim(), "%H:%M")
+                .map_err(|_| anyhow!("Invalid time '{}' in quiet hours (expected HH:MM)", s))
+        };
+        let (start, end) = window.split_once('-')
+            .ok_or_else(|| anyhow!("Invalid quiet hours: {} (expected HH:MM-HH:MM)", window))?;
+        if max_mb_per_sec == 0 {
+            return Err(anyhow!("Quiet hours bandwidth ceiling must be at least 1 MB/s"));
+        }
+
+        Ok(Self { start: time(start)?, end: time(end)?, max_mb_per_sec })
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// Whether the window is in effect right now
+    pub fn is_active(&self) -> bool {
+        self.contains(chrono::Local::now().time())
+    }
+
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.max_mb_per_sec * 1024 * 1024
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_tokens_cancel_independently() {
+        let root = CancellationToken::new();
+        let first = root.child();
+        let second = root.child();
+
+        first.cancel();
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        assert!(!root.is_cancelled());
+
+        // Clones share state
+        let clone = second.clone();
+        root.cancel();
+        assert!(second.is_cancelled());
+        assert!(clone.is_cancelled());
+        assert!(root.child().child().is_cancelled());
+    }
+
+    #[test]
+    fn test_pause_follows_parent() {
+        let root = OperationContext::default();
+        let drive = root.child();
+
+        drive.pause.pause();
+        assert!(drive.pause.is_paused() && !root.pause.is_paused());
+        drive.pause.resume();
+
+        // Resuming the drive does not override the pause of its parent
+        root.pause.pause();
+        drive.pause.resume();
+        assert!(drive.pause.is_paused());
+        root.pause.resume();
+        assert!(!drive.pause.is_paused());
+    }
+
+    #[test]
+    fn test_quiet_hours_window() {
+        let at = |s| NaiveTime::parse_from_str(s, "%H:%M").unwrap();
+
+        let day = QuietHours::parse("08:00-18:00", 50).unwrap();
+        assert!(day.contains(at("08:00")) && day.contains(at("17:59")));
+        assert!(!day.contains(at("18:00")) && !day.contains(at("07:59")));
+        assert_eq!(day.bytes_per_sec(), 50 * 1024 * 1024);
+
+        let night = QuietHours::parse("22:00-06:00", 10).unwrap();
+        assert!(night.contains(at("23:30")) && night.contains(at("05:00")));
+        assert!(!night.contains(at("12:00")));
+
+        assert!(QuietHours::parse("22:00", 10).is_err());
+        assert!(QuietHours::parse("8-18", 10).is_err());
+        assert!(QuietHours::parse("08:00-18:00", 0).is_err());
+    }
+}
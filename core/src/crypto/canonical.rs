@@ -0,0 +1,237 @@
+// Canonical JSON encoding (RFC 8785, JSON Canonicalization Scheme)
+//
+// Certificates are signed and hashed over this encoding rather than over
+// serde_json's output, so any tool that parses and re-serializes a
+// certificate (desktop app, blockchain scripts) reproduces the same bytes:
+// - object members sorted by UTF-16 code units of their names
+// - no insignificant whitespace
+// - strings escaped with the minimal JSON escape set
+// - numbers formatted like ECMAScript's Number.prototype.toString
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use sha2::{Sha256, Digest};
+
+/// Identifier recorded in certificates signed over this encoding
+pub const CANONICAL_ENCODING: &str = "RFC8785";
+
+/// Largest integer that survives a round trip through an IEEE 754 double
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Serialize a value to RFC 8785 canonical JSON bytes
+pub fn to_canonical_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let value = serde_json::to_value(value)?;
+    canonicalize(&value)
+}
+
+/// Canonicalize an already-parsed JSON value
+pub fn canonicalize(value: &Value) -> Result<Vec<u8>> {
+    let mut out = String::new();
+    write_value(&mut out, value)?;
+    Ok(out.into_bytes())
+}
+
+/// Hex-encoded SHA-256 of the canonical encoding
+pub fn canonical_sha256<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let bytes = to_canonical_bytes(value)?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(out, n)?,
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => write_object(out, map)?,
+    }
+
+    Ok(())
+}
+
+fn write_object(out: &mut String, map: &Map<String, Value>) -> Result<()> {
+    let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+    out.push('{');
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(out, key);
+        out.push(':');
+        write_value(out, value)?;
+    }
+    out.push('}');
+
+    Ok(())
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_number(out: &mut String, n: &Number) -> Result<()> {
+    if let Some(u) = n.as_u64() {
+        if u > MAX_SAFE_INTEGER {
+            return Err(anyhow!("Integer {} cannot be represented exactly in canonical JSON", u));
+        }
+        out.push_str(&u.to_string());
+    } else if let Some(i) = n.as_i64() {
+        if i.unsigned_abs() > MAX_SAFE_INTEGER {
+            return Err(anyhow!("Integer {} cannot be represented exactly in canonical JSON", i));
+        }
+        out.push_str(&i.to_string());
+    } else {
+        let f = n.as_f64().ok_or_else(|| anyhow!("Unsupported JSON number {}", n))?;
+        out.push_str(&format_f64(f)?);
+    }
+
+    Ok(())
+}
+
+/// Format a double the way ECMAScript's Number.prototype.toString does
+pub(crate) fn format_f64(value: f64) -> Result<String> {
+    if !value.is_finite() {
+        return Err(anyhow!("Non-finite number {} is not valid JSON", value));
+    }
+    if value == 0.0 {
+        return Ok("0".to_string()); // Also covers -0
+    }
+
+    // Rust's `{:e}` yields the shortest round-tripping digits, e.g. "-1.25e-7"
+    let formatted = format!("{:e}", value);
+    let (mantissa, exponent) = formatted.split_once('e')
+        .ok_or_else(|| anyhow!("Unexpected float format {}", formatted))?;
+    let exponent: i32 = exponent.parse()?;
+
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent + 1; // Position of the decimal point relative to the digits
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let exp_sign = if n - 1 < 0 { "-" } else { "+" };
+        let fraction = if k > 1 { format!(".{}", &digits[1..]) } else { String::new() };
+        format!("{}{}e{}{}", &digits[..1], fraction, exp_sign, (n - 1).abs())
+    };
+
+    Ok(format!("{}{}", sign, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_object_keys_sorted_and_compact() {
+        let value = json!({ "b": 1, "a": [true, null, "x"], "c": { "z": 0, "y": 1 } });
+        let bytes = canonicalize(&value).unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            r#"{"a":[true,null,"x"],"b":1,"c":{"y":1,"z":0}}"#
+        );
+    }
+
+    #[test]
+    fn test_keys_sorted_by_utf16_code_units() {
+        // RFC 8785 section 3.2.3 sorting example
+        let value: Value = serde_json::from_str(
+            r#"{"€":"Euro Sign","\r":"Carriage Return","דּ":"Hebrew Letter Dalet With Dagesh","1":"One","😀":"Emoji: Grinning Face","\u0080":"Control","ö":"Latin Small Letter O With Diaeresis"}"#
+        ).unwrap();
+        let canonical = String::from_utf8(canonicalize(&value).unwrap()).unwrap();
+        let order: Vec<&str> = ["\\r", "1", "\u{80}", "\u{f6}", "\u{20ac}", "\u{1f600}", "\u{fb33}"].to_vec();
+
+        let mut last = 0;
+        for key in order {
+            let pos = canonical.find(&format!("\"{}\":", key)).unwrap();
+            assert!(pos >= last, "key {:?} out of order in {}", key, canonical);
+            last = pos;
+        }
+    }
+
+    #[test]
+    fn test_string_escaping() {
+        let value = json!("quote\" backslash\\ tab\t nl\n ctrl\u{1f} slash/ é");
+        let canonical = String::from_utf8(canonicalize(&value).unwrap()).unwrap();
+        assert_eq!(canonical, "\"quote\\\" backslash\\\\ tab\\t nl\\n ctrl\\u001f slash/ é\"");
+    }
+
+    #[test]
+    fn test_number_formatting_matches_ecmascript() {
+        // Values from RFC 8785 appendix B plus common certificate values
+        let cases: &[(f64, &str)] = &[
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (100.0, "100"),
+            (7.95, "7.95"),
+            (-1.5, "-1.5"),
+            (1e21, "1e+21"),
+            (1e20, "100000000000000000000"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+            (5e-324, "5e-324"),
+            (0.000001, "0.000001"),
+            (0.0000001, "1e-7"),
+            (333333333.3333333, "333333333.3333333"),
+            (295147905179352830000.0, "295147905179352830000"),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(format_f64(*value).unwrap(), *expected, "formatting {}", value);
+        }
+    }
+
+    #[test]
+    fn test_non_finite_and_unsafe_integers_rejected() {
+        assert!(format_f64(f64::NAN).is_err());
+        assert!(format_f64(f64::INFINITY).is_err());
+        assert!(canonicalize(&json!(u64::MAX)).is_err());
+        assert!(canonicalize(&json!(MAX_SAFE_INTEGER)).is_ok());
+    }
+
+    #[test]
+    fn test_reserialization_is_stable() {
+        let value = json!({ "size": 500107862016u64, "score": 7.9, "name": "WD Blue" });
+        let first = canonicalize(&value).unwrap();
+
+        let pretty = serde_json::to_string_pretty(&value).unwrap();
+        let reparsed: Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(canonicalize(&reparsed).unwrap(), first);
+    }
+}
@@ -1,8 +1,20 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow, Context};
 use chrono::{DateTime, Utc};
+use ring::rand::SystemRandom;
+use ring::signature::{
+    self, EcdsaKeyPair, Ed25519KeyPair, KeyPair, UnparsedPublicKey,
+    ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_ASN1_SIGNING,
+};
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
+use super::canonical::{self, CANONICAL_ENCODING};
+
+/// Environment variable that overrides the default signing key location
+pub const SIGNING_KEY_ENV: &str = "SAYONARA_SIGNING_KEY";
+
+/// Default location of the PKCS#8 signing key
+pub const DEFAULT_SIGNING_KEY_PATH: &str = "/etc/sayonara/signing_key.pk8";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WipeCertificate {
@@ -11,7 +23,8 @@ pub struct WipeCertificate {
     pub wipe_details: WipeDetails,
     pub verification: VerificationResult,
     pub timestamp: DateTime<Utc>,
-    pub signature: String,
+    pub signer: SignerInfo,
+    pub signature: String, // Hex-encoded signature over the certificate (excluding this field)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +42,65 @@ pub struct WipeDetails {
     pub passes_completed: u32,
     pub duration_seconds: u64,
     pub operator_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sanitization: Option<crate::policy::SanitizationRecord>, // NIST 800-88 policy decision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nvme_sanitize: Option<crate::drives::NvmeSanitizeRecord>, // Final Sanitize Status log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unwritable_sectors: Option<UnwritableSectors>, // LBAs skipped as unwritable, left unsanitized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystream_commitment: Option<String>, // SHA-256 of the discarded AES-CTR pass key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<crate::drives::ResolvedScope>, // Part of the device wiped; None for all of it
+}
+
+/// Sectors an overwrite had to skip, summarized so the certificate stays the
+/// same size however many a failing drive has. The digest commits to the full
+/// list, which the wipe checkpoint keeps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnwritableSectors {
+    pub count: u64,
+    pub range_count: u64,
+    pub ranges: Vec<LbaRange>, // First MAX_LISTED_RANGES inclusive ranges
+    pub sha256: String, // Over the ascending LBAs as 8-byte little-endian values
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LbaRange {
+    pub first: u64,
+    pub last: u64,
+}
+
+impl UnwritableSectors {
+    pub const MAX_LISTED_RANGES: usize = 64;
+
+    /// Summarize `lbas` (ascending, as recorded by the checkpoint); None if empty
+    pub fn from_lbas(lbas: &[u64]) -> Option<Self> {
+        use sha2::{Sha256, Digest};
+
+        if lbas.is_empty() {
+            return None;
+        }
+
+        let mut hasher = Sha256::new();
+        let mut ranges: Vec<LbaRange> = Vec::new();
+        for &lba in lbas {
+            hasher.update(lba.to_le_bytes());
+            match ranges.last_mut() {
+                Some(range) if range.last + 1 == lba => range.last = lba,
+                _ => ranges.push(LbaRange { first: lba, last: lba }),
+            }
+        }
+
+        let range_count = ranges.len() as u64;
+        ranges.truncate(Self::MAX_LISTED_RANGES);
+        Some(Self {
+            count: lbas.len() as u64,
+            range_count,
+            ranges,
+            sha256: format!("{:x}", hasher.finalize()),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,18 +111,190 @@ pub struct VerificationResult {
     pub verification_timestamp: DateTime<Utc>,
 }
 
+/// Public-key signature scheme used to sign a certificate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SignatureAlgorithm {
+    Ed25519,
+    EcdsaP256Sha256,
+}
+
+/// Identifies the key that signed a certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerInfo {
+    pub algorithm: SignatureAlgorithm,
+    pub encoding: String,        // Canonical encoding of the signed payload
+    pub public_key: String,      // Hex-encoded raw public key
+    pub key_fingerprint: String, // SHA-256 of the raw public key
+}
+
+impl SignerInfo {
+    /// Raw public key as recorded by the signer. This is only trustworthy once
+    /// its fingerprint has been matched against an independently trusted key.
+    pub fn public_key_bytes(&self) -> Result<Vec<u8>> {
+        hex_decode(&self.public_key)
+    }
+
+    /// Check a hex signature over `payload` made by this signer with `public_key`
+    pub(crate) fn verify_payload(&self, payload: &[u8], signature_hex: &str, public_key: &[u8]) -> bool {
+        if key_fingerprint(public_key) != self.key_fingerprint || self.encoding != CANONICAL_ENCODING {
+            return false;
+        }
+
+        let signature = match hex_decode(signature_hex) {
+            Ok(sig) => sig,
+            Err(_) => return false,
+        };
+
+        let algorithm: &dyn signature::VerificationAlgorithm = match self.algorithm {
+            SignatureAlgorithm::Ed25519 => &signature::ED25519,
+            SignatureAlgorithm::EcdsaP256Sha256 => &ECDSA_P256_SHA256_ASN1,
+        };
+
+        UnparsedPublicKey::new(algorithm, public_key)
+            .verify(payload, &signature)
+            .is_ok()
+    }
+}
+
+/// Signing keypair loaded from a PKCS#8 document
+enum SigningKey {
+    Ed25519(Ed25519KeyPair),
+    EcdsaP256(EcdsaKeyPair),
+}
+
 pub struct CertificateGenerator {
-    private_key: String, // In practice, use proper key management
+    key: SigningKey,
+    rng: SystemRandom,
 }
 
 impl CertificateGenerator {
-    pub fn new() -> Self {
-        // In production, load this from secure key store
-        Self {
-            private_key: "your-private-signing-key".to_string(),
+    /// Load the signing key from `$SAYONARA_SIGNING_KEY` or the default path.
+    /// Fails if there is no key yet; see `create_default_key`.
+    pub fn new() -> Result<Self> {
+        Self::from_key_file(Self::default_key_path())
+    }
+
+    /// Generate an Ed25519 key at the default path unless one exists there.
+    /// Returns whether a key was created.
+    pub fn create_default_key() -> Result<bool> {
+        let path = Self::default_key_path();
+        if path.exists() {
+            return Ok(false);
+        }
+
+        Self::generate_key_file(&path)?;
+        log::info!("Generated Ed25519 signing key at {}", path.display());
+        Ok(true)
+    }
+
+    /// Path of the signing key used by `new()`
+    pub fn default_key_path() -> PathBuf {
+        std::env::var_os(SIGNING_KEY_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SIGNING_KEY_PATH))
+    }
+
+    /// Load a signing keypair from a PKCS#8 file (Ed25519 or ECDSA P-256)
+    pub fn from_key_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let pkcs8 = std::fs::read(path)
+            .with_context(|| format!("Failed to read signing key {}", path.display()))?;
+
+        Self::from_pkcs8(&pkcs8)
+            .with_context(|| format!("Invalid signing key {}", path.display()))
+    }
+
+    /// Load a signing keypair from PKCS#8 DER bytes
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self> {
+        let rng = SystemRandom::new();
+
+        let key = if let Ok(pair) = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8) {
+            SigningKey::Ed25519(pair)
+        } else {
+            let pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8, &rng)
+                .map_err(|e| anyhow!("Unsupported PKCS#8 key (expected Ed25519 or ECDSA P-256): {}", e))?;
+            SigningKey::EcdsaP256(pair)
+        };
+
+        Ok(Self { key, rng })
+    }
+
+    /// Generate a new Ed25519 keypair and write it as PKCS#8 with mode 0600
+    pub fn generate_key_file<P: AsRef<Path>>(path: P) -> Result<()> {
+        let path = path.as_ref();
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng)
+            .map_err(|_| anyhow!("Failed to generate Ed25519 keypair"))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        #[cfg(unix)]
+        {
+            use std::io::Write;
+            use std::os::unix::fs::OpenOptionsExt;
+
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(path)
+                .with_context(|| format!("Failed to create signing key {}", path.display()))?;
+            file.write_all(pkcs8.as_ref())?;
+        }
+
+        #[cfg(not(unix))]
+        std::fs::write(path, pkcs8.as_ref())?;
+
+        Ok(())
+    }
+
+    /// Signature scheme of the loaded key
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match &self.key {
+            SigningKey::Ed25519(_) => SignatureAlgorithm::Ed25519,
+            SigningKey::EcdsaP256(_) => SignatureAlgorithm::EcdsaP256Sha256,
+        }
+    }
+
+    /// Raw public key bytes (32-byte Ed25519 key or uncompressed P-256 point)
+    pub fn public_key_bytes(&self) -> &[u8] {
+        match &self.key {
+            SigningKey::Ed25519(pair) => pair.public_key().as_ref(),
+            SigningKey::EcdsaP256(pair) => pair.public_key().as_ref(),
+        }
+    }
+
+    /// Public half of the signing key, as published to third-party verifiers
+    pub fn signer_info(&self) -> SignerInfo {
+        let public_key = self.public_key_bytes();
+
+        SignerInfo {
+            algorithm: self.algorithm(),
+            encoding: CANONICAL_ENCODING.to_string(),
+            public_key: hex_encode(public_key),
+            key_fingerprint: key_fingerprint(public_key),
         }
     }
-    
+
+    /// Sign arbitrary bytes with the loaded key (ECDSA signatures are ASN.1 DER)
+    pub(crate) fn sign_bytes(&self, message: &[u8]) -> Result<Vec<u8>> {
+        match &self.key {
+            SigningKey::Ed25519(pair) => Ok(pair.sign(message).as_ref().to_vec()),
+            SigningKey::EcdsaP256(pair) => Ok(pair.sign(&self.rng, message)
+                .map_err(|_| anyhow!("ECDSA signing failed"))?
+                .as_ref()
+                .to_vec()),
+        }
+    }
+
+    /// Write the hex-encoded public key so auditors can verify certificates
+    pub fn export_public_key<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, format!("{}\n", self.signer_info().public_key))?;
+        Ok(())
+    }
+
     pub fn generate_certificate(
         &self,
         device_info: &crate::DriveInfo,
@@ -58,7 +302,14 @@ impl CertificateGenerator {
         verification: VerificationResult,
     ) -> Result<WipeCertificate> {
         let certificate_id = Uuid::new_v4().to_string();
-        
+
+        // Sectors that were never written are not sanitized, whatever the
+        // read-back found
+        let mut verification = verification;
+        if wipe_details.unwritable_sectors.is_some() {
+            verification.verified = false;
+        }
+
         let device_cert_info = DeviceCertInfo {
             device_path: device_info.device_path.clone(),
             model: device_info.model.clone(),
@@ -66,52 +317,295 @@ impl CertificateGenerator {
             size: device_info.size,
             device_hash: self.calculate_device_hash(device_info)?,
         };
-        
+
         let mut certificate = WipeCertificate {
             certificate_id,
             device_info: device_cert_info,
             wipe_details,
             verification,
             timestamp: Utc::now(),
+            signer: self.signer_info(),
             signature: String::new(), // Will be filled by signing
         };
-        
+
         certificate.signature = self.sign_certificate(&certificate)?;
-        
+
         Ok(certificate)
     }
-    
+
     fn calculate_device_hash(&self, device_info: &crate::DriveInfo) -> Result<String> {
-        let mut hasher = Sha256::new();
-        hasher.update(device_info.model.as_bytes());
-        hasher.update(device_info.serial.as_bytes());
-        hasher.update(&device_info.size.to_le_bytes());
-        
-        Ok(format!("{:x}", hasher.finalize()))
+        canonical::canonical_sha256(&serde_json::json!({
+            "model": device_info.model,
+            "serial": device_info.serial,
+            "size": device_info.size,
+        }))
+    }
+
+    /// Bytes covered by the signature: the canonical certificate without its
+    /// `signature` member
+    pub fn signing_payload(certificate: &WipeCertificate) -> Result<Vec<u8>> {
+        let mut value = serde_json::to_value(certificate)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("signature");
+        }
+
+        canonical::canonicalize(&value)
     }
-    
+
+    /// SHA-256 of the complete signed certificate in canonical form, suitable
+    /// for anchoring (e.g. on-chain) independent of how the file was formatted
+    pub fn certificate_hash(certificate: &WipeCertificate) -> Result<String> {
+        canonical::canonical_sha256(certificate)
+    }
+
     fn sign_certificate(&self, certificate: &WipeCertificate) -> Result<String> {
-        // Create a signing payload (excluding the signature field)
-        let mut signing_data = certificate.clone();
-        signing_data.signature = String::new();
-        
-        let json_data = serde_json::to_string(&signing_data)?;
-        
-        let mut hasher = Sha256::new();
-        hasher.update(json_data.as_bytes());
-        hasher.update(self.private_key.as_bytes());
-        
-        Ok(format!("{:x}", hasher.finalize()))
+        let payload = Self::signing_payload(certificate)?;
+        Ok(hex_encode(&self.sign_bytes(&payload)?))
     }
-    
+
+    /// Verify a certificate against this generator's own public key
     pub fn verify_certificate(&self, certificate: &WipeCertificate) -> Result<bool> {
-        let expected_signature = self.sign_certificate(certificate)?;
-        Ok(expected_signature == certificate.signature)
+        Self::verify_with_public_key(certificate, self.public_key_bytes())
+    }
+
+    /// Verify a certificate using only a trusted public key.
+    ///
+    /// The key must match the fingerprint recorded in the certificate; the
+    /// public key embedded in the certificate itself is never trusted.
+    pub fn verify_with_public_key(certificate: &WipeCertificate, public_key: &[u8]) -> Result<bool> {
+        let payload = Self::signing_payload(certificate)?;
+        Ok(certificate.signer.verify_payload(&payload, &certificate.signature, public_key))
+    }
+
+    /// Verify a certificate using a hex-encoded public key file (see `export_public_key`)
+    pub fn verify_with_public_key_file<P: AsRef<Path>>(certificate: &WipeCertificate, path: P) -> Result<bool> {
+        Self::verify_with_public_key(certificate, &Self::read_public_key_file(path)?)
     }
-    
+
+    /// Read a hex-encoded public key file written by `export_public_key`
+    pub fn read_public_key_file<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read public key {}", path.display()))?;
+
+        hex_decode(contents.trim())
+            .with_context(|| format!("Invalid public key {}", path.display()))
+    }
+
     pub fn save_certificate(&self, certificate: &WipeCertificate, path: &str) -> Result<()> {
         let json_data = serde_json::to_string_pretty(certificate)?;
         std::fs::write(path, json_data)?;
         Ok(())
     }
+
+    /// Load a certificate saved by `save_certificate` (extra fields such as
+    /// `enhanced_verification` are ignored)
+    pub fn load_certificate<P: AsRef<Path>>(path: P) -> Result<WipeCertificate> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read certificate {}", path.display()))?;
+
+        serde_json::from_str(&json)
+            .with_context(|| format!("Invalid certificate {}", path.display()))
+    }
+}
+
+/// SHA-256 fingerprint of a raw public key
+pub fn key_fingerprint(public_key: &[u8]) -> String {
+    use sha2::{Sha256, Digest};
+    format!("{:x}", Sha256::digest(public_key))
+}
+
+pub(crate) fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn hex_decode(s: &str) -> Result<Vec<u8>> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| anyhow!("Invalid hex at position {}", i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_certificate, test_drive, test_verification, test_wipe_details};
+    use tempfile::TempDir;
+
+    fn test_generator(dir: &TempDir) -> CertificateGenerator {
+        let key_path = dir.path().join("signing_key.pk8");
+        CertificateGenerator::generate_key_file(&key_path).unwrap();
+        CertificateGenerator::from_key_file(&key_path).unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify_with_public_key_only() {
+        let dir = TempDir::new().unwrap();
+        let generator = test_generator(&dir);
+        let certificate = test_certificate(&generator);
+
+        assert_eq!(certificate.signer.algorithm, SignatureAlgorithm::Ed25519);
+        assert!(generator.verify_certificate(&certificate).unwrap());
+
+        let pub_path = dir.path().join("signing_key.pub");
+        generator.export_public_key(&pub_path).unwrap();
+        assert!(CertificateGenerator::verify_with_public_key_file(&certificate, &pub_path).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_certificate_fails_verification() {
+        let dir = TempDir::new().unwrap();
+        let generator = test_generator(&dir);
+        let mut certificate = test_certificate(&generator);
+
+        certificate.wipe_details.passes_completed = 35;
+        assert!(!generator.verify_certificate(&certificate).unwrap());
+    }
+
+    #[test]
+    fn test_wrong_public_key_rejected() {
+        let dir = TempDir::new().unwrap();
+        let generator = test_generator(&dir);
+        let certificate = test_certificate(&generator);
+
+        let other_dir = TempDir::new().unwrap();
+        let other = test_generator(&other_dir);
+        let other_key = hex_decode(&other.signer_info().public_key).unwrap();
+
+        assert!(!CertificateGenerator::verify_with_public_key(&certificate, &other_key).unwrap());
+    }
+
+    #[test]
+    fn test_ecdsa_p256_key() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let generator = CertificateGenerator::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let certificate = test_certificate(&generator);
+
+        assert_eq!(certificate.signer.algorithm, SignatureAlgorithm::EcdsaP256Sha256);
+        assert!(generator.verify_certificate(&certificate).unwrap());
+    }
+
+    #[test]
+    fn test_generate_key_file_does_not_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let key_path = dir.path().join("signing_key.pk8");
+        CertificateGenerator::generate_key_file(&key_path).unwrap();
+        assert!(CertificateGenerator::generate_key_file(&key_path).is_err());
+    }
+
+    /// Fixed Ed25519 key (PKCS#8 v1, seed 0x01..0x20) so golden signatures are reproducible
+    fn golden_generator() -> CertificateGenerator {
+        let mut pkcs8 = hex_decode("302e020100300506032b657004220420").unwrap();
+        pkcs8.extend(1u8..=32);
+        CertificateGenerator::from_pkcs8(&pkcs8).unwrap()
+    }
+
+    fn golden_certificate(generator: &CertificateGenerator) -> WipeCertificate {
+        let timestamp = DateTime::parse_from_rfc3339("2025-01-15T10:30:00Z").unwrap().with_timezone(&Utc);
+        let mut certificate = WipeCertificate {
+            certificate_id: "0b1c6a2e-3f5d-4c8e-9a7b-1d2e3f4a5b6c".to_string(),
+            device_info: DeviceCertInfo {
+                device_path: "/dev/sdz".to_string(),
+                model: "Test Model".to_string(),
+                serial: "SN12345".to_string(),
+                size: 500 * 1024 * 1024 * 1024,
+                device_hash: generator.calculate_device_hash(&test_drive("SN12345")).unwrap(),
+            },
+            wipe_details: test_wipe_details(),
+            verification: VerificationResult {
+                verification_timestamp: timestamp,
+                ..test_verification()
+            },
+            timestamp,
+            signer: generator.signer_info(),
+            signature: String::new(),
+        };
+        certificate.signature = generator.sign_certificate(&certificate).unwrap();
+        certificate
+    }
+
+    #[test]
+    fn test_unwritable_sectors_summary() {
+        assert_eq!(UnwritableSectors::from_lbas(&[]), None);
+
+        let summary = UnwritableSectors::from_lbas(&[10, 11, 12, 1500]).unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.ranges, vec![LbaRange { first: 10, last: 12 }, LbaRange { first: 1500, last: 1500 }]);
+        assert_ne!(summary.sha256, UnwritableSectors::from_lbas(&[10, 11, 12, 1501]).unwrap().sha256);
+
+        let scattered: Vec<u64> = (0..10_000).map(|i| i * 2).collect();
+        let summary = UnwritableSectors::from_lbas(&scattered).unwrap();
+        assert_eq!(summary.range_count, 10_000);
+        assert_eq!(summary.ranges.len(), UnwritableSectors::MAX_LISTED_RANGES);
+    }
+
+    #[test]
+    fn test_unwritable_sectors_are_never_verified() {
+        let dir = TempDir::new().unwrap();
+        let generator = test_generator(&dir);
+        let mut certificate = test_certificate(&generator);
+        assert!(certificate.verification.verified);
+
+        certificate.wipe_details.unwritable_sectors = UnwritableSectors::from_lbas(&[7]);
+        let certificate = generator.generate_certificate(
+            &test_drive("SN12345"), certificate.wipe_details, certificate.verification).unwrap();
+        assert!(!certificate.verification.verified);
+        assert!(generator.verify_certificate(&certificate).unwrap());
+    }
+
+    #[test]
+    fn test_golden_device_hash() {
+        let generator = golden_generator();
+        assert_eq!(
+            generator.calculate_device_hash(&test_drive("SN12345")).unwrap(),
+            "3b31dd6aa1f889791863c47e472f4621da4dc2066340d2b41aeb6d4f3df2ef6e"
+        );
+    }
+
+    #[test]
+    fn test_golden_signing_payload() {
+        let generator = golden_generator();
+        let certificate = golden_certificate(&generator);
+
+        let payload = CertificateGenerator::signing_payload(&certificate).unwrap();
+        assert_eq!(
+            String::from_utf8(payload).unwrap(),
+            include_str!("testdata/certificate_payload.golden").trim_end()
+        );
+    }
+
+    #[test]
+    fn test_golden_certificate_verifies_after_reformatting() {
+        let generator = golden_generator();
+        let golden: WipeCertificate =
+            serde_json::from_str(include_str!("testdata/certificate.golden.json")).unwrap();
+
+        // Ed25519 is deterministic, so regenerating must reproduce the golden signature
+        let regenerated = golden_certificate(&generator);
+        assert_eq!(regenerated.signature, golden.signature);
+        assert_eq!(
+            CertificateGenerator::certificate_hash(&golden).unwrap(),
+            CertificateGenerator::certificate_hash(&regenerated).unwrap()
+        );
+
+        // Re-serializing with different layout must not affect verification
+        let compact = serde_json::to_string(&golden).unwrap();
+        let reparsed: WipeCertificate = serde_json::from_str(&compact).unwrap();
+        let public_key = hex_decode(&golden.signer.public_key).unwrap();
+        assert!(CertificateGenerator::verify_with_public_key(&reparsed, &public_key).unwrap());
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let data = [0x00, 0x7f, 0x80, 0xff];
+        assert_eq!(hex_encode(&data), "007f80ff");
+        assert_eq!(hex_decode("007f80ff").unwrap(), data);
+        assert!(hex_decode("abc").is_err());
+    }
 }
@@ -0,0 +1,142 @@
+// Reproducible random passes from an AES-256-CTR keystream
+//
+// A DRBG pass can only be checked statistically afterwards, since nobody
+// knows what was written. Here the data at any offset is the keystream of a
+// per-wipe key, so the verifier regenerates the expected bytes of any sample
+// and compares them exactly. Once the wipe is verified the key is dropped
+// (and zeroed); the certificate keeps only a SHA-256 commitment to it.
+//
+// ring exposes AES only through its AEADs. AES-GCM encrypts with AES-CTR
+// starting at counter block `nonce || 2`, so sealing zeros yields exactly the
+// CTR keystream; the tag is discarded.
+
+use anyhow::{Result, anyhow};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
+use sha2::{Digest, Sha256};
+use crate::crypto::certificates::hex_encode;
+use crate::crypto::secure_rng::secure_random_bytes;
+
+/// Bytes per nonce. Each 4 KiB unit of a pass has its own counter space, so
+/// a sample at any offset is regenerated from its unit alone.
+const UNIT: usize = 4096;
+
+/// Domain separation for the key commitment
+const COMMITMENT_CONTEXT: &[u8] = b"sayonara-wipe keystream v1";
+
+/// Per-wipe AES-256 key for keystream passes
+pub struct Keystream {
+    key: [u8; 32],
+}
+
+impl Keystream {
+    /// Draw a fresh key from the secure RNG
+    pub fn generate() -> Result<Self> {
+        let mut key = [0u8; 32];
+        secure_random_bytes(&mut key)?;
+        Ok(Self { key })
+    }
+
+    /// Hex SHA-256 commitment to the key, recorded in the certificate. Whoever
+    /// escrows the key can prove it was the one used; the hash alone reveals
+    /// nothing about the data written.
+    pub fn commitment(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(COMMITMENT_CONTEXT);
+        hasher.update(self.key);
+        hex_encode(&hasher.finalize())
+    }
+
+    /// Fill `dest` with the keystream of `pass` starting at byte `offset`
+    pub fn fill_at(&self, pass: usize, offset: u64, dest: &mut [u8]) -> Result<()> {
+        let key = UnboundKey::new(&AES_256_GCM, &self.key)
+            .map_err(|_| anyhow!("Invalid keystream key"))?;
+        let key = LessSafeKey::new(key);
+        let mut partial = [0u8; UNIT];
+
+        let mut done = 0;
+        while done < dest.len() {
+            let absolute = offset + done as u64;
+            let unit = absolute / UNIT as u64;
+            let within = (absolute % UNIT as u64) as usize;
+            let take = (UNIT - within).min(dest.len() - done);
+
+            // Whole units are generated in place, partial ones via `partial`
+            let out = if take == UNIT { &mut dest[done..done + UNIT] } else { &mut partial[..] };
+            out.fill(0);
+            let _tag = key.seal_in_place_separate_tag(Self::nonce(pass, unit), Aad::empty(), out)
+                .map_err(|_| anyhow!("Keystream generation failed at offset {}", absolute))?;
+            if take != UNIT {
+                dest[done..done + take].copy_from_slice(&partial[within..within + take]);
+            }
+
+            done += take;
+        }
+
+        Ok(())
+    }
+
+    /// 96-bit nonce: pass (32 bits) || unit index (64 bits)
+    fn nonce(pass: usize, unit: u64) -> Nonce {
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(&(pass as u32).to_be_bytes());
+        nonce[4..].copy_from_slice(&unit.to_be_bytes());
+        Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+impl Drop for Keystream {
+    fn drop(&mut self) {
+        for byte in self.key.iter_mut() {
+            // SAFETY: plain write to an owned byte; volatile so it is not elided
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::secure_rng::verify_randomness;
+
+    fn keystream(byte: u8) -> Keystream {
+        Keystream { key: [byte; 32] }
+    }
+
+    #[test]
+    fn test_matches_aes_ctr() {
+        // AES-256-GCM seals with CTR from counter block nonce || 2; with a
+        // zero key and nonce the first keystream block is AES_0(0^12 || 00000002)
+        let mut block = [0u8; 16];
+        keystream(0).fill_at(0, 0, &mut block).unwrap();
+        assert_eq!(hex_encode(&block), "cea7403d4d606b6e074ec5d3baf39d18");
+    }
+
+    #[test]
+    fn test_any_offset_regenerates_the_same_bytes() {
+        let ks = keystream(7);
+        let mut whole = vec![0u8; 4 * UNIT];
+        ks.fill_at(3, 0, &mut whole).unwrap();
+        assert!(verify_randomness(&whole).unwrap());
+
+        // Unaligned and unit-straddling slices agree with the full run
+        for (offset, len) in [(0usize, 16usize), (100, 5000), (UNIT - 1, 2), (3 * UNIT + 17, UNIT - 17)] {
+            let mut part = vec![0u8; len];
+            ks.fill_at(3, offset as u64, &mut part).unwrap();
+            assert_eq!(part, whole[offset..offset + len]);
+        }
+
+        // Passes and keys get unrelated streams
+        let mut other = vec![0u8; UNIT];
+        ks.fill_at(4, 0, &mut other).unwrap();
+        assert_ne!(other, whole[..UNIT]);
+        keystream(8).fill_at(3, 0, &mut other).unwrap();
+        assert_ne!(other, whole[..UNIT]);
+    }
+
+    #[test]
+    fn test_commitment_binds_key() {
+        assert_eq!(keystream(1).commitment(), keystream(1).commitment());
+        assert_ne!(keystream(1).commitment(), keystream(2).commitment());
+        assert_eq!(keystream(1).commitment().len(), 64);
+    }
+}
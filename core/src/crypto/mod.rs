@@ -1,8 +1,17 @@
+pub mod canonical;
 pub mod certificates;
+pub mod keystream;
+pub mod pkcs7;
+pub(crate) mod random_pipeline;
+pub mod report;
+pub mod revocation;
 pub(crate) mod secure_rng;
 
 #[cfg(test)]
 mod secure_rng_tests;
 
 // Re-export
-pub use certificates::{CertificateGenerator, WipeCertificate, WipeDetails, VerificationResult};
+pub use certificates::{
+    CertificateGenerator, WipeCertificate, WipeDetails, VerificationResult,
+    SignerInfo, SignatureAlgorithm,
+};
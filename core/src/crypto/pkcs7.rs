@@ -0,0 +1,726 @@
+// CMS / PKCS#7 SignedData export of wipe certificates (RFC 5652)
+//
+// The canonical JSON certificate is embedded as id-data content and signed by
+// the operator's X.509 certificate, so `openssl cms -verify` and other PKI
+// tooling can open and validate it. Ed25519 signers follow RFC 8419 (SHA-512
+// message digest), ECDSA P-256 signers use SHA-256.
+
+use anyhow::{Result, anyhow, bail, Context};
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use ring::digest;
+use ring::signature::{UnparsedPublicKey, ED25519, ECDSA_P256_SHA256_ASN1};
+use std::path::Path;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::pem::Pem;
+use x509_parser::prelude::{ASN1Time, FromDer};
+
+use super::canonical;
+use super::certificates::{CertificateGenerator, SignatureAlgorithm, WipeCertificate};
+
+/// PEM label used for exported SignedData blobs
+pub const PKCS7_PEM_LABEL: &str = "PKCS7";
+
+/// Longest issuer chain followed from the signer to a trust anchor
+const MAX_CHAIN_DEPTH: usize = 8;
+
+const OID_DATA: &[u64] = &[1, 2, 840, 113549, 1, 7, 1];
+const OID_SIGNED_DATA: &[u64] = &[1, 2, 840, 113549, 1, 7, 2];
+const OID_CONTENT_TYPE: &[u64] = &[1, 2, 840, 113549, 1, 9, 3];
+const OID_MESSAGE_DIGEST: &[u64] = &[1, 2, 840, 113549, 1, 9, 4];
+const OID_SIGNING_TIME: &[u64] = &[1, 2, 840, 113549, 1, 9, 5];
+const OID_SHA256: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 1];
+const OID_SHA512: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 3];
+const OID_ED25519: &[u64] = &[1, 3, 101, 112];
+const OID_ECDSA_SHA256: &[u64] = &[1, 2, 840, 10045, 4, 3, 2];
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_0: u8 = 0xA0;
+
+/// Outcome of a successful SignedData verification
+#[derive(Debug, Clone)]
+pub struct Pkcs7Verification {
+    pub certificate: WipeCertificate,
+    pub signer_subject: String,
+    /// Subjects from the signer up to the trust anchor
+    pub chain: Vec<String>,
+    pub signing_time: Option<DateTime<Utc>>,
+    /// Whether the embedded certificate's own signature matches the signer key
+    pub inner_signature_valid: bool,
+}
+
+/// Wrap a signed wipe certificate in a DER-encoded CMS SignedData blob.
+///
+/// `chain_pem` holds the operator certificate first, followed by any
+/// intermediates; its public key must be the generator's signing key.
+pub fn export_pkcs7(
+    generator: &CertificateGenerator,
+    certificate: &WipeCertificate,
+    chain_pem: &[u8],
+) -> Result<Vec<u8>> {
+    let chain = pem_certificates(chain_pem)?;
+    let leaf_der = chain.first()
+        .ok_or_else(|| anyhow!("Operator certificate chain is empty"))?;
+    let (_, leaf) = X509Certificate::from_der(leaf_der)
+        .map_err(|e| anyhow!("Invalid operator certificate: {}", e))?;
+
+    if leaf.public_key().subject_public_key.data.as_ref() != generator.public_key_bytes() {
+        bail!("Operator certificate {} does not match the signing key", leaf.subject());
+    }
+    check_signing_usage(&leaf)?;
+
+    let content = canonical::to_canonical_bytes(certificate)?;
+    let (digest_oid, digest_alg, signature_oid) = match generator.algorithm() {
+        SignatureAlgorithm::Ed25519 => (OID_SHA512, &digest::SHA512, OID_ED25519),
+        SignatureAlgorithm::EcdsaP256Sha256 => (OID_SHA256, &digest::SHA256, OID_ECDSA_SHA256),
+    };
+    let message_digest = digest::digest(digest_alg, &content);
+
+    let mut attributes = vec![
+        attribute(OID_CONTENT_TYPE, &oid(OID_DATA)),
+        attribute(OID_MESSAGE_DIGEST, &tlv(TAG_OCTET_STRING, message_digest.as_ref())),
+        attribute(OID_SIGNING_TIME, &signing_time(Utc::now())),
+    ];
+    attributes.sort(); // DER SET OF ordering
+    let signed_attrs = set(&attributes);
+
+    // The signature covers the attributes with their universal SET tag,
+    // while the SignerInfo carries them as [0] IMPLICIT
+    let signature = generator.sign_bytes(&signed_attrs)?;
+    let mut implicit_attrs = signed_attrs;
+    implicit_attrs[0] = TAG_CONTEXT_0;
+
+    let signer_info = sequence(&[
+        integer(1),
+        sequence(&[leaf.issuer().as_raw().to_vec(), tlv(TAG_INTEGER, leaf.raw_serial())]),
+        algorithm_identifier(digest_oid),
+        implicit_attrs,
+        algorithm_identifier(signature_oid),
+        tlv(TAG_OCTET_STRING, &signature),
+    ]);
+
+    let signed_data = sequence(&[
+        integer(1),
+        set(&[algorithm_identifier(digest_oid)]),
+        sequence(&[oid(OID_DATA), tlv(TAG_CONTEXT_0, &tlv(TAG_OCTET_STRING, &content))]),
+        tlv(TAG_CONTEXT_0, &chain.concat()),
+        set(&[signer_info]),
+    ]);
+
+    Ok(sequence(&[oid(OID_SIGNED_DATA), tlv(TAG_CONTEXT_0, &signed_data)]))
+}
+
+/// PEM-armour a DER SignedData blob
+pub fn to_pem(der: &[u8]) -> String {
+    let encoded = base64::encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", PKCS7_PEM_LABEL);
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(&String::from_utf8_lossy(line));
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", PKCS7_PEM_LABEL));
+    pem
+}
+
+/// Export a certificate to a `.p7s` file, DER by default or PEM when requested
+pub fn save_pkcs7<P: AsRef<Path>>(
+    generator: &CertificateGenerator,
+    certificate: &WipeCertificate,
+    chain_pem: &[u8],
+    path: P,
+    pem: bool,
+) -> Result<()> {
+    let der = export_pkcs7(generator, certificate, chain_pem)?;
+    if pem {
+        std::fs::write(path, to_pem(&der))?;
+    } else {
+        std::fs::write(path, der)?;
+    }
+    Ok(())
+}
+
+/// Verify a DER or PEM SignedData blob against the trust anchors in `ca_pem`.
+///
+/// The chain is validated at the signed signing time when present, so
+/// archived certificates stay verifiable after the operator certificate expires.
+pub fn verify_pkcs7(data: &[u8], ca_pem: &[u8]) -> Result<Pkcs7Verification> {
+    let der = if data.starts_with(b"-----BEGIN") {
+        let (_, pem) = x509_parser::pem::parse_x509_pem(data)
+            .map_err(|e| anyhow!("Invalid PEM input: {}", e))?;
+        pem.contents
+    } else {
+        data.to_vec()
+    };
+
+    let anchors_der = pem_certificates(ca_pem)?;
+    if anchors_der.is_empty() {
+        bail!("No CA certificates found in trust anchor file");
+    }
+
+    // ContentInfo
+    let content_info = expect(&der, TAG_SEQUENCE)?;
+    let [content_type, wrapped] = children::<2>(content_info.content)?;
+    expect_oid(&content_type, OID_SIGNED_DATA, "content type")?;
+    let signed_data = expect(nested(&wrapped, TAG_CONTEXT_0)?, TAG_SEQUENCE)?;
+
+    // SignedData
+    let fields = all_children(signed_data.content)?;
+    let encap = fields.get(2)
+        .ok_or_else(|| anyhow!("SignedData is missing encapsulated content"))?;
+    let certificates = fields.iter()
+        .find(|f| f.tag == TAG_CONTEXT_0)
+        .map(|f| all_children(f.content))
+        .transpose()?
+        .unwrap_or_default();
+    let signer_infos = fields.last()
+        .filter(|f| f.tag == TAG_SET)
+        .ok_or_else(|| anyhow!("SignedData has no signerInfos"))?;
+
+    let [encap_type, encap_content] = children::<2>(encap.content)?;
+    expect_oid(&encap_type, OID_DATA, "encapsulated content type")?;
+    let content = expect(nested(&encap_content, TAG_CONTEXT_0)?, TAG_OCTET_STRING)?.content;
+
+    // SignerInfo
+    let signer = all_children(signer_infos.content)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("SignedData has no signers"))?;
+    let signer_fields = all_children(signer.content)?;
+    if signer_fields.len() < 6 {
+        bail!("SignerInfo must carry signed attributes");
+    }
+    let [sid_issuer, sid_serial] = children::<2>(signer_fields[1].content)?;
+    let digest_oid = algorithm_oid(&signer_fields[2])?;
+    let attrs = &signer_fields[3];
+    if attrs.tag != TAG_CONTEXT_0 {
+        bail!("SignerInfo must carry signed attributes");
+    }
+    let signature_oid = algorithm_oid(&signer_fields[4])?;
+    let signature = expect(signer_fields[5].raw, TAG_OCTET_STRING)?.content;
+
+    // Locate the signer certificate by issuer and serial number
+    let embedded: Vec<X509Certificate> = certificates.iter()
+        .map(|c| X509Certificate::from_der(c.raw)
+            .map(|(_, cert)| cert)
+            .map_err(|e| anyhow!("Invalid embedded certificate: {}", e)))
+        .collect::<Result<_>>()?;
+    let leaf = embedded.iter()
+        .find(|c| c.issuer().as_raw() == sid_issuer.raw && c.raw_serial() == sid_serial.content)
+        .ok_or_else(|| anyhow!("Signer certificate is not embedded in the SignedData"))?;
+    check_signing_usage(leaf)?;
+
+    // Signed attributes
+    let (digest_alg, expected_sig_oid): (&'static digest::Algorithm, &[u64]) = match digest_oid.as_slice() {
+        d if d == encode_oid(OID_SHA512) => (&digest::SHA512, OID_ED25519),
+        d if d == encode_oid(OID_SHA256) => (&digest::SHA256, OID_ECDSA_SHA256),
+        _ => bail!("Unsupported digest algorithm"),
+    };
+    let mut content_type_ok = false;
+    let mut digest_ok = false;
+    let mut signing_time = None;
+    for attr in all_children(attrs.content)? {
+        let [attr_type, values] = children::<2>(attr.content)?;
+        let value = all_children(values.content)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Signed attribute has no value"))?;
+
+        if attr_type.content == encode_oid(OID_CONTENT_TYPE) {
+            content_type_ok = value.tag == TAG_OID && value.content == encode_oid(OID_DATA);
+        } else if attr_type.content == encode_oid(OID_MESSAGE_DIGEST) {
+            digest_ok = value.content == digest::digest(digest_alg, content).as_ref();
+        } else if attr_type.content == encode_oid(OID_SIGNING_TIME) {
+            signing_time = Some(parse_time(&value)?);
+        }
+    }
+    if !content_type_ok {
+        bail!("Signed content type attribute is missing or not id-data");
+    }
+    if !digest_ok {
+        bail!("Message digest does not match the embedded certificate (content was modified)");
+    }
+
+    // Signer signature over the DER SET OF attributes
+    if signature_oid != encode_oid(expected_sig_oid) {
+        bail!("Signature algorithm does not match the digest algorithm");
+    }
+    let mut signed_attrs = attrs.raw.to_vec();
+    signed_attrs[0] = TAG_SET;
+    let leaf_key = leaf.public_key().subject_public_key.data.as_ref();
+    let verification_alg: &dyn ring::signature::VerificationAlgorithm =
+        if expected_sig_oid == OID_ED25519 { &ED25519 } else { &ECDSA_P256_SHA256_ASN1 };
+    UnparsedPublicKey::new(verification_alg, leaf_key)
+        .verify(&signed_attrs, signature)
+        .map_err(|_| anyhow!("SignedData signature is invalid"))?;
+
+    // Chain to a trust anchor
+    let anchors: Vec<X509Certificate> = anchors_der.iter()
+        .map(|c| X509Certificate::from_der(c)
+            .map(|(_, cert)| cert)
+            .map_err(|e| anyhow!("Invalid CA certificate: {}", e)))
+        .collect::<Result<_>>()?;
+    let validation_time = match signing_time {
+        Some(time) => ASN1Time::from_timestamp(time.timestamp())
+            .map_err(|e| anyhow!("Invalid signing time: {}", e))?,
+        None => ASN1Time::now(),
+    };
+    let chain = build_chain(leaf, &embedded, &anchors, validation_time)?;
+
+    // The embedded certificate carries its own signature by the same key
+    let certificate: WipeCertificate = serde_json::from_slice(content)
+        .context("Embedded content is not a wipe certificate")?;
+    let inner_signature_valid = CertificateGenerator::verify_with_public_key(&certificate, leaf_key)
+        .unwrap_or(false);
+
+    Ok(Pkcs7Verification {
+        certificate,
+        signer_subject: leaf.subject().to_string(),
+        chain,
+        signing_time,
+        inner_signature_valid,
+    })
+}
+
+/// Walk issuer links from the signer to one of the anchors
+fn build_chain(
+    leaf: &X509Certificate,
+    intermediates: &[X509Certificate],
+    anchors: &[X509Certificate],
+    at: ASN1Time,
+) -> Result<Vec<String>> {
+    let mut chain = Vec::new();
+    let mut current = leaf;
+
+    for _ in 0..MAX_CHAIN_DEPTH {
+        if !current.validity().is_valid_at(at) {
+            bail!("Certificate {} is not valid at the signing time", current.subject());
+        }
+        chain.push(current.subject().to_string());
+
+        // Operator certificate pinned directly as an anchor
+        if anchors.iter().any(|a| a.as_ref() == current.as_ref()) {
+            return Ok(chain);
+        }
+
+        if let Some(anchor) = anchors.iter().find(|a| is_issuer(a, current, at)) {
+            chain.push(anchor.subject().to_string());
+            return Ok(chain);
+        }
+
+        current = intermediates.iter()
+            .find(|c| is_issuer(c, current, at))
+            .ok_or_else(|| anyhow!(
+                "No trusted issuer found for {} (issuer {})", current.subject(), current.issuer()
+            ))?;
+    }
+
+    bail!("Certificate chain exceeds {} certificates", MAX_CHAIN_DEPTH)
+}
+
+fn is_issuer(candidate: &X509Certificate, subject: &X509Certificate, at: ASN1Time) -> bool {
+    candidate.subject().as_raw() == subject.issuer().as_raw()
+        && candidate.is_ca()
+        && candidate.validity().is_valid_at(at)
+        && subject.verify_signature(Some(candidate.public_key())).is_ok()
+}
+
+/// DER certificates from a PEM bundle, skipping non-certificate blocks
+fn pem_certificates(pem: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut certificates = Vec::new();
+    for block in Pem::iter_from_buffer(pem) {
+        let block = block.map_err(|e| anyhow!("Invalid PEM data: {}", e))?;
+        if block.label == "CERTIFICATE" {
+            certificates.push(block.contents);
+        }
+    }
+    Ok(certificates)
+}
+
+fn parse_time(value: &Tlv) -> Result<DateTime<Utc>> {
+    let text = std::str::from_utf8(value.content)?;
+    let format = match value.tag {
+        TAG_UTC_TIME => "%y%m%d%H%M%SZ",
+        TAG_GENERALIZED_TIME => "%Y%m%d%H%M%SZ",
+        _ => bail!("Unsupported signing time encoding"),
+    };
+    let naive = NaiveDateTime::parse_from_str(text, format)
+        .with_context(|| format!("Invalid signing time {}", text))?;
+    // UTCTime years 50-99 are 1950-1999 (RFC 5280 §4.1.2.5.1); chrono pivots at 69
+    if value.tag == TAG_UTC_TIME && naive.year() >= 2050 {
+        let year = naive.year() - 100;
+        return naive.with_year(year)
+            .map(|t| t.and_utc())
+            .ok_or_else(|| anyhow!("Invalid signing time {}", text));
+    }
+    Ok(naive.and_utc())
+}
+
+// Minimal DER writer
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len.to_be_bytes().iter().copied().skip_while(|b| *b == 0).collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend_from_slice(&bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn sequence(parts: &[Vec<u8>]) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, &parts.concat())
+}
+
+fn set(parts: &[Vec<u8>]) -> Vec<u8> {
+    tlv(TAG_SET, &parts.concat())
+}
+
+fn integer(value: u8) -> Vec<u8> {
+    tlv(TAG_INTEGER, &[value])
+}
+
+fn encode_oid(arcs: &[u64]) -> Vec<u8> {
+    let mut out = vec![(arcs[0] * 40 + arcs[1]) as u8];
+    for &arc in &arcs[2..] {
+        let mut chunk = vec![(arc & 0x7f) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            chunk.push(0x80 | (rest & 0x7f) as u8);
+            rest >>= 7;
+        }
+        out.extend(chunk.iter().rev());
+    }
+    out
+}
+
+fn oid(arcs: &[u64]) -> Vec<u8> {
+    tlv(TAG_OID, &encode_oid(arcs))
+}
+
+/// AlgorithmIdentifier with absent parameters (RFC 5754, RFC 8419)
+fn algorithm_identifier(arcs: &[u64]) -> Vec<u8> {
+    sequence(&[oid(arcs)])
+}
+
+fn attribute(arcs: &[u64], value: &[u8]) -> Vec<u8> {
+    sequence(&[oid(arcs), set(&[value.to_vec()])])
+}
+
+/// Time encoding per RFC 5652 §11.3: UTCTime for 1950-2049, GeneralizedTime otherwise
+fn signing_time(time: DateTime<Utc>) -> Vec<u8> {
+    if (1950..2050).contains(&time.year()) {
+        tlv(TAG_UTC_TIME, time.format("%y%m%d%H%M%SZ").to_string().as_bytes())
+    } else {
+        tlv(TAG_GENERALIZED_TIME, time.format("%Y%m%d%H%M%SZ").to_string().as_bytes())
+    }
+}
+
+/// The signer certificate must permit digitalSignature when it carries a key usage extension
+fn check_signing_usage(cert: &X509Certificate) -> Result<()> {
+    let usage = cert.key_usage()
+        .map_err(|e| anyhow!("Invalid key usage extension in {}: {}", cert.subject(), e))?;
+    match usage {
+        Some(usage) if !usage.value.digital_signature() => {
+            bail!("Certificate {} does not permit digitalSignature", cert.subject())
+        }
+        _ => Ok(()),
+    }
+}
+
+// Minimal DER reader
+
+struct Tlv<'a> {
+    tag: u8,
+    content: &'a [u8],
+    raw: &'a [u8],
+}
+
+fn read_tlv(input: &[u8]) -> Result<(Tlv<'_>, &[u8])> {
+    let truncated = || anyhow!("Truncated DER data");
+    let tag = *input.first().ok_or_else(truncated)?;
+    let first = *input.get(1).ok_or_else(truncated)?;
+
+    let (len, header) = if first < 0x80 {
+        (first as usize, 2)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 {
+            bail!("Indefinite-length (BER) encoding is not supported");
+        }
+        if count > 4 {
+            bail!("DER length too large");
+        }
+        let bytes = input.get(2..2 + count).ok_or_else(truncated)?;
+        (bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize), 2 + count)
+    };
+
+    let end = header.checked_add(len).ok_or_else(truncated)?;
+    let raw = input.get(..end).ok_or_else(truncated)?;
+    Ok((Tlv { tag, content: &raw[header..], raw }, &input[end..]))
+}
+
+fn all_children(mut content: &[u8]) -> Result<Vec<Tlv<'_>>> {
+    let mut items = Vec::new();
+    while !content.is_empty() {
+        let (item, rest) = read_tlv(content)?;
+        items.push(item);
+        content = rest;
+    }
+    Ok(items)
+}
+
+fn children<const N: usize>(content: &[u8]) -> Result<[Tlv<'_>; N]> {
+    let items = all_children(content)?;
+    let found = items.len();
+    items.try_into()
+        .map_err(|_| anyhow!("Expected {} DER elements, found {}", N, found))
+}
+
+fn expect(input: &[u8], tag: u8) -> Result<Tlv<'_>> {
+    let (item, _) = read_tlv(input)?;
+    if item.tag != tag {
+        bail!("Unexpected DER tag 0x{:02x} (expected 0x{:02x})", item.tag, tag);
+    }
+    Ok(item)
+}
+
+fn nested<'a>(item: &Tlv<'a>, tag: u8) -> Result<&'a [u8]> {
+    if item.tag != tag {
+        bail!("Unexpected DER tag 0x{:02x} (expected 0x{:02x})", item.tag, tag);
+    }
+    Ok(item.content)
+}
+
+fn expect_oid(item: &Tlv, arcs: &[u64], what: &str) -> Result<()> {
+    if item.tag != TAG_OID || item.content != encode_oid(arcs) {
+        bail!("Unsupported {}", what);
+    }
+    Ok(())
+}
+
+fn algorithm_oid(item: &Tlv) -> Result<Vec<u8>> {
+    let oid = all_children(item.content)?
+        .into_iter()
+        .next()
+        .filter(|o| o.tag == TAG_OID)
+        .ok_or_else(|| anyhow!("Malformed AlgorithmIdentifier"))?;
+    Ok(oid.content.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_drive, test_verification, test_wipe_details};
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    const OID_COMMON_NAME: &[u64] = &[2, 5, 4, 3];
+    const OID_BASIC_CONSTRAINTS: &[u64] = &[2, 5, 29, 19];
+    const OID_KEY_USAGE: &[u64] = &[2, 5, 29, 15];
+
+    fn name(cn: &str) -> Vec<u8> {
+        sequence(&[set(&[sequence(&[oid(OID_COMMON_NAME), tlv(0x0C, cn.as_bytes())])])])
+    }
+
+    /// Self-contained Ed25519 X.509 v3 certificate for tests
+    fn make_cert(subject: &str, issuer: &str, subject_key: &[u8], issuer_key: &Ed25519KeyPair,
+                 serial: u8, ca: bool) -> Vec<u8> {
+        make_cert_with_usage(subject, issuer, subject_key, issuer_key, serial, ca, None)
+    }
+
+    /// `key_usage` is the first byte of the KeyUsage bit string (0x80 = digitalSignature)
+    fn make_cert_with_usage(subject: &str, issuer: &str, subject_key: &[u8], issuer_key: &Ed25519KeyPair,
+                            serial: u8, ca: bool, key_usage: Option<u8>) -> Vec<u8> {
+        let validity = sequence(&[
+            tlv(TAG_UTC_TIME, b"200101000000Z"),
+            tlv(TAG_GENERALIZED_TIME, b"20991231235959Z"),
+        ]);
+        let mut bit_string = vec![0u8];
+        bit_string.extend_from_slice(subject_key);
+        let spki = sequence(&[algorithm_identifier(OID_ED25519), tlv(0x03, &bit_string)]);
+
+        let mut tbs_fields = vec![
+            tlv(TAG_CONTEXT_0, &integer(2)),
+            integer(serial),
+            algorithm_identifier(OID_ED25519),
+            name(issuer),
+            validity,
+            name(subject),
+            spki,
+        ];
+        let mut extensions = Vec::new();
+        if ca {
+            let constraints = sequence(&[tlv(0x01, &[0xff])]);
+            let extension = sequence(&[
+                oid(OID_BASIC_CONSTRAINTS),
+                tlv(0x01, &[0xff]),
+                tlv(TAG_OCTET_STRING, &constraints),
+            ]);
+            extensions.push(extension);
+        }
+        if let Some(bits) = key_usage {
+            extensions.push(sequence(&[
+                oid(OID_KEY_USAGE),
+                tlv(0x01, &[0xff]),
+                tlv(TAG_OCTET_STRING, &tlv(0x03, &[0x00, bits])),
+            ]));
+        }
+        if !extensions.is_empty() {
+            tbs_fields.push(tlv(0xA3, &sequence(&extensions)));
+        }
+        let tbs = sequence(&tbs_fields);
+
+        let mut signature = vec![0u8];
+        signature.extend_from_slice(issuer_key.sign(&tbs).as_ref());
+        sequence(&[tbs, algorithm_identifier(OID_ED25519), tlv(0x03, &signature)])
+    }
+
+    fn pem_cert(der: &[u8]) -> String {
+        to_pem(der).replace(PKCS7_PEM_LABEL, "CERTIFICATE")
+    }
+
+    fn new_key() -> (Vec<u8>, Ed25519KeyPair) {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        (pkcs8.as_ref().to_vec(), pair)
+    }
+
+    struct Pki {
+        generator: CertificateGenerator,
+        chain_pem: String,
+        ca_pem: String,
+    }
+
+    fn test_pki() -> Pki {
+        let (_, ca_key) = new_key();
+        let (operator_pkcs8, operator_key) = new_key();
+        let ca = make_cert("Sayonara Test CA", "Sayonara Test CA",
+                           ca_key.public_key().as_ref(), &ca_key, 1, true);
+        let operator = make_cert("Wipe Operator", "Sayonara Test CA",
+                                 operator_key.public_key().as_ref(), &ca_key, 2, false);
+
+        Pki {
+            generator: CertificateGenerator::from_pkcs8(&operator_pkcs8).unwrap(),
+            chain_pem: pem_cert(&operator),
+            ca_pem: pem_cert(&ca),
+        }
+    }
+
+    fn sample_certificate(generator: &CertificateGenerator) -> WipeCertificate {
+        generator.generate_certificate(&test_drive("PKCS7-0001"), test_wipe_details(), test_verification()).unwrap()
+    }
+
+    #[test]
+    fn test_export_and_verify_roundtrip() {
+        let pki = test_pki();
+        let certificate = sample_certificate(&pki.generator);
+        let der = export_pkcs7(&pki.generator, &certificate, pki.chain_pem.as_bytes()).unwrap();
+
+        let result = verify_pkcs7(&der, pki.ca_pem.as_bytes()).unwrap();
+        assert_eq!(result.certificate.certificate_id, certificate.certificate_id);
+        assert!(result.inner_signature_valid);
+        assert_eq!(result.chain.len(), 2);
+        assert!(result.signer_subject.contains("Wipe Operator"));
+        assert!(result.signing_time.is_some());
+
+        // PEM armour verifies the same way
+        let pem = to_pem(&der);
+        assert!(verify_pkcs7(pem.as_bytes(), pki.ca_pem.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_content_rejected() {
+        let pki = test_pki();
+        let certificate = sample_certificate(&pki.generator);
+        let mut der = export_pkcs7(&pki.generator, &certificate, pki.chain_pem.as_bytes()).unwrap();
+
+        let needle = b"PKCS7-0001";
+        let pos = der.windows(needle.len()).position(|w| w == needle).unwrap();
+        der[pos] = b'X';
+
+        let err = verify_pkcs7(&der, pki.ca_pem.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("Message digest"));
+    }
+
+    #[test]
+    fn test_untrusted_ca_rejected() {
+        let pki = test_pki();
+        let other = test_pki();
+        let certificate = sample_certificate(&pki.generator);
+        let der = export_pkcs7(&pki.generator, &certificate, pki.chain_pem.as_bytes()).unwrap();
+
+        assert!(verify_pkcs7(&der, other.ca_pem.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_chain_must_match_signing_key() {
+        let pki = test_pki();
+        let other = test_pki();
+        let certificate = sample_certificate(&pki.generator);
+
+        assert!(export_pkcs7(&pki.generator, &certificate, other.chain_pem.as_bytes()).is_err());
+        assert!(export_pkcs7(&pki.generator, &certificate, b"").is_err());
+    }
+
+    #[test]
+    fn test_signer_requires_digital_signature_usage() {
+        let (_, ca_key) = new_key();
+        let (operator_pkcs8, operator_key) = new_key();
+        let generator = CertificateGenerator::from_pkcs8(&operator_pkcs8).unwrap();
+        let certificate = sample_certificate(&generator);
+
+        // keyEncipherment only
+        let encipher_only = make_cert_with_usage("Wipe Operator", "Sayonara Test CA",
+                                                 operator_key.public_key().as_ref(), &ca_key, 2, false, Some(0x20));
+        let err = export_pkcs7(&generator, &certificate, pem_cert(&encipher_only).as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("digitalSignature"));
+
+        let signing = make_cert_with_usage("Wipe Operator", "Sayonara Test CA",
+                                           operator_key.public_key().as_ref(), &ca_key, 2, false, Some(0x80));
+        assert!(export_pkcs7(&generator, &certificate, pem_cert(&signing).as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_signing_time_encoding() {
+        let now = DateTime::parse_from_rfc3339("2049-12-31T23:59:59Z").unwrap().with_timezone(&Utc);
+        let encoded = signing_time(now);
+        assert_eq!(encoded[0], TAG_UTC_TIME);
+        let (parsed, _) = read_tlv(&encoded).unwrap();
+        assert_eq!(parse_time(&parsed).unwrap(), now);
+
+        let later = DateTime::parse_from_rfc3339("2050-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let encoded = signing_time(later);
+        assert_eq!(encoded[0], TAG_GENERALIZED_TIME);
+        assert_eq!(&encoded[2..], b"20500101000000Z");
+        let (parsed, _) = read_tlv(&encoded).unwrap();
+        assert_eq!(parse_time(&parsed).unwrap(), later);
+
+        // UTCTime 75 is 1975, not 2075
+        let encoded = tlv(TAG_UTC_TIME, b"750101000000Z");
+        let (parsed, _) = read_tlv(&encoded).unwrap();
+        assert_eq!(parse_time(&parsed).unwrap().year(), 1975);
+    }
+
+    #[test]
+    fn test_der_helpers() {
+        assert_eq!(encode_oid(OID_SHA256), vec![0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01]);
+
+        let long = tlv(TAG_OCTET_STRING, &[0u8; 300]);
+        assert_eq!(&long[..4], &[0x04, 0x82, 0x01, 0x2c]);
+        let (parsed, rest) = read_tlv(&long).unwrap();
+        assert_eq!(parsed.content.len(), 300);
+        assert!(rest.is_empty());
+
+        assert!(read_tlv(&[0x30, 0x80, 0x00, 0x00]).is_err());
+        assert!(read_tlv(&[0x30, 0x05, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+// Parallel random data generation for overwrite passes
+
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::JoinHandle;
+use crate::crypto::keystream::Keystream;
+use crate::crypto::secure_rng::{secure_random_bytes, ContinuousTest, HmacDrbg};
+use crate::io::buffer_pool::{BufferPool, PooledBuffer};
+
+/// Bytes a worker generates before reseeding its DRBG, matching the global
+/// `SecureRNG` reseed interval
+const RESEED_INTERVAL: u64 = 1 << 32;
+
+/// Upper bound on generator threads; beyond this the writer is the bottleneck
+const MAX_WORKERS: usize = 8;
+
+/// Random data generated ahead of the writer.
+///
+/// `secure_random_bytes` serializes every caller through the global RNG
+/// mutex. Here each worker thread owns an HMAC-DRBG seeded from that RNG and
+/// fills pooled buffers into a bounded queue, so the writer only copies. The
+/// queue applies backpressure: workers block once `workers` buffers are
+/// waiting.
+pub struct RandomPipeline {
+    filled: Option<Receiver<Result<PooledBuffer>>>,
+    workers: Vec<JoinHandle<()>>,
+    buffer_size: usize,
+}
+
+impl RandomPipeline {
+    /// Start `workers` generator threads producing `buffer_size` byte buffers
+    pub fn start(buffer_size: usize, workers: usize) -> Result<Self> {
+        let workers = workers.max(1);
+        let (sender, receiver) = mpsc::sync_channel(workers);
+
+        // Each worker holds one buffer, the queue holds `workers` more and
+        // the writer one, so the pool never runs dry
+        let pool = Arc::new(BufferPool::new(buffer_size, 8, 2 * workers + 1));
+
+        let mut handles = Vec::with_capacity(workers);
+        for id in 0..workers {
+            let worker = Worker::new(id, pool.clone())?;
+            let sender = sender.clone();
+            let handle = std::thread::Builder::new()
+                .name(format!("random-gen-{}", id))
+                .spawn(move || worker.run(sender))
+                .map_err(|e| anyhow!("Failed to start random generator thread: {}", e))?;
+            handles.push(handle);
+        }
+
+        Ok(Self {
+            filled: Some(receiver),
+            workers: handles,
+            buffer_size,
+        })
+    }
+
+    /// One worker per core, up to `MAX_WORKERS`
+    pub fn default_workers() -> usize {
+        num_cpus::get().clamp(1, MAX_WORKERS)
+    }
+
+    /// Fill `dest` with random bytes from the generated buffers
+    pub fn fill(&mut self, dest: &mut [u8]) -> Result<()> {
+        let filled = self.filled.as_ref()
+            .ok_or_else(|| anyhow!("Random pipeline stopped"))?;
+
+        for chunk in dest.chunks_mut(self.buffer_size) {
+            let buffer = filled.recv()
+                .map_err(|_| anyhow!("Random generator threads exited"))??;
+            chunk.copy_from_slice(&buffer.as_slice()[..chunk.len()]);
+        }
+
+        Ok(())
+    }
+}
+
+/// Where a random pass gets its data
+pub enum RandomSource<'a> {
+    Drbg(RandomPipeline),
+    /// The keystream of `pass`, tracking the offset of the next fill
+    Keystream { keystream: &'a Keystream, pass: usize, offset: u64 },
+}
+
+impl<'a> RandomSource<'a> {
+    /// Data for `pass` resuming at `offset`: the keystream if there is one,
+    /// otherwise a DRBG pipeline producing `buffer_size` byte buffers
+    pub fn start(keystream: Option<&'a Keystream>, pass: usize, offset: u64, buffer_size: usize) -> Result<Self> {
+        Ok(match keystream {
+            Some(keystream) => RandomSource::Keystream { keystream, pass, offset },
+            None => RandomSource::Drbg(RandomPipeline::start(buffer_size, RandomPipeline::default_workers())?),
+        })
+    }
+
+    /// Fill the next `dest.len()` bytes of the pass
+    pub fn fill(&mut self, dest: &mut [u8]) -> Result<()> {
+        match self {
+            RandomSource::Drbg(pipeline) => pipeline.fill(dest),
+            RandomSource::Keystream { keystream, pass, offset } => {
+                keystream.fill_at(*pass, *offset, dest)?;
+                *offset += dest.len() as u64;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// One generator thread's state
+struct Worker {
+    id: usize,
+    drbg: HmacDrbg,
+    since_reseed: u64,
+    continuous_test: ContinuousTest,
+    pool: Arc<BufferPool>,
+}
+
+impl Worker {
+    fn new(id: usize, pool: Arc<BufferPool>) -> Result<Self> {
+        Ok(Self {
+            id,
+            drbg: Self::seeded_drbg(id)?,
+            since_reseed: 0,
+            continuous_test: ContinuousTest::new(),
+            pool,
+        })
+    }
+
+    fn seeded_drbg(id: usize) -> Result<HmacDrbg> {
+        let mut seed = [0u8; 56];
+        secure_random_bytes(&mut seed[..48])?;
+        // Personalization keeps workers apart even if the seeds were to repeat
+        seed[48..].copy_from_slice(&(id as u64).to_le_bytes());
+        Ok(HmacDrbg::new(&seed))
+    }
+
+    fn run(mut self, sender: SyncSender<Result<PooledBuffer>>) {
+        loop {
+            let result = self.next_buffer();
+            let failed = result.is_err();
+            // A closed queue means the pipeline was dropped
+            if sender.send(result).is_err() || failed {
+                return;
+            }
+        }
+    }
+
+    fn next_buffer(&mut self) -> Result<PooledBuffer> {
+        if self.since_reseed >= RESEED_INTERVAL || self.drbg.needs_reseed() {
+            self.drbg = Self::seeded_drbg(self.id)?;
+            self.since_reseed = 0;
+        }
+
+        let mut buffer = self.pool.acquire()?;
+        self.drbg.generate(buffer.as_mut_slice())?;
+        self.since_reseed += buffer.size() as u64;
+
+        if !self.continuous_test.test(buffer.as_slice()) {
+            return Err(anyhow!("FIPS 140-2 continuous test failed"));
+        }
+        Ok(buffer)
+    }
+}
+
+impl Drop for RandomPipeline {
+    fn drop(&mut self) {
+        // Closing the queue wakes any worker blocked on a full one
+        drop(self.filled.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::secure_rng::verify_randomness;
+
+    #[test]
+    fn test_pipeline_output_is_random() -> Result<()> {
+        let mut pipeline = RandomPipeline::start(64 * 1024, 4)?;
+
+        let mut data = vec![0u8; 1024 * 1024];
+        pipeline.fill(&mut data)?;
+        assert!(verify_randomness(&data)?);
+
+        // No two buffers repeat, whichever worker produced them
+        let mut starts: Vec<&[u8]> = data.chunks(64 * 1024).map(|c| &c[..32]).collect();
+        starts.sort();
+        starts.dedup();
+        assert_eq!(starts.len(), 16);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fill_spans_and_splits_buffers() -> Result<()> {
+        let mut pipeline = RandomPipeline::start(4096, 2)?;
+
+        let mut large = vec![0u8; 3 * 4096 + 100];
+        pipeline.fill(&mut large)?;
+        assert!(large[3 * 4096..].iter().any(|&b| b != 0));
+
+        let mut small = [0u8; 16];
+        pipeline.fill(&mut small)?;
+        assert_ne!(small, [0u8; 16]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drop_stops_blocked_workers() -> Result<()> {
+        let pipeline = RandomPipeline::start(4096, 3)?;
+        // Let the workers fill the queue and block on it
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        drop(pipeline);
+        Ok(())
+    }
+}
@@ -0,0 +1,707 @@
+// Printable wipe certificates (PDF and plain text)
+//
+// Turns a signed `WipeCertificate`, plus the `VerificationReport` embedded by
+// enhanced wipes, into a disposal report for customers. The PDF only uses the
+// standard Type 1 fonts, so nothing is embedded or fetched, and it carries a
+// QR code identifying the signed certificate (ID, hash and signature) so a
+// scanned report can be matched against the certificate file offline.
+
+use anyhow::{Result, anyhow, Context};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+use qrcode::{Color, EcLevel, QrCode};
+use serde_json::Value;
+use std::path::Path;
+
+use super::canonical;
+use super::certificates::{CertificateGenerator, WipeCertificate};
+use crate::verification::VerificationReport;
+
+const PAGE_WIDTH: f32 = 595.0; // A4 in points
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const VALUE_X: f32 = 200.0;
+const ROW_HEIGHT: f32 = 15.0;
+const HEADING_HEIGHT: f32 = 24.0;
+const QR_SIZE: f32 = 180.0;
+const QR_QUIET_ZONE: usize = 4;
+
+/// Characters per line for 10pt Helvetica values and 8pt Courier signatures
+const VALUE_WRAP: usize = 58;
+const SIGNATURE_WRAP: usize = 48;
+
+const FONT_REGULAR: Name = Name(b"F1");
+const FONT_BOLD: Name = Name(b"F2");
+const FONT_MONO: Name = Name(b"F3");
+
+/// Certificate plus the verification details worth printing
+#[derive(Debug, Clone)]
+pub struct CertificateDocument {
+    pub certificate: WipeCertificate,
+    pub confidence_level: Option<f64>,
+    pub verification_method: Option<String>,
+    pub compliance_standards: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+struct Section {
+    title: &'static str,
+    rows: Vec<(String, String)>,
+}
+
+impl CertificateDocument {
+    pub fn new(certificate: WipeCertificate, report: Option<&VerificationReport>) -> Self {
+        Self {
+            certificate,
+            confidence_level: report.map(|r| r.confidence_level),
+            verification_method: report.map(|r| {
+                format!("{} ({:?})", r.verification_method, r.verification_level)
+            }),
+            compliance_standards: report.map(|r| r.compliance_standards.clone()).unwrap_or_default(),
+            warnings: report.map(|r| r.warnings.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Load a certificate JSON file, including the `enhanced_verification`
+    /// report written by enhanced wipes when present
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read certificate {}", path.display()))?;
+        let value: Value = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid certificate {}", path.display()))?;
+
+        let report = match value.get("enhanced_verification") {
+            Some(report) => Some(serde_json::from_value::<VerificationReport>(report.clone())
+                .context("Invalid enhanced verification report")?),
+            None => None,
+        };
+        let certificate: WipeCertificate = serde_json::from_value(value)
+            .with_context(|| format!("Invalid certificate {}", path.display()))?;
+
+        Ok(Self::new(certificate, report.as_ref()))
+    }
+
+    /// Bytes encoded in the QR code: certificate ID, SHA-256 of the signed
+    /// canonical certificate and its signature, as RFC 8785 JSON.
+    ///
+    /// The full certificate grows with the drive's bad sectors and sanitize
+    /// records, so it is not embedded; the hash binds the code to exactly one
+    /// certificate file and stays the same size for every drive.
+    pub fn qr_payload(&self) -> Result<Vec<u8>> {
+        let cert = &self.certificate;
+        canonical::to_canonical_bytes(&serde_json::json!({
+            "certificate_id": cert.certificate_id,
+            "sha256": CertificateGenerator::certificate_hash(cert)?,
+            "signature": cert.signature,
+        }))
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        let cert = &self.certificate;
+        let device = &cert.device_info;
+        let details = &cert.wipe_details;
+        let verification = &cert.verification;
+
+        let mut sanitization_rows = vec![
+            ("Algorithm".to_string(), details.algorithm_used.clone()),
+            ("Passes completed".to_string(), details.passes_completed.to_string()),
+            ("Duration".to_string(), format_duration(details.duration_seconds)),
+            ("Operator".to_string(),
+             details.operator_id.clone().unwrap_or_else(|| "Not recorded".to_string())),
+            ("Scope".to_string(), match &details.scope {
+                Some(scope) => format!("{} (LBA {}-{})", scope.description, scope.first_lba, scope.last_lba),
+                None => "Whole device".to_string(),
+            }),
+            ("Completed".to_string(), cert.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+        ];
+        if let Some(commitment) = &details.keystream_commitment {
+            sanitization_rows.push(("Random data".to_string(), "AES-256-CTR keystream, verified exactly".to_string()));
+            sanitization_rows.push(("Key commitment".to_string(), commitment.clone()));
+        }
+
+        let mut sections = vec![
+            Section {
+                title: "Device",
+                rows: vec![
+                    ("Model".to_string(), device.model.clone()),
+                    ("Serial number".to_string(), device.serial.clone()),
+                    ("Device path".to_string(), device.device_path.clone()),
+                    ("Capacity".to_string(), format_capacity(device.size)),
+                    ("Device hash".to_string(), device.device_hash.clone()),
+                ],
+            },
+            Section {
+                title: "Sanitization",
+                rows: sanitization_rows,
+            },
+        ];
+
+        if let Some(policy) = &details.sanitization {
+            let mut rows = vec![
+                ("Target level".to_string(), format!("{} ({})", policy.level, policy.standard)),
+                ("Media".to_string(), policy.media.clone()),
+                ("Method".to_string(), format!("{} ({})", policy.method,
+                    if policy.operator_selected { "operator selected" } else { "selected by policy" })),
+            ];
+            rows.extend(policy.rationale.iter().map(|r| (String::new(), format!("- {}", r))));
+            rows.extend(policy.rejected.iter()
+                .map(|r| (String::new(), format!("- Rejected {}: {}", r.method, r.reason))));
+            sections.push(Section { title: "Sanitization Policy", rows });
+        }
+
+        if let Some(sanitize) = &details.nvme_sanitize {
+            let estimate = |secs: Option<u32>| secs
+                .map(|s| format_duration(s as u64))
+                .unwrap_or_else(|| "Not reported".to_string());
+            sections.push(Section {
+                title: "NVMe Sanitize",
+                rows: vec![
+                    ("Action".to_string(), sanitize.action.clone()),
+                    ("Final status".to_string(), format!("{:?} (SSTAT 0x{:04X})", sanitize.state, sanitize.sstat)),
+                    ("Command dword 10".to_string(), format!("0x{:08X}", sanitize.scdw10)),
+                    ("Overwrite passes".to_string(), sanitize.overwrite_passes_completed.to_string()),
+                    ("Est. overwrite".to_string(), estimate(sanitize.estimated_overwrite_secs)),
+                    ("Est. block erase".to_string(), estimate(sanitize.estimated_block_erase_secs)),
+                    ("Est. crypto erase".to_string(), estimate(sanitize.estimated_crypto_erase_secs)),
+                    ("Re-attached".to_string(), yes_no(sanitize.reattached).to_string()),
+                ],
+            });
+        }
+
+        if let Some(unwritable) = &details.unwritable_sectors {
+            let mut rows = vec![
+                ("Not sanitized".to_string(),
+                 format!("{} sectors could not be written", unwritable.count)),
+            ];
+            rows.extend(unwritable.ranges.iter().map(|range| (String::new(), if range.first == range.last {
+                format!("- LBA {}", range.first)
+            } else {
+                format!("- LBA {}-{}", range.first, range.last)
+            })));
+            let unlisted = unwritable.range_count.saturating_sub(unwritable.ranges.len() as u64);
+            if unlisted > 0 {
+                rows.push((String::new(), format!("... and {} more ranges", unlisted)));
+            }
+            rows.push(("LBA list SHA-256".to_string(), unwritable.sha256.clone()));
+            sections.push(Section { title: "Unwritable Sectors", rows });
+        }
+
+        let mut verification_rows = vec![
+            ("Verified".to_string(), yes_no(verification.verified).to_string()),
+            ("Entropy score".to_string(), format!("{:.4} / 8.0 bits per byte", verification.entropy_score)),
+            ("Confidence level".to_string(), self.confidence_level
+                .map(|c| format!("{:.2}%", c))
+                .unwrap_or_else(|| "Not assessed".to_string())),
+            ("Recovery test".to_string(),
+             if verification.recovery_test_passed { "Passed" } else { "Failed" }.to_string()),
+        ];
+        if let Some(method) = &self.verification_method {
+            verification_rows.push(("Method".to_string(), method.clone()));
+        }
+        sections.push(Section { title: "Verification", rows: verification_rows });
+
+        let compliance = if self.compliance_standards.is_empty() {
+            vec![(String::new(), "Not assessed".to_string())]
+        } else {
+            self.compliance_standards.iter()
+                .map(|s| (String::new(), format!("- {}", s)))
+                .collect()
+        };
+        sections.push(Section { title: "Compliance Standards", rows: compliance });
+
+        if !self.warnings.is_empty() {
+            sections.push(Section {
+                title: "Warnings",
+                rows: self.warnings.iter()
+                    .map(|w| (String::new(), format!("- {}", printable(w))))
+                    .collect(),
+            });
+        }
+
+        sections
+    }
+
+    /// Plain-text rendering for terminals and e-mail
+    pub fn to_text(&self) -> String {
+        let cert = &self.certificate;
+        let mut out = String::new();
+
+        out.push_str("CERTIFICATE OF DATA SANITIZATION\n");
+        out.push_str(&format!("{}\n", "=".repeat(60)));
+        out.push_str(&format!("Certificate ID: {}\n", cert.certificate_id));
+
+        for section in self.sections() {
+            out.push_str(&format!("\n{}\n{}\n", section.title, "-".repeat(section.title.len())));
+            for (label, value) in section.rows {
+                if label.is_empty() {
+                    out.push_str(&format!("  {}\n", value));
+                } else {
+                    out.push_str(&format!("  {:<18} {}\n", format!("{}:", label), value));
+                }
+            }
+        }
+
+        out.push_str("\nDigital Signature\n-----------------\n");
+        out.push_str(&format!("  {:<18} {:?} over {}\n", "Algorithm:", cert.signer.algorithm, cert.signer.encoding));
+        out.push_str(&format!("  {:<18} {}\n", "Key fingerprint:", cert.signer.key_fingerprint));
+        out.push_str(&format!("  {:<18} {}\n", "Signature:", cert.signature));
+
+        out
+    }
+
+    /// Single-page (or longer, for many warnings) A4 PDF
+    pub fn to_pdf(&self) -> Result<Vec<u8>> {
+        let cert = &self.certificate;
+        let payload = self.qr_payload()?;
+        let qr = QrCode::with_error_correction_level(&payload, EcLevel::M)
+            .or_else(|_| QrCode::with_error_correction_level(&payload, EcLevel::L))
+            .map_err(|e| anyhow!("Failed to encode certificate QR code: {:?}", e))?;
+
+        let mut layout = PageLayout::new();
+        layout.text(FONT_BOLD, 20.0, MARGIN, "Certificate of Data Sanitization");
+        layout.advance(20.0);
+        layout.text(FONT_REGULAR, 10.0, MARGIN, &format!("Certificate ID: {}", cert.certificate_id));
+        layout.advance(6.0);
+
+        for section in self.sections() {
+            layout.ensure(HEADING_HEIGHT + ROW_HEIGHT);
+            layout.heading(section.title);
+
+            for (label, value) in section.rows {
+                let lines = wrap(&value, VALUE_WRAP);
+                layout.ensure(ROW_HEIGHT * lines.len() as f32);
+                let x = if label.is_empty() { MARGIN } else { VALUE_X };
+                if !label.is_empty() {
+                    layout.text(FONT_BOLD, 10.0, MARGIN, &label);
+                }
+                for line in lines {
+                    layout.text(FONT_REGULAR, 10.0, x, &line);
+                    layout.advance(ROW_HEIGHT);
+                }
+            }
+        }
+
+        // Signature details on the left, QR code of the signed payload on the right
+        layout.ensure(HEADING_HEIGHT + QR_SIZE + 2.0 * ROW_HEIGHT);
+        layout.heading("Digital Signature");
+        let qr_top = layout.y + ROW_HEIGHT;
+
+        layout.text(FONT_REGULAR, 9.0, MARGIN,
+                    &format!("{:?} over {} canonical JSON", cert.signer.algorithm, cert.signer.encoding));
+        layout.advance(ROW_HEIGHT);
+        layout.text(FONT_BOLD, 9.0, MARGIN, "Key fingerprint (SHA-256)");
+        layout.advance(12.0);
+        for line in wrap(&cert.signer.key_fingerprint, SIGNATURE_WRAP) {
+            layout.text(FONT_MONO, 8.0, MARGIN, &line);
+            layout.advance(10.0);
+        }
+        layout.advance(4.0);
+        layout.text(FONT_BOLD, 9.0, MARGIN, "Signature");
+        layout.advance(12.0);
+        for line in wrap(&cert.signature, SIGNATURE_WRAP) {
+            layout.text(FONT_MONO, 8.0, MARGIN, &line);
+            layout.advance(10.0);
+        }
+
+        let qr_x = PAGE_WIDTH - MARGIN - QR_SIZE;
+        draw_qr(layout.content(), &qr, qr_x, qr_top - QR_SIZE);
+        layout.content().begin_text();
+        layout.content().set_font(FONT_REGULAR, 8.0);
+        layout.content().next_line(qr_x + 16.0, qr_top - QR_SIZE - 10.0);
+        layout.content().show(Str(b"Scan for certificate ID, hash and signature"));
+        layout.content().end_text();
+
+        Ok(write_pdf(layout.finish(), &cert.certificate_id))
+    }
+
+    pub fn save_pdf<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.to_pdf()?)?;
+        Ok(())
+    }
+}
+
+/// Top-down text flow that starts a new page when the current one is full
+struct PageLayout {
+    pages: Vec<Content>,
+    y: f32,
+}
+
+impl PageLayout {
+    fn new() -> Self {
+        Self { pages: vec![Content::new()], y: PAGE_HEIGHT - MARGIN - 20.0 }
+    }
+
+    fn content(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.pages.push(Content::new());
+            self.y = PAGE_HEIGHT - MARGIN - 20.0;
+        }
+    }
+
+    fn advance(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn text(&mut self, font: Name, size: f32, x: f32, text: &str) {
+        let y = self.y;
+        let encoded = win_ansi(text);
+        let content = self.content();
+        content.begin_text();
+        content.set_font(font, size);
+        content.next_line(x, y);
+        content.show(Str(&encoded));
+        content.end_text();
+    }
+
+    fn heading(&mut self, title: &str) {
+        self.advance(HEADING_HEIGHT - ROW_HEIGHT);
+        self.text(FONT_BOLD, 12.0, MARGIN, title);
+
+        let y = self.y - 4.0;
+        let content = self.content();
+        content.set_line_width(0.5);
+        content.move_to(MARGIN, y);
+        content.line_to(PAGE_WIDTH - MARGIN, y);
+        content.stroke();
+
+        self.advance(ROW_HEIGHT + 2.0);
+    }
+
+    fn finish(self) -> Vec<Content> {
+        self.pages
+    }
+}
+
+fn draw_qr(content: &mut Content, qr: &QrCode, x: f32, y: f32) {
+    let width = qr.width();
+    let module = QR_SIZE / (width + 2 * QR_QUIET_ZONE) as f32;
+    let colors = qr.to_colors();
+
+    content.set_fill_gray(0.0);
+    for row in 0..width {
+        let top = y + QR_SIZE - (row + QR_QUIET_ZONE + 1) as f32 * module;
+        let mut col = 0;
+        while col < width {
+            if colors[row * width + col] != Color::Dark {
+                col += 1;
+                continue;
+            }
+            // Merge horizontal runs of dark modules into one rectangle
+            let start = col;
+            while col < width && colors[row * width + col] == Color::Dark {
+                col += 1;
+            }
+            let left = x + (start + QR_QUIET_ZONE) as f32 * module;
+            content.rect(left, top, (col - start) as f32 * module, module);
+        }
+    }
+    content.fill_nonzero();
+}
+
+fn write_pdf(pages: Vec<Content>, certificate_id: &str) -> Vec<u8> {
+    let mut pdf = Pdf::new();
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let info_id = Ref::new(3);
+    let fonts = [
+        (FONT_REGULAR, Ref::new(4), Name(b"Helvetica")),
+        (FONT_BOLD, Ref::new(5), Name(b"Helvetica-Bold")),
+        (FONT_MONO, Ref::new(6), Name(b"Courier")),
+    ];
+    let page_ids: Vec<Ref> = (0..pages.len()).map(|i| Ref::new(7 + 2 * i as i32)).collect();
+
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id).kids(page_ids.iter().copied()).count(pages.len() as i32);
+    pdf.document_info(info_id)
+        .title(TextStr(&format!("Certificate of Data Sanitization {}", certificate_id)))
+        .producer(TextStr("Sayonara Wipe"));
+
+    for (_, id, base_font) in fonts {
+        pdf.type1_font(id)
+            .base_font(base_font)
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+    }
+
+    for (page_id, content) in page_ids.iter().zip(pages) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(*page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        page.resources().fonts().pairs(fonts.iter().map(|(name, id, _)| (*name, *id)));
+        page.finish();
+
+        pdf.stream(content_id, &content.finish());
+    }
+
+    pdf.finish()
+}
+
+/// Encode text for WinAnsiEncoding, dropping characters it cannot represent
+/// (such as the emoji prefixes used in verification warnings)
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter_map(|c| match c as u32 {
+            0x20..=0x7e | 0xa0..=0xff => Some(c as u8),
+            0x2022 => Some(0x95), // bullet
+            0x2013 => Some(0x96), // en dash
+            0x2014 => Some(0x97), // em dash
+            _ => None,
+        })
+        .collect()
+}
+
+/// Strip emoji and other symbols the standard fonts cannot show
+fn printable(text: &str) -> String {
+    String::from_utf8_lossy(&win_ansi(text)).trim().to_string()
+}
+
+/// Word-wrap to a character budget, hard-splitting words that do not fit
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        let word: String = word.into_iter().collect();
+
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+fn format_capacity(bytes: u64) -> String {
+    format!("{:.2} GB ({} bytes)", bytes as f64 / 1_000_000_000.0, bytes)
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!("{}h {:02}m {:02}s", seconds / 3600, (seconds % 3600) / 60, seconds % 60)
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "Yes" } else { "No" }
+}
+
+/// Sorted LBAs collapsed into runs: "10-11", "1500"
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::certificates::{UnwritableSectors, WipeDetails, VerificationResult};
+    use crate::test_support::{test_drive, test_generator, test_verification, test_wipe_details};
+    use crate::{DriveInfo, DriveType};
+    use crate::policy::{RejectedMethod, SanitizationLevel, SanitizationRecord, NIST_800_88};
+    use crate::drives::NvmeSanitizeRecord;
+    use crate::drives::nvme_admin::SanitizeState;
+
+    fn test_document() -> CertificateDocument {
+        test_document_with(None)
+    }
+
+    fn test_document_with(sanitization: Option<SanitizationRecord>) -> CertificateDocument {
+        test_document_full(sanitization, None, Vec::new())
+    }
+
+    fn test_document_full(
+        sanitization: Option<SanitizationRecord>,
+        nvme_sanitize: Option<NvmeSanitizeRecord>,
+        unwritable_sectors: Vec<u64>,
+    ) -> CertificateDocument {
+        let generator = test_generator();
+        let drive = DriveInfo {
+            model: "Samsung SSD 870 EVO".to_string(),
+            size: 500_107_862_016,
+            drive_type: DriveType::SSD,
+            ..test_drive("S6PNNX0R123456")
+        };
+        let details = WipeDetails {
+            duration_seconds: 3725,
+            operator_id: Some("op-42".to_string()),
+            sanitization,
+            nvme_sanitize,
+            unwritable_sectors: UnwritableSectors::from_lbas(&unwritable_sectors),
+            ..test_wipe_details()
+        };
+        let verification = VerificationResult { entropy_score: 7.9991, ..test_verification() };
+        let certificate = generator.generate_certificate(&drive, details, verification).unwrap();
+
+        let mut document = CertificateDocument::new(certificate, None);
+        document.confidence_level = Some(99.5);
+        document.compliance_standards = vec!["NIST 800-88 Rev. 1".to_string(), "DoD 5220.22-M".to_string()];
+        document.warnings = vec!["⚠️ 2 suspicious sectors detected".to_string()];
+        document
+    }
+
+    #[test]
+    fn test_pdf_contains_certificate_fields() {
+        let document = test_document();
+        let pdf = document.to_pdf().unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        for expected in ["Samsung SSD 870 EVO", "S6PNNX0R123456", "DoD5220", "1h 02m 05s",
+                         "7.9991", "99.50%", "NIST 800-88 Rev. 1", "op-42",
+                         "2 suspicious sectors detected", "/Count 1"] {
+            assert!(text.contains(expected), "PDF is missing {:?}", expected);
+        }
+    }
+
+    #[test]
+    fn test_policy_decision_rendered() {
+        let document = test_document_with(Some(SanitizationRecord {
+            standard: NIST_800_88.to_string(),
+            level: SanitizationLevel::Purge,
+            media: "ATA solid state drive".to_string(),
+            method: "CryptoErase".to_string(),
+            operator_selected: false,
+            rationale: vec!["Cryptographic erase destroys the media encryption key".to_string()],
+            rejected: vec![RejectedMethod {
+                method: "Sanitize".to_string(),
+                reason: "drive does not support ATA SANITIZE".to_string(),
+            }],
+        }));
+
+        let text = document.to_text();
+        for expected in ["Sanitization Policy", "Purge (NIST SP 800-88 Rev. 1)",
+                         "CryptoErase (selected by policy)", "- Rejected Sanitize"] {
+            assert!(text.contains(expected), "Report is missing {:?}", expected);
+        }
+        assert!(String::from_utf8_lossy(&document.to_pdf().unwrap()).contains("Sanitization Policy"));
+    }
+
+    #[test]
+    fn test_nvme_sanitize_status_rendered() {
+        let document = test_document_full(None, Some(NvmeSanitizeRecord {
+            action: "Crypto Erase".to_string(),
+            state: SanitizeState::Completed,
+            sstat: 0x0101,
+            scdw10: 4,
+            overwrite_passes_completed: 0,
+            estimated_overwrite_secs: None,
+            estimated_block_erase_secs: Some(400),
+            estimated_crypto_erase_secs: Some(8),
+            reattached: true,
+            duration_seconds: 12,
+        }), Vec::new());
+
+        let text = document.to_text();
+        for expected in ["NVMe Sanitize", "Crypto Erase", "Completed (SSTAT 0x0101)", "0x00000004",
+                         "0h 06m 40s", "Not reported"] {
+            assert!(text.contains(expected), "Report is missing {:?}", expected);
+        }
+        assert!(!test_document().to_text().contains("NVMe Sanitize"));
+    }
+
+    #[test]
+    fn test_unwritable_sectors_listed() {
+        let document = test_document_full(None, None, vec![10, 11, 12, 1500, 90_000, 90_001]);
+
+        let text = document.to_text();
+        for expected in ["Unwritable Sectors", "6 sectors could not be written",
+                         "- LBA 10-12", "- LBA 1500", "- LBA 90000-90001", "LBA list SHA-256"] {
+            assert!(text.contains(expected), "Report is missing {:?}", expected);
+        }
+        assert!(text.contains(&format!("{:<18} No", "Verified:")));
+        assert!(!text.contains("more ranges"));
+        assert!(!test_document().to_text().contains("Unwritable Sectors"));
+
+        let scattered = test_document_full(None, None, (0..1000).map(|i| i * 2).collect()).to_text();
+        assert!(scattered.contains("1000 sectors could not be written"));
+        assert!(scattered.contains(&format!("... and {} more ranges", 1000 - UnwritableSectors::MAX_LISTED_RANGES)));
+    }
+
+    #[test]
+    fn test_keystream_commitment_rendered() {
+        let mut document = test_document();
+        assert!(!document.to_text().contains("Key commitment"));
+
+        document.certificate.wipe_details.keystream_commitment = Some("ab".repeat(32));
+        let text = document.to_text();
+        assert!(text.contains("AES-256-CTR keystream"));
+        assert!(text.contains(&"ab".repeat(32)));
+    }
+
+    #[test]
+    fn test_scope_rendered() {
+        let mut document = test_document();
+        assert!(document.to_text().contains("Whole device"));
+
+        document.certificate.wipe_details.scope = Some(crate::drives::ResolvedScope {
+            description: "partition 3".to_string(),
+            first_lba: 2048,
+            last_lba: 4095,
+            sector_size: 512,
+        });
+        assert!(document.to_text().contains("partition 3 (LBA 2048-4095)"));
+    }
+
+    #[test]
+    fn test_long_reports_paginate() {
+        let mut document = test_document();
+        document.warnings = (0..80).map(|i| format!("Warning number {}", i)).collect();
+
+        let pdf = document.to_pdf().unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(!text.contains("/Count 1"));
+        assert!(text.contains("Warning number 79"));
+    }
+
+    #[test]
+    fn test_qr_payload_identifies_certificate() {
+        let document = test_document();
+        let payload = document.qr_payload().unwrap();
+        let decoded: Value = serde_json::from_slice(&payload).unwrap();
+
+        assert_eq!(decoded["certificate_id"], document.certificate.certificate_id.as_str());
+        assert_eq!(decoded["signature"], document.certificate.signature.as_str());
+        assert_eq!(decoded["sha256"],
+                   CertificateGenerator::certificate_hash(&document.certificate).unwrap().as_str());
+        assert!(QrCode::with_error_correction_level(&payload, EcLevel::M).is_ok());
+    }
+
+    #[test]
+    fn test_qr_size_independent_of_bad_sectors() {
+        // Every other LBA bad, so none of them merge into ranges
+        let document = test_document_full(None, None, (0..2000).map(|i| i * 2).collect());
+
+        assert_eq!(document.qr_payload().unwrap().len(), test_document().qr_payload().unwrap().len());
+        assert!(document.to_pdf().is_ok());
+    }
+
+    #[test]
+    fn test_text_rendering() {
+        let text = test_document().to_text();
+        assert!(text.contains("Serial number:"));
+        assert!(text.contains("- DoD 5220.22-M"));
+        assert!(text.contains("- 2 suspicious sectors detected"));
+        assert!(!text.contains('⚠'));
+    }
+
+    #[test]
+    fn test_wrap_and_encoding() {
+        assert_eq!(wrap("a bb ccc", 4), vec!["a bb", "ccc"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![""]);
+        assert_eq!(win_ansi("é ✅ x"), vec![0xe9, b' ', b' ', b'x']);
+    }
+}
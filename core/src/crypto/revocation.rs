@@ -0,0 +1,422 @@
+// Signed certificate revocation list
+//
+// `sayonara cert revoke` appends entries to a local JSON list signed by the
+// same key that issued the certificates, over the same RFC 8785 canonical
+// encoding. Verification only honours a list whose signer fingerprint matches
+// the certificate's, and fails closed if that list has been tampered with.
+// The highest sequence accepted from each key is kept separately, so an older
+// validly signed list, or none at all, cannot be put back to hide a revocation.
+
+use anyhow::{Result, anyhow, bail, Context};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::canonical;
+use super::certificates::{hex_encode, key_fingerprint, CertificateGenerator, SignerInfo, WipeCertificate};
+
+/// Environment variable that overrides the default revocation list location
+pub const REVOCATION_LIST_ENV: &str = "SAYONARA_REVOCATION_LIST";
+
+/// Default location of the revocation list, next to the signing key
+pub const DEFAULT_REVOCATION_LIST_PATH: &str = "/etc/sayonara/revocations.json";
+
+/// Environment variable that overrides where accepted list sequences are kept
+pub const REVOCATION_STATE_ENV: &str = "SAYONARA_REVOCATION_STATE";
+
+/// Default location of the accepted list sequences, next to the wipe history
+pub const DEFAULT_REVOCATION_STATE_PATH: &str = "/var/lib/sayonara/revocation_state.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RevocationEntry {
+    pub certificate_id: String,
+    pub certificate_hash: Option<String>, // Canonical SHA-256, when revoked from the file
+    pub reason: String,
+    pub revoked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationList {
+    pub sequence: u64, // Incremented on every change
+    pub updated_at: DateTime<Utc>,
+    pub entries: Vec<RevocationEntry>,
+    pub signer: SignerInfo,
+    pub signature: String, // Hex-encoded signature over the list (excluding this field)
+}
+
+/// Highest revocation list sequence accepted from each signing key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SequenceState {
+    #[serde(skip)]
+    path: PathBuf,
+    sequences: BTreeMap<String, u64>, // Key fingerprint -> sequence
+}
+
+/// Result of checking a certificate's signature and revocation status
+#[derive(Debug, Clone, PartialEq)]
+pub enum CertificateStatus {
+    Valid,
+    InvalidSignature,
+    Revoked(RevocationEntry),
+}
+
+impl RevocationList {
+    /// Path from `$SAYONARA_REVOCATION_LIST` or the default location
+    pub fn default_path() -> PathBuf {
+        std::env::var_os(REVOCATION_LIST_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_REVOCATION_LIST_PATH))
+    }
+
+    /// Empty list signed by `generator`
+    pub fn new(generator: &CertificateGenerator) -> Result<Self> {
+        let mut list = Self {
+            sequence: 0,
+            updated_at: Utc::now(),
+            entries: Vec::new(),
+            signer: generator.signer_info(),
+            signature: String::new(),
+        };
+        list.sign(generator)?;
+        Ok(list)
+    }
+
+    /// Load and verify a list against a trusted public key
+    pub fn load<P: AsRef<Path>>(path: P, public_key: &[u8]) -> Result<Self> {
+        let list = Self::read(path.as_ref())?;
+        if !list.verify(public_key)? {
+            bail!("Revocation list {} has an invalid signature", path.as_ref().display());
+        }
+        Ok(list)
+    }
+
+    /// Load the list maintained by `generator`, or start an empty one if it
+    /// never had one
+    pub fn load_or_new<P: AsRef<Path>>(path: P, generator: &CertificateGenerator, state: &mut SequenceState) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            let list = Self::load(path, generator.public_key_bytes())?;
+            state.accept(&list)?;
+            Ok(list)
+        } else {
+            state.check_missing(path, generator.public_key_bytes())?;
+            Self::new(generator)
+        }
+    }
+
+    /// The list issued by the key that signed `certificate`, if one exists.
+    ///
+    /// `public_key` must be the trusted key the certificate was verified with;
+    /// lists signed by other keys do not apply and are ignored. A list older
+    /// than one already accepted from that key is refused, as is a missing one.
+    pub fn load_for<P: AsRef<Path>>(path: P, public_key: &[u8], state: &mut SequenceState) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        let list = match path.exists() {
+            true => Self::read(path)?,
+            false => {
+                state.check_missing(path, public_key)?;
+                return Ok(None);
+            }
+        };
+
+        if list.signer.key_fingerprint != key_fingerprint(public_key) {
+            state.check_missing(path, public_key)?;
+            return Ok(None);
+        }
+        if !list.verify(public_key)? {
+            bail!("Revocation list {} has an invalid signature", path.display());
+        }
+        state.accept(&list)?;
+        Ok(Some(list))
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read revocation list {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Invalid revocation list {}", path.display()))
+    }
+
+    /// Write atomically so an interrupted save never leaves a truncated list
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Revoke a certificate and re-sign the list. Returns false if it was
+    /// already revoked.
+    pub fn revoke(
+        &mut self,
+        generator: &CertificateGenerator,
+        certificate_id: &str,
+        certificate_hash: Option<String>,
+        reason: &str,
+    ) -> Result<bool> {
+        if self.signer.key_fingerprint != generator.signer_info().key_fingerprint {
+            return Err(anyhow!("Revocation list is maintained by a different signing key"));
+        }
+        if self.find(certificate_id).is_some() {
+            return Ok(false);
+        }
+
+        self.entries.push(RevocationEntry {
+            certificate_id: certificate_id.to_string(),
+            certificate_hash,
+            reason: reason.to_string(),
+            revoked_at: Utc::now(),
+        });
+        self.sequence += 1;
+        self.updated_at = Utc::now();
+        self.sign(generator)?;
+        Ok(true)
+    }
+
+    pub fn find(&self, certificate_id: &str) -> Option<&RevocationEntry> {
+        self.entries.iter().find(|e| e.certificate_id == certificate_id)
+    }
+
+    fn signing_payload(&self) -> Result<Vec<u8>> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("signature");
+        }
+        canonical::canonicalize(&value)
+    }
+
+    fn sign(&mut self, generator: &CertificateGenerator) -> Result<()> {
+        self.signer = generator.signer_info();
+        let payload = self.signing_payload()?;
+        self.signature = hex_encode(&generator.sign_bytes(&payload)?);
+        Ok(())
+    }
+
+    fn verify(&self, public_key: &[u8]) -> Result<bool> {
+        Ok(self.signer.verify_payload(&self.signing_payload()?, &self.signature, public_key))
+    }
+}
+
+impl SequenceState {
+    /// Path from `$SAYONARA_REVOCATION_STATE` or the default location
+    pub fn default_path() -> PathBuf {
+        std::env::var_os(REVOCATION_STATE_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_REVOCATION_STATE_PATH))
+    }
+
+    /// Sequences recorded at `path`; none if nothing was recorded yet
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut state = match path.exists() {
+            true => {
+                let json = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read revocation state {}", path.display()))?;
+                serde_json::from_str::<Self>(&json)
+                    .with_context(|| format!("Invalid revocation state {}", path.display()))?
+            }
+            false => Self::default(),
+        };
+        state.path = path.to_path_buf();
+        Ok(state)
+    }
+
+    /// Highest sequence accepted from the key with `fingerprint`
+    pub fn highest(&self, fingerprint: &str) -> Option<u64> {
+        self.sequences.get(fingerprint).copied()
+    }
+
+    /// Refuse a list older than one already accepted from its signer and
+    /// remember a newer one. Failing to record it only costs the protection
+    /// for later checks, so it is logged rather than fatal.
+    pub fn accept(&mut self, list: &RevocationList) -> Result<()> {
+        let fingerprint = &list.signer.key_fingerprint;
+        match self.highest(fingerprint) {
+            Some(seen) if list.sequence < seen => bail!(
+                "Revocation list sequence {} is older than sequence {} already seen; it may have been rolled back",
+                list.sequence, seen
+            ),
+            Some(seen) if list.sequence == seen => return Ok(()),
+            _ => {}
+        }
+
+        self.sequences.insert(fingerprint.clone(), list.sequence);
+        if let Err(e) = self.save() {
+            log::warn!("Failed to record revocation list sequence: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Fail if a list from `public_key` was accepted before but `path` no
+    /// longer holds one
+    fn check_missing(&self, path: &Path, public_key: &[u8]) -> Result<()> {
+        match self.highest(&key_fingerprint(public_key)) {
+            Some(seen) => bail!(
+                "Revocation list {} is missing, but sequence {} from this key was seen before",
+                path.display(), seen
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Check a certificate against a trusted public key and the issuer's
+/// revocation list
+pub fn certificate_status(
+    certificate: &WipeCertificate,
+    public_key: &[u8],
+    revocations: Option<&RevocationList>,
+) -> Result<CertificateStatus> {
+    if !CertificateGenerator::verify_with_public_key(certificate, public_key)? {
+        return Ok(CertificateStatus::InvalidSignature);
+    }
+
+    match revocations.and_then(|list| list.find(&certificate.certificate_id)) {
+        Some(entry) => Ok(CertificateStatus::Revoked(entry.clone())),
+        None => Ok(CertificateStatus::Valid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_certificate, test_generator};
+    use tempfile::TempDir;
+
+    fn test_state(dir: &TempDir) -> SequenceState {
+        SequenceState::load(dir.path().join("revocation_state.json")).unwrap()
+    }
+
+    #[test]
+    fn test_revoke_and_status() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("revocations.json");
+        let generator = test_generator();
+        let certificate = test_certificate(&generator);
+        let key = generator.public_key_bytes().to_vec();
+
+        let status = certificate_status(&certificate, &key, None).unwrap();
+        assert_eq!(status, CertificateStatus::Valid);
+
+        let mut state = test_state(&dir);
+        let mut list = RevocationList::load_or_new(&path, &generator, &mut state).unwrap();
+        assert!(list.revoke(&generator, &certificate.certificate_id, None, "Drive re-used").unwrap());
+        assert!(!list.revoke(&generator, &certificate.certificate_id, None, "again").unwrap());
+        list.save(&path).unwrap();
+
+        let loaded = RevocationList::load_for(&path, &key, &mut state).unwrap().unwrap();
+        assert_eq!(loaded.sequence, 1);
+        match certificate_status(&certificate, &key, Some(&loaded)).unwrap() {
+            CertificateStatus::Revoked(entry) => assert_eq!(entry.reason, "Drive re-used"),
+            other => panic!("expected revoked, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tampered_list_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("revocations.json");
+        let generator = test_generator();
+        let certificate = test_certificate(&generator);
+
+        let mut list = RevocationList::new(&generator).unwrap();
+        list.revoke(&generator, &certificate.certificate_id, None, "Lost").unwrap();
+        list.save(&path).unwrap();
+
+        // Dropping an entry without re-signing must not silently un-revoke
+        let mut value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        value["entries"] = serde_json::json!([]);
+        std::fs::write(&path, value.to_string()).unwrap();
+
+        let mut state = test_state(&dir);
+        assert!(RevocationList::load_for(&path, generator.public_key_bytes(), &mut state).is_err());
+        assert!(RevocationList::load_or_new(&path, &generator, &mut state).is_err());
+    }
+
+    #[test]
+    fn test_other_issuers_list_ignored() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("revocations.json");
+        let issuer = test_generator();
+        let other = test_generator();
+        let certificate = test_certificate(&issuer);
+
+        let mut list = RevocationList::new(&other).unwrap();
+        list.revoke(&other, &certificate.certificate_id, None, "Not yours").unwrap();
+        list.save(&path).unwrap();
+
+        let mut state = test_state(&dir);
+        assert!(RevocationList::load_for(&path, issuer.public_key_bytes(), &mut state).unwrap().is_none());
+        assert!(list.revoke(&issuer, "another-id", None, "x").is_err());
+    }
+
+    #[test]
+    fn test_invalid_signature_status() {
+        let generator = test_generator();
+        let mut certificate = test_certificate(&generator);
+        certificate.device_info.serial = "FORGED".to_string();
+
+        let status = certificate_status(&certificate, generator.public_key_bytes(), None).unwrap();
+        assert_eq!(status, CertificateStatus::InvalidSignature);
+    }
+
+    #[test]
+    fn test_missing_list_is_none() {
+        let dir = TempDir::new().unwrap();
+        let generator = test_generator();
+        let missing = dir.path().join("none.json");
+        let mut state = test_state(&dir);
+        assert!(RevocationList::load_for(&missing, generator.public_key_bytes(), &mut state).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rolled_back_list_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("revocations.json");
+        let generator = test_generator();
+        let key = generator.public_key_bytes().to_vec();
+
+        let mut list = RevocationList::new(&generator).unwrap();
+        list.revoke(&generator, "first", None, "Lost").unwrap();
+        list.save(&path).unwrap();
+        let older = std::fs::read(&path).unwrap();
+        list.revoke(&generator, "second", None, "Stolen").unwrap();
+        list.save(&path).unwrap();
+
+        let mut state = test_state(&dir);
+        assert_eq!(RevocationList::load_for(&path, &key, &mut state).unwrap().unwrap().sequence, 2);
+
+        // The state survives a restart, and the validly signed older list is refused
+        std::fs::write(&path, older).unwrap();
+        let mut state = test_state(&dir);
+        assert_eq!(state.highest(&list.signer.key_fingerprint), Some(2));
+        let err = RevocationList::load_for(&path, &key, &mut state).unwrap_err().to_string();
+        assert!(err.contains("rolled back"), "{}", err);
+
+        // So is a list that disappeared after one was seen
+        std::fs::remove_file(&path).unwrap();
+        assert!(RevocationList::load_for(&path, &key, &mut state).is_err());
+        assert!(RevocationList::load_or_new(&path, &generator, &mut state).is_err());
+    }
+}
@@ -333,6 +333,9 @@ impl HmacDrbg {
     /// Maximum number of requests between reseeds (2^48 per NIST SP800-90A)
     pub(crate) const MAX_REQUESTS: u64 = 1u64 << 48;
 
+    /// Maximum output of one request (2^19 bits per NIST SP800-90A Table 2)
+    pub(crate) const MAX_BYTES_PER_REQUEST: usize = 1 << 16;
+
     /// Instantiate with seed_material (entropy_input || nonce || personalization)
     pub(crate) fn new(seed_material: &[u8]) -> Self {
         // K = 0x00..00, V = 0x01..01 (32 bytes each for SHA-256)
@@ -376,21 +379,30 @@ impl HmacDrbg {
         self.reseed_counter = 1;
     }
 
-    /// Generate output bytes into `out`.
+    /// Generate output bytes into `out`, as one SP800-90A request per
+    /// `MAX_BYTES_PER_REQUEST` bytes with an Update step after each
     pub(crate) fn generate(&mut self, out: &mut [u8]) -> Result<()> {
+        for request in out.chunks_mut(Self::MAX_BYTES_PER_REQUEST) {
+            self.generate_request(request)?;
+        }
+        Ok(())
+    }
+
+    fn generate_request(&mut self, out: &mut [u8]) -> Result<()> {
         // Check if reseed is required
         if self.reseed_counter >= Self::MAX_REQUESTS {
             return Err(anyhow!("DRBG requires reseeding after {} requests", Self::MAX_REQUESTS));
         }
 
-        let mut generated = Vec::with_capacity(out.len());
-        while generated.len() < out.len() {
-            self.v = hmac_sha256(&self.k, &self.v).to_vec();
-            generated.extend_from_slice(&self.v);
+        // K is fixed for the whole request, so key the HMAC once. ring's
+        // SHA-256 keeps requests fast.
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, &self.k);
+        for chunk in out.chunks_mut(32) {
+            let tag = ring::hmac::sign(&key, &self.v);
+            self.v.copy_from_slice(tag.as_ref());
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
         }
 
-        out.copy_from_slice(&generated[..out.len()]);
-
         // Per SP800-90A, do Update with no additional input to advance internal state
         self.update(&[]);
         self.reseed_counter = self.reseed_counter.saturating_add(1);
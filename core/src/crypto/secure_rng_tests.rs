@@ -103,6 +103,25 @@ mod tests {
         Ok(())
     }
 
+    /// Large outputs are split into 64 KiB requests with an Update between them
+    #[test]
+    fn test_hmac_drbg_request_size_limit() -> Result<()> {
+        let seed = b"test seed material with sufficient entropy";
+        let mut large = HmacDrbg::new(seed);
+        let mut out = vec![0u8; HmacDrbg::MAX_BYTES_PER_REQUEST * 2 + 1];
+        large.generate(&mut out)?;
+        assert_eq!(large.reseed_counter, 4);
+
+        let mut separate = HmacDrbg::new(seed);
+        let mut expected = vec![0u8; out.len()];
+        for request in expected.chunks_mut(HmacDrbg::MAX_BYTES_PER_REQUEST) {
+            separate.generate(request)?;
+        }
+        assert_eq!(out, expected);
+
+        Ok(())
+    }
+
     /// Test HMAC-DRBG reseed counter limit
     #[test]
     fn test_hmac_drbg_reseed_limit() -> Result<()> {
@@ -0,0 +1,191 @@
+// Typed views of IDENTIFY DEVICE and DCO IDENTIFY data
+
+use crate::FreezeStatus;
+
+/// Security feature set state (IDENTIFY word 128)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SecurityState {
+    pub supported: bool,
+    pub enabled: bool,
+    pub locked: bool,
+    pub frozen: bool,
+    pub count_expired: bool,
+    pub enhanced_erase_supported: bool,
+    pub master_password_maximum: bool,
+}
+
+impl SecurityState {
+    pub fn freeze_status(&self) -> FreezeStatus {
+        if self.locked {
+            FreezeStatus::SecurityLocked
+        } else if self.frozen {
+            FreezeStatus::Frozen
+        } else {
+            FreezeStatus::NotFrozen
+        }
+    }
+}
+
+/// SANITIZE feature set support (IDENTIFY word 59)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SanitizeSupport {
+    pub supported: bool,
+    pub crypto_scramble: bool,
+    pub overwrite: bool,
+    pub block_erase: bool,
+}
+
+/// Parsed IDENTIFY DEVICE response
+#[derive(Debug, Clone)]
+pub struct IdentifyData {
+    words: Vec<u16>,
+}
+
+impl IdentifyData {
+    /// Parse the 512-byte little-endian IDENTIFY DEVICE block
+    pub fn from_bytes(data: &[u8]) -> Self {
+        Self { words: to_words(data) }
+    }
+
+    /// Raw 16-bit word (0 when out of range)
+    pub fn word(&self, index: usize) -> u16 {
+        self.words.get(index).copied().unwrap_or(0)
+    }
+
+    pub fn model(&self) -> String {
+        ata_string(&self.words, 27, 47)
+    }
+
+    pub fn serial(&self) -> String {
+        ata_string(&self.words, 10, 20)
+    }
+
+    pub fn firmware(&self) -> String {
+        ata_string(&self.words, 23, 27)
+    }
+
+    pub fn lba48_supported(&self) -> bool {
+        self.word(83) & (1 << 10) != 0
+    }
+
+    /// Sectors addressable by the host (honours HPA/DCO limits)
+    pub fn user_addressable_sectors(&self) -> u64 {
+        if self.lba48_supported() {
+            qword(&self.words, 100)
+        } else {
+            (self.word(60) as u64) | ((self.word(61) as u64) << 16)
+        }
+    }
+
+    pub fn logical_sector_size(&self) -> u32 {
+        let word = self.word(106);
+        // Word 106 is valid when bit 14 is set and bit 15 is clear
+        if word & 0xC000 == 0x4000 && word & (1 << 12) != 0 {
+            let words_per_sector = (self.word(117) as u32) | ((self.word(118) as u32) << 16);
+            words_per_sector * 2
+        } else {
+            512
+        }
+    }
+
+    pub fn hpa_supported(&self) -> bool {
+        self.word(82) & (1 << 10) != 0
+    }
+
+    pub fn dco_supported(&self) -> bool {
+        self.word(83) & (1 << 11) != 0
+    }
+
+    /// Volatile write cache currently enabled (word 85 bit 5)
+    pub fn write_cache_enabled(&self) -> bool {
+        self.word(85) & (1 << 5) != 0
+    }
+
+    pub fn trim_supported(&self) -> bool {
+        self.word(169) & 1 != 0
+    }
+
+    /// Deterministic read after TRIM (word 69 bit 14)
+    pub fn deterministic_trim(&self) -> bool {
+        self.word(69) & (1 << 14) != 0
+    }
+
+    /// Nominal media rotation rate; 1 means non-rotating (solid state)
+    pub fn rotation_rate(&self) -> u16 {
+        self.word(217)
+    }
+
+    pub fn security(&self) -> SecurityState {
+        let word = self.word(128);
+        SecurityState {
+            supported: word & (1 << 0) != 0,
+            enabled: word & (1 << 1) != 0,
+            locked: word & (1 << 2) != 0,
+            frozen: word & (1 << 3) != 0,
+            count_expired: word & (1 << 4) != 0,
+            enhanced_erase_supported: word & (1 << 5) != 0,
+            master_password_maximum: word & (1 << 8) != 0,
+        }
+    }
+
+    pub fn sanitize(&self) -> SanitizeSupport {
+        let word = self.word(59);
+        SanitizeSupport {
+            supported: word & (1 << 12) != 0,
+            crypto_scramble: word & (1 << 13) != 0,
+            overwrite: word & (1 << 14) != 0,
+            block_erase: word & (1 << 15) != 0,
+        }
+    }
+
+    /// Estimated SECURITY ERASE UNIT time in minutes (None if not reported)
+    pub fn erase_time_minutes(&self, enhanced: bool) -> Option<u32> {
+        let word = self.word(if enhanced { 90 } else { 89 });
+        // Bit 15 selects the extended 15-bit format, otherwise bits 7:0
+        let value = if word & 0x8000 != 0 { word & 0x7FFF } else { word & 0x00FF };
+        (value != 0).then_some(value as u32 * 2)
+    }
+}
+
+/// Parsed DEVICE CONFIGURATION IDENTIFY response
+#[derive(Debug, Clone)]
+pub struct DcoIdentify {
+    words: Vec<u16>,
+}
+
+impl DcoIdentify {
+    pub fn from_bytes(data: &[u8]) -> Self {
+        Self { words: to_words(data) }
+    }
+
+    /// Highest LBA the drive can expose once the overlay is restored
+    pub fn max_lba(&self) -> u64 {
+        qword(&self.words, 3)
+    }
+
+    pub fn revision(&self) -> u16 {
+        self.words.first().copied().unwrap_or(0)
+    }
+}
+
+fn to_words(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+fn qword(words: &[u16], start: usize) -> u64 {
+    (0..4).fold(0u64, |acc, i| {
+        acc | ((words.get(start + i).copied().unwrap_or(0) as u64) << (16 * i))
+    })
+}
+
+/// ATA strings store the first character of each pair in the high byte
+fn ata_string(words: &[u16], start: usize, end: usize) -> String {
+    let bytes: Vec<u8> = words.get(start..end)
+        .unwrap_or(&[])
+        .iter()
+        .flat_map(|w| w.to_be_bytes())
+        .collect();
+    String::from_utf8_lossy(&bytes).trim().to_string()
+}
@@ -0,0 +1,423 @@
+// Native ATA command layer
+//
+// Issues ATA commands directly instead of shelling out to hdparm and scraping
+// its output. Commands go through the `AtaTransport` trait: `SgIoTransport`
+// talks to real drives over SG_IO, tests plug in a simulated drive.
+
+pub mod identify;
+pub mod sgio;
+
+#[cfg(test)]
+mod tests;
+
+pub use identify::{DcoIdentify, IdentifyData, SanitizeSupport, SecurityState};
+pub use sgio::SgIoTransport;
+
+use crate::ui::progress::ProgressBar;
+use crate::{DriveError, DriveResult};
+use std::time::{Duration, Instant};
+
+// Command opcodes
+const ATA_DATA_SET_MANAGEMENT: u8 = 0x06;
+const ATA_READ_NATIVE_MAX_ADDRESS_EXT: u8 = 0x27;
+const ATA_SET_MAX_ADDRESS_EXT: u8 = 0x37;
+const ATA_DEVICE_CONFIGURATION: u8 = 0xB1;
+const ATA_SANITIZE_DEVICE: u8 = 0xB4;
+const ATA_FLUSH_CACHE_EXT: u8 = 0xEA;
+const ATA_IDENTIFY_DEVICE: u8 = 0xEC;
+const ATA_SET_FEATURES: u8 = 0xEF;
+const ATA_SECURITY_SET_PASSWORD: u8 = 0xF1;
+const ATA_SECURITY_UNLOCK: u8 = 0xF2;
+const ATA_SECURITY_ERASE_PREPARE: u8 = 0xF3;
+const ATA_SECURITY_ERASE_UNIT: u8 = 0xF4;
+const ATA_SECURITY_DISABLE_PASSWORD: u8 = 0xF6;
+
+// Feature codes
+const DSM_TRIM: u16 = 0x0001;
+const DCO_RESTORE: u16 = 0x00C0;
+const DCO_IDENTIFY: u16 = 0x00C2;
+const SANITIZE_STATUS_EXT: u16 = 0x0000;
+const SANITIZE_CRYPTO_SCRAMBLE_EXT: u16 = 0x0011;
+const SANITIZE_BLOCK_ERASE_EXT: u16 = 0x0012;
+const SETFEATURES_WC_ON: u16 = 0x0002;
+const SETFEATURES_WC_OFF: u16 = 0x0082;
+
+// Signatures SANITIZE requires in the LBA field
+const SANITIZE_CRYPTO_SIGNATURE: u64 = 0x4372_7970; // "Cryp"
+const SANITIZE_BLOCK_SIGNATURE: u64 = 0x426B_4572;  // "BkEr"
+
+const STATUS_ERR: u8 = 0x01;
+const STATUS_DF: u8 = 0x20;
+const DEVICE_LBA: u8 = 0x40;
+
+const SECTOR_SIZE: usize = 512;
+const DSM_MAX_RANGE: u64 = 0xFFFF;
+const DSM_ENTRIES_PER_BLOCK: usize = SECTOR_SIZE / 8;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MIN_ERASE_TIMEOUT: Duration = Duration::from_secs(2 * 3600);
+
+/// How the command moves data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AtaProtocol {
+    NonData,
+    PioIn,
+    PioOut,
+    DmaOut,
+}
+
+/// Input taskfile of one ATA command
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtaCommand {
+    pub command: u8,
+    pub features: u16,
+    pub count: u16,
+    pub lba: u64,
+    pub device: u8,
+    pub extended: bool, // 48-bit command
+    pub protocol: AtaProtocol,
+}
+
+impl AtaCommand {
+    fn new(command: u8, protocol: AtaProtocol) -> Self {
+        Self { command, features: 0, count: 0, lba: 0, device: 0, extended: false, protocol }
+    }
+
+    fn ext(command: u8, protocol: AtaProtocol) -> Self {
+        Self { device: DEVICE_LBA, extended: true, ..Self::new(command, protocol) }
+    }
+}
+
+/// Output registers returned by the drive
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AtaRegisters {
+    pub status: u8,
+    pub error: u8,
+    pub count: u16,
+    pub lba: u64,
+    pub device: u8,
+}
+
+/// Data phase of a command
+pub enum DataTransfer<'a> {
+    None,
+    In(&'a mut [u8]),
+    Out(&'a [u8]),
+}
+
+/// Something that can execute ATA commands: a real drive or a simulator
+pub trait AtaTransport: Send {
+    fn execute(&mut self, command: &AtaCommand, data: DataTransfer<'_>, timeout: Duration)
+        -> DriveResult<AtaRegisters>;
+}
+
+/// Which ATA security password a command refers to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PasswordType {
+    User,
+    Master,
+}
+
+/// Progress reported by SANITIZE STATUS EXT
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtaSanitizeStatus {
+    pub in_progress: bool,
+    pub completed_without_error: bool,
+    pub failed: bool,
+    pub progress: f64, // Percent complete while in progress
+}
+
+/// High-level ATA operations on top of a transport
+pub struct AtaDevice<T: AtaTransport = SgIoTransport> {
+    transport: T,
+}
+
+impl AtaDevice<SgIoTransport> {
+    /// Open a block device for ATA passthrough
+    pub fn open(device_path: &str) -> DriveResult<Self> {
+        Ok(Self::new(SgIoTransport::open(device_path)?))
+    }
+}
+
+impl<T: AtaTransport> AtaDevice<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    pub fn identify(&mut self) -> DriveResult<IdentifyData> {
+        let mut buf = [0u8; SECTOR_SIZE];
+        self.run(&AtaCommand::new(ATA_IDENTIFY_DEVICE, AtaProtocol::PioIn), DataTransfer::In(&mut buf), DEFAULT_TIMEOUT)?;
+        Ok(IdentifyData::from_bytes(&buf))
+    }
+
+    pub fn security_set_password(&mut self, which: PasswordType, password: &str) -> DriveResult<()> {
+        let mut block = password_block(which, password)?;
+        if which == PasswordType::Master {
+            // Master password identifier; never 0x0000 or 0xFFFF
+            block[34..36].copy_from_slice(&0xFFFEu16.to_le_bytes());
+        }
+        self.run(&AtaCommand::new(ATA_SECURITY_SET_PASSWORD, AtaProtocol::PioOut), DataTransfer::Out(&block), DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    pub fn security_unlock(&mut self, which: PasswordType, password: &str) -> DriveResult<()> {
+        let block = password_block(which, password)?;
+        self.run(&AtaCommand::new(ATA_SECURITY_UNLOCK, AtaProtocol::PioOut), DataTransfer::Out(&block), DEFAULT_TIMEOUT)
+            .map_err(|e| DriveError::UnlockFailed(e.to_string()))?;
+        Ok(())
+    }
+
+    pub fn security_disable_password(&mut self, which: PasswordType, password: &str) -> DriveResult<()> {
+        let block = password_block(which, password)?;
+        self.run(&AtaCommand::new(ATA_SECURITY_DISABLE_PASSWORD, AtaProtocol::PioOut), DataTransfer::Out(&block), DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    pub fn security_erase_prepare(&mut self) -> DriveResult<()> {
+        self.run(&AtaCommand::new(ATA_SECURITY_ERASE_PREPARE, AtaProtocol::NonData), DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    /// SECURITY ERASE UNIT; blocks until the drive finishes (can take hours)
+    pub fn security_erase_unit(
+        &mut self,
+        which: PasswordType,
+        password: &str,
+        enhanced: bool,
+        timeout: Duration,
+    ) -> DriveResult<()> {
+        let mut block = password_block(which, password)?;
+        if enhanced {
+            block[0] |= 0x02;
+        }
+        self.run(&AtaCommand::new(ATA_SECURITY_ERASE_UNIT, AtaProtocol::PioOut), DataTransfer::Out(&block), timeout)?;
+        Ok(())
+    }
+
+    /// Highest LBA the drive supports, ignoring any HPA
+    pub fn read_native_max_address(&mut self) -> DriveResult<u64> {
+        let registers = self.run(
+            &AtaCommand::ext(ATA_READ_NATIVE_MAX_ADDRESS_EXT, AtaProtocol::NonData),
+            DataTransfer::None,
+            DEFAULT_TIMEOUT,
+        )?;
+        Ok(registers.lba)
+    }
+
+    /// Set the highest user-accessible LBA (creates, resizes or removes an HPA).
+    /// A volatile setting is lost at the next power cycle.
+    pub fn set_max_address(&mut self, max_lba: u64, volatile: bool) -> DriveResult<()> {
+        // The spec requires READ NATIVE MAX ADDRESS immediately before SET MAX
+        self.read_native_max_address()?;
+
+        let command = AtaCommand {
+            lba: max_lba,
+            count: if volatile { 0 } else { 1 }, // Bit 0: value volatile
+            ..AtaCommand::ext(ATA_SET_MAX_ADDRESS_EXT, AtaProtocol::NonData)
+        };
+        self.run(&command, DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    pub fn dco_identify(&mut self) -> DriveResult<DcoIdentify> {
+        let mut buf = [0u8; SECTOR_SIZE];
+        let command = AtaCommand {
+            features: DCO_IDENTIFY,
+            device: DEVICE_LBA,
+            ..AtaCommand::new(ATA_DEVICE_CONFIGURATION, AtaProtocol::PioIn)
+        };
+        self.run(&command, DataTransfer::In(&mut buf), DEFAULT_TIMEOUT)?;
+        Ok(DcoIdentify::from_bytes(&buf))
+    }
+
+    /// Restore the factory configuration, removing the DCO (permanent)
+    pub fn dco_restore(&mut self) -> DriveResult<()> {
+        let command = AtaCommand {
+            features: DCO_RESTORE,
+            device: DEVICE_LBA,
+            ..AtaCommand::new(ATA_DEVICE_CONFIGURATION, AtaProtocol::NonData)
+        };
+        self.run(&command, DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    /// Write everything in the drive's volatile cache to the medium
+    pub fn flush_cache(&mut self) -> DriveResult<()> {
+        self.run(&AtaCommand::ext(ATA_FLUSH_CACHE_EXT, AtaProtocol::NonData), DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    /// Enable or disable the volatile write cache (SET FEATURES)
+    pub fn set_write_cache(&mut self, enabled: bool) -> DriveResult<()> {
+        let command = AtaCommand {
+            features: if enabled { SETFEATURES_WC_ON } else { SETFEATURES_WC_OFF },
+            ..AtaCommand::new(ATA_SET_FEATURES, AtaProtocol::NonData)
+        };
+        self.run(&command, DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    /// DATA SET MANAGEMENT TRIM of `(start_lba, sectors)` ranges
+    pub fn trim(&mut self, ranges: &[(u64, u64)]) -> DriveResult<()> {
+        let entries = dsm_entries(ranges);
+        for blocks in entries.chunks(DSM_ENTRIES_PER_BLOCK * 8) {
+            let mut payload = vec![0u8; blocks.len().div_ceil(DSM_ENTRIES_PER_BLOCK) * SECTOR_SIZE];
+            for (i, entry) in blocks.iter().enumerate() {
+                payload[i * 8..i * 8 + 8].copy_from_slice(&entry.to_le_bytes());
+            }
+
+            let command = AtaCommand {
+                features: DSM_TRIM,
+                count: (payload.len() / SECTOR_SIZE) as u16,
+                ..AtaCommand::ext(ATA_DATA_SET_MANAGEMENT, AtaProtocol::DmaOut)
+            };
+            self.run(&command, DataTransfer::Out(&payload), DEFAULT_TIMEOUT)
+                .map_err(|e| DriveError::TRIMFailed(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    pub fn sanitize_block_erase(&mut self) -> DriveResult<()> {
+        self.sanitize(SANITIZE_BLOCK_ERASE_EXT, SANITIZE_BLOCK_SIGNATURE)
+    }
+
+    pub fn sanitize_crypto_scramble(&mut self) -> DriveResult<()> {
+        self.sanitize(SANITIZE_CRYPTO_SCRAMBLE_EXT, SANITIZE_CRYPTO_SIGNATURE)
+    }
+
+    pub fn sanitize_status(&mut self) -> DriveResult<AtaSanitizeStatus> {
+        let command = AtaCommand {
+            features: SANITIZE_STATUS_EXT,
+            ..AtaCommand::ext(ATA_SANITIZE_DEVICE, AtaProtocol::NonData)
+        };
+        // A failed sanitize reports itself through the error bit, which is
+        // status information here rather than a failure of this command
+        let registers = self.transport.execute(&command, DataTransfer::None, DEFAULT_TIMEOUT)?;
+
+        let in_progress = registers.count & (1 << 14) != 0;
+        Ok(AtaSanitizeStatus {
+            in_progress,
+            completed_without_error: registers.count & (1 << 15) != 0,
+            failed: registers.status & STATUS_ERR != 0,
+            progress: if in_progress { (registers.lba & 0xFFFF) as f64 / 65536.0 * 100.0 } else { 100.0 },
+        })
+    }
+
+    fn sanitize(&mut self, feature: u16, signature: u64) -> DriveResult<()> {
+        let command = AtaCommand {
+            features: feature,
+            lba: signature,
+            ..AtaCommand::ext(ATA_SANITIZE_DEVICE, AtaProtocol::NonData)
+        };
+        self.run(&command, DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    /// Execute and turn an ERR/DF status into an error
+    fn run(&mut self, command: &AtaCommand, data: DataTransfer<'_>, timeout: Duration) -> DriveResult<AtaRegisters> {
+        let registers = self.transport.execute(command, data, timeout)?;
+        if registers.status & (STATUS_ERR | STATUS_DF) != 0 {
+            return Err(DriveError::HardwareCommandFailed(format!(
+                "ATA command 0x{:02X} aborted (status 0x{:02X}, error 0x{:02X})",
+                command.command, registers.status, registers.error
+            )));
+        }
+        Ok(registers)
+    }
+}
+
+/// ATA security erase with a temporary user password and a progress bar
+/// driven by the drive's own erase time estimate.
+///
+/// If the erase fails the temporary password is removed again so the drive
+/// is not left with security enabled.
+pub fn secure_erase<T: AtaTransport + 'static>(mut device: AtaDevice<T>, enhanced: bool) -> DriveResult<()> {
+    const TEMP_PASSWORD: &str = "sayonara";
+
+    let identify = device.identify()?;
+    let security = identify.security();
+    if !security.supported {
+        return Err(DriveError::Unsupported("ATA Security feature set not supported".to_string()));
+    }
+    if security.frozen {
+        return Err(DriveError::DriveFrozen("security commands are blocked until the drive is unfrozen".to_string()));
+    }
+    if security.locked {
+        return Err(DriveError::HardwareCommandFailed("drive is security locked".to_string()));
+    }
+    if enhanced && !security.enhanced_erase_supported {
+        return Err(DriveError::Unsupported("enhanced security erase not supported".to_string()));
+    }
+
+    let estimate = identify.erase_time_minutes(enhanced)
+        .map(|m| Duration::from_secs(m as u64 * 60));
+    let timeout = estimate.map(|e| (e * 2).max(MIN_ERASE_TIMEOUT)).unwrap_or(MIN_ERASE_TIMEOUT * 6);
+
+    device.security_set_password(PasswordType::User, TEMP_PASSWORD)?;
+
+    let started = Instant::now();
+    let worker = std::thread::spawn(move || {
+        let result = device.security_erase_prepare()
+            .and_then(|_| device.security_erase_unit(PasswordType::User, TEMP_PASSWORD, enhanced, timeout));
+        (device, result)
+    });
+
+    let mut bar = ProgressBar::new(48);
+    while !worker.is_finished() {
+        let percent = match estimate {
+            Some(e) if !e.is_zero() => (started.elapsed().as_secs_f64() / e.as_secs_f64() * 100.0).min(99.0),
+            _ => 50.0,
+        };
+        bar.render(percent, None, None);
+        std::thread::sleep(Duration::from_millis(200));
+    }
+
+    let (mut device, result) = worker.join()
+        .map_err(|_| DriveError::HardwareCommandFailed("security erase thread panicked".to_string()))?;
+
+    match result {
+        Ok(()) => {
+            bar.render(100.0, None, None);
+            println!();
+            Ok(())
+        }
+        Err(e) => {
+            let _ = device.security_disable_password(PasswordType::User, TEMP_PASSWORD);
+            Err(e)
+        }
+    }
+}
+
+/// 512-byte security command payload: control word plus 32-byte password
+fn password_block(which: PasswordType, password: &str) -> DriveResult<[u8; SECTOR_SIZE]> {
+    if password.len() > 32 {
+        return Err(DriveError::HardwareCommandFailed("ATA passwords are limited to 32 bytes".to_string()));
+    }
+
+    let mut block = [0u8; SECTOR_SIZE];
+    if which == PasswordType::Master {
+        block[0] = 0x01;
+    }
+    block[2..2 + password.len()].copy_from_slice(password.as_bytes());
+    Ok(block)
+}
+
+/// DSM range entries: 48-bit LBA with a 16-bit sector count on top
+fn dsm_entries(ranges: &[(u64, u64)]) -> Vec<u64> {
+    let mut entries = Vec::new();
+    for &(start, sectors) in ranges {
+        let mut lba = start;
+        let mut remaining = sectors;
+        while remaining > 0 {
+            let length = remaining.min(DSM_MAX_RANGE);
+            entries.push((lba & 0xFFFF_FFFF_FFFF) | (length << 48));
+            lba += length;
+            remaining -= length;
+        }
+    }
+    entries
+}
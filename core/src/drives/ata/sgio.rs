@@ -0,0 +1,229 @@
+// SG_IO transport: ATA commands wrapped in SCSI ATA PASS-THROUGH (16)
+//
+// Works for SATA drives behind libata, USB bridges that implement SAT and
+// most HBAs. CK_COND is always set so the drive's output registers come back
+// in a descriptor-format sense buffer.
+
+use super::{AtaCommand, AtaProtocol, AtaRegisters, AtaTransport, DataTransfer};
+use crate::{DriveError, DriveResult};
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
+const SG_IO: libc::c_ulong = 0x2285;
+const SG_INTERFACE_ID: libc::c_int = b'S' as libc::c_int;
+const SG_DXFER_NONE: libc::c_int = -1;
+const SG_DXFER_TO_DEV: libc::c_int = -2;
+const SG_DXFER_FROM_DEV: libc::c_int = -3;
+
+const ATA_PASS_THROUGH_16: u8 = 0x85;
+const SENSE_LEN: usize = 32;
+
+const SAM_STAT_CHECK_CONDITION: u8 = 0x02;
+const DRIVER_SENSE: u16 = 0x08;
+
+/// Linux `struct sg_io_hdr` (scsi/sg.h)
+#[repr(C)]
+struct SgIoHdr {
+    interface_id: libc::c_int,
+    dxfer_direction: libc::c_int,
+    cmd_len: libc::c_uchar,
+    mx_sb_len: libc::c_uchar,
+    iovec_count: libc::c_ushort,
+    dxfer_len: libc::c_uint,
+    dxferp: *mut libc::c_void,
+    cmdp: *mut libc::c_uchar,
+    sbp: *mut libc::c_uchar,
+    timeout: libc::c_uint,
+    flags: libc::c_uint,
+    pack_id: libc::c_int,
+    usr_ptr: *mut libc::c_void,
+    status: libc::c_uchar,
+    masked_status: libc::c_uchar,
+    msg_status: libc::c_uchar,
+    sb_len_wr: libc::c_uchar,
+    host_status: libc::c_ushort,
+    driver_status: libc::c_ushort,
+    resid: libc::c_int,
+    duration: libc::c_uint,
+    info: libc::c_uint,
+}
+
+/// ATA transport over the Linux SCSI generic SG_IO ioctl
+pub struct SgIoTransport {
+    file: File,
+    device_path: String,
+}
+
+impl SgIoTransport {
+    pub fn open(device_path: &str) -> DriveResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(device_path)
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::PermissionDenied => DriveError::PermissionDenied(
+                    format!("ATA passthrough on {} requires root: {}", device_path, e)
+                ),
+                std::io::ErrorKind::NotFound => DriveError::NotFound(device_path.to_string()),
+                _ => DriveError::IoError(e),
+            })?;
+
+        Ok(Self { file, device_path: device_path.to_string() })
+    }
+}
+
+impl AtaTransport for SgIoTransport {
+    fn execute(
+        &mut self,
+        command: &AtaCommand,
+        data: DataTransfer<'_>,
+        timeout: Duration,
+    ) -> DriveResult<AtaRegisters> {
+        let (direction, buffer, length) = match data {
+            DataTransfer::None => (SG_DXFER_NONE, std::ptr::null_mut(), 0),
+            DataTransfer::In(buf) => (SG_DXFER_FROM_DEV, buf.as_mut_ptr() as *mut libc::c_void, buf.len()),
+            DataTransfer::Out(buf) => (SG_DXFER_TO_DEV, buf.as_ptr() as *mut libc::c_void, buf.len()),
+        };
+
+        let mut cdb = build_cdb(command, length);
+        let mut sense = [0u8; SENSE_LEN];
+        let mut hdr = SgIoHdr {
+            interface_id: SG_INTERFACE_ID,
+            dxfer_direction: direction,
+            cmd_len: cdb.len() as libc::c_uchar,
+            mx_sb_len: SENSE_LEN as libc::c_uchar,
+            iovec_count: 0,
+            dxfer_len: length as libc::c_uint,
+            dxferp: buffer,
+            cmdp: cdb.as_mut_ptr(),
+            sbp: sense.as_mut_ptr(),
+            timeout: timeout.as_millis().min(u32::MAX as u128) as libc::c_uint,
+            flags: 0,
+            pack_id: 0,
+            usr_ptr: std::ptr::null_mut(),
+            status: 0,
+            masked_status: 0,
+            msg_status: 0,
+            sb_len_wr: 0,
+            host_status: 0,
+            driver_status: 0,
+            resid: 0,
+            duration: 0,
+            info: 0,
+        };
+
+        // SAFETY: `hdr` points at buffers (cdb, sense, data) that outlive the
+        // ioctl and whose lengths match the header fields
+        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), SG_IO as _, &mut hdr) };
+        if ret < 0 {
+            return Err(DriveError::IoError(std::io::Error::last_os_error()));
+        }
+
+        // Host adapter timeouts (DID_TIME_OUT) and other transport failures
+        if hdr.host_status == 0x03 {
+            return Err(DriveError::Timeout(format!(
+                "ATA command 0x{:02X} on {} timed out", command.command, self.device_path
+            )));
+        }
+        if hdr.host_status != 0 || (hdr.driver_status & !DRIVER_SENSE) != 0 {
+            return Err(DriveError::HardwareCommandFailed(format!(
+                "ATA command 0x{:02X} on {} failed (host status 0x{:02X}, driver status 0x{:02X})",
+                command.command, self.device_path, hdr.host_status, hdr.driver_status
+            )));
+        }
+
+        match parse_sense(&sense[..hdr.sb_len_wr as usize]) {
+            Some(registers) => Ok(registers),
+            None if hdr.status == 0 => Ok(AtaRegisters::default()),
+            None if hdr.status == SAM_STAT_CHECK_CONDITION => Err(DriveError::HardwareCommandFailed(format!(
+                "ATA command 0x{:02X} on {} rejected (sense key 0x{:X}); the device may not support ATA passthrough",
+                command.command, self.device_path, sense_key(&sense)
+            ))),
+            None => Err(DriveError::HardwareCommandFailed(format!(
+                "ATA command 0x{:02X} on {} failed with SCSI status 0x{:02X}",
+                command.command, self.device_path, hdr.status
+            ))),
+        }
+    }
+}
+
+/// ATA PASS-THROUGH (16) command block
+pub(crate) fn build_cdb(command: &AtaCommand, transfer_len: usize) -> [u8; 16] {
+    let (protocol, t_dir, t_length) = match command.protocol {
+        AtaProtocol::NonData => (3u8, 0u8, 0u8),
+        AtaProtocol::PioIn => (4, 1, 2),
+        AtaProtocol::PioOut => (5, 0, 2),
+        AtaProtocol::DmaOut => (6, 0, 2),
+    };
+    let extend = command.extended as u8;
+    // Sector count carries the transfer length in 512-byte blocks unless the
+    // command uses it for something else
+    let count = if t_length != 0 && command.count == 0 {
+        (transfer_len / 512) as u16
+    } else {
+        command.count
+    };
+
+    let mut cdb = [0u8; 16];
+    cdb[0] = ATA_PASS_THROUGH_16;
+    cdb[1] = (protocol << 1) | extend;
+    // CK_COND=1, T_TYPE=0, BYTE_BLOCK=1, T_LENGTH in sector count
+    cdb[2] = (1 << 5) | (t_dir << 3) | if t_length != 0 { (1 << 2) | t_length } else { 0 };
+    cdb[3] = (command.features >> 8) as u8;
+    cdb[4] = command.features as u8;
+    cdb[5] = (count >> 8) as u8;
+    cdb[6] = count as u8;
+    cdb[7] = (command.lba >> 24) as u8;
+    cdb[8] = command.lba as u8;
+    cdb[9] = (command.lba >> 32) as u8;
+    cdb[10] = (command.lba >> 8) as u8;
+    cdb[11] = (command.lba >> 40) as u8;
+    cdb[12] = (command.lba >> 16) as u8;
+    cdb[13] = command.device;
+    cdb[14] = command.command;
+    cdb
+}
+
+/// Output registers from the ATA Status Return sense descriptor (0x09)
+pub(crate) fn parse_sense(sense: &[u8]) -> Option<AtaRegisters> {
+    // Descriptor format sense data only
+    if sense.len() < 8 || (sense[0] & 0x7F) != 0x72 {
+        return None;
+    }
+
+    let additional = (sense[7] as usize).min(sense.len() - 8);
+    let mut descriptors = &sense[8..8 + additional];
+    while descriptors.len() >= 2 {
+        let length = descriptors[1] as usize + 2;
+        if descriptors[0] == 0x09 && length >= 14 && descriptors.len() >= 14 {
+            let d = descriptors;
+            let lba = (d[7] as u64)
+                | ((d[9] as u64) << 8)
+                | ((d[11] as u64) << 16)
+                | ((d[6] as u64) << 24)
+                | ((d[8] as u64) << 32)
+                | ((d[10] as u64) << 40);
+            return Some(AtaRegisters {
+                error: d[3],
+                count: ((d[4] as u16) << 8) | d[5] as u16,
+                lba,
+                device: d[12],
+                status: d[13],
+            });
+        }
+        descriptors = &descriptors[length.min(descriptors.len())..];
+    }
+
+    None
+}
+
+fn sense_key(sense: &[u8]) -> u8 {
+    match sense.first().map(|b| b & 0x7F) {
+        Some(0x72) | Some(0x73) => sense.get(1).copied().unwrap_or(0) & 0x0F,
+        Some(0x70) | Some(0x71) => sense.get(2).copied().unwrap_or(0) & 0x0F,
+        _ => 0,
+    }
+}
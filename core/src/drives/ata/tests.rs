@@ -0,0 +1,358 @@
+// Tests for the native ATA layer against a simulated drive
+
+use super::*;
+use super::sgio::{build_cdb, parse_sense};
+use std::sync::{Arc, Mutex};
+
+const NATIVE_MAX_LBA: u64 = 1_953_525_167; // 1 TB drive
+const DCO_MAX_LBA: u64 = 1_953_525_167 + 1_000_000;
+
+/// In-memory drive that implements just enough of ATA for the tests
+#[derive(Default)]
+struct SimState {
+    max_lba: u64,
+    native_max_read: bool,
+    dco_active: bool,
+    password: Option<[u8; 32]>,
+    locked: bool,
+    frozen: bool,
+    erase_prepared: bool,
+    erased: bool,
+    enhanced_erase: Option<bool>,
+    trimmed: Vec<(u64, u64)>,
+    write_cache: bool,
+    flushes: u32,
+    commands: Vec<u8>,
+}
+
+#[derive(Clone)]
+struct SimulatedAta {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl SimulatedAta {
+    fn new() -> Self {
+        let state = SimState { max_lba: NATIVE_MAX_LBA - 1000, dco_active: true, ..Default::default() };
+        Self { state: Arc::new(Mutex::new(state)) }
+    }
+
+    fn identify_block(state: &SimState) -> Vec<u8> {
+        let mut words = [0u16; 256];
+        put_string(&mut words, 10, 20, "SIM-SERIAL-0001");
+        put_string(&mut words, 23, 27, "1.0");
+        put_string(&mut words, 27, 47, "Simulated ATA Disk");
+        words[59] = (1 << 12) | (1 << 15); // Sanitize with block erase
+        words[82] = 1 << 10;                // HPA feature set
+        words[83] = (1 << 10) | (1 << 11);  // 48-bit, DCO
+        words[85] = if state.write_cache { 1 << 5 } else { 0 };
+        words[89] = 30;                     // 60 minute erase
+        words[90] = 0x8000 | 5;             // 10 minutes, extended format
+        words[169] = 1;                     // TRIM
+        words[217] = 1;                     // Solid state
+
+        let sectors = state.max_lba + 1;
+        for i in 0..4 {
+            words[100 + i] = (sectors >> (16 * i)) as u16;
+        }
+
+        words[128] = 1
+            | if state.password.is_some() { 1 << 1 } else { 0 }
+            | if state.locked { 1 << 2 } else { 0 }
+            | if state.frozen { 1 << 3 } else { 0 }
+            | (1 << 5);
+
+        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+}
+
+fn put_string(words: &mut [u16], start: usize, end: usize, text: &str) {
+    let mut bytes = text.as_bytes().to_vec();
+    bytes.resize((end - start) * 2, b' ');
+    for (i, pair) in bytes.chunks(2).enumerate() {
+        words[start + i] = u16::from_be_bytes([pair[0], pair[1]]);
+    }
+}
+
+fn aborted() -> AtaRegisters {
+    AtaRegisters { status: STATUS_ERR | 0x40, error: 0x04, ..Default::default() }
+}
+
+fn ok(lba: u64) -> AtaRegisters {
+    AtaRegisters { status: 0x50, lba, ..Default::default() }
+}
+
+impl AtaTransport for SimulatedAta {
+    fn execute(&mut self, command: &AtaCommand, data: DataTransfer<'_>, _timeout: Duration) -> DriveResult<AtaRegisters> {
+        let mut state = self.state.lock().unwrap();
+        state.commands.push(command.command);
+        let was_native_read = std::mem::take(&mut state.native_max_read);
+
+        let password = |data: &DataTransfer<'_>| -> [u8; 32] {
+            match data {
+                DataTransfer::Out(block) => block[2..34].try_into().unwrap(),
+                _ => panic!("security command without payload"),
+            }
+        };
+
+        let registers = match command.command {
+            ATA_IDENTIFY_DEVICE => {
+                if let DataTransfer::In(buf) = data {
+                    buf.copy_from_slice(&Self::identify_block(&state));
+                }
+                ok(0)
+            }
+            ATA_READ_NATIVE_MAX_ADDRESS_EXT => {
+                state.native_max_read = true;
+                ok(NATIVE_MAX_LBA)
+            }
+            ATA_SET_MAX_ADDRESS_EXT => {
+                if !was_native_read || command.lba > NATIVE_MAX_LBA {
+                    aborted()
+                } else {
+                    state.max_lba = command.lba;
+                    ok(0)
+                }
+            }
+            ATA_DEVICE_CONFIGURATION => match command.features {
+                DCO_IDENTIFY => {
+                    if let DataTransfer::In(buf) = data {
+                        buf.fill(0);
+                        buf[0..2].copy_from_slice(&2u16.to_le_bytes());
+                        let max = if state.dco_active { DCO_MAX_LBA } else { NATIVE_MAX_LBA };
+                        buf[6..14].copy_from_slice(&max.to_le_bytes());
+                    }
+                    ok(0)
+                }
+                DCO_RESTORE => {
+                    state.dco_active = false;
+                    ok(0)
+                }
+                _ => aborted(),
+            },
+            ATA_SECURITY_SET_PASSWORD => {
+                if state.frozen {
+                    aborted()
+                } else {
+                    state.password = Some(password(&data));
+                    ok(0)
+                }
+            }
+            ATA_SECURITY_UNLOCK => {
+                if state.password == Some(password(&data)) {
+                    state.locked = false;
+                    ok(0)
+                } else {
+                    aborted()
+                }
+            }
+            ATA_SECURITY_DISABLE_PASSWORD => {
+                if state.password == Some(password(&data)) {
+                    state.password = None;
+                    ok(0)
+                } else {
+                    aborted()
+                }
+            }
+            ATA_SECURITY_ERASE_PREPARE => {
+                state.erase_prepared = true;
+                ok(0)
+            }
+            ATA_SECURITY_ERASE_UNIT => {
+                let enhanced = matches!(&data, DataTransfer::Out(block) if block[0] & 0x02 != 0);
+                if !std::mem::take(&mut state.erase_prepared) || state.password != Some(password(&data)) {
+                    aborted()
+                } else {
+                    state.erased = true;
+                    state.enhanced_erase = Some(enhanced);
+                    state.password = None; // A successful erase disables security
+                    ok(0)
+                }
+            }
+            ATA_FLUSH_CACHE_EXT => {
+                state.flushes += 1;
+                ok(0)
+            }
+            ATA_SET_FEATURES => match command.features {
+                SETFEATURES_WC_ON => {
+                    state.write_cache = true;
+                    ok(0)
+                }
+                SETFEATURES_WC_OFF => {
+                    state.write_cache = false;
+                    ok(0)
+                }
+                _ => aborted(),
+            },
+            ATA_DATA_SET_MANAGEMENT => {
+                if let DataTransfer::Out(payload) = data {
+                    for entry in payload.chunks(8) {
+                        let entry = u64::from_le_bytes(entry.try_into().unwrap());
+                        if entry != 0 {
+                            state.trimmed.push((entry & 0xFFFF_FFFF_FFFF, entry >> 48));
+                        }
+                    }
+                }
+                ok(0)
+            }
+            _ => aborted(),
+        };
+
+        Ok(registers)
+    }
+}
+
+#[test]
+fn test_identify_parsing() {
+    let mut device = AtaDevice::new(SimulatedAta::new());
+    let identify = device.identify().unwrap();
+
+    assert_eq!(identify.model(), "Simulated ATA Disk");
+    assert_eq!(identify.serial(), "SIM-SERIAL-0001");
+    assert_eq!(identify.firmware(), "1.0");
+    assert_eq!(identify.user_addressable_sectors(), NATIVE_MAX_LBA - 999);
+    assert_eq!(identify.logical_sector_size(), 512);
+    assert!(identify.hpa_supported() && identify.dco_supported() && identify.trim_supported());
+    assert_eq!(identify.rotation_rate(), 1);
+    assert_eq!(identify.erase_time_minutes(false), Some(60));
+    assert_eq!(identify.erase_time_minutes(true), Some(10));
+
+    let security = identify.security();
+    assert!(security.supported && security.enhanced_erase_supported);
+    assert!(!security.enabled && !security.frozen);
+
+    let sanitize = identify.sanitize();
+    assert!(sanitize.supported && sanitize.block_erase && !sanitize.crypto_scramble);
+}
+
+#[test]
+fn test_hpa_remove_and_restore() {
+    let sim = SimulatedAta::new();
+    let mut device = AtaDevice::new(sim.clone());
+
+    let original = device.identify().unwrap().user_addressable_sectors();
+    assert_eq!(device.read_native_max_address().unwrap(), NATIVE_MAX_LBA);
+
+    device.set_max_address(NATIVE_MAX_LBA, true).unwrap();
+    assert_eq!(device.identify().unwrap().user_addressable_sectors(), NATIVE_MAX_LBA + 1);
+
+    device.set_max_address(original - 1, true).unwrap();
+    assert_eq!(device.identify().unwrap().user_addressable_sectors(), original);
+
+    // SET MAX is always preceded by READ NATIVE MAX
+    let commands = sim.state.lock().unwrap().commands.clone();
+    for (i, &command) in commands.iter().enumerate() {
+        if command == ATA_SET_MAX_ADDRESS_EXT {
+            assert_eq!(commands[i - 1], ATA_READ_NATIVE_MAX_ADDRESS_EXT);
+        }
+    }
+}
+
+#[test]
+fn test_dco_identify_and_restore() {
+    let mut device = AtaDevice::new(SimulatedAta::new());
+
+    let dco = device.dco_identify().unwrap();
+    assert_eq!(dco.revision(), 2);
+    assert_eq!(dco.max_lba(), DCO_MAX_LBA);
+
+    device.dco_restore().unwrap();
+    assert_eq!(device.dco_identify().unwrap().max_lba(), NATIVE_MAX_LBA);
+}
+
+#[test]
+fn test_secure_erase_flow() {
+    let sim = SimulatedAta::new();
+    secure_erase(AtaDevice::new(sim.clone()), true).unwrap();
+
+    let state = sim.state.lock().unwrap();
+    assert!(state.erased);
+    assert_eq!(state.enhanced_erase, Some(true));
+    assert!(state.password.is_none());
+}
+
+#[test]
+fn test_secure_erase_refuses_frozen_drive() {
+    let sim = SimulatedAta::new();
+    sim.state.lock().unwrap().frozen = true;
+
+    let result = secure_erase(AtaDevice::new(sim.clone()), false);
+    assert!(matches!(result, Err(DriveError::DriveFrozen(_))));
+    assert!(!sim.state.lock().unwrap().commands.contains(&ATA_SECURITY_SET_PASSWORD));
+}
+
+#[test]
+fn test_security_unlock_and_wrong_password() {
+    let sim = SimulatedAta::new();
+    let mut device = AtaDevice::new(sim.clone());
+    device.security_set_password(PasswordType::User, "secret").unwrap();
+    sim.state.lock().unwrap().locked = true;
+
+    assert!(matches!(device.security_unlock(PasswordType::User, "wrong"), Err(DriveError::UnlockFailed(_))));
+    device.security_unlock(PasswordType::User, "secret").unwrap();
+    assert!(!device.identify().unwrap().security().locked);
+
+    assert!(device.security_set_password(PasswordType::User, &"x".repeat(33)).is_err());
+}
+
+#[test]
+fn test_write_cache_and_flush() {
+    let drive = SimulatedAta::new();
+    let mut device = AtaDevice::new(drive.clone());
+
+    device.set_write_cache(true).unwrap();
+    assert!(device.identify().unwrap().write_cache_enabled());
+    device.flush_cache().unwrap();
+    device.set_write_cache(false).unwrap();
+    assert!(!device.identify().unwrap().write_cache_enabled());
+
+    let state = drive.state.lock().unwrap();
+    assert_eq!(state.flushes, 1);
+    assert!(state.commands.contains(&ATA_SET_FEATURES));
+}
+
+#[test]
+fn test_trim_splits_ranges() {
+    let sim = SimulatedAta::new();
+    let mut device = AtaDevice::new(sim.clone());
+    device.trim(&[(0, 70_000), (1_000_000, 10)]).unwrap();
+
+    let state = sim.state.lock().unwrap();
+    assert_eq!(state.trimmed, vec![(0, 65_535), (65_535, 4_465), (1_000_000, 10)]);
+}
+
+#[test]
+fn test_cdb_layout() {
+    let command = AtaCommand {
+        lba: 0x0000_1234_5678_9ABC,
+        ..AtaCommand::ext(ATA_SET_MAX_ADDRESS_EXT, AtaProtocol::NonData)
+    };
+    let cdb = build_cdb(&command, 0);
+    assert_eq!(cdb[0], 0x85);
+    assert_eq!(cdb[1], (3 << 1) | 1);
+    assert_eq!(cdb[2], 1 << 5);
+    assert_eq!(&cdb[7..13], &[0x56, 0xBC, 0x34, 0x9A, 0x12, 0x78]);
+    assert_eq!(cdb[13], DEVICE_LBA);
+    assert_eq!(cdb[14], ATA_SET_MAX_ADDRESS_EXT);
+
+    let identify = build_cdb(&AtaCommand::new(ATA_IDENTIFY_DEVICE, AtaProtocol::PioIn), 512);
+    assert_eq!(identify[1], 4 << 1);
+    assert_eq!(identify[2], (1 << 5) | (1 << 3) | (1 << 2) | 2);
+    assert_eq!(identify[6], 1);
+}
+
+#[test]
+fn test_sense_descriptor_parsing() {
+    let mut sense = [0u8; 22];
+    sense[0] = 0x72;
+    sense[1] = 0x01; // Recovered error
+    sense[7] = 14;
+    sense[8..22].copy_from_slice(&[
+        0x09, 0x0C, 0x01, 0x00, 0x00, 0x01, 0x74, 0xAF, 0x00, 0x6D, 0x00, 0x70, 0x40, 0x50,
+    ]);
+
+    let registers = parse_sense(&sense).unwrap();
+    assert_eq!(registers.status, 0x50);
+    assert_eq!(registers.count, 1);
+    assert_eq!(registers.lba, NATIVE_MAX_LBA);
+    assert!(parse_sense(&[0x70, 0, 0x05, 0, 0, 0, 0, 0]).is_none());
+}
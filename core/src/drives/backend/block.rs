@@ -0,0 +1,272 @@
+// Real block device backend: positional I/O on the device node plus the
+// native ATA/NVMe layers and the operations modules for everything else
+
+use super::{DeviceArea, DeviceBackend, DeviceNamespace, HardwarePartition};
+use crate::drives::ata::{self, AtaDevice};
+use crate::drives::detection::DriveDetector;
+use crate::drives::nvme_admin::{NvmeDevice, SecureEraseSetting};
+use crate::drives::operations::hpa_dco::{DCOInfo, HPAInfo};
+use crate::drives::operations::smart::SMARTHealth;
+use crate::drives::types::emmc::{EMMCDevice, UFSDevice};
+use crate::drives::types::smr::{SMRDrive, Zone};
+use crate::drives::{FreezeMitigation, HPADCOManager, NVMeWipe, SEDManager, SMARTMonitor, SSDWipe};
+use crate::{DriveError, DriveInfo, DriveResult, DriveType, EncryptionStatus, SanitizeOption};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Seek, SeekFrom};
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::AsRawFd;
+
+// _IO(0x12, 119)
+const BLKDISCARD: libc::c_ulong = 0x1277;
+
+pub struct BlockDevice {
+    device_path: String,
+    file: Option<File>,
+    drive_type: Option<DriveType>,
+    smr: Option<SMRDrive>,
+    boot_partitions: HashMap<u8, File>,
+}
+
+impl BlockDevice {
+    /// The device node is only opened for I/O once it is first needed
+    pub fn open(device_path: &str) -> DriveResult<Self> {
+        if !std::path::Path::new(device_path).exists() {
+            return Err(DriveError::NotFound(device_path.to_string()));
+        }
+
+        Ok(Self {
+            device_path: device_path.to_string(),
+            file: None,
+            drive_type: None,
+            smr: None,
+            boot_partitions: HashMap::new(),
+        })
+    }
+
+    fn file(&mut self) -> DriveResult<&mut File> {
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&self.device_path)
+                .map_err(|e| match e.kind() {
+                    std::io::ErrorKind::PermissionDenied => DriveError::PermissionDenied(
+                        format!("Opening {} for writing requires root: {}", self.device_path, e)
+                    ),
+                    _ => DriveError::IoError(e),
+                })?;
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().expect("opened above"))
+    }
+
+    fn drive_type(&mut self) -> DriveResult<DriveType> {
+        match &self.drive_type {
+            Some(drive_type) => Ok(drive_type.clone()),
+            None => Ok(self.identify()?.drive_type),
+        }
+    }
+
+    fn smr(&mut self) -> DriveResult<&SMRDrive> {
+        if self.smr.is_none() {
+            let smr = SMRDrive::get_zone_configuration(&self.device_path)
+                .map_err(|e| DriveError::HardwareCommandFailed(format!("Zone report failed: {}", e)))?;
+            self.smr = Some(smr);
+        }
+        Ok(self.smr.as_ref().expect("loaded above"))
+    }
+
+    fn emmc(&self) -> DriveResult<EMMCDevice> {
+        EMMCDevice::get_configuration(&self.device_path)
+            .map_err(|e| DriveError::HardwareCommandFailed(format!("eMMC detection failed: {}", e)))
+    }
+
+    /// Boot partition n is the mmcblkXboot(n-1) node, which the kernel keeps
+    /// read-only until force_ro is cleared
+    fn boot_partition(&mut self, number: u8) -> DriveResult<&mut File> {
+        if !self.boot_partitions.contains_key(&number) {
+            let node = format!("{}boot{}", self.device_path, number.saturating_sub(1));
+            let name = node.trim_start_matches("/dev/");
+            std::fs::write(format!("/sys/block/{}/force_ro", name), "0")?;
+            let file = OpenOptions::new().write(true).open(&node)?;
+            self.boot_partitions.insert(number, file);
+        }
+        Ok(self.boot_partitions.get_mut(&number).expect("opened above"))
+    }
+
+    /// Identity guessed from the device name when detection tools fail
+    fn basic_drive_info(&mut self) -> DriveResult<DriveInfo> {
+        let drive_type = if self.device_path.contains("nvme") {
+            DriveType::NVMe
+        } else if self.device_path.contains("mmcblk") {
+            DriveType::EMMC
+        } else {
+            DriveType::HDD
+        };
+        let size = self.file()?.seek(SeekFrom::End(0))?;
+
+        Ok(DriveInfo {
+            device_path: self.device_path.clone(),
+            model: "Unknown".to_string(),
+            serial: "Unknown".to_string(),
+            size,
+            drive_type,
+            encryption_status: EncryptionStatus::None,
+            capabilities: Default::default(),
+            health_status: None,
+            temperature_celsius: None,
+        })
+    }
+}
+
+impl DeviceBackend for BlockDevice {
+    fn identify(&mut self) -> DriveResult<DriveInfo> {
+        let info = match DriveDetector::analyze_drive(&self.device_path) {
+            Ok(info) => info,
+            Err(_) => self.basic_drive_info()?,
+        };
+        self.drive_type = Some(info.drive_type.clone());
+        Ok(info)
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> DriveResult<()> {
+        Ok(self.file()?.read_exact_at(buf, offset)?)
+    }
+
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> DriveResult<()> {
+        Ok(self.file()?.write_all_at(data, offset)?)
+    }
+
+    fn flush(&mut self) -> DriveResult<()> {
+        Ok(self.file()?.sync_all()?)
+    }
+
+    fn set_write_cache(&mut self, enabled: bool) -> DriveResult<()> {
+        AtaDevice::open(&self.device_path)?.set_write_cache(enabled)
+    }
+
+    fn discard(&mut self, offset: u64, length: u64) -> DriveResult<()> {
+        let range = [offset, length];
+        let fd = self.file()?.as_raw_fd();
+        // SAFETY: BLKDISCARD reads a [start, length] pair of u64
+        let ret = unsafe { libc::ioctl(fd, BLKDISCARD as _, range.as_ptr()) };
+        if ret < 0 {
+            return Err(DriveError::TRIMFailed(std::io::Error::last_os_error().to_string()));
+        }
+        Ok(())
+    }
+
+    fn hidden_areas(&mut self) -> DriveResult<(Option<HPAInfo>, Option<DCOInfo>)> {
+        HPADCOManager::check_hidden_areas(&self.device_path)
+    }
+
+    fn remove_hpa(&mut self) -> DriveResult<()> {
+        HPADCOManager::remove_hpa_temporary(&self.device_path)
+    }
+
+    fn restore_hpa(&mut self, max_sectors: u64) -> DriveResult<()> {
+        HPADCOManager::restore_hpa(&self.device_path, max_sectors)
+    }
+
+    fn remove_dco(&mut self) -> DriveResult<()> {
+        HPADCOManager::remove_dco(&self.device_path)
+    }
+
+    fn unfreeze(&mut self) -> DriveResult<()> {
+        FreezeMitigation::unfreeze_drive(&self.device_path)
+    }
+
+    fn secure_erase(&mut self, enhanced: bool) -> DriveResult<()> {
+        match self.drive_type()? {
+            DriveType::NVMe | DriveType::Optane => Ok(NVMeWipe::secure_erase(&self.device_path)?),
+            DriveType::EMMC => Ok(self.emmc()?.secure_erase()?),
+            DriveType::UFS => Err(DriveError::Unsupported("UFS has no secure erase; use purge".to_string())),
+            _ => ata::secure_erase(AtaDevice::open(&self.device_path)?, enhanced),
+        }
+    }
+
+    fn sanitize(&mut self, option: SanitizeOption) -> DriveResult<()> {
+        match self.drive_type()? {
+            DriveType::NVMe | DriveType::Optane => NVMeWipe::sanitize(&self.device_path, &[option]).map(|_| ())?,
+            DriveType::EMMC => self.emmc()?.sanitize()?,
+            DriveType::UFS => {
+                let ufs = UFSDevice {
+                    device_path: self.device_path.clone(),
+                    luns: Vec::new(),
+                    supports_purge: true,
+                    ufs_version: String::new(),
+                };
+                ufs.purge()?
+            }
+            _ => SSDWipe::sanitize(&self.device_path, &[option])?,
+        }
+        Ok(())
+    }
+
+    fn crypto_erase(&mut self) -> DriveResult<()> {
+        let sed_info = SEDManager::detect_sed(&self.device_path)?;
+        SEDManager::crypto_erase(&self.device_path, &sed_info)
+    }
+
+    fn namespaces(&mut self) -> DriveResult<Vec<DeviceNamespace>> {
+        if !matches!(self.drive_type()?, DriveType::NVMe | DriveType::Optane) {
+            return Ok(Vec::new());
+        }
+
+        let mut device = NvmeDevice::open(&self.device_path)?;
+        device.active_namespaces()?
+            .into_iter()
+            .map(|nsid| Ok(DeviceNamespace { nsid, size: device.identify_namespace(nsid)?.size_bytes() }))
+            .collect()
+    }
+
+    fn format_namespace(&mut self, nsid: u32) -> DriveResult<()> {
+        NvmeDevice::open(&self.device_path)?.format_nvm(nsid, SecureEraseSetting::UserData)
+    }
+
+    fn zones(&mut self) -> DriveResult<Vec<Zone>> {
+        if self.drive_type()? != DriveType::SMR {
+            return Ok(Vec::new());
+        }
+        Ok(self.smr()?.zones.clone())
+    }
+
+    fn reset_zone(&mut self, zone: &Zone) -> DriveResult<()> {
+        self.smr()?
+            .reset_zone(zone.zone_number)
+            .map_err(|e| DriveError::HardwareCommandFailed(e.to_string()))
+    }
+
+    fn hardware_partitions(&mut self) -> DriveResult<Vec<HardwarePartition>> {
+        if self.drive_type()? != DriveType::EMMC {
+            return Ok(Vec::new());
+        }
+        Ok(self.emmc()?.boot_partitions.iter()
+            .map(|boot| HardwarePartition { number: boot.partition_number, size: boot.size })
+            .collect())
+    }
+
+    fn write_hardware_partition(&mut self, number: u8, offset: u64, data: &[u8]) -> DriveResult<()> {
+        Ok(self.boot_partition(number)?.write_all_at(data, offset)?)
+    }
+
+    fn smart(&mut self) -> DriveResult<SMARTHealth> {
+        SMARTMonitor::get_health(&self.device_path)
+    }
+
+    /// Sequential zones have to be reset before they are rewritten and boot
+    /// partitions are nodes of their own, so those overwrites need the backend
+    fn overwrite_backend(&mut self, area: DeviceArea) -> DriveResult<Option<Box<dyn DeviceBackend>>> {
+        if area == DeviceArea::User && self.drive_type()? != DriveType::SMR {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(Self {
+            device_path: self.device_path.clone(),
+            file: None,
+            drive_type: self.drive_type.clone(),
+            smr: self.smr.clone(),
+            boot_partitions: HashMap::new(),
+        })))
+    }
+}
@@ -0,0 +1,107 @@
+// Platform I/O over a device backend
+//
+// Lets an `IOHandle`, and so the overwrite algorithms with their checkpoints,
+// bad sector handling, throttling and read-back, run on drives the backend
+// reaches in ways other than a plain device node: simulated drives, eMMC boot
+// partitions and SMR drives whose sequential zones must be reset before they
+// are rewritten.
+
+use super::{DeviceArea, DeviceBackend};
+use crate::drives::types::smr::{Zone, ZoneType};
+use crate::io::platform_specific::PlatformIO;
+use crate::io::{IOError, IOResult};
+use crate::DriveError;
+use std::fs::File;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Zones report their position in 512 byte sectors
+const ZONE_SECTOR_SIZE: u64 = 512;
+
+/// `PlatformIO` that issues reads, writes and flushes for one area of a drive
+/// to its backend. Clones share the backend.
+#[derive(Clone)]
+pub struct BackendIO {
+    backend: Arc<Mutex<Box<dyn DeviceBackend>>>,
+    area: DeviceArea,
+    /// Sequential zones of the user area; each is reset when a write
+    /// reaches its start
+    sequential_zones: Vec<Zone>,
+}
+
+impl BackendIO {
+    pub fn new(mut backend: Box<dyn DeviceBackend>, area: DeviceArea) -> IOResult<Self> {
+        let sequential_zones = match area {
+            DeviceArea::User => backend.zones().map_err(io_error)?
+                .into_iter()
+                .filter(|zone| zone.zone_type != ZoneType::Conventional)
+                .collect(),
+            DeviceArea::HardwarePartition(_) => Vec::new(),
+        };
+
+        Ok(Self { backend: Arc::new(Mutex::new(backend)), area, sequential_zones })
+    }
+
+    fn backend(&self) -> MutexGuard<'_, Box<dyn DeviceBackend>> {
+        self.backend.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl PlatformIO for BackendIO {
+    /// The handle only takes the sector size from this file; all I/O goes
+    /// through the backend
+    fn open_optimized(&self, path: &str, _direct_io: bool) -> IOResult<File> {
+        Ok(File::open(path)?)
+    }
+
+    fn write_optimized(&self, _file: &File, data: &[u8], offset: u64) -> IOResult<usize> {
+        let mut backend = self.backend();
+        match self.area {
+            DeviceArea::User => {
+                let end = offset + data.len() as u64;
+                for zone in &self.sequential_zones {
+                    if (offset..end).contains(&(zone.zone_start_lba * ZONE_SECTOR_SIZE)) {
+                        backend.reset_zone(zone).map_err(io_error)?;
+                    }
+                }
+                backend.write_at(offset, data).map_err(io_error)?;
+            }
+            DeviceArea::HardwarePartition(number) => {
+                backend.write_hardware_partition(number, offset, data).map_err(io_error)?;
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn read_optimized(&self, _file: &File, buffer: &mut [u8], offset: u64) -> IOResult<usize> {
+        match self.area {
+            DeviceArea::User => self.backend().read_at(offset, buffer).map_err(io_error)?,
+            DeviceArea::HardwarePartition(number) => {
+                return Err(IOError::PlatformNotSupported(format!(
+                    "Hardware partition {} cannot be read back", number
+                )));
+            }
+        }
+        Ok(buffer.len())
+    }
+
+    fn sync_data(&self, _file: &File) -> IOResult<()> {
+        self.backend().flush().map_err(io_error)
+    }
+
+    fn temperature(&self, _device_path: &str) -> Option<u32> {
+        self.backend().smart().ok().and_then(|health| health.temperature_celsius)
+    }
+
+    fn platform_name(&self) -> &str {
+        "Device backend"
+    }
+}
+
+/// Failed writes stay `std::io::Error`s, so the handle recognizes medium
+/// errors and skips bad sectors; anything else keeps its `DriveError`
+fn io_error(err: DriveError) -> IOError {
+    match err {
+        DriveError::IoError(e) => IOError::IoError(e),
+        other => IOError::AnyhowError(other.into()),
+    }
+}
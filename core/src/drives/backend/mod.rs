@@ -0,0 +1,106 @@
+// Device backends: the operations a wipe needs from a drive, behind one trait
+//
+// `BlockDevice` drives real hardware through the native ATA/NVMe layers and
+// the existing operations modules. `SimulatedDevice` is a file-backed stand-in
+// that emulates HDDs, SATA SSDs, NVMe namespaces, SMR zones, Optane, hybrid
+// SSHD, eMMC, UFS and RAID member drives and SEDs, with fault injection, so
+// whole wipe flows can run without hardware.
+
+pub mod block;
+pub mod io;
+pub mod simulated;
+
+#[cfg(test)]
+mod tests;
+
+pub use block::BlockDevice;
+pub use io::BackendIO;
+pub use simulated::{Fault, SimulatedCommand, SimulatedDevice, SimulatedKind, SimulatedProfile};
+
+use crate::drives::operations::hpa_dco::{DCOInfo, HPAInfo};
+use crate::drives::operations::smart::SMARTHealth;
+use crate::drives::types::smr::Zone;
+use crate::{DriveInfo, DriveResult, SanitizeOption};
+
+/// A namespace and its capacity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeviceNamespace {
+    pub nsid: u32,
+    pub size: u64,
+}
+
+/// A hardware partition outside the user data area, such as an eMMC boot
+/// partition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HardwarePartition {
+    pub number: u8,
+    pub size: u64,
+}
+
+/// Part of a drive an overwrite covers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceArea {
+    User,
+    HardwarePartition(u8),
+}
+
+/// Everything a wipe flow issues to a drive
+pub trait DeviceBackend: Send {
+    /// Identity, type and capabilities, as drive detection reports them
+    fn identify(&mut self) -> DriveResult<DriveInfo>;
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> DriveResult<()>;
+
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> DriveResult<()>;
+
+    fn flush(&mut self) -> DriveResult<()>;
+
+    /// Enable or disable the drive's volatile write cache
+    fn set_write_cache(&mut self, enabled: bool) -> DriveResult<()>;
+
+    /// TRIM / deallocate a byte range
+    fn discard(&mut self, offset: u64, length: u64) -> DriveResult<()>;
+
+    fn hidden_areas(&mut self) -> DriveResult<(Option<HPAInfo>, Option<DCOInfo>)>;
+
+    /// Expose the area hidden by an HPA until the next power cycle
+    fn remove_hpa(&mut self) -> DriveResult<()>;
+
+    fn restore_hpa(&mut self, max_sectors: u64) -> DriveResult<()>;
+
+    fn remove_dco(&mut self) -> DriveResult<()>;
+
+    fn unfreeze(&mut self) -> DriveResult<()>;
+
+    /// ATA SECURITY ERASE UNIT, or Format NVM with secure erase on NVMe
+    fn secure_erase(&mut self, enhanced: bool) -> DriveResult<()>;
+
+    /// Run a sanitize to completion
+    fn sanitize(&mut self, option: SanitizeOption) -> DriveResult<()>;
+
+    /// SED cryptographic erase
+    fn crypto_erase(&mut self) -> DriveResult<()>;
+
+    /// Active namespaces; empty for drives without namespaces
+    fn namespaces(&mut self) -> DriveResult<Vec<DeviceNamespace>>;
+
+    /// Format NVM with user data erase on one namespace
+    fn format_namespace(&mut self, nsid: u32) -> DriveResult<()>;
+
+    /// Zones of a zoned drive; empty for conventional drives
+    fn zones(&mut self) -> DriveResult<Vec<Zone>>;
+
+    fn reset_zone(&mut self, zone: &Zone) -> DriveResult<()>;
+
+    /// Boot partitions and the like; empty for most drives
+    fn hardware_partitions(&mut self) -> DriveResult<Vec<HardwarePartition>>;
+
+    fn write_hardware_partition(&mut self, number: u8, offset: u64, data: &[u8]) -> DriveResult<()>;
+
+    /// A second handle on this drive for overwrites of `area` to run on
+    /// through `BackendIO`, or None when they can open the device node
+    /// themselves
+    fn overwrite_backend(&mut self, area: DeviceArea) -> DriveResult<Option<Box<dyn DeviceBackend>>>;
+
+    fn smart(&mut self) -> DriveResult<SMARTHealth>;
+}
@@ -0,0 +1,827 @@
+// File-backed simulated drive
+//
+// The image file holds the whole medium, including sectors hidden behind an
+// HPA or DCO and, after those, the NAND cache of a hybrid drive or the boot
+// partitions of an eMMC device. A new image is filled with `RESIDUAL_PATTERN` so tests can check
+// that nothing of the "old data" survived a wipe. State lives behind an
+// `Arc<Mutex<..>>`: hand one clone to the code under test and keep another to
+// inject faults and inspect the drive afterwards.
+
+use super::{DeviceArea, DeviceBackend, DeviceNamespace, HardwarePartition};
+use crate::drives::operations::hpa_dco::{DCOInfo, HPAInfo};
+use crate::drives::operations::smart::SMARTHealth;
+use crate::drives::types::smr::{Zone, ZoneCondition, ZoneType};
+use crate::{
+    DriveCapabilities, DriveError, DriveInfo, DriveResult, DriveType, EncryptionStatus, FreezeStatus,
+    HealthStatus, SEDType, SanitizeOption,
+};
+use rand::RngCore;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+pub const SECTOR_SIZE: u64 = 512;
+
+/// Content of a freshly created image
+pub const RESIDUAL_PATTERN: &[u8; 16] = b"SIMULATED-DATA! ";
+
+const FILL_CHUNK: usize = 1024 * 1024;
+
+/// Kind of drive to emulate
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulatedKind {
+    Hdd,
+    SataSsd,
+    /// NVMe controller whose capacity is split evenly across `namespaces`
+    Nvme { namespaces: u32 },
+    /// Host-managed SMR; the first `conventional_zones` zones accept random writes
+    Smr { zone_size: u64, conventional_zones: u32 },
+    /// Optane NVMe drive with a single namespace
+    Optane,
+    /// SSHD whose NAND cache keeps copies of old data until it is flushed
+    /// with the write cache disabled
+    Hybrid { cache_size: u64 },
+    /// eMMC device with two boot partitions next to the user data area
+    Emmc { boot_partition_size: u64 },
+    Ufs,
+    /// Member disk of a software or firmware RAID array
+    RaidMember,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulatedProfile {
+    pub kind: SimulatedKind,
+    pub model: String,
+    pub serial: String,
+    /// User-accessible capacity in bytes
+    pub capacity: u64,
+    /// Sectors hidden behind an HPA, above `capacity`
+    pub hpa_sectors: u64,
+    /// Sectors hidden behind a DCO, above the HPA
+    pub dco_sectors: u64,
+    pub frozen: bool,
+    /// Whether unfreezing (e.g. a suspend/resume cycle) lifts the freeze
+    pub unfreezable: bool,
+    pub sed: Option<SEDType>,
+}
+
+impl SimulatedProfile {
+    fn new(kind: SimulatedKind, model: &str, capacity: u64) -> Self {
+        Self {
+            kind,
+            model: model.to_string(),
+            serial: "SIM0000001".to_string(),
+            capacity,
+            hpa_sectors: 0,
+            dco_sectors: 0,
+            frozen: false,
+            unfreezable: true,
+            sed: None,
+        }
+    }
+
+    pub fn hdd(capacity: u64) -> Self {
+        Self::new(SimulatedKind::Hdd, "Simulated HDD", capacity)
+    }
+
+    pub fn sata_ssd(capacity: u64) -> Self {
+        Self::new(SimulatedKind::SataSsd, "Simulated SATA SSD", capacity)
+    }
+
+    pub fn nvme(capacity: u64, namespaces: u32) -> Self {
+        Self::new(SimulatedKind::Nvme { namespaces }, "Simulated NVMe SSD", capacity)
+    }
+
+    pub fn smr(capacity: u64, zone_size: u64, conventional_zones: u32) -> Self {
+        Self::new(SimulatedKind::Smr { zone_size, conventional_zones }, "Simulated SMR HDD", capacity)
+    }
+
+    pub fn optane(capacity: u64) -> Self {
+        Self::new(SimulatedKind::Optane, "Simulated Optane SSD", capacity)
+    }
+
+    pub fn hybrid(capacity: u64, cache_size: u64) -> Self {
+        Self::new(SimulatedKind::Hybrid { cache_size }, "Simulated SSHD", capacity)
+    }
+
+    pub fn emmc(capacity: u64, boot_partition_size: u64) -> Self {
+        Self::new(SimulatedKind::Emmc { boot_partition_size }, "Simulated eMMC", capacity)
+    }
+
+    pub fn ufs(capacity: u64) -> Self {
+        Self::new(SimulatedKind::Ufs, "Simulated UFS", capacity)
+    }
+
+    pub fn raid_member(capacity: u64) -> Self {
+        Self::new(SimulatedKind::RaidMember, "Simulated RAID member", capacity)
+    }
+
+    fn is_ata(&self) -> bool {
+        matches!(self.kind, SimulatedKind::Hdd | SimulatedKind::SataSsd | SimulatedKind::Smr { .. } |
+                            SimulatedKind::Hybrid { .. } | SimulatedKind::RaidMember)
+    }
+
+    fn drive_type(&self) -> DriveType {
+        match self.kind {
+            SimulatedKind::Hdd => DriveType::HDD,
+            SimulatedKind::SataSsd => DriveType::SSD,
+            SimulatedKind::Nvme { .. } => DriveType::NVMe,
+            SimulatedKind::Smr { .. } => DriveType::SMR,
+            SimulatedKind::Optane => DriveType::Optane,
+            SimulatedKind::Hybrid { .. } => DriveType::HybridSSHD,
+            SimulatedKind::Emmc { .. } => DriveType::EMMC,
+            SimulatedKind::Ufs => DriveType::UFS,
+            SimulatedKind::RaidMember => DriveType::RAID,
+        }
+    }
+
+    fn sanitize_options(&self) -> Vec<SanitizeOption> {
+        match self.kind {
+            SimulatedKind::SataSsd => vec![SanitizeOption::BlockErase, SanitizeOption::CryptoScramble],
+            SimulatedKind::Nvme { .. } => {
+                vec![SanitizeOption::CryptoErase, SanitizeOption::BlockErase, SanitizeOption::Overwrite]
+            }
+            SimulatedKind::Optane => vec![SanitizeOption::CryptoErase],
+            // eMMC sanitize and UFS purge
+            SimulatedKind::Emmc { .. } | SimulatedKind::Ufs => vec![SanitizeOption::BlockErase],
+            _ => Vec::new(),
+        }
+    }
+
+    fn supports_trim(&self) -> bool {
+        matches!(self.kind, SimulatedKind::SataSsd | SimulatedKind::Nvme { .. } | SimulatedKind::Optane |
+                            SimulatedKind::Emmc { .. } | SimulatedKind::Ufs)
+    }
+
+    fn supports_secure_erase(&self) -> bool {
+        self.kind != SimulatedKind::Ufs
+    }
+
+    /// Bytes stored after the hidden areas
+    fn extra_bytes(&self) -> u64 {
+        match self.kind {
+            SimulatedKind::Hybrid { cache_size } => cache_size,
+            SimulatedKind::Emmc { boot_partition_size } => 2 * boot_partition_size,
+            _ => 0,
+        }
+    }
+}
+
+/// Commands the simulator accepts, for fault injection and the command log.
+/// Reads and writes can be faulted but are not logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulatedCommand {
+    Identify,
+    Read,
+    Write,
+    Flush,
+    Discard,
+    HiddenAreas,
+    RemoveHpa,
+    RestoreHpa,
+    RemoveDco,
+    Unfreeze,
+    SecureErase,
+    Sanitize,
+    CryptoErase,
+    Namespaces,
+    FormatNamespace,
+    Zones,
+    ResetZone,
+    SetWriteCache,
+    HardwarePartitions,
+    Smart,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// Reads and writes touching this LBA fail with a medium error (EIO)
+    BadSector(u64),
+    /// Every issue of the command times out
+    Timeout(SimulatedCommand),
+    /// The drive disappears once this many bytes have been written
+    SurpriseRemoval { after_bytes: u64 },
+    /// SMART reports this temperature
+    Overheating { celsius: u32 },
+}
+
+enum Fill {
+    Zero,
+    Random,
+}
+
+struct SimState {
+    profile: SimulatedProfile,
+    file: File,
+    path: PathBuf,
+    _temp: Option<tempfile::TempPath>,
+    max_sectors: u64,
+    dco_removed: bool,
+    frozen: bool,
+    write_cache: bool,
+    zones: Vec<Zone>,
+    faults: Vec<Fault>,
+    commands: Vec<SimulatedCommand>,
+    bytes_written: u64,
+    removed: bool,
+}
+
+#[derive(Clone)]
+pub struct SimulatedDevice {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl SimulatedDevice {
+    /// Create a drive backed by an image file at `path`
+    pub fn create(path: impl AsRef<Path>, profile: SimulatedProfile) -> DriveResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path.as_ref())?;
+        Self::with_file(file, path.as_ref().to_path_buf(), None, profile)
+    }
+
+    /// Create a drive backed by a temporary image, removed with the last clone
+    pub fn temporary(profile: SimulatedProfile) -> DriveResult<Self> {
+        let (file, temp) = tempfile::Builder::new()
+            .prefix("sayonara-sim-")
+            .suffix(".img")
+            .tempfile()?
+            .into_parts();
+        let path = temp.to_path_buf();
+        Self::with_file(file, path, Some(temp), profile)
+    }
+
+    fn with_file(
+        file: File,
+        path: PathBuf,
+        temp: Option<tempfile::TempPath>,
+        profile: SimulatedProfile,
+    ) -> DriveResult<Self> {
+        if profile.capacity == 0 || !profile.capacity.is_multiple_of(SECTOR_SIZE)
+            || !profile.extra_bytes().is_multiple_of(SECTOR_SIZE) {
+            return Err(DriveError::Unsupported(format!(
+                "Simulated capacity must be a non-zero multiple of {} bytes", SECTOR_SIZE
+            )));
+        }
+        if let SimulatedKind::Nvme { namespaces } = profile.kind {
+            if namespaces == 0 || !profile.capacity.is_multiple_of(namespaces as u64 * SECTOR_SIZE) {
+                return Err(DriveError::Unsupported(
+                    "Simulated NVMe capacity must split evenly into whole-sector namespaces".to_string()
+                ));
+            }
+        }
+        let zones = match profile.kind {
+            SimulatedKind::Smr { zone_size, conventional_zones } => {
+                if zone_size == 0 || !zone_size.is_multiple_of(SECTOR_SIZE) || !profile.capacity.is_multiple_of(zone_size) {
+                    return Err(DriveError::Unsupported(
+                        "Simulated SMR capacity must be a whole number of sector-aligned zones".to_string()
+                    ));
+                }
+                initial_zones(profile.capacity, zone_size, conventional_zones)
+            }
+            _ => Vec::new(),
+        };
+
+        let total_sectors = profile.capacity / SECTOR_SIZE + profile.hpa_sectors + profile.dco_sectors;
+        file.set_len(total_sectors * SECTOR_SIZE + profile.extra_bytes())?;
+
+        let state = SimState {
+            max_sectors: profile.capacity / SECTOR_SIZE,
+            dco_removed: false,
+            frozen: profile.frozen,
+            write_cache: true,
+            profile,
+            file,
+            path,
+            _temp: temp,
+            zones,
+            faults: Vec::new(),
+            commands: Vec::new(),
+            bytes_written: 0,
+            removed: false,
+        };
+
+        let pattern: Vec<u8> = RESIDUAL_PATTERN.iter().copied().cycle().take(FILL_CHUNK).collect();
+        let total = state.total_bytes();
+        let mut offset = 0;
+        while offset < total {
+            let len = (total - offset).min(FILL_CHUNK as u64) as usize;
+            state.file.write_all_at(&pattern[..len], offset)?;
+            offset += len as u64;
+        }
+
+        Ok(Self { state: Arc::new(Mutex::new(state)) })
+    }
+
+    pub fn inject(&self, fault: Fault) {
+        self.lock().faults.push(fault);
+    }
+
+    pub fn clear_faults(&self) {
+        self.lock().faults.clear();
+    }
+
+    /// Commands issued so far, oldest first (reads and writes excluded)
+    pub fn commands(&self) -> Vec<SimulatedCommand> {
+        self.lock().commands.clone()
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.lock().bytes_written
+    }
+
+    pub fn image_path(&self) -> PathBuf {
+        self.lock().path.clone()
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.lock().frozen
+    }
+
+    pub fn write_cache_enabled(&self) -> bool {
+        self.lock().write_cache
+    }
+
+    /// Currently accessible sectors (the HPA boundary)
+    pub fn max_sectors(&self) -> u64 {
+        self.lock().max_sectors
+    }
+
+    /// Bytes of the original content left anywhere on the medium, hidden areas included
+    pub fn residual_bytes(&self) -> DriveResult<u64> {
+        let state = self.lock();
+        let total = state.total_bytes();
+        let mut buf = vec![0u8; FILL_CHUNK];
+        let mut residual = 0;
+        let mut offset = 0;
+
+        while offset < total {
+            let len = (total - offset).min(FILL_CHUNK as u64) as usize;
+            state.file.read_exact_at(&mut buf[..len], offset)?;
+            residual += buf[..len]
+                .chunks_exact(RESIDUAL_PATTERN.len())
+                .filter(|chunk| *chunk == RESIDUAL_PATTERN)
+                .count() as u64 * RESIDUAL_PATTERN.len() as u64;
+            offset += len as u64;
+        }
+
+        Ok(residual)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SimState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Lock the state and apply removal and timeout faults for `command`
+    fn begin(&self, command: SimulatedCommand) -> DriveResult<MutexGuard<'_, SimState>> {
+        let mut state = self.lock();
+        if state.removed {
+            return Err(DriveError::NotFound(state.path.display().to_string()));
+        }
+        if !matches!(command, SimulatedCommand::Read | SimulatedCommand::Write) {
+            state.commands.push(command);
+        }
+        if state.faults.contains(&Fault::Timeout(command)) {
+            return Err(DriveError::Timeout(format!("{:?} on simulated drive {}", command, state.path.display())));
+        }
+        Ok(state)
+    }
+}
+
+fn initial_zones(capacity: u64, zone_size: u64, conventional_zones: u32) -> Vec<Zone> {
+    let zone_sectors = zone_size / SECTOR_SIZE;
+    (0..(capacity / zone_size) as u32)
+        .map(|zone_number| {
+            let start = zone_number as u64 * zone_sectors;
+            let conventional = zone_number < conventional_zones;
+            Zone {
+                zone_number,
+                zone_type: if conventional { ZoneType::Conventional } else { ZoneType::SequentialWriteRequired },
+                // Sequential zones start out full of old data
+                write_pointer: start + zone_sectors,
+                zone_start_lba: start,
+                zone_size,
+                zone_condition: if conventional { ZoneCondition::Empty } else { ZoneCondition::Full },
+                zone_length: zone_sectors,
+            }
+        })
+        .collect()
+}
+
+/// EIO, as the kernel reports an unrecovered medium error
+fn medium_error() -> DriveError {
+    DriveError::IoError(std::io::Error::from_raw_os_error(libc::EIO))
+}
+
+impl SimState {
+    /// User area and hidden areas
+    fn medium_bytes(&self) -> u64 {
+        self.profile.capacity + (self.profile.hpa_sectors + self.profile.dco_sectors) * SECTOR_SIZE
+    }
+
+    fn total_bytes(&self) -> u64 {
+        self.medium_bytes() + self.profile.extra_bytes()
+    }
+
+    /// What erasing the whole drive reaches: everything but eMMC boot partitions
+    fn erasable_bytes(&self) -> u64 {
+        match self.profile.kind {
+            SimulatedKind::Emmc { .. } => self.medium_bytes(),
+            _ => self.total_bytes(),
+        }
+    }
+
+    /// READ NATIVE MAX: everything except what a DCO still hides
+    fn native_max_sectors(&self) -> u64 {
+        let total = self.medium_bytes() / SECTOR_SIZE;
+        if self.dco_removed { total } else { total - self.profile.dco_sectors }
+    }
+
+    fn check_range(&self, offset: u64, len: u64) -> DriveResult<()> {
+        if offset.checked_add(len).is_none_or(|end| end > self.max_sectors * SECTOR_SIZE) {
+            return Err(DriveError::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("access at {}+{} beyond end of device ({} sectors)", offset, len, self.max_sectors),
+            )));
+        }
+
+        let first = offset / SECTOR_SIZE;
+        let last = (offset + len).div_ceil(SECTOR_SIZE);
+        for fault in &self.faults {
+            if let Fault::BadSector(lba) = fault {
+                if (first..last).contains(lba) {
+                    return Err(medium_error());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sequential zones only accept writes at their write pointer
+    fn advance_zones(&mut self, offset: u64, len: u64) -> DriveResult<()> {
+        let end = offset + len;
+        for zone in &mut self.zones {
+            let zone_start = zone.zone_start_lba * SECTOR_SIZE;
+            let zone_end = zone_start + zone.zone_size;
+            if zone.zone_type == ZoneType::Conventional || end <= zone_start || offset >= zone_end {
+                continue;
+            }
+
+            let write_start = offset.max(zone_start);
+            if zone.zone_condition == ZoneCondition::Full || write_start != zone.write_pointer * SECTOR_SIZE {
+                return Err(DriveError::HardwareCommandFailed(format!(
+                    "unaligned write in sequential zone {} (write pointer at LBA {})",
+                    zone.zone_number, zone.write_pointer
+                )));
+            }
+
+            zone.write_pointer = end.min(zone_end) / SECTOR_SIZE;
+            zone.zone_condition = if zone.write_pointer * SECTOR_SIZE == zone_end {
+                ZoneCondition::Full
+            } else {
+                ZoneCondition::ImplicitlyOpen
+            };
+        }
+        Ok(())
+    }
+
+    fn fill(&self, offset: u64, len: u64, fill: Fill) -> DriveResult<()> {
+        let mut buf = vec![0u8; FILL_CHUNK];
+        let mut done = 0;
+        while done < len {
+            let n = (len - done).min(FILL_CHUNK as u64) as usize;
+            if let Fill::Random = fill {
+                rand::thread_rng().fill_bytes(&mut buf[..n]);
+            }
+            self.file.write_all_at(&buf[..n], offset + done)?;
+            done += n as u64;
+        }
+        Ok(())
+    }
+
+    fn bad_sector_count(&self) -> u64 {
+        self.faults.iter().filter(|f| matches!(f, Fault::BadSector(_))).count() as u64
+    }
+
+    fn require_ata(&self, what: &str) -> DriveResult<()> {
+        if self.profile.is_ata() {
+            Ok(())
+        } else {
+            Err(DriveError::Unsupported(format!("{} is an ATA feature", what)))
+        }
+    }
+}
+
+impl DeviceBackend for SimulatedDevice {
+    fn identify(&mut self) -> DriveResult<DriveInfo> {
+        let state = self.begin(SimulatedCommand::Identify)?;
+        let profile = &state.profile;
+        let native = state.native_max_sectors();
+
+        let capabilities = DriveCapabilities {
+            secure_erase: profile.supports_secure_erase(),
+            enhanced_erase: profile.is_ata(),
+            crypto_erase: profile.sed.is_some(),
+            trim_support: profile.supports_trim(),
+            hpa_enabled: profile.is_ata() && state.max_sectors < native,
+            dco_enabled: profile.is_ata() && profile.dco_sectors > 0 && !state.dco_removed,
+            sed_type: profile.sed.clone(),
+            sanitize_options: profile.sanitize_options(),
+            max_temperature: Some(70),
+            is_frozen: state.frozen,
+            freeze_status: if state.frozen { FreezeStatus::Frozen } else { FreezeStatus::NotFrozen },
+        };
+
+        Ok(DriveInfo {
+            device_path: state.path.display().to_string(),
+            model: profile.model.clone(),
+            serial: profile.serial.clone(),
+            size: state.max_sectors * SECTOR_SIZE,
+            drive_type: profile.drive_type(),
+            encryption_status: match profile.sed {
+                Some(SEDType::OPAL20) | Some(SEDType::OPAL10) | Some(SEDType::TCGEnterprise) => EncryptionStatus::OPAL,
+                _ => EncryptionStatus::None,
+            },
+            capabilities,
+            health_status: Some(if state.bad_sector_count() > 0 { HealthStatus::Warning } else { HealthStatus::Good }),
+            temperature_celsius: Some(35),
+        })
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> DriveResult<()> {
+        let state = self.begin(SimulatedCommand::Read)?;
+        state.check_range(offset, buf.len() as u64)?;
+        Ok(state.file.read_exact_at(buf, offset)?)
+    }
+
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> DriveResult<()> {
+        let mut state = self.begin(SimulatedCommand::Write)?;
+        state.check_range(offset, data.len() as u64)?;
+
+        let removal = state.faults.iter().find_map(|f| match f {
+            Fault::SurpriseRemoval { after_bytes } => Some(*after_bytes),
+            _ => None,
+        });
+        if let Some(after_bytes) = removal {
+            let room = after_bytes.saturating_sub(state.bytes_written);
+            if (data.len() as u64) > room {
+                // The part that made it to the medium before the drive vanished
+                state.file.write_all_at(&data[..room as usize], offset)?;
+                state.bytes_written += room;
+                state.removed = true;
+                return Err(DriveError::NotFound(state.path.display().to_string()));
+            }
+        }
+
+        state.advance_zones(offset, data.len() as u64)?;
+        state.file.write_all_at(data, offset)?;
+        state.bytes_written += data.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> DriveResult<()> {
+        let state = self.begin(SimulatedCommand::Flush)?;
+        if let SimulatedKind::Hybrid { cache_size } = state.profile.kind {
+            // With caching off the drive writes the NAND cache back and drops it
+            if !state.write_cache {
+                state.fill(state.medium_bytes(), cache_size, Fill::Zero)?;
+            }
+        }
+        Ok(state.file.sync_data()?)
+    }
+
+    fn set_write_cache(&mut self, enabled: bool) -> DriveResult<()> {
+        let mut state = self.begin(SimulatedCommand::SetWriteCache)?;
+        state.require_ata("Write cache control")?;
+        state.write_cache = enabled;
+        Ok(())
+    }
+
+    fn discard(&mut self, offset: u64, length: u64) -> DriveResult<()> {
+        let state = self.begin(SimulatedCommand::Discard)?;
+        if !state.profile.supports_trim() {
+            return Err(DriveError::TRIMFailed("Device does not support TRIM".to_string()));
+        }
+        state.check_range(offset, length)?;
+        // Deterministic read-zero after TRIM
+        state.fill(offset, length, Fill::Zero)
+    }
+
+    fn hidden_areas(&mut self) -> DriveResult<(Option<HPAInfo>, Option<DCOInfo>)> {
+        let state = self.begin(SimulatedCommand::HiddenAreas)?;
+        if !state.profile.is_ata() {
+            return Ok((None, None));
+        }
+
+        let native = state.native_max_sectors();
+        let hpa = (state.max_sectors < native).then(|| HPAInfo {
+            enabled: true,
+            native_max_sectors: native,
+            current_max_sectors: state.max_sectors,
+            hidden_sectors: native - state.max_sectors,
+            hidden_size_bytes: (native - state.max_sectors) * SECTOR_SIZE,
+        });
+        let dco = (state.profile.dco_sectors > 0 && !state.dco_removed).then(|| DCOInfo {
+            enabled: true,
+            real_max_sectors: native + state.profile.dco_sectors,
+            dco_max_sectors: native,
+            hidden_sectors: state.profile.dco_sectors,
+            hidden_size_bytes: state.profile.dco_sectors * SECTOR_SIZE,
+        });
+
+        Ok((hpa, dco))
+    }
+
+    fn remove_hpa(&mut self) -> DriveResult<()> {
+        let mut state = self.begin(SimulatedCommand::RemoveHpa)?;
+        state.require_ata("HPA")?;
+        state.max_sectors = state.native_max_sectors();
+        Ok(())
+    }
+
+    fn restore_hpa(&mut self, max_sectors: u64) -> DriveResult<()> {
+        let mut state = self.begin(SimulatedCommand::RestoreHpa)?;
+        state.require_ata("HPA")?;
+        if max_sectors == 0 || max_sectors > state.native_max_sectors() {
+            return Err(DriveError::HardwareCommandFailed(format!(
+                "Failed to restore HPA: {} sectors is outside the native capacity", max_sectors
+            )));
+        }
+        state.max_sectors = max_sectors;
+        Ok(())
+    }
+
+    fn remove_dco(&mut self) -> DriveResult<()> {
+        let mut state = self.begin(SimulatedCommand::RemoveDco)?;
+        state.require_ata("DCO")?;
+        state.dco_removed = true;
+        Ok(())
+    }
+
+    fn unfreeze(&mut self) -> DriveResult<()> {
+        let mut state = self.begin(SimulatedCommand::Unfreeze)?;
+        if state.frozen && !state.profile.unfreezable {
+            return Err(DriveError::DriveFrozen("all unfreeze strategies failed".to_string()));
+        }
+        state.frozen = false;
+        Ok(())
+    }
+
+    fn secure_erase(&mut self, enhanced: bool) -> DriveResult<()> {
+        let state = self.begin(SimulatedCommand::SecureErase)?;
+        if !state.profile.supports_secure_erase() {
+            return Err(DriveError::Unsupported("Secure erase not supported".to_string()));
+        }
+        if state.frozen {
+            return Err(DriveError::DriveFrozen(
+                "security commands are blocked until the drive is unfrozen".to_string()
+            ));
+        }
+
+        // Normal erase covers the user area; enhanced also reaches hidden and
+        // reallocated areas. On NVMe this is a Format NVM of every namespace.
+        let length = if enhanced || !state.profile.is_ata() {
+            state.erasable_bytes()
+        } else {
+            state.max_sectors * SECTOR_SIZE
+        };
+        state.fill(0, length, Fill::Zero)
+    }
+
+    fn sanitize(&mut self, option: SanitizeOption) -> DriveResult<()> {
+        let state = self.begin(SimulatedCommand::Sanitize)?;
+        if !state.profile.sanitize_options().contains(&option) {
+            return Err(DriveError::Unsupported(format!("Sanitize {:?} not supported", option)));
+        }
+
+        let fill = match option {
+            SanitizeOption::CryptoErase | SanitizeOption::CryptoScramble => Fill::Random,
+            SanitizeOption::BlockErase | SanitizeOption::Overwrite => Fill::Zero,
+        };
+        state.fill(0, state.erasable_bytes(), fill)
+    }
+
+    fn crypto_erase(&mut self) -> DriveResult<()> {
+        let state = self.begin(SimulatedCommand::CryptoErase)?;
+        if state.profile.sed.is_none() {
+            return Err(DriveError::CryptoEraseFailed("Drive is not self-encrypting".to_string()));
+        }
+        // A new media key leaves only undecryptable ciphertext behind
+        state.fill(0, state.erasable_bytes(), Fill::Random)
+    }
+
+    fn namespaces(&mut self) -> DriveResult<Vec<DeviceNamespace>> {
+        let state = self.begin(SimulatedCommand::Namespaces)?;
+        Ok(match state.profile.kind {
+            SimulatedKind::Nvme { namespaces } => (1..=namespaces)
+                .map(|nsid| DeviceNamespace { nsid, size: state.profile.capacity / namespaces as u64 })
+                .collect(),
+            SimulatedKind::Optane => vec![DeviceNamespace { nsid: 1, size: state.profile.capacity }],
+            _ => Vec::new(),
+        })
+    }
+
+    fn format_namespace(&mut self, nsid: u32) -> DriveResult<()> {
+        let state = self.begin(SimulatedCommand::FormatNamespace)?;
+        let namespaces = match state.profile.kind {
+            SimulatedKind::Nvme { namespaces } => namespaces,
+            SimulatedKind::Optane => 1,
+            _ => return Err(DriveError::Unsupported("Format NVM requires an NVMe drive".to_string())),
+        };
+        if nsid == 0 || nsid > namespaces {
+            return Err(DriveError::HardwareCommandFailed(format!("Invalid namespace {}", nsid)));
+        }
+
+        let size = state.profile.capacity / namespaces as u64;
+        state.fill((nsid - 1) as u64 * size, size, Fill::Zero)
+    }
+
+    fn zones(&mut self) -> DriveResult<Vec<Zone>> {
+        let state = self.begin(SimulatedCommand::Zones)?;
+        Ok(state.zones.clone())
+    }
+
+    fn reset_zone(&mut self, zone: &Zone) -> DriveResult<()> {
+        let mut state = self.begin(SimulatedCommand::ResetZone)?;
+        let index = state.zones.iter()
+            .position(|z| z.zone_number == zone.zone_number)
+            .ok_or_else(|| DriveError::HardwareCommandFailed(format!("Invalid zone {}", zone.zone_number)))?;
+
+        let target = state.zones[index].clone();
+        if target.zone_type == ZoneType::Conventional {
+            return Ok(());
+        }
+        state.fill(target.zone_start_lba * SECTOR_SIZE, target.zone_size, Fill::Zero)?;
+
+        let reset = &mut state.zones[index];
+        reset.write_pointer = reset.zone_start_lba;
+        reset.zone_condition = ZoneCondition::Empty;
+        Ok(())
+    }
+
+    fn hardware_partitions(&mut self) -> DriveResult<Vec<HardwarePartition>> {
+        let state = self.begin(SimulatedCommand::HardwarePartitions)?;
+        Ok(match state.profile.kind {
+            SimulatedKind::Emmc { boot_partition_size } => (1..=2)
+                .map(|number| HardwarePartition { number, size: boot_partition_size })
+                .collect(),
+            _ => Vec::new(),
+        })
+    }
+
+    fn write_hardware_partition(&mut self, number: u8, offset: u64, data: &[u8]) -> DriveResult<()> {
+        let mut state = self.begin(SimulatedCommand::Write)?;
+        let SimulatedKind::Emmc { boot_partition_size } = state.profile.kind else {
+            return Err(DriveError::Unsupported("Drive has no hardware partitions".to_string()));
+        };
+        if !(1..=2).contains(&number) || offset + data.len() as u64 > boot_partition_size {
+            return Err(DriveError::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("access at {}+{} outside boot partition {}", offset, data.len(), number),
+            )));
+        }
+
+        let start = state.medium_bytes() + (number - 1) as u64 * boot_partition_size;
+        state.file.write_all_at(data, start + offset)?;
+        state.bytes_written += data.len() as u64;
+        Ok(())
+    }
+
+    fn smart(&mut self) -> DriveResult<SMARTHealth> {
+        let state = self.begin(SimulatedCommand::Smart)?;
+        let bad = state.bad_sector_count();
+        let nvme = !state.profile.is_ata();
+
+        let temperature = state.faults.iter().find_map(|f| match f {
+            Fault::Overheating { celsius } => Some(*celsius),
+            _ => None,
+        });
+
+        Ok(SMARTHealth {
+            overall_health: if bad > 0 { HealthStatus::Warning } else { HealthStatus::Good },
+            temperature_celsius: Some(temperature.unwrap_or(35)),
+            power_on_hours: Some(1_000),
+            power_cycle_count: Some(42),
+            reallocated_sectors: (!nvme).then_some(0),
+            pending_sectors: (!nvme).then_some(bad),
+            uncorrectable_errors: (!nvme).then_some(bad),
+            wear_level: state.profile.supports_trim().then_some(3),
+            bad_block_count: None,
+            erase_fail_count: None,
+            program_fail_count: None,
+            critical_warning: nvme.then_some(0),
+            available_spare: nvme.then_some(100),
+            media_errors: nvme.then_some(bad),
+            attributes: HashMap::new(),
+        })
+    }
+
+    fn overwrite_backend(&mut self, _area: DeviceArea) -> DriveResult<Option<Box<dyn DeviceBackend>>> {
+        Ok(Some(Box::new(self.clone())))
+    }
+}
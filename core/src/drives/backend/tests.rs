@@ -0,0 +1,273 @@
+// Tests for the simulated device backend
+
+use super::*;
+use crate::drives::types::smr::{ZoneCondition, ZoneType};
+use crate::{DriveError, DriveType, HealthStatus, SEDType};
+
+const MIB: u64 = 1024 * 1024;
+
+fn open(profile: SimulatedProfile) -> SimulatedDevice {
+    SimulatedDevice::temporary(profile).unwrap()
+}
+
+#[test]
+fn test_identify_reflects_profile() {
+    let mut hdd = open(SimulatedProfile { hpa_sectors: 2048, ..SimulatedProfile::hdd(4 * MIB) });
+    let info = hdd.identify().unwrap();
+    assert_eq!(info.drive_type, DriveType::HDD);
+    assert_eq!(info.size, 4 * MIB);
+    assert!(info.capabilities.secure_erase);
+    assert!(info.capabilities.hpa_enabled);
+    assert!(!info.capabilities.trim_support);
+    assert!(info.capabilities.sanitize_options.is_empty());
+
+    let mut ssd = open(SimulatedProfile { frozen: true, sed: Some(SEDType::OPAL20), ..SimulatedProfile::sata_ssd(4 * MIB) });
+    let info = ssd.identify().unwrap();
+    assert_eq!(info.drive_type, DriveType::SSD);
+    assert!(info.capabilities.is_frozen);
+    assert!(info.capabilities.crypto_erase);
+    assert!(info.capabilities.trim_support);
+    assert!(info.capabilities.sanitize_options.contains(&SanitizeOption::CryptoScramble));
+
+    let mut nvme = open(SimulatedProfile::nvme(4 * MIB, 2));
+    let info = nvme.identify().unwrap();
+    assert_eq!(info.drive_type, DriveType::NVMe);
+    assert!(!info.capabilities.hpa_enabled);
+    assert!(info.capabilities.sanitize_options.contains(&SanitizeOption::Overwrite));
+    assert_eq!(nvme.namespaces().unwrap(), vec![
+        DeviceNamespace { nsid: 1, size: 2 * MIB },
+        DeviceNamespace { nsid: 2, size: 2 * MIB },
+    ]);
+}
+
+#[test]
+fn test_embedded_and_hybrid_profiles() {
+    let mut emmc = open(SimulatedProfile::emmc(4 * MIB, MIB));
+    assert_eq!(emmc.identify().unwrap().drive_type, DriveType::EMMC);
+    assert_eq!(emmc.hardware_partitions().unwrap(), vec![
+        HardwarePartition { number: 1, size: MIB },
+        HardwarePartition { number: 2, size: MIB },
+    ]);
+    emmc.write_hardware_partition(2, 0, &[0u8; 4096]).unwrap();
+    assert!(emmc.write_hardware_partition(3, 0, &[0u8; 512]).is_err());
+    assert!(emmc.write_hardware_partition(1, MIB - 256, &[0u8; 512]).is_err());
+    // Erase commands leave the boot partitions alone
+    emmc.sanitize(SanitizeOption::BlockErase).unwrap();
+    assert_eq!(emmc.residual_bytes().unwrap(), 2 * MIB - 4096);
+    assert!(emmc.set_write_cache(false).is_err());
+
+    let mut sshd = open(SimulatedProfile::hybrid(4 * MIB, MIB));
+    assert_eq!(sshd.identify().unwrap().drive_type, DriveType::HybridSSHD);
+    sshd.flush().unwrap();
+    assert_eq!(sshd.residual_bytes().unwrap(), 5 * MIB);
+    sshd.set_write_cache(false).unwrap();
+    sshd.flush().unwrap();
+    assert_eq!(sshd.residual_bytes().unwrap(), 4 * MIB);
+
+    let mut optane = open(SimulatedProfile::optane(4 * MIB));
+    assert_eq!(optane.identify().unwrap().drive_type, DriveType::Optane);
+    assert_eq!(optane.namespaces().unwrap(), vec![DeviceNamespace { nsid: 1, size: 4 * MIB }]);
+    assert!(matches!(open(SimulatedProfile::ufs(MIB)).secure_erase(false), Err(DriveError::Unsupported(_))));
+}
+
+#[test]
+fn test_image_starts_full_of_residual_data() {
+    let mut sim = open(SimulatedProfile { hpa_sectors: 1024, ..SimulatedProfile::hdd(MIB) });
+    assert_eq!(sim.residual_bytes().unwrap(), MIB + 1024 * 512);
+
+    sim.write_at(0, &[0u8; 4096]).unwrap();
+    assert_eq!(sim.residual_bytes().unwrap(), MIB + 1024 * 512 - 4096);
+    assert_eq!(sim.bytes_written(), 4096);
+
+    let mut buf = [0u8; 16];
+    sim.read_at(4096, &mut buf).unwrap();
+    assert_eq!(&buf, b"SIMULATED-DATA! ");
+}
+
+#[test]
+fn test_access_beyond_user_area_rejected() {
+    let mut sim = open(SimulatedProfile { hpa_sectors: 8, ..SimulatedProfile::hdd(MIB) });
+
+    let err = sim.write_at(MIB, &[0u8; 512]).unwrap_err();
+    assert!(matches!(err, DriveError::IoError(ref e) if e.kind() == std::io::ErrorKind::InvalidInput));
+
+    sim.remove_hpa().unwrap();
+    sim.write_at(MIB, &[0u8; 512]).unwrap();
+}
+
+#[test]
+fn test_hidden_areas() {
+    let mut sim = open(SimulatedProfile { hpa_sectors: 100, dco_sectors: 50, ..SimulatedProfile::hdd(MIB) });
+    let user = MIB / 512;
+
+    let (hpa, dco) = sim.hidden_areas().unwrap();
+    let hpa = hpa.unwrap();
+    assert_eq!(hpa.current_max_sectors, user);
+    assert_eq!(hpa.native_max_sectors, user + 100);
+    assert_eq!(hpa.hidden_sectors, 100);
+    let dco = dco.unwrap();
+    assert_eq!(dco.dco_max_sectors, user + 100);
+    assert_eq!(dco.real_max_sectors, user + 150);
+
+    sim.remove_hpa().unwrap();
+    assert_eq!(sim.max_sectors(), user + 100);
+    assert!(sim.hidden_areas().unwrap().0.is_none());
+
+    sim.restore_hpa(user).unwrap();
+    assert_eq!(sim.max_sectors(), user);
+    assert!(sim.restore_hpa(user + 150).is_err());
+
+    sim.remove_dco().unwrap();
+    sim.remove_hpa().unwrap();
+    assert_eq!(sim.max_sectors(), user + 150);
+    assert!(matches!(sim.hidden_areas().unwrap(), (None, None)));
+
+    // NVMe has neither
+    let mut nvme = open(SimulatedProfile::nvme(MIB, 1));
+    assert!(matches!(nvme.hidden_areas().unwrap(), (None, None)));
+    assert!(matches!(nvme.remove_hpa(), Err(DriveError::Unsupported(_))));
+}
+
+#[test]
+fn test_freeze_and_secure_erase() {
+    let profile = SimulatedProfile { frozen: true, hpa_sectors: 1024, ..SimulatedProfile::sata_ssd(MIB) };
+
+    let mut sim = open(profile.clone());
+    assert!(matches!(sim.secure_erase(false), Err(DriveError::DriveFrozen(_))));
+    sim.unfreeze().unwrap();
+    assert!(!sim.is_frozen());
+
+    // Normal erase leaves the HPA alone, enhanced reaches it
+    sim.secure_erase(false).unwrap();
+    assert_eq!(sim.residual_bytes().unwrap(), 1024 * 512);
+    sim.secure_erase(true).unwrap();
+    assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+    let mut stuck = open(SimulatedProfile { unfreezable: false, ..profile });
+    assert!(matches!(stuck.unfreeze(), Err(DriveError::DriveFrozen(_))));
+    assert!(stuck.is_frozen());
+}
+
+#[test]
+fn test_sanitize_and_crypto_erase() {
+    let mut hdd = open(SimulatedProfile::hdd(MIB));
+    assert!(matches!(hdd.sanitize(SanitizeOption::BlockErase), Err(DriveError::Unsupported(_))));
+    assert!(matches!(hdd.crypto_erase(), Err(DriveError::CryptoEraseFailed(_))));
+    assert!(matches!(hdd.discard(0, MIB), Err(DriveError::TRIMFailed(_))));
+
+    let mut ssd = open(SimulatedProfile { sed: Some(SEDType::OPAL20), ..SimulatedProfile::sata_ssd(MIB) });
+    assert!(matches!(ssd.sanitize(SanitizeOption::Overwrite), Err(DriveError::Unsupported(_))));
+    ssd.crypto_erase().unwrap();
+    assert_eq!(ssd.residual_bytes().unwrap(), 0);
+
+    let mut nvme = open(SimulatedProfile::nvme(MIB, 1));
+    nvme.sanitize(SanitizeOption::BlockErase).unwrap();
+    assert_eq!(nvme.residual_bytes().unwrap(), 0);
+    assert_eq!(nvme.bytes_written(), 0);
+}
+
+#[test]
+fn test_format_namespace_covers_only_that_namespace() {
+    let mut sim = open(SimulatedProfile::nvme(4 * MIB, 2));
+    sim.format_namespace(2).unwrap();
+    assert_eq!(sim.residual_bytes().unwrap(), 2 * MIB);
+
+    assert!(sim.format_namespace(3).is_err());
+    assert!(matches!(open(SimulatedProfile::hdd(MIB)).format_namespace(1), Err(DriveError::Unsupported(_))));
+}
+
+#[test]
+fn test_smr_write_pointer_enforced() {
+    let mut sim = open(SimulatedProfile::smr(4 * MIB, MIB, 1));
+    let zones = sim.zones().unwrap();
+    assert_eq!(zones.len(), 4);
+    assert_eq!(zones[0].zone_type, ZoneType::Conventional);
+    assert_eq!(zones[1].zone_condition, ZoneCondition::Full);
+
+    // Conventional zones take writes anywhere, full sequential zones take none
+    sim.write_at(4096, &[0u8; 512]).unwrap();
+    assert!(matches!(sim.write_at(MIB, &[0u8; 512]), Err(DriveError::HardwareCommandFailed(_))));
+
+    sim.reset_zone(&zones[1]).unwrap();
+    let zone = &sim.zones().unwrap()[1];
+    assert_eq!(zone.zone_condition, ZoneCondition::Empty);
+    assert_eq!(zone.write_pointer, zone.zone_start_lba);
+
+    // Only at the write pointer, which then advances
+    assert!(sim.write_at(MIB + 512, &[0u8; 512]).is_err());
+    sim.write_at(MIB, &[0u8; 512]).unwrap();
+    sim.write_at(MIB + 512, &[0u8; 512]).unwrap();
+    let zone = &sim.zones().unwrap()[1];
+    assert_eq!(zone.write_pointer, zone.zone_start_lba + 2);
+    assert_eq!(zone.zone_condition, ZoneCondition::ImplicitlyOpen);
+
+    sim.write_at(MIB + 1024, &vec![0u8; (MIB - 1024) as usize]).unwrap();
+    assert_eq!(sim.zones().unwrap()[1].zone_condition, ZoneCondition::Full);
+}
+
+#[test]
+fn test_bad_sector_fault() {
+    let mut sim = open(SimulatedProfile::hdd(MIB));
+    sim.inject(Fault::BadSector(10));
+
+    let err = sim.write_at(0, &[0u8; 8192]).unwrap_err();
+    assert!(matches!(&err, DriveError::IoError(e) if e.raw_os_error() == Some(libc::EIO)), "{}", err);
+    let mut buf = [0u8; 512];
+    assert!(sim.read_at(10 * 512, &mut buf).is_err());
+    sim.write_at(11 * 512, &[0u8; 512]).unwrap();
+
+    let health = sim.smart().unwrap();
+    assert_eq!(health.overall_health, HealthStatus::Warning);
+    assert_eq!(health.pending_sectors, Some(1));
+
+    sim.clear_faults();
+    sim.write_at(0, &[0u8; 8192]).unwrap();
+    assert_eq!(sim.smart().unwrap().overall_health, HealthStatus::Good);
+}
+
+#[test]
+fn test_timeout_fault() {
+    let mut sim = open(SimulatedProfile::nvme(MIB, 1));
+    sim.inject(Fault::Timeout(SimulatedCommand::Sanitize));
+
+    assert!(matches!(sim.sanitize(SanitizeOption::BlockErase), Err(DriveError::Timeout(_))));
+    // Only the targeted command times out
+    sim.format_namespace(1).unwrap();
+    assert_eq!(sim.commands(), vec![SimulatedCommand::Sanitize, SimulatedCommand::FormatNamespace]);
+}
+
+#[test]
+fn test_surprise_removal_fault() {
+    let mut sim = open(SimulatedProfile::hdd(MIB));
+    sim.inject(Fault::SurpriseRemoval { after_bytes: 1000 });
+
+    sim.write_at(0, &[0u8; 512]).unwrap();
+    assert!(matches!(sim.write_at(512, &[0u8; 1024]), Err(DriveError::NotFound(_))));
+    assert_eq!(sim.bytes_written(), 1000);
+
+    // Gone for good, including commands that never touch the medium
+    assert!(matches!(sim.identify(), Err(DriveError::NotFound(_))));
+    assert!(matches!(sim.smart(), Err(DriveError::NotFound(_))));
+}
+
+#[test]
+fn test_clones_share_the_drive() {
+    let sim = open(SimulatedProfile::hdd(MIB));
+    let mut handle = sim.clone();
+    handle.write_at(0, &[0u8; 512]).unwrap();
+    handle.flush().unwrap();
+
+    assert_eq!(sim.bytes_written(), 512);
+    assert!(sim.image_path().exists());
+}
+
+#[test]
+fn test_create_persists_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("drive.img");
+    let mut sim = SimulatedDevice::create(&path, SimulatedProfile::hdd(MIB)).unwrap();
+    sim.write_at(0, &[0u8; 512]).unwrap();
+
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), MIB);
+    assert_eq!(sim.identify().unwrap().device_path, path.display().to_string());
+}
@@ -8,6 +8,8 @@ use std::fs;
 use std::path::Path;
 
 // Import submodules for capability detection
+use super::ata::{AtaDevice, IdentifyData};
+use super::nvme_admin::{IdentifyController, NvmeDevice};
 use super::freeze::FreezeMitigation;
 use super::operations::hpa_dco::HPADCOManager;
 use super::operations::sed::SEDManager;
@@ -41,22 +43,24 @@ impl DriveDetector {
                 continue;
             }
 
-            // Comprehensive analysis with error recovery
-            match Self::analyze_drive_comprehensive(&device_path) {
-                Ok(drive_info) => drives.push(drive_info),
-                Err(e) => {
-                    eprintln!("Warning: Failed to analyze {}: {}", device_path, e);
-                    // Try basic detection as fallback
-                    if let Ok(basic_info) = Self::analyze_drive_basic(&device_path) {
-                        drives.push(basic_info);
-                    }
-                }
+            if let Ok(drive_info) = Self::analyze_drive(&device_path) {
+                drives.push(drive_info);
             }
         }
 
         Ok(drives)
     }
 
+    /// Detect a single drive, falling back to basic detection when the
+    /// capability checks fail
+    pub fn analyze_drive(device_path: &str) -> Result<DriveInfo> {
+        // Comprehensive analysis with error recovery
+        Self::analyze_drive_comprehensive(device_path).or_else(|e| {
+            eprintln!("Warning: Failed to analyze {}: {}", device_path, e);
+            Self::analyze_drive_basic(device_path)
+        })
+    }
+
     /// Check if device should be skipped
     fn should_skip_device(device_name: &str) -> bool {
         // Skip loop devices, ram disks, device mapper, etc.
@@ -104,13 +108,24 @@ impl DriveDetector {
             capabilities.trim_support = trim_supported;
         }
 
-        // Check secure erase support
-        capabilities.secure_erase = Self::check_secure_erase_support(device_path)?;
-        capabilities.enhanced_erase = Self::check_enhanced_erase_support(device_path)?;
+        // Check secure erase support (ATA only; NVMe and non-SAT bridges fail IDENTIFY)
+        let identify = AtaDevice::open(device_path).and_then(|mut device| device.identify()).ok();
+        if let Some(identify) = &identify {
+            let security = identify.security();
+            capabilities.secure_erase = security.supported;
+            capabilities.enhanced_erase = security.enhanced_erase_supported;
+        }
 
-        // Check NVMe sanitize options
-        if drive_info.drive_type == DriveType::NVMe {
-            capabilities.sanitize_options = Self::get_nvme_sanitize_options(device_path)?;
+        // Check NVMe sanitize options; Format NVM with user data erase is
+        // the NVMe secure erase
+        if matches!(drive_info.drive_type, DriveType::NVMe | DriveType::Optane) {
+            let controller = NvmeDevice::open(device_path)?.identify_controller()?;
+            capabilities.sanitize_options = Self::get_nvme_sanitize_options(&controller);
+            capabilities.secure_erase = controller.format_supported();
+        } else if drive_info.drive_type == DriveType::SSD {
+            capabilities.sanitize_options = identify.as_ref()
+                .map(Self::get_ata_sanitize_options)
+                .unwrap_or_default();
         }
 
         // Get SMART health and temperature
@@ -366,58 +381,45 @@ impl DriveDetector {
         entropy
     }
 
-    /// Check secure erase support
-    fn check_secure_erase_support(device_path: &str) -> Result<bool> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()?;
+    /// Get ATA SANITIZE feature set options
+    fn get_ata_sanitize_options(identify: &IdentifyData) -> Vec<SanitizeOption> {
+        let mut options = Vec::new();
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        Ok(output_str.contains("supported: enhanced erase") ||
-            output_str.contains("SECURITY ERASE UNIT"))
-    }
+        let sanitize = identify.sanitize();
+        if !sanitize.supported {
+            return options;
+        }
 
-    /// Check enhanced secure erase support
-    fn check_enhanced_erase_support(device_path: &str) -> Result<bool> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()?;
+        if sanitize.block_erase {
+            options.push(SanitizeOption::BlockErase);
+        }
+        if sanitize.crypto_scramble {
+            options.push(SanitizeOption::CryptoScramble);
+        }
+        if sanitize.overwrite {
+            options.push(SanitizeOption::Overwrite);
+        }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        Ok(output_str.contains("enhanced erase"))
+        options
     }
 
     /// Get NVMe sanitize options
-    fn get_nvme_sanitize_options(device_path: &str) -> Result<Vec<SanitizeOption>> {
+    fn get_nvme_sanitize_options(controller: &IdentifyController) -> Vec<SanitizeOption> {
         let mut options = Vec::new();
 
-        let output = Command::new("nvme")
-            .args(["id-ctrl", device_path])
-            .output()?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-
         // Parse sanitize capabilities
-        if output_str.contains("Crypto Erase Supported") {
+        let sanitize = controller.sanitize();
+        if sanitize.crypto_erase {
             options.push(SanitizeOption::CryptoErase);
         }
-        if output_str.contains("Block Erase Supported") {
+        if sanitize.block_erase {
             options.push(SanitizeOption::BlockErase);
         }
-        if output_str.contains("Overwrite Supported") {
+        if sanitize.overwrite {
             options.push(SanitizeOption::Overwrite);
         }
-        if output_str.contains("Crypto Scramble Supported") {
-            options.push(SanitizeOption::CryptoScramble);
-        }
-
-        // If no specific info, assume basic support
-        if options.is_empty() && device_path.contains("nvme") {
-            options.push(SanitizeOption::CryptoErase);
-            options.push(SanitizeOption::BlockErase);
-        }
 
-        Ok(options)
+        options
     }
 
     /// Check if drive is system drive
@@ -1,9 +1,9 @@
 // Advanced drive freeze mitigation with vendor-specific support
 // and multiple unfreeze strategies
 
+use crate::drives::ata::AtaDevice;
 use crate::{DriveError, DriveResult, FreezeStatus};
 use anyhow::Result;
-use std::process::Command;
 use std::thread;
 use std::time::{Duration, Instant};
 use std::fs;
@@ -253,28 +253,11 @@ impl AdvancedFreezeMitigation {
 
     /// Get current freeze status
     fn get_freeze_status(&self, device_path: &str) -> DriveResult<FreezeStatus> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(
-                format!("hdparm failed: {}", e)
-            ))?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-
-        if output_str.contains("frozen") && output_str.contains("not") {
-            Ok(FreezeStatus::NotFrozen)
-        } else if output_str.contains("frozen") {
-            if output_str.contains("BIOS") {
-                Ok(FreezeStatus::FrozenByBIOS)
-            } else {
-                Ok(FreezeStatus::Frozen)
-            }
-        } else if output_str.contains("locked") {
-            Ok(FreezeStatus::SecurityLocked)
-        } else {
-            Ok(FreezeStatus::Unknown)
+        let security = AtaDevice::open(device_path)?.identify()?.security();
+        if !security.supported {
+            return Ok(FreezeStatus::Unknown);
         }
+        Ok(security.freeze_status())
     }
 
     /// Check if secure erase is blocked
@@ -1,6 +1,6 @@
+use crate::drives::ata::AtaDevice;
 use crate::{DriveError, DriveResult, FreezeStatus};
 use anyhow::{Result, anyhow};
-use std::process::Command;
 use std::thread;
 use std::time::Duration;
 use std::fs;
@@ -66,28 +66,8 @@ impl FreezeMitigation {
 
     /// Get the freeze status of a drive
     pub fn get_freeze_status(device_path: &str) -> DriveResult<FreezeStatus> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(
-                format!("hdparm failed: {}", e)
-            ))?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-
-        if output_str.contains("frozen") && output_str.contains("not") {
-            Ok(FreezeStatus::NotFrozen)
-        } else if output_str.contains("frozen") {
-            if output_str.contains("BIOS") {
-                Ok(FreezeStatus::FrozenByBIOS)
-            } else {
-                Ok(FreezeStatus::Frozen)
-            }
-        } else if output_str.contains("locked") {
-            Ok(FreezeStatus::SecurityLocked)
-        } else {
-            Ok(FreezeStatus::NotFrozen)
-        }
+        let identify = AtaDevice::open(device_path)?.identify()?;
+        Ok(identify.security().freeze_status())
     }
 
     /// Method 1: System sleep/wake cycle
@@ -1,5 +1,6 @@
 // Detect the reason why a drive is frozen
 
+use crate::drives::ata::AtaDevice;
 use anyhow::{Result, anyhow};
 use std::process::Command;
 use std::fs;
@@ -60,16 +61,11 @@ impl FreezeDetector {
         Ok(FreezeReason::Unknown)
     }
 
-    /// Check if drive is BIOS frozen
+    /// Check if drive is BIOS frozen: outside a RAID controller, the
+    /// security frozen bit is set by the BIOS during POST
     fn is_bios_frozen(device_path: &str) -> Result<bool> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-
-        // Look for specific BIOS freeze indicators
-        Ok(output_str.contains("frozen") && output_str.contains("BIOS"))
+        let identify = AtaDevice::open(device_path)?.identify()?;
+        Ok(identify.security().frozen)
     }
 
     /// Check if drive is part of RAID
@@ -13,6 +13,7 @@ use super::types::hybrid::HybridDrive;
 use super::types::emmc::EMMCDevice;
 use super::types::raid::RAIDArray;
 use super::types::nvme::advanced::{NVMeAdvanced, NVMeNamespace, NamespaceType};
+use super::nvme_admin::{NvmeDevice, SecureEraseSetting};
 
 // ==================== SMR DRIVE INTEGRATION ====================
 
@@ -431,20 +432,9 @@ pub fn wipe_nvme_advanced_integrated(
 
 /// Format NVMe namespace using Format command
 fn format_nvme_namespace(controller_path: &str, nsid: u32) -> Result<()> {
-    use std::process::Command;
-
-    let output = Command::new("nvme")
-        .args(&["format", controller_path, "-n", &nsid.to_string()])
-        .output()?;
-
-    if !output.status.success() {
-        return Err(anyhow::anyhow!(
-            "NVMe format failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    Ok(())
+    NvmeDevice::open(controller_path)?
+        .format_nvm(nsid, SecureEraseSetting::None)
+        .map_err(|e| anyhow::anyhow!("NVMe format failed: {}", e))
 }
 
 /// Wipe standard namespace with multiple passes
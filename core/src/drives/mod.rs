@@ -5,6 +5,10 @@
 // - types/: Drive-type specific implementations (HDD, SSD, NVMe, SMR, etc.)
 // - operations/: Drive operations (SMART, TRIM, HPA/DCO, SED)
 // - freeze/: Freeze detection and mitigation
+// - ata/: Native ATA passthrough (SG_IO) used instead of hdparm
+// - nvme_admin/: Native NVMe admin commands (ioctl) used instead of nvme-cli
+// - backend/: Device backend trait with real and file-backed simulated drives
+// - partitions.rs: GPT/MBR parsing and partition- or range-scoped wipe targets
 // - integrated_wipe.rs: OptimizedIO-integrated wipe operations for advanced drives
 
 // Core functionality
@@ -19,11 +23,24 @@ pub mod operations;
 // Freeze mitigation (already well-organized)
 pub mod freeze;
 
+// Native ATA command layer
+pub mod ata;
+
+// Native NVMe admin command layer
+pub mod nvme_admin;
+
+// Device backends (real hardware and simulator)
+pub mod backend;
+
+// Partition tables and wipe scopes
+pub mod partitions;
+
 // Integrated wipe operations (Phase 1, Step 5 - I/O Engine Integration)
 pub mod integrated_wipe;
 
 // Re-exports for backward compatibility and convenience
 pub use detection::DriveDetector;
+pub use partitions::{PartitionTable, Partition, PartitionRef, ResolvedScope, WipeScope};
 
 // Drive types
 pub use types::{
@@ -31,6 +48,8 @@ pub use types::{
     HDDWipe,
     SSDWipe,
     NVMeWipe,
+    NvmeSanitizeRecord,
+    SanitizeSession,
 
     // Advanced NVMe
     NVMeAdvanced,
@@ -65,6 +84,15 @@ pub use types::{
     MetadataLocation,
 };
 
+// ATA passthrough
+pub use ata::{AtaDevice, AtaTransport, IdentifyData, SgIoTransport};
+
+// NVMe passthrough
+pub use nvme_admin::{IoctlTransport, NvmeDevice, NvmeTransport};
+
+// Device backends
+pub use backend::{BlockDevice, DeviceBackend, SimulatedDevice, SimulatedProfile};
+
 // Operations
 pub use operations::{
     HPADCOManager,
@@ -0,0 +1,316 @@
+// Typed views of NVMe Identify data and log pages
+
+use serde::{Deserialize, Serialize};
+
+/// Identify Controller data structure (CNS 01h)
+#[derive(Debug, Clone)]
+pub struct IdentifyController {
+    data: Vec<u8>,
+}
+
+impl IdentifyController {
+    pub fn from_bytes(data: &[u8]) -> Self {
+        Self { data: data.to_vec() }
+    }
+
+    pub fn vendor_id(&self) -> u16 {
+        le16(&self.data, 0)
+    }
+
+    pub fn serial(&self) -> String {
+        ascii(&self.data, 4, 24)
+    }
+
+    pub fn model(&self) -> String {
+        ascii(&self.data, 24, 64)
+    }
+
+    pub fn firmware(&self) -> String {
+        ascii(&self.data, 64, 72)
+    }
+
+    pub fn controller_id(&self) -> u16 {
+        le16(&self.data, 78)
+    }
+
+    /// Optional Admin Command Support
+    pub fn oacs(&self) -> u16 {
+        le16(&self.data, 256)
+    }
+
+    pub fn security_send_receive_supported(&self) -> bool {
+        self.oacs() & (1 << 0) != 0
+    }
+
+    pub fn format_supported(&self) -> bool {
+        self.oacs() & (1 << 1) != 0
+    }
+
+    pub fn namespace_management_supported(&self) -> bool {
+        self.oacs() & (1 << 3) != 0
+    }
+
+    /// Total NVM capacity in bytes (lower 64 bits of TNVMCAP)
+    pub fn total_capacity(&self) -> u64 {
+        le64(&self.data, 280)
+    }
+
+    pub fn sanitize(&self) -> SanitizeCapabilities {
+        let sanicap = le32(&self.data, 328);
+        SanitizeCapabilities {
+            crypto_erase: sanicap & (1 << 0) != 0,
+            block_erase: sanicap & (1 << 1) != 0,
+            overwrite: sanicap & (1 << 2) != 0,
+        }
+    }
+
+    /// Highest namespace ID the controller supports
+    pub fn namespace_count(&self) -> u32 {
+        le32(&self.data, 516)
+    }
+
+    /// Format NVM Attributes
+    pub fn format_attributes(&self) -> FormatAttributes {
+        let fna = self.data.get(524).copied().unwrap_or(0);
+        FormatAttributes {
+            applies_to_all_namespaces: fna & (1 << 0) != 0,
+            secure_erase_applies_to_all: fna & (1 << 1) != 0,
+            crypto_erase_supported: fna & (1 << 2) != 0,
+        }
+    }
+}
+
+/// SANICAP: which Sanitize actions the controller supports
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SanitizeCapabilities {
+    pub crypto_erase: bool,
+    pub block_erase: bool,
+    pub overwrite: bool,
+}
+
+impl SanitizeCapabilities {
+    pub fn any(&self) -> bool {
+        self.crypto_erase || self.block_erase || self.overwrite
+    }
+}
+
+/// FNA: scope and secure erase options of Format NVM
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FormatAttributes {
+    pub applies_to_all_namespaces: bool,
+    pub secure_erase_applies_to_all: bool,
+    pub crypto_erase_supported: bool,
+}
+
+/// Identify Namespace data structure (CNS 00h)
+#[derive(Debug, Clone)]
+pub struct IdentifyNamespace {
+    data: Vec<u8>,
+}
+
+impl IdentifyNamespace {
+    pub fn from_bytes(data: &[u8]) -> Self {
+        Self { data: data.to_vec() }
+    }
+
+    /// Namespace size in logical blocks
+    pub fn size_blocks(&self) -> u64 {
+        le64(&self.data, 0)
+    }
+
+    pub fn capacity_blocks(&self) -> u64 {
+        le64(&self.data, 8)
+    }
+
+    pub fn utilization_blocks(&self) -> u64 {
+        le64(&self.data, 16)
+    }
+
+    /// Index of the LBA format currently in use
+    pub fn formatted_lba_index(&self) -> u8 {
+        let flbas = self.data.get(26).copied().unwrap_or(0);
+        // Bits 3:0 plus bits 6:5 as the upper part when more than 16 formats exist
+        (flbas & 0x0F) | ((flbas >> 1) & 0x30)
+    }
+
+    /// Logical block size of the current format in bytes
+    pub fn lba_size(&self) -> u32 {
+        let lbaf = le32(&self.data, 128 + 4 * self.formatted_lba_index() as usize);
+        let lbads = (lbaf >> 16) & 0xFF;
+        if (9..32).contains(&lbads) { 1 << lbads } else { 512 }
+    }
+
+    pub fn size_bytes(&self) -> u64 {
+        self.size_blocks() * self.lba_size() as u64
+    }
+}
+
+/// Status reported in the Sanitize Status log page (SSTAT bits 2:0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SanitizeState {
+    NeverSanitized,
+    Completed,
+    InProgress,
+    Failed,
+    CompletedNoDeallocate,
+    Unknown(u8),
+}
+
+/// Sanitize Status log page (Log ID 81h)
+#[derive(Debug, Clone)]
+pub struct SanitizeLog {
+    data: Vec<u8>,
+}
+
+impl SanitizeLog {
+    pub fn from_bytes(data: &[u8]) -> Self {
+        Self { data: data.to_vec() }
+    }
+
+    /// SPROG: progress of the current sanitize, numerator of x/65536
+    pub fn sprog(&self) -> u16 {
+        le16(&self.data, 0)
+    }
+
+    /// Raw SSTAT field
+    pub fn sstat(&self) -> u16 {
+        le16(&self.data, 2)
+    }
+
+    pub fn state(&self) -> SanitizeState {
+        match (self.sstat() & 0x7) as u8 {
+            0 => SanitizeState::NeverSanitized,
+            1 => SanitizeState::Completed,
+            2 => SanitizeState::InProgress,
+            3 => SanitizeState::Failed,
+            4 => SanitizeState::CompletedNoDeallocate,
+            other => SanitizeState::Unknown(other),
+        }
+    }
+
+    /// Number of completed overwrite passes (SSTAT bits 7:3)
+    pub fn overwrite_passes_completed(&self) -> u8 {
+        ((self.sstat() >> 3) & 0x1F) as u8
+    }
+
+    /// Command Dword 10 of the sanitize command that started the operation
+    pub fn scdw10(&self) -> u32 {
+        le32(&self.data, 4)
+    }
+
+    /// Progress in percent; 100 once the operation is no longer running
+    pub fn progress_percent(&self) -> f64 {
+        match self.state() {
+            SanitizeState::InProgress => self.sprog() as f64 / 65536.0 * 100.0,
+            _ => 100.0,
+        }
+    }
+
+    pub fn estimated_overwrite_secs(&self) -> Option<u32> {
+        estimate(le32(&self.data, 8))
+    }
+
+    pub fn estimated_block_erase_secs(&self) -> Option<u32> {
+        estimate(le32(&self.data, 12))
+    }
+
+    pub fn estimated_crypto_erase_secs(&self) -> Option<u32> {
+        estimate(le32(&self.data, 16))
+    }
+}
+
+/// SMART / Health Information log page (Log ID 02h)
+#[derive(Debug, Clone)]
+pub struct SmartLog {
+    data: Vec<u8>,
+}
+
+impl SmartLog {
+    pub fn from_bytes(data: &[u8]) -> Self {
+        Self { data: data.to_vec() }
+    }
+
+    pub fn critical_warning(&self) -> u8 {
+        self.data.first().copied().unwrap_or(0)
+    }
+
+    /// Composite temperature in Kelvin
+    pub fn temperature_kelvin(&self) -> u16 {
+        le16(&self.data, 1)
+    }
+
+    pub fn temperature_celsius(&self) -> Option<u32> {
+        (self.temperature_kelvin() >= 273).then(|| (self.temperature_kelvin() - 273) as u32)
+    }
+
+    pub fn available_spare(&self) -> u8 {
+        self.data.get(3).copied().unwrap_or(0)
+    }
+
+    pub fn available_spare_threshold(&self) -> u8 {
+        self.data.get(4).copied().unwrap_or(0)
+    }
+
+    pub fn percentage_used(&self) -> u8 {
+        self.data.get(5).copied().unwrap_or(0)
+    }
+
+    /// Data units read, in thousands of 512-byte units
+    pub fn data_units_read(&self) -> u64 {
+        le64(&self.data, 32)
+    }
+
+    /// Data units written, in thousands of 512-byte units
+    pub fn data_units_written(&self) -> u64 {
+        le64(&self.data, 48)
+    }
+
+    pub fn power_cycles(&self) -> u64 {
+        le64(&self.data, 112)
+    }
+
+    pub fn power_on_hours(&self) -> u64 {
+        le64(&self.data, 128)
+    }
+
+    pub fn unsafe_shutdowns(&self) -> u64 {
+        le64(&self.data, 144)
+    }
+
+    pub fn media_errors(&self) -> u64 {
+        le64(&self.data, 160)
+    }
+
+    pub fn error_log_entries(&self) -> u64 {
+        le64(&self.data, 176)
+    }
+}
+
+/// 0xFFFFFFFF means "no estimate reported"
+fn estimate(value: u32) -> Option<u32> {
+    (value != u32::MAX).then_some(value)
+}
+
+fn le16(data: &[u8], offset: usize) -> u16 {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .unwrap_or(0)
+}
+
+fn le32(data: &[u8], offset: usize) -> u32 {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .unwrap_or(0)
+}
+
+fn le64(data: &[u8], offset: usize) -> u64 {
+    data.get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .unwrap_or(0)
+}
+
+/// Space-padded ASCII field
+fn ascii(data: &[u8], start: usize, end: usize) -> String {
+    let bytes = data.get(start..end).unwrap_or(&[]);
+    String::from_utf8_lossy(bytes).trim().trim_end_matches('\0').to_string()
+}
@@ -0,0 +1,127 @@
+// ioctl transport: NVME_IOCTL_ADMIN_CMD / NVME_IOCTL_IO_CMD on the
+// controller character device or a namespace block device
+
+use super::{DataTransfer, NvmeCommand, NvmeCompletion, NvmeTransport};
+use crate::{DriveError, DriveResult};
+use std::fs::{File, OpenOptions};
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
+// _IO('N', 0x40) and _IOWR('N', 0x41/0x43, struct nvme_passthru_cmd)
+const NVME_IOCTL_ID: libc::c_ulong = 0x4E40;
+const NVME_IOCTL_ADMIN_CMD: libc::c_ulong = 0xC048_4E41;
+const NVME_IOCTL_IO_CMD: libc::c_ulong = 0xC048_4E43;
+
+/// Linux `struct nvme_passthru_cmd` (linux/nvme_ioctl.h)
+#[repr(C)]
+#[derive(Default)]
+struct NvmePassthruCmd {
+    opcode: u8,
+    flags: u8,
+    rsvd1: u16,
+    nsid: u32,
+    cdw2: u32,
+    cdw3: u32,
+    metadata: u64,
+    addr: u64,
+    metadata_len: u32,
+    data_len: u32,
+    cdw10: u32,
+    cdw11: u32,
+    cdw12: u32,
+    cdw13: u32,
+    cdw14: u32,
+    cdw15: u32,
+    timeout_ms: u32,
+    result: u32,
+}
+
+/// NVMe transport over the kernel passthrough ioctls
+pub struct IoctlTransport {
+    file: File,
+    device_path: String,
+}
+
+impl IoctlTransport {
+    pub fn open(device_path: &str) -> DriveResult<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .open(device_path)
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::PermissionDenied => DriveError::PermissionDenied(
+                    format!("NVMe passthrough on {} requires root: {}", device_path, e)
+                ),
+                std::io::ErrorKind::NotFound => DriveError::NotFound(device_path.to_string()),
+                _ => DriveError::IoError(e),
+            })?;
+
+        Ok(Self { file, device_path: device_path.to_string() })
+    }
+
+    fn submit(
+        &mut self,
+        request: libc::c_ulong,
+        command: &NvmeCommand,
+        data: DataTransfer<'_>,
+        timeout: Duration,
+    ) -> DriveResult<NvmeCompletion> {
+        let (addr, length) = match data {
+            DataTransfer::None => (0, 0),
+            DataTransfer::In(buf) => (buf.as_mut_ptr() as u64, buf.len()),
+            DataTransfer::Out(buf) => (buf.as_ptr() as u64, buf.len()),
+        };
+
+        let mut cmd = NvmePassthruCmd {
+            opcode: command.opcode,
+            nsid: command.nsid,
+            addr,
+            data_len: length as u32,
+            cdw10: command.cdw10,
+            cdw11: command.cdw11,
+            cdw12: command.cdw12,
+            cdw13: command.cdw13,
+            cdw14: command.cdw14,
+            cdw15: command.cdw15,
+            timeout_ms: timeout.as_millis().min(u32::MAX as u128) as u32,
+            ..Default::default()
+        };
+
+        // SAFETY: `cmd.addr` points at a buffer of `data_len` bytes that
+        // outlives the ioctl
+        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), request as _, &mut cmd) };
+        if ret < 0 {
+            let error = std::io::Error::last_os_error();
+            return Err(match error.raw_os_error() {
+                Some(libc::EINTR) | Some(libc::ETIMEDOUT) => DriveError::Timeout(format!(
+                    "NVMe command 0x{:02X} on {} timed out", command.opcode, self.device_path
+                )),
+                _ => DriveError::IoError(error),
+            });
+        }
+
+        // A positive return value is the NVMe status field
+        Ok(NvmeCompletion { result: cmd.result, status: ret as u16 })
+    }
+}
+
+impl NvmeTransport for IoctlTransport {
+    fn admin(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, timeout: Duration) -> DriveResult<NvmeCompletion> {
+        self.submit(NVME_IOCTL_ADMIN_CMD, command, data, timeout)
+    }
+
+    fn io(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, timeout: Duration) -> DriveResult<NvmeCompletion> {
+        self.submit(NVME_IOCTL_IO_CMD, command, data, timeout)
+    }
+
+    fn namespace_id(&mut self) -> DriveResult<u32> {
+        // SAFETY: NVME_IOCTL_ID takes no argument
+        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), NVME_IOCTL_ID as _) };
+        if ret < 0 {
+            // The controller character device has no namespace of its own
+            return Err(DriveError::Unsupported(format!(
+                "{} is not an NVMe namespace device", self.device_path
+            )));
+        }
+        Ok(ret as u32)
+    }
+}
@@ -0,0 +1,521 @@
+// Native NVMe admin command layer
+//
+// Issues NVMe admin (and the few I/O) commands the wipe needs directly
+// through the kernel passthrough ioctls instead of spawning nvme-cli and
+// parsing its text output. Commands go through the `NvmeTransport` trait:
+// `IoctlTransport` talks to real controllers, tests plug in a simulator.
+
+pub mod identify;
+pub mod ioctl;
+
+#[cfg(test)]
+mod tests;
+
+pub use identify::{
+    FormatAttributes, IdentifyController, IdentifyNamespace, SanitizeCapabilities, SanitizeLog,
+    SanitizeState, SmartLog,
+};
+pub use ioctl::IoctlTransport;
+
+use crate::{DriveError, DriveResult};
+use std::time::Duration;
+
+// Admin opcodes
+const ADMIN_GET_LOG_PAGE: u8 = 0x02;
+const ADMIN_IDENTIFY: u8 = 0x06;
+const ADMIN_NS_MANAGEMENT: u8 = 0x0D;
+const ADMIN_NS_ATTACHMENT: u8 = 0x15;
+const ADMIN_FORMAT_NVM: u8 = 0x80;
+const ADMIN_SANITIZE: u8 = 0x84;
+
+// I/O opcodes
+const IO_DATASET_MANAGEMENT: u8 = 0x09;
+const IO_ZONE_MGMT_SEND: u8 = 0x79;
+const IO_ZONE_MGMT_RECV: u8 = 0x7A;
+
+// Identify CNS values
+const CNS_NAMESPACE: u32 = 0x00;
+const CNS_CONTROLLER: u32 = 0x01;
+const CNS_ACTIVE_NAMESPACES: u32 = 0x02;
+const CNS_CSI_NAMESPACE: u32 = 0x05;
+const CNS_CSI_CONTROLLER: u32 = 0x06;
+
+const CSI_KEY_VALUE: u32 = 0x01;
+const CSI_ZONED: u32 = 0x02;
+
+// Log page identifiers
+const LOG_SMART: u8 = 0x02;
+const LOG_SANITIZE: u8 = 0x81;
+
+const NSID_ALL: u32 = 0xFFFF_FFFF;
+const DATA_SIZE: usize = 4096;
+const LOG_SIZE: usize = 512;
+const DSM_MAX_RANGES: usize = 256;
+const ZONE_REPORT_SIZE: usize = 64 * 1024;
+const ZONE_DESCRIPTOR_SIZE: usize = 64;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const FORMAT_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// Submission queue entry fields a passthrough command can set
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NvmeCommand {
+    pub opcode: u8,
+    pub nsid: u32,
+    pub cdw10: u32,
+    pub cdw11: u32,
+    pub cdw12: u32,
+    pub cdw13: u32,
+    pub cdw14: u32,
+    pub cdw15: u32,
+}
+
+impl NvmeCommand {
+    fn new(opcode: u8, nsid: u32) -> Self {
+        Self { opcode, nsid, ..Default::default() }
+    }
+}
+
+/// Completion queue entry: command-specific result and status field
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NvmeCompletion {
+    pub result: u32,
+    pub status: u16,
+}
+
+impl NvmeCompletion {
+    /// Status Code Type (bits 10:8)
+    pub fn status_code_type(&self) -> u8 {
+        ((self.status >> 8) & 0x7) as u8
+    }
+
+    /// Status Code (bits 7:0)
+    pub fn status_code(&self) -> u8 {
+        self.status as u8
+    }
+}
+
+/// Data phase of a command
+pub enum DataTransfer<'a> {
+    None,
+    In(&'a mut [u8]),
+    Out(&'a [u8]),
+}
+
+/// Something that can execute NVMe commands: a real controller or a simulator
+pub trait NvmeTransport: Send {
+    fn admin(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, timeout: Duration)
+        -> DriveResult<NvmeCompletion>;
+
+    fn io(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, timeout: Duration)
+        -> DriveResult<NvmeCompletion>;
+
+    /// Namespace the device node refers to
+    fn namespace_id(&mut self) -> DriveResult<u32>;
+}
+
+/// Secure Erase Settings of Format NVM
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SecureEraseSetting {
+    None = 0,
+    UserData = 1,
+    Cryptographic = 2,
+}
+
+/// Sanitize Action (SANACT) with its parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SanitizeAction {
+    ExitFailureMode,
+    BlockErase,
+    Overwrite { passes: u8, pattern: u32 },
+    CryptoErase,
+}
+
+impl SanitizeAction {
+    fn cdw10(&self, no_deallocate: bool) -> u32 {
+        let action = match self {
+            SanitizeAction::ExitFailureMode => 1,
+            SanitizeAction::BlockErase => 2,
+            SanitizeAction::Overwrite { passes, .. } => 3 | ((*passes as u32 & 0xF) << 4),
+            SanitizeAction::CryptoErase => 4,
+        };
+        action | if no_deallocate { 1 << 9 } else { 0 }
+    }
+
+    /// Decode the action recorded in SCDW10 of the Sanitize Status log.
+    /// The overwrite pattern is not part of SCDW10 and reads back as 0.
+    pub fn from_cdw10(cdw10: u32) -> Option<Self> {
+        match cdw10 & 0x7 {
+            1 => Some(SanitizeAction::ExitFailureMode),
+            2 => Some(SanitizeAction::BlockErase),
+            3 => Some(SanitizeAction::Overwrite { passes: ((cdw10 >> 4) & 0xF) as u8, pattern: 0 }),
+            4 => Some(SanitizeAction::CryptoErase),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SanitizeAction::ExitFailureMode => "Exit Failure Mode",
+            SanitizeAction::BlockErase => "Block Erase",
+            SanitizeAction::Overwrite { .. } => "Overwrite",
+            SanitizeAction::CryptoErase => "Crypto Erase",
+        }
+    }
+}
+
+/// One entry of a Report Zones response
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZoneDescriptor {
+    pub zone_type: u8,
+    pub state: u8,
+    pub capacity: u64,
+    pub start_lba: u64,
+    pub write_pointer: u64,
+}
+
+impl ZoneDescriptor {
+    pub const TYPE_SEQUENTIAL_WRITE_REQUIRED: u8 = 0x2;
+
+    fn from_bytes(data: &[u8]) -> Self {
+        let qword = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        Self {
+            zone_type: data[0] & 0x0F,
+            state: data[1] >> 4,
+            capacity: qword(8),
+            start_lba: qword(16),
+            write_pointer: qword(24),
+        }
+    }
+}
+
+/// High-level NVMe operations on top of a transport
+pub struct NvmeDevice<T: NvmeTransport = IoctlTransport> {
+    transport: T,
+}
+
+impl NvmeDevice<IoctlTransport> {
+    /// Open a controller (/dev/nvmeX) or namespace (/dev/nvmeXnY) device
+    pub fn open(device_path: &str) -> DriveResult<Self> {
+        Ok(Self::new(IoctlTransport::open(device_path)?))
+    }
+}
+
+impl<T: NvmeTransport> NvmeDevice<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    /// Namespace of the opened device, or namespace 1 for a controller node
+    pub fn namespace_id(&mut self) -> u32 {
+        self.transport.namespace_id().unwrap_or(1)
+    }
+
+    pub fn identify_controller(&mut self) -> DriveResult<IdentifyController> {
+        let data = self.identify(CNS_CONTROLLER, 0, 0)?;
+        Ok(IdentifyController::from_bytes(&data))
+    }
+
+    pub fn identify_namespace(&mut self, nsid: u32) -> DriveResult<IdentifyNamespace> {
+        let data = self.identify(CNS_NAMESPACE, nsid, 0)?;
+        Ok(IdentifyNamespace::from_bytes(&data))
+    }
+
+    /// IDs of all active namespaces, in ascending order
+    pub fn active_namespaces(&mut self) -> DriveResult<Vec<u32>> {
+        let mut namespaces = Vec::new();
+        let mut start = 0u32;
+        loop {
+            let data = self.identify(CNS_ACTIVE_NAMESPACES, start, 0)?;
+            let page: Vec<u32> = data.chunks_exact(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .take_while(|&nsid| nsid != 0)
+                .collect();
+
+            let full = page.len() == DATA_SIZE / 4;
+            start = page.last().copied().unwrap_or(0);
+            namespaces.extend(page);
+            if !full {
+                return Ok(namespaces);
+            }
+        }
+    }
+
+    /// Whether the controller implements the Zoned Namespace command set
+    pub fn zns_supported(&mut self) -> bool {
+        self.identify(CNS_CSI_CONTROLLER, 0, CSI_ZONED).is_ok()
+    }
+
+    /// Whether the controller implements the Key Value command set
+    pub fn kv_supported(&mut self) -> bool {
+        self.identify(CNS_CSI_CONTROLLER, 0, CSI_KEY_VALUE).is_ok()
+    }
+
+    /// Whether a namespace is a zoned namespace
+    pub fn is_zoned_namespace(&mut self, nsid: u32) -> bool {
+        self.identify(CNS_CSI_NAMESPACE, nsid, CSI_ZONED).is_ok()
+    }
+
+    /// Format NVM with the given secure erase setting, keeping the current LBA format
+    pub fn format_nvm(&mut self, nsid: u32, ses: SecureEraseSetting) -> DriveResult<()> {
+        let lbaf = self.identify_namespace(if nsid == NSID_ALL { 1 } else { nsid })
+            .map(|ns| ns.formatted_lba_index() as u32)
+            .unwrap_or(0);
+
+        let command = NvmeCommand {
+            cdw10: (lbaf & 0xF) | ((ses as u32) << 9) | ((lbaf >> 4) << 12),
+            ..NvmeCommand::new(ADMIN_FORMAT_NVM, nsid)
+        };
+        self.admin(&command, DataTransfer::None, FORMAT_TIMEOUT)?;
+        Ok(())
+    }
+
+    /// Start a sanitize operation; progress is reported in the sanitize log
+    pub fn sanitize(&mut self, action: SanitizeAction, no_deallocate: bool) -> DriveResult<()> {
+        let pattern = match action {
+            SanitizeAction::Overwrite { pattern, .. } => pattern,
+            _ => 0,
+        };
+        let command = NvmeCommand {
+            cdw10: action.cdw10(no_deallocate),
+            cdw11: pattern,
+            ..NvmeCommand::new(ADMIN_SANITIZE, 0)
+        };
+        self.admin(&command, DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    pub fn sanitize_log(&mut self) -> DriveResult<SanitizeLog> {
+        let mut buf = [0u8; LOG_SIZE];
+        self.get_log_page(LOG_SANITIZE, NSID_ALL, &mut buf)?;
+        Ok(SanitizeLog::from_bytes(&buf))
+    }
+
+    /// Poll the sanitize log until the running operation finishes, reporting
+    /// each poll to `on_progress`; returns the final log on success
+    pub fn wait_for_sanitize<F>(&mut self, poll_interval: Duration, mut on_progress: F) -> DriveResult<SanitizeLog>
+    where
+        F: FnMut(&SanitizeLog),
+    {
+        loop {
+            let log = self.sanitize_log()?;
+            on_progress(&log);
+
+            match log.state() {
+                SanitizeState::InProgress => std::thread::sleep(poll_interval),
+                SanitizeState::Completed | SanitizeState::CompletedNoDeallocate => return Ok(log),
+                SanitizeState::Failed => {
+                    return Err(DriveError::HardwareCommandFailed(
+                        "NVMe sanitize failed; the controller is in sanitize failure mode".to_string()
+                    ));
+                }
+                state => {
+                    return Err(DriveError::HardwareCommandFailed(format!(
+                        "NVMe sanitize ended in unexpected state {:?}", state
+                    )));
+                }
+            }
+        }
+    }
+
+    pub fn smart_log(&mut self) -> DriveResult<SmartLog> {
+        let mut buf = [0u8; LOG_SIZE];
+        self.get_log_page(LOG_SMART, NSID_ALL, &mut buf)?;
+        Ok(SmartLog::from_bytes(&buf))
+    }
+
+    pub fn get_log_page(&mut self, log_id: u8, nsid: u32, buf: &mut [u8]) -> DriveResult<()> {
+        let dwords = (buf.len() / 4).saturating_sub(1) as u32;
+        let command = NvmeCommand {
+            cdw10: log_id as u32 | ((dwords & 0xFFFF) << 16),
+            cdw11: dwords >> 16,
+            ..NvmeCommand::new(ADMIN_GET_LOG_PAGE, nsid)
+        };
+        self.admin(&command, DataTransfer::In(buf), DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    /// Create a namespace and return its ID
+    pub fn create_namespace(&mut self, size_blocks: u64, capacity_blocks: u64, lba_format: u8) -> DriveResult<u32> {
+        let mut data = [0u8; DATA_SIZE];
+        data[0..8].copy_from_slice(&size_blocks.to_le_bytes());
+        data[8..16].copy_from_slice(&capacity_blocks.to_le_bytes());
+        data[26] = (lba_format & 0x0F) | ((lba_format & 0x30) << 1);
+
+        let command = NvmeCommand::new(ADMIN_NS_MANAGEMENT, 0);
+        let completion = self.admin(&command, DataTransfer::Out(&data), DEFAULT_TIMEOUT)?;
+        Ok(completion.result)
+    }
+
+    pub fn delete_namespace(&mut self, nsid: u32) -> DriveResult<()> {
+        let command = NvmeCommand { cdw10: 1, ..NvmeCommand::new(ADMIN_NS_MANAGEMENT, nsid) };
+        self.admin(&command, DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    pub fn attach_namespace(&mut self, nsid: u32, controllers: &[u16]) -> DriveResult<()> {
+        self.namespace_attachment(nsid, controllers, false)
+    }
+
+    pub fn detach_namespace(&mut self, nsid: u32, controllers: &[u16]) -> DriveResult<()> {
+        self.namespace_attachment(nsid, controllers, true)
+    }
+
+    /// Deallocate (TRIM) ranges of (start LBA, block count)
+    pub fn deallocate(&mut self, nsid: u32, ranges: &[(u64, u64)]) -> DriveResult<()> {
+        let entries = dsm_ranges(ranges);
+        for chunk in entries.chunks(DSM_MAX_RANGES) {
+            let mut data = vec![0u8; chunk.len() * 16];
+            for (slot, &(slba, blocks)) in data.chunks_exact_mut(16).zip(chunk) {
+                slot[4..8].copy_from_slice(&blocks.to_le_bytes());
+                slot[8..16].copy_from_slice(&slba.to_le_bytes());
+            }
+
+            let command = NvmeCommand {
+                cdw10: (chunk.len() - 1) as u32,
+                cdw11: 1 << 2, // Attribute - Deallocate
+                ..NvmeCommand::new(IO_DATASET_MANAGEMENT, nsid)
+            };
+            self.io(&command, DataTransfer::Out(&data), DEFAULT_TIMEOUT)
+                .map_err(|e| DriveError::TRIMFailed(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Report every zone of a zoned namespace
+    pub fn report_zones(&mut self, nsid: u32) -> DriveResult<Vec<ZoneDescriptor>> {
+        let mut zones = Vec::new();
+        let mut start_lba = 0u64;
+        let mut buf = vec![0u8; ZONE_REPORT_SIZE];
+
+        loop {
+            let command = NvmeCommand {
+                cdw10: start_lba as u32,
+                cdw11: (start_lba >> 32) as u32,
+                cdw12: (ZONE_REPORT_SIZE / 4 - 1) as u32,
+                cdw13: 1 << 16, // Report all zones, partial report
+                ..NvmeCommand::new(IO_ZONE_MGMT_RECV, nsid)
+            };
+            buf.fill(0);
+            self.io(&command, DataTransfer::In(&mut buf), DEFAULT_TIMEOUT)?;
+
+            let reported = u64::from_le_bytes(buf[0..8].try_into().unwrap()) as usize;
+            let page: Vec<ZoneDescriptor> = buf[ZONE_DESCRIPTOR_SIZE..]
+                .chunks_exact(ZONE_DESCRIPTOR_SIZE)
+                .take(reported)
+                .map(ZoneDescriptor::from_bytes)
+                .collect();
+
+            let Some(last) = page.last().copied() else {
+                return Ok(zones);
+            };
+            let full = page.len() == ZONE_REPORT_SIZE / ZONE_DESCRIPTOR_SIZE - 1;
+            zones.extend(page);
+
+            // Zones are contiguous, so the next zone starts where this one
+            // ends; zone size is the distance between two zone starts
+            let zone_size = match zones.len() {
+                n if n >= 2 => zones[n - 1].start_lba - zones[n - 2].start_lba,
+                _ => last.capacity,
+            };
+            if !full || zone_size == 0 {
+                return Ok(zones);
+            }
+            start_lba = last.start_lba + zone_size;
+        }
+    }
+
+    /// Reset one zone, or every zone when `start_lba` is None
+    pub fn reset_zone(&mut self, nsid: u32, start_lba: Option<u64>) -> DriveResult<()> {
+        let slba = start_lba.unwrap_or(0);
+        let command = NvmeCommand {
+            cdw10: slba as u32,
+            cdw11: (slba >> 32) as u32,
+            cdw13: 0x04 | if start_lba.is_none() { 1 << 8 } else { 0 },
+            ..NvmeCommand::new(IO_ZONE_MGMT_SEND, nsid)
+        };
+        self.io(&command, DataTransfer::None, DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    fn namespace_attachment(&mut self, nsid: u32, controllers: &[u16], detach: bool) -> DriveResult<()> {
+        let mut data = [0u8; DATA_SIZE];
+        data[0..2].copy_from_slice(&(controllers.len() as u16).to_le_bytes());
+        for (i, id) in controllers.iter().take(DATA_SIZE / 2 - 1).enumerate() {
+            data[2 + i * 2..4 + i * 2].copy_from_slice(&id.to_le_bytes());
+        }
+
+        let command = NvmeCommand { cdw10: detach as u32, ..NvmeCommand::new(ADMIN_NS_ATTACHMENT, nsid) };
+        self.admin(&command, DataTransfer::Out(&data), DEFAULT_TIMEOUT)?;
+        Ok(())
+    }
+
+    fn identify(&mut self, cns: u32, nsid: u32, csi: u32) -> DriveResult<Vec<u8>> {
+        let mut buf = vec![0u8; DATA_SIZE];
+        let command = NvmeCommand {
+            cdw10: cns,
+            cdw11: csi << 24,
+            ..NvmeCommand::new(ADMIN_IDENTIFY, nsid)
+        };
+        self.admin(&command, DataTransfer::In(&mut buf), DEFAULT_TIMEOUT)?;
+        Ok(buf)
+    }
+
+    fn admin(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, timeout: Duration) -> DriveResult<NvmeCompletion> {
+        let completion = self.transport.admin(command, data, timeout)?;
+        check_status(command, completion)
+    }
+
+    fn io(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, timeout: Duration) -> DriveResult<NvmeCompletion> {
+        let completion = self.transport.io(command, data, timeout)?;
+        check_status(command, completion)
+    }
+}
+
+/// Turn a non-zero status field into an error
+fn check_status(command: &NvmeCommand, completion: NvmeCompletion) -> DriveResult<NvmeCompletion> {
+    if completion.status & 0x7FF == 0 {
+        return Ok(completion);
+    }
+
+    let reason = match (completion.status_code_type(), completion.status_code()) {
+        (0, 0x01) => "invalid command opcode",
+        (0, 0x02) => "invalid field in command",
+        (0, 0x0B) => "invalid namespace or format",
+        (0, 0x1C) => "sanitize failed; exit failure mode first",
+        (0, 0x1D) => "sanitize in progress",
+        (1, 0x0A) => "invalid format",
+        (1, 0x0B) => "firmware activation requires reset",
+        (1, 0x15) => "namespace insufficient capacity",
+        (1, 0x18) => "namespace already attached",
+        (1, 0x1A) => "namespace not attached",
+        (1, 0x23) => "sanitize prohibited while persistent memory region is enabled",
+        (2, _) => "media or data integrity error",
+        _ => "command failed",
+    };
+
+    Err(DriveError::HardwareCommandFailed(format!(
+        "NVMe command 0x{:02X} failed: {} (status 0x{:03X})",
+        command.opcode, reason, completion.status & 0x7FF
+    )))
+}
+
+/// DSM ranges: 32-bit block count per range
+fn dsm_ranges(ranges: &[(u64, u64)]) -> Vec<(u64, u32)> {
+    let mut entries = Vec::new();
+    for &(start, blocks) in ranges {
+        let mut lba = start;
+        let mut remaining = blocks;
+        while remaining > 0 {
+            let length = remaining.min(u32::MAX as u64);
+            entries.push((lba, length as u32));
+            lba += length;
+            remaining -= length;
+        }
+    }
+    entries
+}
@@ -0,0 +1,380 @@
+// Tests for the native NVMe layer against a simulated controller
+
+use super::*;
+use std::sync::{Arc, Mutex};
+
+const NAMESPACE_BLOCKS: u64 = 1_000_215_216;
+const ZONE_BLOCKS: u64 = 0x8000;
+
+/// In-memory controller that implements just enough of NVMe for the tests
+#[derive(Default)]
+struct SimState {
+    namespaces: Vec<u32>,
+    zoned: bool,
+    sanitize_cdw10: u32,
+    sanitize_polls_left: u32,
+    sanitize_failed: bool,
+    formatted: Vec<(u32, u32)>,
+    deallocated: Vec<(u64, u32)>,
+    reset_zones: Vec<Option<u64>>,
+    commands: Vec<u8>,
+}
+
+#[derive(Clone)]
+struct SimulatedNvme {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl SimulatedNvme {
+    fn new() -> Self {
+        let state = SimState { namespaces: vec![1, 2], ..Default::default() };
+        Self { state: Arc::new(Mutex::new(state)) }
+    }
+
+    fn identify_controller() -> Vec<u8> {
+        let mut data = vec![0u8; DATA_SIZE];
+        data[0..2].copy_from_slice(&0x144Du16.to_le_bytes());
+        put_ascii(&mut data, 4, 24, "S5GXNF0N123456");
+        put_ascii(&mut data, 24, 64, "Simulated NVMe SSD 1TB");
+        put_ascii(&mut data, 64, 72, "2B2QEXM7");
+        data[78..80].copy_from_slice(&7u16.to_le_bytes());
+        data[256..258].copy_from_slice(&0b1011u16.to_le_bytes()); // Security, Format, NS Mgmt
+        data[280..288].copy_from_slice(&(NAMESPACE_BLOCKS * 512).to_le_bytes());
+        data[328..332].copy_from_slice(&0b011u32.to_le_bytes()); // Crypto + block erase
+        data[516..520].copy_from_slice(&32u32.to_le_bytes());
+        data[524] = 0b100; // Crypto erase in Format NVM
+        data
+    }
+
+    fn identify_namespace() -> Vec<u8> {
+        let mut data = vec![0u8; DATA_SIZE];
+        data[0..8].copy_from_slice(&NAMESPACE_BLOCKS.to_le_bytes());
+        data[8..16].copy_from_slice(&NAMESPACE_BLOCKS.to_le_bytes());
+        data[25] = 1;    // Two LBA formats
+        data[26] = 1;    // Using format 1
+        data[128..132].copy_from_slice(&(9u32 << 16).to_le_bytes());
+        data[132..136].copy_from_slice(&(12u32 << 16).to_le_bytes());
+        data
+    }
+}
+
+fn put_ascii(data: &mut [u8], start: usize, end: usize, text: &str) {
+    data[start..end].fill(b' ');
+    data[start..start + text.len()].copy_from_slice(text.as_bytes());
+}
+
+fn status(sct: u16, sc: u16) -> NvmeCompletion {
+    NvmeCompletion { result: 0, status: (sct << 8) | sc }
+}
+
+impl NvmeTransport for SimulatedNvme {
+    fn admin(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, _timeout: Duration) -> DriveResult<NvmeCompletion> {
+        let mut state = self.state.lock().unwrap();
+        state.commands.push(command.opcode);
+
+        let fill = |data: DataTransfer<'_>, content: &[u8]| {
+            if let DataTransfer::In(buf) = data {
+                let n = buf.len().min(content.len());
+                buf[..n].copy_from_slice(&content[..n]);
+            }
+        };
+
+        let completion = match command.opcode {
+            ADMIN_IDENTIFY => match (command.cdw10, command.cdw11 >> 24) {
+                (CNS_CONTROLLER, 0) => {
+                    fill(data, &Self::identify_controller());
+                    NvmeCompletion::default()
+                }
+                (CNS_NAMESPACE, 0) if state.namespaces.contains(&command.nsid) => {
+                    fill(data, &Self::identify_namespace());
+                    NvmeCompletion::default()
+                }
+                (CNS_ACTIVE_NAMESPACES, 0) => {
+                    let list: Vec<u8> = state.namespaces.iter()
+                        .filter(|&&nsid| nsid > command.nsid)
+                        .flat_map(|nsid| nsid.to_le_bytes())
+                        .collect();
+                    fill(data, &list);
+                    NvmeCompletion::default()
+                }
+                (CNS_CSI_CONTROLLER, CSI_ZONED) | (CNS_CSI_NAMESPACE, CSI_ZONED) if state.zoned => {
+                    NvmeCompletion::default()
+                }
+                _ => status(0, 0x02),
+            },
+            ADMIN_GET_LOG_PAGE => {
+                let mut log = vec![0u8; LOG_SIZE];
+                match command.cdw10 & 0xFF {
+                    0x81 => {
+                        let sstat: u16 = if state.sanitize_cdw10 == 0 {
+                            0
+                        } else if state.sanitize_polls_left > 0 {
+                            state.sanitize_polls_left -= 1;
+                            log[0..2].copy_from_slice(&0x8000u16.to_le_bytes());
+                            2
+                        } else if state.sanitize_failed {
+                            3
+                        } else {
+                            1
+                        };
+                        log[2..4].copy_from_slice(&sstat.to_le_bytes());
+                        log[4..8].copy_from_slice(&state.sanitize_cdw10.to_le_bytes());
+                        log[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+                        log[12..16].copy_from_slice(&120u32.to_le_bytes());
+                        log[16..20].copy_from_slice(&5u32.to_le_bytes());
+                    }
+                    0x02 => {
+                        log[0] = 0;
+                        log[1..3].copy_from_slice(&313u16.to_le_bytes());
+                        log[3] = 100;
+                        log[4] = 10;
+                        log[5] = 3;
+                        log[48..56].copy_from_slice(&12_345u64.to_le_bytes());
+                        log[112..120].copy_from_slice(&42u64.to_le_bytes());
+                        log[128..136].copy_from_slice(&1_000u64.to_le_bytes());
+                        log[160..168].copy_from_slice(&2u64.to_le_bytes());
+                    }
+                    _ => {}
+                }
+                fill(data, &log);
+                NvmeCompletion::default()
+            }
+            ADMIN_FORMAT_NVM => {
+                state.formatted.push((command.nsid, command.cdw10));
+                NvmeCompletion::default()
+            }
+            ADMIN_SANITIZE => {
+                if state.sanitize_polls_left > 0 {
+                    status(0, 0x1D)
+                } else {
+                    state.sanitize_cdw10 = command.cdw10;
+                    state.sanitize_polls_left = 3;
+                    NvmeCompletion::default()
+                }
+            }
+            ADMIN_NS_MANAGEMENT => match command.cdw10 {
+                0 => {
+                    let nsid = state.namespaces.iter().max().copied().unwrap_or(0) + 1;
+                    state.namespaces.push(nsid);
+                    NvmeCompletion { result: nsid, status: 0 }
+                }
+                1 if state.namespaces.contains(&command.nsid) => {
+                    state.namespaces.retain(|&n| n != command.nsid);
+                    NvmeCompletion::default()
+                }
+                _ => status(0, 0x0B),
+            },
+            ADMIN_NS_ATTACHMENT => NvmeCompletion::default(),
+            _ => status(0, 0x01),
+        };
+
+        Ok(completion)
+    }
+
+    fn io(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, _timeout: Duration) -> DriveResult<NvmeCompletion> {
+        let mut state = self.state.lock().unwrap();
+        state.commands.push(command.opcode);
+
+        match command.opcode {
+            IO_DATASET_MANAGEMENT => {
+                if let DataTransfer::Out(payload) = data {
+                    for range in payload.chunks_exact(16).take(command.cdw10 as usize + 1) {
+                        let blocks = u32::from_le_bytes(range[4..8].try_into().unwrap());
+                        let slba = u64::from_le_bytes(range[8..16].try_into().unwrap());
+                        state.deallocated.push((slba, blocks));
+                    }
+                }
+            }
+            IO_ZONE_MGMT_RECV => {
+                if let DataTransfer::In(buf) = data {
+                    let start = command.cdw10 as u64 | ((command.cdw11 as u64) << 32);
+                    let total_zones = 3u64;
+                    let zones: Vec<u64> = (0..total_zones)
+                        .map(|z| z * ZONE_BLOCKS)
+                        .filter(|&slba| slba >= start)
+                        .collect();
+                    buf[0..8].copy_from_slice(&(zones.len() as u64).to_le_bytes());
+                    for (i, slba) in zones.iter().enumerate() {
+                        let d = &mut buf[64 * (i + 1)..64 * (i + 2)];
+                        d[0] = 0x2;
+                        d[1] = if i == 0 { 0xE0 } else { 0x10 };
+                        d[8..16].copy_from_slice(&(ZONE_BLOCKS - 16).to_le_bytes());
+                        d[16..24].copy_from_slice(&slba.to_le_bytes());
+                        d[24..32].copy_from_slice(&slba.to_le_bytes());
+                    }
+                }
+            }
+            IO_ZONE_MGMT_SEND => {
+                let select_all = command.cdw13 & (1 << 8) != 0;
+                let slba = command.cdw10 as u64 | ((command.cdw11 as u64) << 32);
+                state.reset_zones.push((!select_all).then_some(slba));
+            }
+            _ => return Ok(status(0, 0x01)),
+        }
+
+        Ok(NvmeCompletion::default())
+    }
+
+    fn namespace_id(&mut self) -> DriveResult<u32> {
+        Ok(1)
+    }
+}
+
+#[test]
+fn test_identify_controller_parsing() {
+    let mut device = NvmeDevice::new(SimulatedNvme::new());
+    let ctrl = device.identify_controller().unwrap();
+
+    assert_eq!(ctrl.vendor_id(), 0x144D);
+    assert_eq!(ctrl.serial(), "S5GXNF0N123456");
+    assert_eq!(ctrl.model(), "Simulated NVMe SSD 1TB");
+    assert_eq!(ctrl.firmware(), "2B2QEXM7");
+    assert_eq!(ctrl.controller_id(), 7);
+    assert!(ctrl.security_send_receive_supported());
+    assert!(ctrl.format_supported());
+    assert!(ctrl.namespace_management_supported());
+    assert_eq!(ctrl.namespace_count(), 32);
+
+    let sanitize = ctrl.sanitize();
+    assert!(sanitize.crypto_erase && sanitize.block_erase && !sanitize.overwrite);
+    assert!(ctrl.format_attributes().crypto_erase_supported);
+}
+
+#[test]
+fn test_identify_namespace_uses_current_lba_format() {
+    let mut device = NvmeDevice::new(SimulatedNvme::new());
+    let ns = device.identify_namespace(1).unwrap();
+
+    assert_eq!(ns.formatted_lba_index(), 1);
+    assert_eq!(ns.lba_size(), 4096);
+    assert_eq!(ns.size_bytes(), NAMESPACE_BLOCKS * 4096);
+    assert!(device.identify_namespace(9).is_err());
+}
+
+#[test]
+fn test_namespace_management() {
+    let sim = SimulatedNvme::new();
+    let mut device = NvmeDevice::new(sim.clone());
+    assert_eq!(device.active_namespaces().unwrap(), vec![1, 2]);
+
+    let nsid = device.create_namespace(1000, 1000, 0).unwrap();
+    assert_eq!(nsid, 3);
+    device.attach_namespace(nsid, &[7]).unwrap();
+    assert_eq!(device.active_namespaces().unwrap(), vec![1, 2, 3]);
+
+    device.detach_namespace(2, &[7]).unwrap();
+    device.delete_namespace(2).unwrap();
+    assert_eq!(device.active_namespaces().unwrap(), vec![1, 3]);
+
+    let err = device.delete_namespace(2).unwrap_err().to_string();
+    assert!(err.contains("invalid namespace"), "{}", err);
+}
+
+#[test]
+fn test_format_keeps_lba_format() {
+    let sim = SimulatedNvme::new();
+    let mut device = NvmeDevice::new(sim.clone());
+    device.format_nvm(1, SecureEraseSetting::Cryptographic).unwrap();
+
+    let formatted = sim.state.lock().unwrap().formatted.clone();
+    assert_eq!(formatted, vec![(1, 1 | (2 << 9))]);
+}
+
+#[test]
+fn test_sanitize_status_codes() {
+    let command = NvmeCommand { opcode: ADMIN_SANITIZE, ..Default::default() };
+    let reason = |sc| check_status(&command, status(0, sc)).unwrap_err().to_string();
+
+    assert!(reason(0x1C).contains("sanitize failed"));
+    assert!(reason(0x1D).contains("sanitize in progress"));
+    // SGL Data Block Granularity Invalid, not a sanitize status
+    assert!(!reason(0x1E).contains("sanitize"));
+}
+
+#[test]
+fn test_sanitize_and_log_polling() {
+    let sim = SimulatedNvme::new();
+    let mut device = NvmeDevice::new(sim.clone());
+    assert_eq!(device.sanitize_log().unwrap().state(), SanitizeState::NeverSanitized);
+
+    device.sanitize(SanitizeAction::BlockErase, false).unwrap();
+
+    let log = device.sanitize_log().unwrap();
+    assert_eq!(log.state(), SanitizeState::InProgress);
+    assert_eq!(log.progress_percent(), 50.0);
+    assert_eq!(log.scdw10(), 2);
+    assert_eq!(log.estimated_overwrite_secs(), None);
+    assert_eq!(log.estimated_block_erase_secs(), Some(120));
+
+    // A second sanitize while one is running is rejected
+    let err = device.sanitize(SanitizeAction::CryptoErase, false).unwrap_err().to_string();
+    assert!(err.contains("sanitize in progress"), "{}", err);
+
+    let mut polls = 0;
+    while device.sanitize_log().unwrap().state() == SanitizeState::InProgress {
+        polls += 1;
+    }
+    assert_eq!(polls, 2);
+    assert_eq!(device.sanitize_log().unwrap().state(), SanitizeState::Completed);
+}
+
+#[test]
+fn test_sanitize_action_encoding() {
+    assert_eq!(SanitizeAction::CryptoErase.cdw10(false), 4);
+    assert_eq!(SanitizeAction::BlockErase.cdw10(true), 2 | (1 << 9));
+    assert_eq!(SanitizeAction::Overwrite { passes: 3, pattern: 0 }.cdw10(false), 3 | (3 << 4));
+
+    // SCDW10 in the sanitize log decodes back to the action that was issued
+    assert_eq!(SanitizeAction::from_cdw10(2 | (1 << 9)), Some(SanitizeAction::BlockErase));
+    assert_eq!(SanitizeAction::from_cdw10(3 | (3 << 4)), Some(SanitizeAction::Overwrite { passes: 3, pattern: 0 }));
+    assert_eq!(SanitizeAction::from_cdw10(0), None);
+}
+
+#[test]
+fn test_smart_log_parsing() {
+    let mut device = NvmeDevice::new(SimulatedNvme::new());
+    let smart = device.smart_log().unwrap();
+
+    assert_eq!(smart.critical_warning(), 0);
+    assert_eq!(smart.temperature_celsius(), Some(40));
+    assert_eq!(smart.available_spare(), 100);
+    assert_eq!(smart.available_spare_threshold(), 10);
+    assert_eq!(smart.percentage_used(), 3);
+    assert_eq!(smart.data_units_written(), 12_345);
+    assert_eq!(smart.power_cycles(), 42);
+    assert_eq!(smart.power_on_hours(), 1_000);
+    assert_eq!(smart.media_errors(), 2);
+}
+
+#[test]
+fn test_deallocate_splits_ranges() {
+    let sim = SimulatedNvme::new();
+    let mut device = NvmeDevice::new(sim.clone());
+    let big = u32::MAX as u64 + 10;
+    device.deallocate(1, &[(0, big)]).unwrap();
+
+    let deallocated = sim.state.lock().unwrap().deallocated.clone();
+    assert_eq!(deallocated, vec![(0, u32::MAX), (u32::MAX as u64, 10)]);
+}
+
+#[test]
+fn test_zns_report_and_reset() {
+    let sim = SimulatedNvme::new();
+    let mut device = NvmeDevice::new(sim.clone());
+    assert!(!device.zns_supported());
+
+    sim.state.lock().unwrap().zoned = true;
+    assert!(device.zns_supported());
+    assert!(device.is_zoned_namespace(1));
+
+    let zones = device.report_zones(1).unwrap();
+    assert_eq!(zones.len(), 3);
+    assert_eq!(zones[1].start_lba, ZONE_BLOCKS);
+    assert_eq!(zones[0].state, 0xE);
+    assert_eq!(zones[0].zone_type, ZoneDescriptor::TYPE_SEQUENTIAL_WRITE_REQUIRED);
+    assert_eq!(zones[2].capacity, ZONE_BLOCKS - 16);
+
+    device.reset_zone(1, Some(ZONE_BLOCKS)).unwrap();
+    device.reset_zone(1, None).unwrap();
+    assert_eq!(sim.state.lock().unwrap().reset_zones, vec![Some(ZONE_BLOCKS), None]);
+}
@@ -1,6 +1,6 @@
+use crate::drives::ata::AtaDevice;
 use crate::{DriveError, DriveResult};
 use std::process::Command;
-use std::str;
 
 #[derive(Debug, Clone)]
 pub struct HPAInfo {
@@ -86,23 +86,15 @@ impl HPADCOManager {
     pub fn remove_hpa_temporary(device_path: &str) -> DriveResult<()> {
         println!("Temporarily removing HPA on {}...", device_path);
 
-        let native_max = Self::get_native_max_address(device_path)?;
+        let mut device = AtaDevice::open(device_path)?;
+        let native_max_lba = device.read_native_max_address()?;
 
-        // Use hdparm to set max address to native max
-        let output = Command::new("hdparm")
-            .args(["--yes-i-know-what-i-am-doing", "-N", &format!("{}", native_max), device_path])
-            .output()
+        // Volatile SET MAX: the drive reverts to its HPA on the next power cycle
+        device.set_max_address(native_max_lba, true)
             .map_err(|e| DriveError::HardwareCommandFailed(
                 format!("Failed to remove HPA: {}", e)
             ))?;
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(DriveError::HardwareCommandFailed(
-                format!("Failed to remove HPA: {}", error)
-            ));
-        }
-
         println!("HPA temporarily removed. Full capacity now accessible.");
         Ok(())
     }
@@ -111,20 +103,18 @@ impl HPADCOManager {
     pub fn restore_hpa(device_path: &str, original_max_sectors: u64) -> DriveResult<()> {
         println!("Restoring HPA on {} to {} sectors...", device_path, original_max_sectors);
 
-        let output = Command::new("hdparm")
-            .args(["--yes-i-know-what-i-am-doing", "-N", &format!("{}", original_max_sectors), device_path])
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(
-                format!("Failed to restore HPA: {}", e)
-            ))?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
+        if original_max_sectors == 0 {
             return Err(DriveError::HardwareCommandFailed(
-                format!("Failed to restore HPA: {}", error)
+                "Failed to restore HPA: sector count must be non-zero".to_string()
             ));
         }
 
+        AtaDevice::open(device_path)?
+            .set_max_address(original_max_sectors - 1, true)
+            .map_err(|e| DriveError::HardwareCommandFailed(
+                format!("Failed to restore HPA: {}", e)
+            ))?;
+
         println!("HPA restored to original configuration");
         Ok(())
     }
@@ -134,198 +124,53 @@ impl HPADCOManager {
         println!("WARNING: Removing DCO is typically permanent!");
         println!("Attempting to remove DCO on {}...", device_path);
 
-        // DCO removal requires special ATA commands
-        // Using hdparm's DCO features if available
-        let output = Command::new("hdparm")
-            .args(["--dco-restore", device_path])
-            .output()
+        AtaDevice::open(device_path)?
+            .dco_restore()
             .map_err(|e| DriveError::HardwareCommandFailed(
                 format!("Failed to remove DCO: {}", e)
             ))?;
 
-        if !output.status.success() {
-            // Try alternative method with HDIO_DRIVE_CMD
-            return Self::remove_dco_via_ata_command(device_path);
-        }
-
         println!("DCO removed successfully");
         Ok(())
     }
 
-    /// Get the native max address (without HPA)
+    /// Get the native max address (without HPA), in sectors
     fn get_native_max_address(device_path: &str) -> DriveResult<u64> {
-        let output = Command::new("hdparm")
-            .args(["-N", device_path])
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(
-                format!("Failed to get native max address: {}", e)
-            ))?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-
-        // Parse output looking for native max
-        for line in output_str.lines() {
-            if line.contains("native") && line.contains("max") {
-                // Extract number from line like "max sectors = 1234567890/1234567890"
-                if let Some(pos) = line.rfind('/') {
-                    let native_str = &line[pos + 1..];
-                    if let Some(num_end) = native_str.find(|c: char| !c.is_numeric()) {
-                        let num_str = &native_str[..num_end];
-                        if let Ok(native_max) = num_str.parse::<u64>() {
-                            return Ok(native_max);
-                        }
-                    } else if let Ok(native_max) = native_str.trim().parse::<u64>() {
-                        return Ok(native_max);
-                    }
-                }
-            }
+        match AtaDevice::open(device_path).and_then(|mut device| device.read_native_max_address()) {
+            Ok(max_lba) => Ok(max_lba + 1),
+            // Not an ATA device (or no passthrough): no HPA can be seen
+            Err(_) => Self::get_size_via_blockdev(device_path),
         }
-
-        // Fallback: try to get via ATA IDENTIFY
-        Self::get_native_max_via_identify(device_path)
     }
 
-    /// Get current max address (with HPA if present)
+    /// Get current max address (with HPA if present), in sectors
     fn get_max_address(device_path: &str) -> DriveResult<u64> {
-        let output = Command::new("hdparm")
-            .args(["-N", device_path])
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(
-                format!("Failed to get max address: {}", e)
-            ))?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-
-        // Parse output looking for current max
-        for line in output_str.lines() {
-            if line.contains("max sectors") {
-                // Extract number from line like "max sectors = 1234567890/2345678901"
-                if let Some(equals_pos) = line.find('=') {
-                    let after_equals = &line[equals_pos + 1..].trim();
-                    if let Some(slash_pos) = after_equals.find('/') {
-                        let current_str = &after_equals[..slash_pos];
-                        if let Ok(current_max) = current_str.trim().parse::<u64>() {
-                            return Ok(current_max);
-                        }
-                    }
-                }
-            }
+        match AtaDevice::open(device_path).and_then(|mut device| device.identify()) {
+            Ok(identify) => Ok(identify.user_addressable_sectors()),
+            Err(_) => Self::get_size_via_blockdev(device_path),
         }
-
-        // Fallback to blockdev
-        Self::get_size_via_blockdev(device_path)
     }
 
-    /// Get DCO status
+    /// Get DCO status as (real max sectors, DCO-limited max sectors)
     fn get_dco_status(device_path: &str) -> DriveResult<Option<(u64, u64)>> {
-        // Try hdparm DCO identify
-        let output = Command::new("hdparm")
-            .args(["--dco-identify", device_path])
-            .output();
-
-        if let Ok(output) = output {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                return Self::parse_dco_output(&output_str);
-            }
-        }
-
-        // Fallback: check via ATA commands
-        Ok(None)
-    }
-
-    /// Parse DCO output from hdparm
-    fn parse_dco_output(output: &str) -> DriveResult<Option<(u64, u64)>> {
-        let mut real_max = None;
-        let mut dco_max = None;
-
-        for line in output.lines() {
-            if line.contains("Real max sectors") {
-                if let Some(num) = Self::extract_number_from_line(line) {
-                    real_max = Some(num);
-                }
-            } else if line.contains("DCO max sectors") {
-                if let Some(num) = Self::extract_number_from_line(line) {
-                    dco_max = Some(num);
-                }
-            }
-        }
-
-        if let (Some(real), Some(dco)) = (real_max, dco_max) {
-            Ok(Some((real, dco)))
-        } else {
-            Ok(None)
-        }
-    }
+        let Ok(mut device) = AtaDevice::open(device_path) else {
+            return Ok(None);
+        };
 
-    /// Extract number from a line of text
-    fn extract_number_from_line(line: &str) -> Option<u64> {
-        // Find all numeric sequences in the line
-        let parts: Vec<&str> = line.split(|c: char| !c.is_numeric())
-            .filter(|s| !s.is_empty())
-            .collect();
-
-        // Return the largest number (likely the sector count)
-        parts.iter()
-            .filter_map(|s| s.parse::<u64>().ok())
-            .max()
-    }
-
-    /// Remove DCO via direct ATA command
-    fn remove_dco_via_ata_command(device_path: &str) -> DriveResult<()> {
-        // This would require low-level ATA commands
-        // Feature 0xC6 (DCO RESTORE)
-
-        println!("Attempting DCO removal via ATA command...");
-
-        // Try using smartctl to send ATA command
-        let output = Command::new("smartctl")
-            .args(["-s", "dco,restore", device_path])
-            .output();
-
-        if let Ok(output) = output {
-            if output.status.success() {
-                println!("DCO removed via smartctl");
-                return Ok(());
-            }
+        match device.identify() {
+            Ok(identify) if identify.dco_supported() => {}
+            _ => return Ok(None),
         }
 
-        Err(DriveError::HardwareCommandFailed(
-            "DCO removal not supported or failed".to_string()
-        ))
-    }
-
-    /// Get native max via ATA IDENTIFY
-    fn get_native_max_via_identify(device_path: &str) -> DriveResult<u64> {
-        let output = Command::new("smartctl")
-            .args(["-i", device_path])
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(
-                format!("Failed to get drive info: {}", e)
-            ))?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-
-        // Look for LBA sectors
-        for line in output_str.lines() {
-            if line.contains("User Capacity") || line.contains("Total NVM Capacity") {
-                // Extract sector count from line like:
-                // "User Capacity: 512,110,190,592 bytes [512 GB]"
-                if let Some(bytes_str) = line.split("bytes").next() {
-                    if let Some(num_part) = bytes_str.split(':').nth(1) {
-                        let clean_num = num_part.chars()
-                            .filter(|c| c.is_numeric())
-                            .collect::<String>();
-                        if let Ok(bytes) = clean_num.parse::<u64>() {
-                            return Ok(bytes / 512); // Convert to sectors
-                        }
-                    }
-                }
-            }
-        }
+        // DCO IDENTIFY reports the factory maximum; READ NATIVE MAX reports
+        // the maximum as limited by the overlay
+        let real_max = match device.dco_identify() {
+            Ok(dco) if dco.max_lba() > 0 => dco.max_lba() + 1,
+            _ => return Ok(None),
+        };
+        let dco_max = device.read_native_max_address()? + 1;
 
-        // Last resort: use blockdev
-        Self::get_size_via_blockdev(device_path)
+        Ok(Some((real_max, dco_max)))
     }
 
     /// Get size via blockdev command
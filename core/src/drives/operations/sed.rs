@@ -1,3 +1,5 @@
+use crate::drives::ata::{self, AtaDevice, PasswordType};
+use crate::drives::nvme_admin::NvmeDevice;
 use crate::{DriveError, DriveResult};
 use crate::SEDType;
 use anyhow::{Result, anyhow};
@@ -66,15 +68,12 @@ impl SEDManager {
             }
         }
 
-        // Try nvme for NVMe OPAL
+        // NVMe drives expose TCG storage through Security Send/Receive
         if device_path.contains("nvme") {
-            let output = Command::new("nvme")
-                .args(["id-ctrl", device_path])
-                .output();
+            let controller = NvmeDevice::open(device_path).and_then(|mut device| device.identify_controller());
 
-            if let Ok(output) = output {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                if output_str.contains("OPAL") || output_str.contains("TCG") {
+            if let Ok(controller) = controller {
+                if controller.security_send_receive_supported() {
                     return Ok(SEDInfo {
                         sed_type: SEDType::OPAL20,
                         locked: false,
@@ -83,7 +82,8 @@ impl SEDManager {
                         max_password_tries: Some(5),
                         supports_crypto_erase: true,
                         supports_instant_secure_erase: true,
-                        firmware_version: Self::get_firmware_version(device_path),
+                        firmware_version: Some(controller.firmware()).filter(|f| !f.is_empty())
+                            .or_else(|| Self::get_firmware_version(device_path)),
                     });
                 }
             }
@@ -158,31 +158,21 @@ impl SEDManager {
 
     /// Detect ATA Security (non-OPAL)
     fn detect_ata_security(device_path: &str) -> Result<SEDInfo> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output();
+        let identify = AtaDevice::open(device_path)?.identify()?;
+        let security = identify.security();
 
-        if let Ok(output) = output {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            
-            if output_str.contains("Security:") {
-                let enabled = output_str.contains("enabled");
-                let locked = output_str.contains("locked");
-                let frozen = output_str.contains("frozen") && !output_str.contains("not frozen");
-                
-                if output_str.contains("supported") {
-                    return Ok(SEDInfo {
-                        sed_type: SEDType::ATASecurity,
-                        locked,
-                        enabled,
-                        frozen,
-                        max_password_tries: Some(5),
-                        supports_crypto_erase: false,
-                        supports_instant_secure_erase: enabled,
-                        firmware_version: Self::get_firmware_version(device_path),
-                    });
-                }
-            }
+        if security.supported {
+            return Ok(SEDInfo {
+                sed_type: SEDType::ATASecurity,
+                locked: security.locked,
+                enabled: security.enabled,
+                frozen: security.frozen,
+                max_password_tries: Some(5),
+                supports_crypto_erase: false,
+                supports_instant_secure_erase: security.enabled,
+                firmware_version: Some(identify.firmware()).filter(|f| !f.is_empty())
+                    .or_else(|| Self::get_firmware_version(device_path)),
+            });
         }
 
         Err(anyhow!("No ATA Security support detected"))
@@ -314,30 +304,14 @@ impl SEDManager {
     fn ata_secure_erase(device_path: &str) -> DriveResult<()> {
         // This is handled by existing secure erase code
         println!("Using ATA Secure Erase for crypto erase...");
-        
-        // Set temporary password and erase
-        let password = "temporary_erase_pwd";
-        
-        let output = Command::new("hdparm")
-            .args(["--user-master", "u", "--security-set-pass", password, device_path])
-            .output()
-            .map_err(|e| DriveError::CryptoEraseFailed(format!("Failed to set password: {}", e)))?;
-
-        if !output.status.success() {
-            return Err(DriveError::CryptoEraseFailed("Failed to set security password".to_string()));
-        }
 
-        let output = Command::new("hdparm")
-            .args(["--user-master", "u", "--security-erase", password, device_path])
-            .output()
-            .map_err(|e| DriveError::CryptoEraseFailed(format!("Secure erase failed: {}", e)))?;
+        let mut device = AtaDevice::open(device_path)?;
+        let enhanced = device.identify()?.security().enhanced_erase_supported;
+        ata::secure_erase(device, enhanced)
+            .map_err(|e| DriveError::CryptoEraseFailed(format!("ATA Secure Erase failed: {}", e)))?;
 
-        if output.status.success() {
-            println!("ATA Secure Erase completed");
-            Ok(())
-        } else {
-            Err(DriveError::CryptoEraseFailed("ATA Secure Erase failed".to_string()))
-        }
+        println!("ATA Secure Erase completed");
+        Ok(())
     }
 
     /// eDrive (BitLocker hardware encryption) crypto erase
@@ -464,17 +438,11 @@ impl SEDManager {
                 }
             }
             SEDType::ATASecurity => {
-                let output = Command::new("hdparm")
-                    .args(["--user-master", "u", "--security-unlock", password, device_path])
-                    .output()
-                    .map_err(|e| DriveError::UnlockFailed(format!("Failed to unlock: {}", e)))?;
+                AtaDevice::open(device_path)?
+                    .security_unlock(PasswordType::User, password)?;
 
-                if output.status.success() {
-                    println!("Drive unlocked successfully");
-                    Ok(())
-                } else {
-                    Err(DriveError::UnlockFailed("Invalid password or unlock failed".to_string()))
-                }
+                println!("Drive unlocked successfully");
+                Ok(())
             }
             _ => Err(DriveError::UnlockFailed("Unlock not supported for this SED type".to_string()))
         }
@@ -1,3 +1,4 @@
+use crate::drives::nvme_admin::{NvmeDevice, SmartLog};
 use crate::{DriveError, DriveResult};
 pub(crate) use crate::HealthStatus;
 use std::process::Command;
@@ -115,16 +116,10 @@ impl SMARTMonitor {
 
     /// Get NVMe drive SMART health - FIXED VERSION
     fn get_nvme_health(device_path: &str) -> DriveResult<SMARTHealth> {
-        // Try nvme-cli first
-        let output = Command::new("nvme")
-            .args(["smart-log", device_path])
-            .output();
-
-        if let Ok(output) = output {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                return Self::parse_nvme_smart(&output_str, device_path);
-            }
+        // Read the SMART / Health log page directly first
+        let log = NvmeDevice::open(device_path).and_then(|mut device| device.smart_log());
+        if let Ok(log) = log {
+            return Ok(Self::nvme_health_from_log(&log));
         }
 
         // Fall back to smartctl for NVMe
@@ -245,81 +240,28 @@ impl SMARTMonitor {
         Ok(health)
     }
 
-    /// Parse NVMe SMART output from nvme-cli - FIXED VERSION
-    fn parse_nvme_smart(output: &str, device_path: &str) -> DriveResult<SMARTHealth> {
+    /// Convert the NVMe SMART / Health log page
+    fn nvme_health_from_log(log: &SmartLog) -> SMARTHealth {
         let mut health = SMARTHealth {
             overall_health: HealthStatus::Unknown,
-            temperature_celsius: None,
-            power_on_hours: None,
-            power_cycle_count: None,
+            temperature_celsius: log.temperature_celsius(),
+            power_on_hours: Some(log.power_on_hours()),
+            power_cycle_count: Some(log.power_cycles()),
             reallocated_sectors: None,
             pending_sectors: None,
             uncorrectable_errors: None,
-            wear_level: None,
+            wear_level: Some(log.percentage_used()),
             bad_block_count: None,
             erase_fail_count: None,
             program_fail_count: None,
-            critical_warning: None,
-            available_spare: None,
-            media_errors: None,
+            critical_warning: Some(log.critical_warning()),
+            available_spare: Some(log.available_spare()),
+            media_errors: Some(log.media_errors()),
             attributes: HashMap::new(),
         };
 
-        for line in output.lines() {
-            let line_lower = line.to_lowercase();
-
-            if line_lower.contains("critical_warning") {
-                if let Some(value) = Self::extract_hex_value(&line_lower) {
-                    health.critical_warning = Some(value as u8);
-                }
-            } else if line_lower.contains("temperature") && !line_lower.contains("sensor") {
-                if let Some(value) = Self::extract_number(&line_lower) {
-                    // FIXED: Use robust temperature parsing
-                    health.temperature_celsius = Self::parse_temperature_robust(value, line);
-                }
-            } else if line_lower.contains("power_on_hours") || line_lower.contains("power on hours") {
-                if let Some(value) = Self::extract_number(&line_lower) {
-                    health.power_on_hours = Some(value);
-                }
-            } else if line_lower.contains("power_cycles") || line_lower.contains("power cycles") {
-                if let Some(value) = Self::extract_number(&line_lower) {
-                    health.power_cycle_count = Some(value);
-                }
-            } else if line_lower.contains("media_errors") || line_lower.contains("media and data integrity") {
-                if let Some(value) = Self::extract_number(&line_lower) {
-                    health.media_errors = Some(value);
-                }
-            } else if line_lower.contains("percentage_used") || line_lower.contains("percentage used") {
-                if let Some(value) = Self::extract_number(&line_lower) {
-                    health.wear_level = Some(value as u8);
-                }
-            } else if line_lower.contains("available_spare") && !line_lower.contains("threshold") {
-                if let Some(value) = Self::extract_percentage(&line_lower) {
-                    health.available_spare = Some(value as u8);
-                }
-            }
-        }
-
-        // Get additional health status
-        let health_output = Command::new("nvme")
-            .args(["id-ctrl", device_path])
-            .output();
-
-        if let Ok(output) = health_output {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            for line in output_str.lines() {
-                if line.contains("Critical Warning") {
-                    health.overall_health = if line.contains("0x00") {
-                        HealthStatus::Good
-                    } else {
-                        HealthStatus::Warning
-                    };
-                }
-            }
-        }
-
         health.overall_health = Self::determine_health_status(&health);
-        Ok(health)
+        health
     }
 
     /// Parse NVMe SMART from smartctl output
@@ -645,28 +587,6 @@ impl SMARTMonitor {
         0
     }
 
-    /// Extract number from a line
-    fn extract_number(line: &str) -> Option<u64> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        for part in parts.iter().rev() {
-            if let Ok(num) = part.replace(",", "").parse::<u64>() {
-                return Some(num);
-            }
-        }
-        None
-    }
-
-    /// Extract hex value
-    fn extract_hex_value(line: &str) -> Option<u64> {
-        if let Some(hex_start) = line.find("0x") {
-            let hex_str = &line[hex_start + 2..];
-            let end = hex_str.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(hex_str.len());
-            u64::from_str_radix(&hex_str[..end], 16).ok()
-        } else {
-            None
-        }
-    }
-
     /// Extract percentage value
     fn extract_percentage(line: &str) -> Option<u64> {
         if let Some(percent_pos) = line.find('%') {
@@ -740,22 +660,12 @@ impl SMARTMonitor {
             }
         }
 
-        // For NVMe, try different approach
+        // For NVMe, read the SMART / Health log page
         if device_path.contains("nvme") {
-            let output = Command::new("nvme")
-                .args(["smart-log", device_path])
-                .output();
-
-            if let Ok(output) = output {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                for line in output_str.lines() {
-                    if line.contains("data_units_written") {
-                        if let Some(units) = Self::extract_number(&line) {
-                            // NVMe reports in 512KB units
-                            return Ok(Some(units * 512 * 1024));
-                        }
-                    }
-                }
+            let log = NvmeDevice::open(device_path).and_then(|mut device| device.smart_log());
+            if let Ok(log) = log {
+                // NVMe reports in units of 1000 512-byte blocks
+                return Ok(Some(log.data_units_written() * 512 * 1000));
             }
         }
 
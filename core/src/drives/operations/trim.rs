@@ -1,3 +1,5 @@
+use crate::drives::ata::AtaDevice;
+use crate::drives::nvme_admin::NvmeDevice;
 use crate::{DriveError, DriveResult, DriveType};
 use std::process::Command;
 
@@ -37,7 +39,7 @@ impl TrimOperations {
         }
     }
 
-    /// TRIM an SSD using blkdiscard or hdparm
+    /// TRIM an SSD using blkdiscard or ATA DSM
     fn trim_ssd_device(device_path: &str) -> DriveResult<()> {
         // First try blkdiscard (most reliable)
         if Self::trim_via_blkdiscard(device_path).is_ok() {
@@ -45,9 +47,9 @@ impl TrimOperations {
             return Ok(());
         }
 
-        // Fallback to hdparm TRIM
-        if Self::trim_via_hdparm(device_path).is_ok() {
-            println!("TRIM completed successfully via hdparm");
+        // Fallback to native ATA TRIM
+        if Self::trim_via_ata(device_path).is_ok() {
+            println!("TRIM completed successfully via ATA passthrough");
             return Ok(());
         }
 
@@ -91,31 +93,13 @@ impl TrimOperations {
         Ok(())
     }
 
-    /// TRIM using hdparm
-    fn trim_via_hdparm(device_path: &str) -> DriveResult<()> {
-        println!("Attempting TRIM via hdparm...");
+    /// TRIM using ATA DATA SET MANAGEMENT
+    fn trim_via_ata(device_path: &str) -> DriveResult<()> {
+        println!("Attempting TRIM via ATA DATA SET MANAGEMENT...");
 
-        // Get device size
-        let size = Self::get_device_size(device_path)?;
-        let sectors = size / 512;
-
-        // Create TRIM command
-        // hdparm --trim-sector-ranges START:COUNT
-        let output = Command::new("hdparm")
-            .args(["--trim-sector-ranges", &format!("0:{}", sectors), device_path])
-            .output()
-            .map_err(|e| DriveError::TRIMFailed(
-                format!("hdparm TRIM failed: {}", e)
-            ))?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(DriveError::TRIMFailed(
-                format!("hdparm TRIM failed: {}", error)
-            ));
-        }
-
-        Ok(())
+        let mut device = AtaDevice::open(device_path)?;
+        let sectors = device.identify()?.user_addressable_sectors();
+        device.trim(&[(0, sectors)])
     }
 
     /// TRIM using direct ioctl calls
@@ -133,25 +117,14 @@ impl TrimOperations {
     fn nvme_deallocate(device_path: &str) -> DriveResult<()> {
         println!("Performing NVMe deallocate...");
 
-        // Get namespace ID
-        let nsid = Self::get_nvme_nsid(device_path)?;
+        let mut device = NvmeDevice::open(device_path)?;
+        let nsid = device.namespace_id();
+        let blocks = device.identify_namespace(nsid)?.size_blocks();
 
-        // Create deallocate command
-        let output = Command::new("nvme")
-            .args(["dsm", device_path, "-n", &nsid, "-d", "-a", "0", "-b", "0", "-s", "1"])
-            .output()
+        device.deallocate(nsid, &[(0, blocks)])
             .map_err(|e| DriveError::TRIMFailed(
                 format!("NVMe deallocate failed: {}", e)
-            ))?;
-
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(DriveError::TRIMFailed(
-                format!("NVMe deallocate failed: {}", error)
-            ));
-        }
-
-        Ok(())
+            ))
     }
 
     /// Verify TRIM effectiveness by checking for zeroes
@@ -232,19 +205,8 @@ impl TrimOperations {
 
     /// Check ATA TRIM support
     fn check_ata_trim_support(device_path: &str) -> DriveResult<bool> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(
-                format!("Failed to check TRIM support: {}", e)
-            ))?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-
-        // Look for TRIM support indicators
-        Ok(output_str.contains("Data Set Management TRIM supported") ||
-            output_str.contains("TRIM supported") ||
-            output_str.contains("Deterministic read data after TRIM"))
+        let identify = AtaDevice::open(device_path)?.identify()?;
+        Ok(identify.trim_supported())
     }
 
     /// Get device size in bytes
@@ -289,19 +251,6 @@ impl TrimOperations {
         }
     }
 
-    /// Get NVMe namespace ID
-    fn get_nvme_nsid(device_path: &str) -> DriveResult<String> {
-        // Extract namespace from path like /dev/nvme0n1
-        if let Some(n_pos) = device_path.rfind('n') {
-            let after_n = &device_path[n_pos + 1..];
-            if let Ok(nsid) = after_n.parse::<u32>() {
-                return Ok(nsid.to_string());
-            }
-        }
-
-        Ok("1".to_string()) // Default to namespace 1
-    }
-
     /// Perform secure TRIM with verification
     pub fn secure_trim_with_verify(device_path: &str) -> DriveResult<()> {
         println!("Performing secure TRIM with verification...");
@@ -0,0 +1,631 @@
+// Partition tables and wipe scopes
+//
+// A wipe can target less than the whole device: one partition (by number or
+// GPT unique GUID), an inclusive LBA range, or everything except the
+// partition table. GPT and MBR (including logical partitions in an extended
+// partition) are parsed straight from the device, so the result does not
+// depend on sfdisk/parted or on what the kernel last re-read.
+
+use anyhow::{Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::io::{Extent, IOConfig, IOHandle, OptimizedIO};
+
+const GPT_SIGNATURE: &[u8] = b"EFI PART";
+const GPT_MIN_HEADER_SIZE: usize = 92;
+const GPT_MIN_ENTRY_SIZE: usize = 128;
+const GPT_MAX_ENTRY_SIZE: usize = 4096;
+const GPT_MAX_ENTRIES: usize = 1024;
+
+const MBR_SIGNATURE: [u8; 2] = [0x55, 0xAA];
+const MBR_TABLE_OFFSET: usize = 446;
+const MBR_PROTECTIVE: u8 = 0xEE;
+const MBR_EXTENDED: [u8; 3] = [0x05, 0x0F, 0x85];
+
+/// Bound on the EBR chain, which a corrupt table could make circular
+const MAX_LOGICAL_PARTITIONS: u32 = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableKind {
+    Gpt,
+    Mbr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partition {
+    pub number: u32,          // As the kernel numbers it: GPT slot + 1; MBR 1-4 primary, 5+ logical
+    pub first_lba: u64,
+    pub last_lba: u64,        // Inclusive
+    pub guid: Option<Uuid>,   // GPT unique partition GUID
+    pub name: String,         // GPT partition name
+    pub extended: bool,       // MBR extended partition holding the logical ones
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionTable {
+    pub kind: TableKind,
+    pub sector_size: u64,
+    pub partitions: Vec<Partition>,
+    /// LBAs not occupied by the table itself: between the primary and backup
+    /// GPT, or everything after the MBR. The EBRs of an MBR extended
+    /// partition lie inside this range.
+    pub usable: (u64, u64),
+}
+
+impl PartitionTable {
+    /// Parse the GPT or MBR on `device_path`; None if it has neither
+    pub fn read(device_path: &str, size: u64) -> Result<Option<Self>> {
+        let mut handle = OptimizedIO::open(device_path, IOConfig::small_read_optimized())?;
+        Self::read_from(&mut handle, size)
+    }
+
+    pub(crate) fn read_from(handle: &mut IOHandle, size: u64) -> Result<Option<Self>> {
+        let sector_size = handle.sector_size();
+        let sectors = size / sector_size;
+        if sectors < 2 {
+            return Ok(None);
+        }
+
+        // A valid GPT wins; its backup header covers a damaged primary
+        for header_lba in [1, sectors - 1] {
+            if let Some(table) = Self::read_gpt(handle, sector_size, sectors, header_lba)? {
+                return Ok(Some(table));
+            }
+        }
+
+        let mbr = read_sectors(handle, sector_size, 0, 1)?;
+        if mbr[510..512] != MBR_SIGNATURE {
+            return Ok(None);
+        }
+        let primary = mbr_entries(&mbr);
+        if primary.iter().any(|entry| entry.kind == MBR_PROTECTIVE) {
+            bail!("Protective MBR found but neither GPT header is valid");
+        }
+
+        let mut partitions = Vec::new();
+        for (index, entry) in primary.iter().enumerate() {
+            if entry.sectors == 0 {
+                continue;
+            }
+            let extended = MBR_EXTENDED.contains(&entry.kind);
+            partitions.push(Partition {
+                number: index as u32 + 1,
+                first_lba: entry.start,
+                last_lba: entry.start + entry.sectors - 1,
+                guid: None,
+                name: String::new(),
+                extended,
+            });
+            if extended {
+                Self::read_logical(handle, sector_size, entry.start, &mut partitions)?;
+            }
+        }
+
+        Ok(Some(Self {
+            kind: TableKind::Mbr,
+            sector_size,
+            partitions,
+            usable: (1, sectors - 1),
+        }))
+    }
+
+    /// The partition `reference` names
+    pub fn find(&self, reference: &PartitionRef) -> Result<&Partition> {
+        let found = self.partitions.iter().find(|p| match reference {
+            PartitionRef::Number(number) => p.number == *number,
+            PartitionRef::Guid(guid) => p.guid == Some(*guid),
+        });
+        found.ok_or_else(|| anyhow!("No partition {} in the {:?} partition table", reference, self.kind))
+    }
+
+    fn read_gpt(handle: &mut IOHandle, sector_size: u64, sectors: u64, header_lba: u64) -> Result<Option<Self>> {
+        let header = read_sectors(handle, sector_size, header_lba, 1)?;
+        if &header[..8] != GPT_SIGNATURE {
+            return Ok(None);
+        }
+
+        let header_size = le32(&header, 12) as usize;
+        if !(GPT_MIN_HEADER_SIZE..=header.len()).contains(&header_size) {
+            return Ok(None);
+        }
+        let mut check = header[..header_size].to_vec();
+        check[16..20].fill(0);
+        if crc32(&check) != le32(&header, 16) || le64(&header, 24) != header_lba {
+            return Ok(None);
+        }
+
+        let first_usable = le64(&header, 40);
+        let last_usable = le64(&header, 48);
+        let entries_lba = le64(&header, 72);
+        let count = le32(&header, 80) as usize;
+        let entry_size = le32(&header, 84) as usize;
+        if first_usable > last_usable || last_usable >= sectors
+            || count > GPT_MAX_ENTRIES
+            || !(GPT_MIN_ENTRY_SIZE..=GPT_MAX_ENTRY_SIZE).contains(&entry_size) || !entry_size.is_power_of_two()
+        {
+            return Ok(None);
+        }
+
+        let array_bytes = (count * entry_size) as u64;
+        let array = read_sectors(handle, sector_size, entries_lba, array_bytes.div_ceil(sector_size))?;
+        let array = &array[..array_bytes as usize];
+        if crc32(array) != le32(&header, 88) {
+            return Ok(None);
+        }
+
+        let partitions = array.chunks(entry_size)
+            .enumerate()
+            .filter(|(_, entry)| entry[..16].iter().any(|&b| b != 0))
+            .map(|(slot, entry)| {
+                let mut guid = [0u8; 16];
+                guid.copy_from_slice(&entry[16..32]);
+                let name: Vec<u16> = entry[56..128].chunks(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                    .take_while(|&c| c != 0)
+                    .collect();
+                Partition {
+                    number: slot as u32 + 1,
+                    first_lba: le64(entry, 32),
+                    last_lba: le64(entry, 40),
+                    guid: Some(Uuid::from_bytes_le(guid)),
+                    name: String::from_utf16_lossy(&name),
+                    extended: false,
+                }
+            })
+            .collect();
+
+        Ok(Some(Self {
+            kind: TableKind::Gpt,
+            sector_size,
+            partitions,
+            usable: (first_usable, last_usable),
+        }))
+    }
+
+    /// Follow the EBR chain of the extended partition at `extended_start`
+    fn read_logical(
+        handle: &mut IOHandle,
+        sector_size: u64,
+        extended_start: u64,
+        partitions: &mut Vec<Partition>,
+    ) -> Result<()> {
+        let mut ebr_lba = extended_start;
+
+        for number in 5..5 + MAX_LOGICAL_PARTITIONS {
+            let ebr = read_sectors(handle, sector_size, ebr_lba, 1)?;
+            if ebr[510..512] != MBR_SIGNATURE {
+                bail!("Invalid extended boot record at LBA {}", ebr_lba);
+            }
+
+            // Entry 0 is relative to this EBR, entry 1 (the next EBR) to the
+            // start of the extended partition
+            let entries = mbr_entries(&ebr);
+            if entries[0].sectors > 0 {
+                let first_lba = ebr_lba + entries[0].start;
+                partitions.push(Partition {
+                    number,
+                    first_lba,
+                    last_lba: first_lba + entries[0].sectors - 1,
+                    guid: None,
+                    name: String::new(),
+                    extended: false,
+                });
+            }
+
+            if entries[1].sectors == 0 {
+                return Ok(());
+            }
+            ebr_lba = extended_start + entries[1].start;
+        }
+
+        bail!("Extended partition at LBA {} has more than {} logical partitions", extended_start, MAX_LOGICAL_PARTITIONS)
+    }
+}
+
+/// Part of a device a wipe is limited to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum WipeScope {
+    #[default]
+    WholeDevice,
+    Partition(PartitionRef),
+    LbaRange { first: u64, last: u64 },  // Inclusive
+    ExceptPartitionTable,                // Keep the MBR or both GPTs, wipe the rest
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PartitionRef {
+    Number(u32),
+    Guid(Uuid),
+}
+
+impl std::fmt::Display for PartitionRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PartitionRef::Number(number) => write!(f, "{}", number),
+            PartitionRef::Guid(guid) => write!(f, "{}", guid),
+        }
+    }
+}
+
+impl std::fmt::Display for WipeScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WipeScope::WholeDevice => write!(f, "the whole device"),
+            WipeScope::Partition(reference) => write!(f, "partition {}", reference),
+            WipeScope::LbaRange { first, last } => write!(f, "LBA {}-{}", first, last),
+            WipeScope::ExceptPartitionTable => write!(f, "everything except the partition table"),
+        }
+    }
+}
+
+/// A scope resolved against the device, as recorded in the certificate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedScope {
+    pub description: String,
+    pub first_lba: u64,
+    pub last_lba: u64,    // Inclusive
+    pub sector_size: u64,
+}
+
+impl ResolvedScope {
+    /// Byte range the overwrite is confined to
+    pub fn extent(&self) -> Extent {
+        Extent {
+            offset: self.first_lba * self.sector_size,
+            length: (self.last_lba - self.first_lba + 1) * self.sector_size,
+        }
+    }
+}
+
+impl WipeScope {
+    /// "whole", "partition:<number|guid>", "lba:<first>-<last>" or "except-table"
+    pub fn parse(s: &str) -> Result<Self> {
+        let lower = s.to_lowercase();
+        if let Some(reference) = lower.strip_prefix("partition:") {
+            let reference = match reference.parse::<u32>() {
+                Ok(number) if number > 0 => PartitionRef::Number(number),
+                _ => PartitionRef::Guid(Uuid::parse_str(reference)
+                    .map_err(|_| anyhow!("Invalid partition {} (expected a number or GUID)", reference))?),
+            };
+            return Ok(WipeScope::Partition(reference));
+        }
+        if let Some(range) = lower.strip_prefix("lba:") {
+            let (first, last) = range.split_once('-')
+                .ok_or_else(|| anyhow!("Invalid LBA range {} (expected <first>-<last>)", range))?;
+            let first: u64 = first.trim().parse().map_err(|_| anyhow!("Invalid first LBA: {}", first))?;
+            let last: u64 = last.trim().parse().map_err(|_| anyhow!("Invalid last LBA: {}", last))?;
+            if first > last {
+                bail!("LBA range {}-{} is empty", first, last);
+            }
+            return Ok(WipeScope::LbaRange { first, last });
+        }
+
+        match lower.as_str() {
+            "whole" | "device" => Ok(WipeScope::WholeDevice),
+            "except-table" | "all-except-table" => Ok(WipeScope::ExceptPartitionTable),
+            _ => Err(anyhow!("Unknown scope: {} (expected whole, partition:<n|guid>, lba:<first>-<last> or except-table)", s)),
+        }
+    }
+
+    pub fn is_whole_device(&self) -> bool {
+        *self == WipeScope::WholeDevice
+    }
+
+    /// Locate the scope on `device_path`, `size` bytes long
+    pub fn resolve(&self, device_path: &str, size: u64) -> Result<ResolvedScope> {
+        let mut handle = OptimizedIO::open(device_path, IOConfig::small_read_optimized())?;
+        self.resolve_with(&mut handle, size)
+    }
+
+    pub(crate) fn resolve_with(&self, handle: &mut IOHandle, size: u64) -> Result<ResolvedScope> {
+        let sector_size = handle.sector_size();
+        let sectors = size / sector_size;
+        if sectors == 0 {
+            bail!("Device is smaller than one {} byte sector", sector_size);
+        }
+        let resolved = |description: String, first_lba: u64, last_lba: u64| ResolvedScope {
+            description,
+            first_lba,
+            last_lba,
+            sector_size,
+        };
+
+        let mut table = || -> Result<PartitionTable> {
+            PartitionTable::read_from(handle, size)?
+                .ok_or_else(|| anyhow!("No GPT or MBR partition table found"))
+        };
+
+        let scope = match self {
+            WipeScope::WholeDevice => resolved("whole device".to_string(), 0, sectors - 1),
+            WipeScope::LbaRange { first, last } => {
+                if *last >= sectors {
+                    bail!("LBA range {}-{} extends past the last LBA {}", first, last, sectors - 1);
+                }
+                resolved(format!("LBA {}-{}", first, last), *first, *last)
+            }
+            WipeScope::Partition(reference) => {
+                let table = table()?;
+                let partition = table.find(reference)?;
+                if partition.extended {
+                    bail!("Partition {} is an extended partition; wipe its logical partitions instead", partition.number);
+                }
+                if partition.last_lba >= sectors || partition.first_lba > partition.last_lba {
+                    bail!("Partition {} (LBA {}-{}) does not fit on the device", partition.number, partition.first_lba, partition.last_lba);
+                }
+
+                let mut description = format!("partition {}", partition.number);
+                if !partition.name.is_empty() {
+                    description.push_str(&format!(" \"{}\"", partition.name));
+                }
+                if let Some(guid) = partition.guid {
+                    description.push_str(&format!(" {}", guid));
+                }
+                resolved(description, partition.first_lba, partition.last_lba)
+            }
+            WipeScope::ExceptPartitionTable => {
+                let table = table()?;
+                // The EBR chain is spread through the extended partition, so
+                // no single range keeps all of the table
+                if let Some(extended) = table.partitions.iter().find(|p| p.extended) {
+                    bail!("Extended partition {} holds boot records that except-table would overwrite; wipe the partitions individually",
+                          extended.number);
+                }
+                let kind = match table.kind {
+                    TableKind::Gpt => "GPT",
+                    TableKind::Mbr => "MBR",
+                };
+                resolved(format!("all except {}", kind), table.usable.0, table.usable.1)
+            }
+        };
+
+        Ok(scope)
+    }
+}
+
+struct MbrEntry {
+    kind: u8,
+    start: u64,
+    sectors: u64,
+}
+
+fn mbr_entries(sector: &[u8]) -> Vec<MbrEntry> {
+    (0..4)
+        .map(|i| {
+            let entry = &sector[MBR_TABLE_OFFSET + 16 * i..MBR_TABLE_OFFSET + 16 * (i + 1)];
+            MbrEntry {
+                kind: entry[4],
+                start: le32(entry, 8) as u64,
+                sectors: le32(entry, 12) as u64,
+            }
+        })
+        .collect()
+}
+
+fn read_sectors(handle: &mut IOHandle, sector_size: u64, lba: u64, count: u64) -> Result<Vec<u8>> {
+    let len = (count * sector_size) as usize;
+    let data = OptimizedIO::read_range(handle, lba * sector_size, len)?;
+    if data.len() != len {
+        bail!("Short read at LBA {}: {} of {} bytes", lba, data.len(), len);
+    }
+    Ok(data)
+}
+
+fn le32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(data[at..at + 4].try_into().unwrap())
+}
+
+fn le64(data: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(data[at..at + 8].try_into().unwrap())
+}
+
+/// CRC-32 (IEEE, reflected) as used by the GPT header and entry array
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    const SECTOR: u64 = 512;
+    const SECTORS: u64 = 4096;  // 2 MiB image
+
+    fn image() -> Vec<u8> {
+        vec![0u8; (SECTORS * SECTOR) as usize]
+    }
+
+    fn put32(data: &mut [u8], at: usize, value: u32) {
+        data[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put64(data: &mut [u8], at: usize, value: u64) {
+        data[at..at + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn mbr_entry(sector: &mut [u8], index: usize, kind: u8, start: u32, sectors: u32) {
+        let at = MBR_TABLE_OFFSET + 16 * index;
+        sector[at + 4] = kind;
+        put32(sector, at + 8, start);
+        put32(sector, at + 12, sectors);
+        sector[510..512].copy_from_slice(&MBR_SIGNATURE);
+    }
+
+    /// GPT with two partitions; entries at LBA 2-33 and a backup at the end
+    fn gpt_image(guid: Uuid) -> Vec<u8> {
+        let mut data = image();
+        mbr_entry(&mut data[..512], 0, MBR_PROTECTIVE, 1, (SECTORS - 1) as u32);
+
+        let mut entries = vec![0u8; 128 * 128];
+        for (slot, (first, last, name)) in [(2048u64, 3071u64, "EFI"), (3072, 4062, "data")].iter().enumerate() {
+            let entry = &mut entries[slot * 128..(slot + 1) * 128];
+            entry[..16].copy_from_slice(&[0xAB; 16]);
+            let unique = if slot == 1 { guid } else { Uuid::from_bytes([0x11; 16]) };
+            entry[16..32].copy_from_slice(&unique.to_bytes_le());
+            put64(entry, 32, *first);
+            put64(entry, 40, *last);
+            for (i, c) in name.encode_utf16().enumerate() {
+                entry[56 + 2 * i..58 + 2 * i].copy_from_slice(&c.to_le_bytes());
+            }
+        }
+
+        for (header_lba, entries_lba) in [(1u64, 2u64), (SECTORS - 1, SECTORS - 33)] {
+            let mut header = vec![0u8; 512];
+            header[..8].copy_from_slice(GPT_SIGNATURE);
+            put32(&mut header, 12, 92);
+            put64(&mut header, 24, header_lba);
+            put64(&mut header, 40, 34);
+            put64(&mut header, 48, SECTORS - 34);
+            put64(&mut header, 72, entries_lba);
+            put32(&mut header, 80, 128);
+            put32(&mut header, 84, 128);
+            put32(&mut header, 88, crc32(&entries));
+            let crc = crc32(&header[..92]);
+            put32(&mut header, 16, crc);
+
+            let at = (header_lba * SECTOR) as usize;
+            data[at..at + 512].copy_from_slice(&header);
+            let at = (entries_lba * SECTOR) as usize;
+            data[at..at + entries.len()].copy_from_slice(&entries);
+        }
+        data
+    }
+
+    fn handle_for(data: &[u8]) -> (NamedTempFile, IOHandle) {
+        let temp = NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), data).unwrap();
+        let handle = OptimizedIO::open(temp.path().to_str().unwrap(), IOConfig::small_read_optimized()).unwrap();
+        (temp, handle)
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_parse_gpt() {
+        let guid = Uuid::parse_str("0fc63daf-8483-4772-8e79-3d69d8477de4").unwrap();
+        let (_temp, mut handle) = handle_for(&gpt_image(guid));
+        let table = PartitionTable::read_from(&mut handle, SECTORS * SECTOR).unwrap().unwrap();
+
+        assert_eq!(table.kind, TableKind::Gpt);
+        assert_eq!(table.usable, (34, SECTORS - 34));
+        assert_eq!(table.partitions.len(), 2);
+        assert_eq!(table.partitions[0].name, "EFI");
+        assert_eq!((table.partitions[1].first_lba, table.partitions[1].last_lba), (3072, 4062));
+        assert_eq!(table.find(&PartitionRef::Guid(guid)).unwrap().number, 2);
+        assert!(table.find(&PartitionRef::Number(3)).is_err());
+    }
+
+    #[test]
+    fn test_damaged_primary_gpt_falls_back_to_backup() {
+        let guid = Uuid::new_v4();
+        let mut data = gpt_image(guid);
+        data[512 + 40] ^= 0xFF;  // Primary header CRC no longer matches
+        let (_temp, mut handle) = handle_for(&data);
+
+        let table = PartitionTable::read_from(&mut handle, SECTORS * SECTOR).unwrap().unwrap();
+        assert_eq!(table.find(&PartitionRef::Guid(guid)).unwrap().first_lba, 3072);
+
+        // With both headers gone the protective MBR is an error, not "no table"
+        let last = ((SECTORS - 1) * SECTOR) as usize;
+        data[last..last + 8].fill(0);
+        let (_temp, mut handle) = handle_for(&data);
+        assert!(PartitionTable::read_from(&mut handle, SECTORS * SECTOR).is_err());
+    }
+
+    #[test]
+    fn test_oversized_gpt_entries_rejected() {
+        // Same 16 KiB entry array, described as two 8 KiB entries
+        let mut data = gpt_image(Uuid::new_v4());
+        for header_lba in [1, SECTORS - 1] {
+            let at = (header_lba * SECTOR) as usize;
+            let header = &mut data[at..at + 512];
+            put32(header, 80, 2);
+            put32(header, 84, 8192);
+            header[16..20].fill(0);
+            let crc = crc32(&header[..92]);
+            put32(header, 16, crc);
+        }
+
+        let (_temp, mut handle) = handle_for(&data);
+        assert!(PartitionTable::read_from(&mut handle, SECTORS * SECTOR).is_err());
+    }
+
+    #[test]
+    fn test_parse_mbr_with_logical_partitions() {
+        let mut data = image();
+        mbr_entry(&mut data[..512], 0, 0x83, 2048, 1024);
+        mbr_entry(&mut data[..512], 1, 0x05, 3072, 1024);
+        // Two EBRs: logical 5 at 3072+16, logical 6 at 3584+16
+        let ebr1 = (3072 * SECTOR) as usize;
+        mbr_entry(&mut data[ebr1..ebr1 + 512], 0, 0x83, 16, 400);
+        mbr_entry(&mut data[ebr1..ebr1 + 512], 1, 0x05, 512, 512);
+        let ebr2 = (3584 * SECTOR) as usize;
+        mbr_entry(&mut data[ebr2..ebr2 + 512], 0, 0x83, 16, 200);
+
+        let (_temp, mut handle) = handle_for(&data);
+        let table = PartitionTable::read_from(&mut handle, SECTORS * SECTOR).unwrap().unwrap();
+        assert_eq!(table.kind, TableKind::Mbr);
+        let numbers: Vec<u32> = table.partitions.iter().map(|p| p.number).collect();
+        assert_eq!(numbers, vec![1, 2, 5, 6]);
+        let logical = table.find(&PartitionRef::Number(6)).unwrap();
+        assert_eq!((logical.first_lba, logical.last_lba), (3600, 3799));
+
+        // The extended container itself is not a wipe target, and its EBRs
+        // cannot be kept by a single except-table range
+        assert!(WipeScope::Partition(PartitionRef::Number(2)).resolve_with(&mut handle, SECTORS * SECTOR).is_err());
+        assert!(WipeScope::ExceptPartitionTable.resolve_with(&mut handle, SECTORS * SECTOR).is_err());
+
+        // Without the extended partition only the MBR itself is kept
+        data[..512].fill(0);
+        mbr_entry(&mut data[..512], 0, 0x83, 2048, 1024);
+        let (_temp, mut handle) = handle_for(&data);
+        let except = WipeScope::ExceptPartitionTable.resolve_with(&mut handle, SECTORS * SECTOR).unwrap();
+        assert_eq!((except.first_lba, except.last_lba), (1, SECTORS - 1));
+    }
+
+    #[test]
+    fn test_no_table() {
+        let (_temp, mut handle) = handle_for(&image());
+        assert!(PartitionTable::read_from(&mut handle, SECTORS * SECTOR).unwrap().is_none());
+        assert!(WipeScope::ExceptPartitionTable.resolve_with(&mut handle, SECTORS * SECTOR).is_err());
+    }
+
+    #[test]
+    fn test_resolve_scopes() {
+        let guid = Uuid::parse_str("0fc63daf-8483-4772-8e79-3d69d8477de4").unwrap();
+        let (_temp, mut handle) = handle_for(&gpt_image(guid));
+        let size = SECTORS * SECTOR;
+        let resolve = |scope: &str, handle: &mut IOHandle| {
+            WipeScope::parse(scope).and_then(|scope| scope.resolve_with(handle, size))
+        };
+
+        let whole = resolve("whole", &mut handle).unwrap();
+        assert_eq!(whole.extent(), Extent { offset: 0, length: size });
+
+        let partition = resolve("partition:2", &mut handle).unwrap();
+        assert_eq!((partition.first_lba, partition.last_lba), (3072, 4062));
+        assert_eq!(partition.description, format!("partition 2 \"data\" {}", guid));
+        assert_eq!(resolve(&format!("partition:{}", guid), &mut handle).unwrap(), partition);
+
+        let except = resolve("except-table", &mut handle).unwrap();
+        assert_eq!(except.extent(), Extent { offset: 34 * SECTOR, length: (SECTORS - 67) * SECTOR });
+        assert_eq!(except.description, "all except GPT");
+
+        assert_eq!(resolve("lba:100-199", &mut handle).unwrap().extent(), Extent { offset: 100 * SECTOR, length: 100 * SECTOR });
+        assert!(resolve("lba:100-4096", &mut handle).is_err());
+        assert!(resolve("lba:9-3", &mut handle).is_err());
+        assert!(resolve("partition:0", &mut handle).is_err());
+        assert!(resolve("partition:9", &mut handle).is_err());
+        assert!(resolve("everything", &mut handle).is_err());
+    }
+}
@@ -1,8 +1,5 @@
 use anyhow::Result;
-use std::process::Command;
-use std::time::Duration;
-use std::thread;
-use crate::ui::progress::ProgressBar;
+use crate::drives::ata::{self, AtaDevice};
 
 pub struct HDDWipe;
 
@@ -19,45 +16,18 @@ impl HDDWipe {
     }
 
     fn supports_secure_erase(device_path: &str) -> Result<bool> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        Ok(output_str.contains("supported: enhanced erase"))
+        let identify = AtaDevice::open(device_path)?.identify()?;
+        Ok(identify.security().supported)
     }
 
     fn hardware_secure_erase(device_path: &str) -> Result<()> {
         println!("Running ATA secure erase...");
 
-        // set password (blocking)
-        let mut bar = ProgressBar::new(48);
-        let mut set_cmd = Command::new("hdparm");
-        set_cmd.args(["--user-master", "u", "--security-set-pass", "temp123", device_path]);
-        let _ = set_cmd.spawn()?.wait()?;
-
-        let mut cmd = Command::new("hdparm");
-        cmd.args(["--user-master", "u", "--security-erase", "temp123", device_path]);
-        let mut process = cmd.spawn()?;
+        let mut device = AtaDevice::open(device_path)?;
+        let enhanced = device.identify()?.security().enhanced_erase_supported;
+        ata::secure_erase(device, enhanced)?;
 
-        // animate until process ends
-        loop {
-            match process.try_wait()? {
-                Some(status) => {
-                    bar.render(100.0, None, None);
-                    if status.success() {
-                        println!("\nHardware secure erase completed successfully");
-                        return Ok(());
-                    } else {
-                        return Err(anyhow::anyhow!("Hardware secure erase failed"));
-                    }
-                }
-                None => {
-                    // show mid-progress with no byte info (animated)
-                    bar.render(50.0, None, None);
-                    thread::sleep(Duration::from_millis(200));
-                }
-            }
-        }
+        println!("\nHardware secure erase completed successfully");
+        Ok(())
     }
 }
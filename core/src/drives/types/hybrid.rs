@@ -3,6 +3,7 @@
 // Hybrid drives combine HDD (magnetic) and SSD (flash) cache
 // Both portions must be wiped separately to ensure complete data destruction
 
+use crate::drives::ata::AtaDevice;
 use anyhow::{Result, anyhow};
 use std::process::Command;
 use serde::{Serialize, Deserialize};
@@ -157,18 +158,9 @@ impl HybridDrive {
     pub fn flush_cache(&self) -> Result<()> {
         println!("Flushing SSD cache to HDD...");
 
-        // Send SYNCHRONIZE CACHE command
-        let output = Command::new("hdparm")
-            .arg("-F")  // Flush cache
-            .arg(&self.device_path)
-            .output()?;
-
-        if !output.status.success() {
-            return Err(anyhow!("Cache flush failed"));
-        }
-
-        // Wait for flush to complete
-        std::thread::sleep(std::time::Duration::from_secs(5));
+        // FLUSH CACHE EXT returns once the cache is on the medium
+        AtaDevice::open(&self.device_path)?.flush_cache()
+            .map_err(|e| anyhow!("Cache flush failed: {}", e))?;
 
         println!("Cache flushed successfully");
         Ok(())
@@ -195,9 +187,9 @@ impl HybridDrive {
             _ => {}
         }
 
-        // Fall back to generic hdparm
+        // Fall back to the generic ATA write cache feature
         if self.try_generic_cache_disable().is_ok() {
-            println!("✅ Cache disabled via hdparm");
+            println!("✅ Write cache disabled");
             return Ok(());
         }
 
@@ -215,7 +207,7 @@ impl HybridDrive {
             .output()?;
 
         if output.status.success() {
-            // Also try hdparm
+            // Also disable the ATA write cache
             self.try_generic_cache_disable()?;
             Ok(())
         } else {
@@ -226,40 +218,24 @@ impl HybridDrive {
     /// Try WD-specific cache disable
     fn try_wd_cache_disable(&self) -> Result<()> {
         // WD uses vendor-specific ATA commands
-        // First try generic hdparm
+        // First disable the generic ATA write cache
         self.try_generic_cache_disable()?;
 
-        // Then issue WD-specific flush
-        let _ = Command::new("hdparm")
-            .arg("-F")  // Flush
-            .arg(&self.device_path)
-            .output();
+        // Then flush what it holds
+        let _ = AtaDevice::open(&self.device_path).and_then(|mut device| device.flush_cache());
 
         Ok(())
     }
 
-    /// Try generic cache disable via hdparm
+    /// Try generic cache disable via SET FEATURES
     fn try_generic_cache_disable(&self) -> Result<()> {
-        let output = Command::new("hdparm")
-            .arg("-W")
-            .arg("0")  // Disable write caching
-            .arg(&self.device_path)
-            .output()?;
-
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(anyhow!("hdparm cache disable failed"))
-        }
+        AtaDevice::open(&self.device_path)?.set_write_cache(false)
+            .map_err(|e| anyhow!("Write cache disable failed: {}", e))
     }
 
     /// Re-enable cache after wipe
     pub fn enable_cache(&self) -> Result<()> {
-        let _ = Command::new("hdparm")
-            .arg("-W")
-            .arg("1")  // Enable write caching
-            .arg(&self.device_path)
-            .output();
+        let _ = AtaDevice::open(&self.device_path).and_then(|mut device| device.set_write_cache(true));
 
         Ok(())
     }
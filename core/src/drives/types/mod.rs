@@ -17,7 +17,7 @@ pub mod raid;      // RAID array handling
 // Re-exports for convenience
 pub use hdd::HDDWipe;
 pub use ssd::SSDWipe;
-pub use nvme::{NVMeWipe, NVMeAdvanced, NVMeNamespace, NamespaceType, ZNSZone, ZNSZoneState};
+pub use nvme::{NVMeWipe, NvmeSanitizeRecord, SanitizeSession, NVMeAdvanced, NVMeNamespace, NamespaceType, ZNSZone, ZNSZoneState};
 pub use smr::{SMRDrive, Zone, ZoneType, ZoneCondition, ZoneModel};
 pub use optane::{OptaneDrive, OptaneMode, OptaneNamespace};
 pub use hybrid::{HybridDrive, HDDInfo, SSDCacheInfo, PinnedRegion};
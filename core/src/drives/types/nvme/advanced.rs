@@ -3,7 +3,7 @@
 // This module extends basic NVMe support with modern advanced features
 
 use anyhow::{Result, anyhow};
-use std::process::Command;
+use crate::drives::nvme_admin::{IdentifyController, NvmeDevice, SecureEraseSetting, ZoneDescriptor};
 use serde::{Serialize, Deserialize};
 
 /// NVMe namespace type
@@ -128,19 +128,18 @@ pub struct NVMeAdvanced {
 impl NVMeAdvanced {
     /// Detect if device has advanced NVMe features
     pub fn detect_advanced_features(device_path: &str) -> Result<bool> {
-        let info = Self::get_controller_info(device_path)?;
+        let mut device = NvmeDevice::open(device_path)?;
+        let controller = device.identify_controller()?;
 
         // Check for ZNS, KV, or computational storage indicators
-        if info.contains("ZNS") ||
-           info.contains("Zoned") ||
-           info.contains("Key-Value") ||
-           info.contains("KV") ||
-           info.contains("Computational") {
+        if device.zns_supported() ||
+           device.kv_supported() ||
+           Self::detect_computational_storage(&controller) {
             return Ok(true);
         }
 
         // Check number of namespaces
-        let ns_count = Self::count_namespaces(device_path)?;
+        let ns_count = Self::count_namespaces(&mut device);
         if ns_count > 1 {
             return Ok(true);
         }
@@ -148,51 +147,27 @@ impl NVMeAdvanced {
         Ok(false)
     }
 
-    /// Get controller information
-    fn get_controller_info(device_path: &str) -> Result<String> {
-        let output = Command::new("nvme")
-            .arg("id-ctrl")
-            .arg(device_path)
-            .output()?;
-
-        if !output.status.success() {
-            return Err(anyhow!("Failed to get controller info"));
-        }
-
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
-    }
-
     /// Count namespaces on controller
-    fn count_namespaces(device_path: &str) -> Result<usize> {
-        let output = Command::new("nvme")
-            .arg("list-ns")
-            .arg(device_path)
-            .output()?;
-
-        if !output.status.success() {
-            return Ok(1);  // Assume single namespace
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let count = stdout.lines()
-            .filter(|line| line.contains("[") && line.contains("]"))
-            .count();
-
-        Ok(count.max(1))
+    fn count_namespaces(device: &mut NvmeDevice) -> usize {
+        device.active_namespaces()
+            .map(|namespaces| namespaces.len())
+            .unwrap_or(1)  // Assume single namespace
+            .max(1)
     }
 
     /// Get full advanced configuration
     pub fn get_configuration(device_path: &str) -> Result<NVMeAdvanced> {
         let controller_path = Self::extract_controller_path(device_path);
-        let info = Self::get_controller_info(&controller_path)?;
+        let mut device = NvmeDevice::open(&controller_path)?;
+        let controller = device.identify_controller()?;
 
-        let zns_support = Self::detect_zns_support(&controller_path)?;
-        let kv_support = Self::detect_kv_support(&info);
-        let is_computational_storage = Self::detect_computational_storage(&info);
-        let model = Self::parse_model(&info);
-        let firmware = Self::parse_firmware(&info);
+        let zns_support = device.zns_supported();
+        let kv_support = device.kv_supported();
+        let is_computational_storage = Self::detect_computational_storage(&controller);
+        let model = Self::or_unknown(controller.model());
+        let firmware = Self::or_unknown(controller.firmware());
 
-        let namespaces = Self::enumerate_namespaces(&controller_path)?;
+        let namespaces = Self::enumerate_namespaces(&mut device, &controller_path)?;
 
         Ok(NVMeAdvanced {
             device_path: device_path.to_string(),
@@ -216,90 +191,29 @@ impl NVMeAdvanced {
         }
     }
 
-    /// Detect ZNS support
-    fn detect_zns_support(controller_path: &str) -> Result<bool> {
-        // Check via nvme zns id-ctrl
-        let output = Command::new("nvme")
-            .arg("zns")
-            .arg("id-ctrl")
-            .arg(controller_path)
-            .output();
-
-        if let Ok(output) = output {
-            if output.status.success() {
-                return Ok(true);
-            }
-        }
-
-        // Fallback: check controller capabilities
-        let info = Self::get_controller_info(controller_path)?;
-        Ok(info.contains("Zoned Namespace") || info.contains("ZNS"))
-    }
-
-    /// Detect Key-Value support
-    fn detect_kv_support(info: &str) -> bool {
-        info.contains("Key-Value") || info.contains("KV Command Set")
-    }
-
     /// Detect computational storage
-    fn detect_computational_storage(info: &str) -> bool {
-        info.contains("Computational") ||
-        info.contains("In-Storage Compute") ||
-        info.contains("SmartNIC")
+    fn detect_computational_storage(controller: &IdentifyController) -> bool {
+        let model = controller.model();
+        model.contains("Computational") ||
+        model.contains("In-Storage Compute") ||
+        model.contains("SmartNIC")
     }
 
-    /// Parse model name
-    fn parse_model(info: &str) -> String {
-        for line in info.lines() {
-            if line.contains("mn") && line.contains(":") {
-                if let Some(model) = line.split(':').nth(1) {
-                    return model.trim().to_string();
-                }
-            }
-        }
-        "Unknown".to_string()
-    }
-
-    /// Parse firmware version
-    fn parse_firmware(info: &str) -> String {
-        for line in info.lines() {
-            if line.contains("fr") && line.contains(":") {
-                if let Some(fw) = line.split(':').nth(1) {
-                    return fw.trim().to_string();
-                }
-            }
-        }
-        "Unknown".to_string()
+    fn or_unknown(value: String) -> String {
+        if value.is_empty() { "Unknown".to_string() } else { value }
     }
 
     /// Enumerate all namespaces
-    fn enumerate_namespaces(controller_path: &str) -> Result<Vec<NVMeNamespace>> {
-        let output = Command::new("nvme")
-            .arg("list-ns")
-            .arg(controller_path)
-            .arg("-a")  // All namespaces
-            .output()?;
-
-        if !output.status.success() {
+    fn enumerate_namespaces(device: &mut NvmeDevice, controller_path: &str) -> Result<Vec<NVMeNamespace>> {
+        let Ok(nsids) = device.active_namespaces() else {
             // Fallback: assume single namespace with ID 1
             return Ok(vec![Self::create_default_namespace(controller_path, 1)?]);
-        }
+        };
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
         let mut namespaces = Vec::new();
-
-        for line in stdout.lines() {
-            if line.starts_with('[') {
-                // Parse line like "[ 0]:0x1"
-                if let Some(nsid_str) = line.split(']').nth(1) {
-                    if let Ok(nsid) = nsid_str.trim().trim_start_matches("0x").parse::<u32>() {
-                        if nsid > 0 {
-                            if let Ok(ns) = Self::get_namespace_details(controller_path, nsid) {
-                                namespaces.push(ns);
-                            }
-                        }
-                    }
-                }
+        for nsid in nsids {
+            if let Ok(ns) = Self::get_namespace_details(device, controller_path, nsid) {
+                namespaces.push(ns);
             }
         }
 
@@ -311,37 +225,31 @@ impl NVMeAdvanced {
         Ok(namespaces)
     }
 
-    /// Get details for specific namespace
-    fn get_namespace_details(controller_path: &str, nsid: u32) -> Result<NVMeNamespace> {
+    /// Get details for a specific namespace
+    fn get_namespace_details(device: &mut NvmeDevice, controller_path: &str, nsid: u32) -> Result<NVMeNamespace> {
         let device_path = format!("{}n{}", controller_path, nsid);
 
-        let output = Command::new("nvme")
-            .arg("id-ns")
-            .arg(&device_path)
-            .output()?;
-
-        if !output.status.success() {
+        let Ok(identify) = device.identify_namespace(nsid) else {
             return Self::create_default_namespace(controller_path, nsid);
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-
-        // Parse size
-        let size = Self::parse_namespace_size(&stdout);
+        };
 
         // Detect namespace type
-        let namespace_type = Self::detect_namespace_type(&device_path)?;
+        let namespace_type = if device.is_zoned_namespace(nsid) {
+            NamespaceType::ZonedNamespace
+        } else {
+            NamespaceType::Block
+        };
 
         // Get ZNS zones if applicable
         let zones = if namespace_type == NamespaceType::ZonedNamespace {
-            Some(Self::get_zns_zones(&device_path)?)
+            Some(Self::get_zns_zones(device, nsid)?)
         } else {
             None
         };
 
         Ok(NVMeNamespace {
             nsid,
-            size,
+            size: identify.size_bytes(),
             namespace_type,
             device_path,
             is_active: true,
@@ -363,82 +271,32 @@ impl NVMeAdvanced {
         })
     }
 
-    /// Parse namespace size from id-ns output
-    fn parse_namespace_size(output: &str) -> u64 {
-        for line in output.lines() {
-            if line.contains("nsze") && line.contains(":") {
-                if let Some(size_str) = line.split(':').nth(1) {
-                    if let Ok(blocks) = size_str.trim().parse::<u64>() {
-                        return blocks * 512;  // Assume 512-byte blocks
-                    }
-                }
-            }
-        }
-        0
-    }
-
-    /// Detect namespace type
-    fn detect_namespace_type(device_path: &str) -> Result<NamespaceType> {
-        // Check for ZNS
-        let output = Command::new("nvme")
-            .arg("zns")
-            .arg("id-ns")
-            .arg(device_path)
-            .output();
-
-        if let Ok(output) = output {
-            if output.status.success() {
-                return Ok(NamespaceType::ZonedNamespace);
-            }
-        }
-
-        // Check for KV (would need vendor-specific command)
-        // For now, default to Block
-
-        Ok(NamespaceType::Block)
-    }
-
     /// Get ZNS zones for a namespace
-    fn get_zns_zones(device_path: &str) -> Result<Vec<ZNSZone>> {
-        let output = Command::new("nvme")
-            .arg("zns")
-            .arg("report-zones")
-            .arg(device_path)
-            .output()?;
-
-        if !output.status.success() {
-            return Ok(Vec::new());
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        Self::parse_zns_zones(&stdout)
+    fn get_zns_zones(device: &mut NvmeDevice, nsid: u32) -> Result<Vec<ZNSZone>> {
+        let zones = device.report_zones(nsid).unwrap_or_default();
+        Ok(zones.iter().enumerate().map(|(zone_id, zone)| Self::zone_from_descriptor(zone_id as u32, zone)).collect())
     }
 
-    /// Parse ZNS zone report output
-    fn parse_zns_zones(output: &str) -> Result<Vec<ZNSZone>> {
-        let mut zones = Vec::new();
-        let mut zone_id = 0u32;
-
-        for line in output.lines() {
-            if line.contains("SLBA:") {
-                // Parse zone info
-                // Example: "SLBA: 0x0 WP: 0x0 Cap: 0x10000 State: EMPTY Type: SEQWRITE_REQ"
-
-                let zone = ZNSZone {
-                    zone_id,
-                    zone_start_lba: 0,  // Would parse from SLBA
-                    zone_capacity: 0,    // Would parse from Cap
-                    write_pointer: 0,    // Would parse from WP
-                    zone_state: ZNSZoneState::Empty,  // Would parse from State
-                    is_sequential: line.contains("SEQWRITE"),
-                };
-
-                zones.push(zone);
-                zone_id += 1;
-            }
-        }
+    /// Convert a Report Zones descriptor
+    fn zone_from_descriptor(zone_id: u32, zone: &ZoneDescriptor) -> ZNSZone {
+        let zone_state = match zone.state {
+            0x2 => ZNSZoneState::ImplicitlyOpen,
+            0x3 => ZNSZoneState::ExplicitlyOpen,
+            0x4 => ZNSZoneState::Closed,
+            0xD => ZNSZoneState::ReadOnly,
+            0xE => ZNSZoneState::Full,
+            0xF => ZNSZoneState::Offline,
+            _ => ZNSZoneState::Empty,
+        };
 
-        Ok(zones)
+        ZNSZone {
+            zone_id,
+            zone_start_lba: zone.start_lba,
+            zone_capacity: zone.capacity,
+            write_pointer: zone.write_pointer,
+            zone_state,
+            is_sequential: zone.zone_type == ZoneDescriptor::TYPE_SEQUENTIAL_WRITE_REQUIRED,
+        }
     }
 
     /// Reset ZNS zone
@@ -447,20 +305,15 @@ impl NVMeAdvanced {
             return Err(anyhow!("Not a ZNS namespace"));
         }
 
+        let zone = ns.zones.as_ref()
+            .and_then(|zones| zones.iter().find(|z| z.zone_id == zone_id))
+            .ok_or_else(|| anyhow!("Unknown ZNS zone {}", zone_id))?;
+
         println!("Resetting ZNS zone {} on {}", zone_id, ns.device_path);
 
-        let output = Command::new("nvme")
-            .arg("zns")
-            .arg("reset-zone")
-            .arg(&ns.device_path)
-            .arg("-s")
-            .arg(zone_id.to_string())
-            .output()?;
-
-        if !output.status.success() {
-            let err = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("ZNS zone reset failed: {}", err));
-        }
+        NvmeDevice::open(&ns.device_path)?
+            .reset_zone(ns.nsid, Some(zone.zone_start_lba))
+            .map_err(|e| anyhow!("ZNS zone reset failed: {}", e))?;
 
         Ok(())
     }
@@ -553,17 +406,9 @@ impl NVMeAdvanced {
     fn format_namespace(&self, ns: &NVMeNamespace) -> Result<()> {
         println!("Formatting namespace {} with secure erase", ns.nsid);
 
-        let output = Command::new("nvme")
-            .arg("format")
-            .arg(&ns.device_path)
-            .arg("--ses=1")  // Secure erase
-            .arg("--force")
-            .output()?;
-
-        if !output.status.success() {
-            let err = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Format failed: {}", err));
-        }
+        NvmeDevice::open(&ns.device_path)?
+            .format_nvm(ns.nsid, SecureEraseSetting::UserData)
+            .map_err(|e| anyhow!("Format failed: {}", e))?;
 
         Ok(())
     }
@@ -1,8 +1,10 @@
 use anyhow::{Result, anyhow};
-use std::process::Command;
 use std::thread;
 use std::time::Duration;
+use crate::drives::nvme_admin::{NvmeDevice, SanitizeAction, SecureEraseSetting};
+use super::sanitize::{NvmeSanitizeRecord, SanitizeSession};
 use crate::ui::progress::ProgressBar;
+use crate::SanitizeOption;
 
 pub struct NVMeWipe;
 
@@ -10,61 +12,79 @@ impl NVMeWipe {
     pub fn secure_erase(device_path: &str) -> Result<()> {
         println!("Starting NVMe secure erase on {}", device_path);
 
-        let _device_info = Self::get_nvme_info(device_path)?;
+        let mut device = NvmeDevice::open(device_path)?;
+        let controller = device.identify_controller()?;
 
-        if Self::supports_format_nvm(device_path)? {
-            let mut cmd = Command::new("nvme");
-            cmd.args(["format", device_path, "--ses=1", "--force"]);
-            Self::run_command_with_bar(&mut cmd, "Format NVM secure erase")
-        } else if Self::supports_sanitize(device_path)? {
-            let mut cmd = Command::new("nvme");
-            cmd.args(["sanitize", device_path, "--crypto-erase", "--force"]);
-            Self::run_command_with_bar(&mut cmd, "Sanitize crypto erase")
+        if controller.format_supported() {
+            let nsid = device.namespace_id();
+            let ses = if controller.format_attributes().crypto_erase_supported {
+                SecureEraseSetting::Cryptographic
+            } else {
+                SecureEraseSetting::UserData
+            };
+            Self::run_with_bar("Format NVM secure erase", move || {
+                device.format_nvm(nsid, ses).map_err(Into::into)
+            })
+        } else if controller.sanitize().crypto_erase {
+            Self::run_sanitize(device, SanitizeAction::CryptoErase, "Sanitize crypto erase").map(|_| ())
         } else {
             Err(anyhow!("No secure erase method available for this NVMe device"))
         }
     }
 
-    fn run_command_with_bar(cmd: &mut Command, label: &str) -> Result<()> {
+    /// NVMe Sanitize, preferring crypto erase over block erase. Re-attaches
+    /// to a sanitize that is already running instead of starting another.
+    pub fn sanitize(device_path: &str, options: &[SanitizeOption]) -> Result<NvmeSanitizeRecord> {
+        let (action, label) = if options.contains(&SanitizeOption::CryptoErase) {
+            (SanitizeAction::CryptoErase, "Sanitize crypto erase")
+        } else if options.contains(&SanitizeOption::BlockErase) {
+            (SanitizeAction::BlockErase, "Sanitize block erase")
+        } else if options.contains(&SanitizeOption::Overwrite) {
+            (SanitizeAction::Overwrite { passes: 1, pattern: 0 }, "Sanitize overwrite")
+        } else {
+            return Err(anyhow!("NVMe Sanitize not supported on this device"));
+        };
+
+        println!("Starting NVMe sanitize on {}", device_path);
+        Self::run_sanitize(NvmeDevice::open(device_path)?, action, label)
+    }
+
+    /// Start (or re-attach to) a sanitize and follow the Sanitize Status log
+    /// until it finishes
+    fn run_sanitize(device: NvmeDevice, action: SanitizeAction, label: &str) -> Result<NvmeSanitizeRecord> {
+        let mut session = SanitizeSession::start(device, action)?;
+        let label = match session.action() {
+            Some(running) if session.reattached() => format!("Sanitize {}", running.name().to_lowercase()),
+            _ => label.to_string(),
+        };
         println!("Using {}...", label);
-        let mut process = cmd.spawn()?;
+
         let mut bar = ProgressBar::new(48);
+        let record = session.wait(Duration::from_secs(1), |status| {
+            bar.render(status.progress, None, None);
+        }).map_err(|e| anyhow!("{} failed: {}", label, e))?;
 
-        loop {
-            match process.try_wait()? {
-                Some(status) => {
-                    bar.render(100.0, None, None);
-                    if status.success() {
-                        println!("\n{} completed successfully", label);
-                        return Ok(());
-                    } else {
-                        return Err(anyhow!("{} failed", label));
-                    }
-                }
-                None => {
-                    bar.render(50.0, None, None);
-                    thread::sleep(Duration::from_millis(200));
-                }
-            }
-        }
+        println!("\n{} completed successfully (SSTAT 0x{:04X})", label, record.sstat);
+        Ok(record)
     }
 
-    fn get_nvme_info(device_path: &str) -> Result<String> {
-        let output = Command::new("nvme")
-            .args(["id-ctrl", device_path])
-            .output()?;
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to get NVMe info: {}", error));
-        }
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
-    }
+    /// Run a blocking command on a worker thread while animating a bar
+    fn run_with_bar<F>(label: &str, operation: F) -> Result<()>
+    where
+        F: FnOnce() -> Result<()> + Send + 'static,
+    {
+        println!("Using {}...", label);
+        let worker = thread::spawn(operation);
+        let mut bar = ProgressBar::new(48);
 
-    fn supports_format_nvm(device_path: &str) -> Result<bool> {
-        Ok(Self::get_nvme_info(device_path)?.contains("Format NVM Supported"))
-    }
+        while !worker.is_finished() {
+            bar.render(50.0, None, None);
+            thread::sleep(Duration::from_millis(200));
+        }
 
-    fn supports_sanitize(device_path: &str) -> Result<bool> {
-        Ok(Self::get_nvme_info(device_path)?.contains("Sanitize Operation Supported"))
+        worker.join().map_err(|_| anyhow!("{} thread panicked", label))??;
+        bar.render(100.0, None, None);
+        println!("\n{} completed successfully", label);
+        Ok(())
     }
 }
@@ -3,12 +3,15 @@
 // This module provides both basic and advanced NVMe functionality:
 // - basic.rs: Standard NVMe secure erase and sanitize operations
 // - advanced.rs: ZNS (Zoned Namespaces), multiple namespaces, Key-Value, Computational storage
+// - sanitize.rs: Sanitize sessions that track progress through the Sanitize Status log
 
 pub mod basic;
 pub mod advanced;
+pub mod sanitize;
 
 // Re-export commonly used types
 pub use basic::NVMeWipe;
+pub use sanitize::{NvmeSanitizeRecord, SanitizeSession};
 pub use advanced::{
     NVMeAdvanced,
     NVMeNamespace,
@@ -0,0 +1,329 @@
+// NVMe Sanitize session
+//
+// A sanitize keeps running in the controller after the command completes,
+// and across resets and power cycles. The session follows it through the
+// Sanitize Status log page (Log ID 81h) instead of firing the command and
+// hoping, and re-attaches to a sanitize that is already running (e.g. one
+// started before a reboot) rather than issuing a new one.
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use crate::drives::nvme_admin::{
+    IoctlTransport, NvmeDevice, NvmeTransport, SanitizeAction, SanitizeLog, SanitizeState,
+};
+use crate::{OperationStatus, WipePhase};
+
+/// Outcome of a sanitize as reported by the controller, kept in the certificate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NvmeSanitizeRecord {
+    pub action: String,
+    pub state: SanitizeState,
+    pub sstat: u16,
+    pub scdw10: u32,
+    pub overwrite_passes_completed: u8,
+    pub estimated_overwrite_secs: Option<u32>,
+    pub estimated_block_erase_secs: Option<u32>,
+    pub estimated_crypto_erase_secs: Option<u32>,
+    /// The session joined a sanitize that was already running
+    pub reattached: bool,
+    pub duration_seconds: u64,
+}
+
+pub struct SanitizeSession<T: NvmeTransport = IoctlTransport> {
+    device: NvmeDevice<T>,
+    action: Option<SanitizeAction>,
+    reattached: bool,
+    started: Instant,
+    last_log: Option<SanitizeLog>,
+}
+
+impl SanitizeSession<IoctlTransport> {
+    pub fn open(device_path: &str, action: SanitizeAction) -> Result<Self> {
+        Self::start(NvmeDevice::open(device_path)?, action)
+    }
+
+    /// Whether a sanitize is currently running on `device_path`
+    pub fn in_progress(device_path: &str) -> bool {
+        NvmeDevice::open(device_path)
+            .and_then(|mut device| device.sanitize_log())
+            .map(|log| log.state() == SanitizeState::InProgress)
+            .unwrap_or(false)
+    }
+}
+
+impl<T: NvmeTransport> SanitizeSession<T> {
+    /// Issue `action`, or re-attach if the controller is already sanitizing.
+    /// A running sanitize cannot be aborted, so it wins over `action`.
+    pub fn start(mut device: NvmeDevice<T>, action: SanitizeAction) -> Result<Self> {
+        let log = device.sanitize_log()?;
+
+        match log.state() {
+            SanitizeState::InProgress => {
+                let running = SanitizeAction::from_cdw10(log.scdw10());
+                println!("🔁 Sanitize {} already in progress ({:.1}%), re-attaching",
+                         running.map(|a| a.name()).unwrap_or("(unknown action)"),
+                         log.progress_percent());
+
+                return Ok(Self {
+                    device,
+                    action: running,
+                    reattached: true,
+                    started: Instant::now(),
+                    last_log: Some(log),
+                });
+            }
+            SanitizeState::Failed => {
+                println!("⚠️  Previous sanitize failed (SSTAT 0x{:04X}), starting a new one", log.sstat());
+            }
+            _ => {}
+        }
+
+        device.sanitize(action, false)?;
+
+        Ok(Self {
+            device,
+            action: Some(action),
+            reattached: false,
+            started: Instant::now(),
+            last_log: None,
+        })
+    }
+
+    pub fn reattached(&self) -> bool {
+        self.reattached
+    }
+
+    /// The action being tracked; `None` if a re-attached SCDW10 was unrecognized
+    pub fn action(&self) -> Option<SanitizeAction> {
+        self.action
+    }
+
+    /// Read the Sanitize Status log once and report it as an operation status
+    pub fn poll(&mut self) -> Result<OperationStatus> {
+        let log = self.device.sanitize_log()?;
+        let status = self.status(&log);
+        self.last_log = Some(log);
+        Ok(status)
+    }
+
+    /// Poll every `poll_interval` until the sanitize finishes, reporting each
+    /// poll to `on_progress`
+    pub fn wait<F>(&mut self, poll_interval: Duration, mut on_progress: F) -> Result<NvmeSanitizeRecord>
+    where
+        F: FnMut(&OperationStatus),
+    {
+        loop {
+            let status = self.poll()?;
+            on_progress(&status);
+
+            let log = self.last_log.as_ref().expect("poll stores the log");
+            match log.state() {
+                SanitizeState::InProgress => std::thread::sleep(poll_interval),
+                SanitizeState::Completed | SanitizeState::CompletedNoDeallocate => {
+                    return Ok(self.record(log));
+                }
+                SanitizeState::Failed => {
+                    return Err(anyhow!(
+                        "NVMe sanitize failed (SSTAT 0x{:04X}); the controller is in sanitize failure mode",
+                        log.sstat()
+                    ));
+                }
+                state => return Err(anyhow!("NVMe sanitize ended in unexpected state {:?}", state)),
+            }
+        }
+    }
+
+    fn status(&self, log: &SanitizeLog) -> OperationStatus {
+        let progress = log.progress_percent();
+        let estimate = match self.action {
+            Some(SanitizeAction::Overwrite { .. }) => log.estimated_overwrite_secs(),
+            Some(SanitizeAction::BlockErase) => log.estimated_block_erase_secs(),
+            Some(SanitizeAction::CryptoErase) => log.estimated_crypto_erase_secs(),
+            _ => None,
+        };
+
+        let mut warnings = Vec::new();
+        if self.reattached {
+            warnings.push("Re-attached to a sanitize already in progress".to_string());
+        }
+
+        OperationStatus {
+            phase: WipePhase::Sanitizing,
+            progress,
+            bytes_processed: None,
+            total_bytes: None,
+            current_temperature: None,
+            estimated_time_remaining: estimate
+                .map(|secs| (secs as f64 * (100.0 - progress) / 100.0).round() as u64),
+            warnings,
+        }
+    }
+
+    fn record(&self, log: &SanitizeLog) -> NvmeSanitizeRecord {
+        let action = SanitizeAction::from_cdw10(log.scdw10()).or(self.action);
+
+        NvmeSanitizeRecord {
+            action: action.map(|a| a.name().to_string()).unwrap_or_else(|| "Unknown".to_string()),
+            state: log.state(),
+            sstat: log.sstat(),
+            scdw10: log.scdw10(),
+            overwrite_passes_completed: log.overwrite_passes_completed(),
+            estimated_overwrite_secs: log.estimated_overwrite_secs(),
+            estimated_block_erase_secs: log.estimated_block_erase_secs(),
+            estimated_crypto_erase_secs: log.estimated_crypto_erase_secs(),
+            reattached: self.reattached,
+            duration_seconds: self.started.elapsed().as_secs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drives::nvme_admin::{DataTransfer, NvmeCommand, NvmeCompletion};
+    use crate::DriveResult;
+    use std::sync::{Arc, Mutex};
+
+    const GET_LOG_PAGE: u8 = 0x02;
+    const SANITIZE: u8 = 0x84;
+
+    /// Controller that only knows the Sanitize command and its status log
+    #[derive(Default)]
+    struct SimState {
+        cdw10: u32,
+        sstat: u16,
+        polls_left: u32,
+        fail: bool,
+        issued: u32,
+    }
+
+    #[derive(Clone, Default)]
+    struct SanitizingNvme {
+        state: Arc<Mutex<SimState>>,
+    }
+
+    impl SanitizingNvme {
+        /// A controller that is part way through a sanitize started earlier
+        fn already_sanitizing(cdw10: u32, polls_left: u32) -> Self {
+            let sim = Self::default();
+            {
+                let mut state = sim.state.lock().unwrap();
+                state.cdw10 = cdw10;
+                state.sstat = 2;
+                state.polls_left = polls_left;
+            }
+            sim
+        }
+    }
+
+    impl NvmeTransport for SanitizingNvme {
+        fn admin(&mut self, command: &NvmeCommand, data: DataTransfer<'_>, _timeout: Duration) -> DriveResult<NvmeCompletion> {
+            let mut state = self.state.lock().unwrap();
+
+            match command.opcode {
+                SANITIZE => {
+                    if state.sstat & 0x7 == 2 {
+                        return Ok(NvmeCompletion { result: 0, status: 0x1D });
+                    }
+                    state.issued += 1;
+                    state.cdw10 = command.cdw10;
+                    state.sstat = 2;
+                    state.polls_left = 3;
+                }
+                GET_LOG_PAGE => {
+                    let mut log = [0u8; 512];
+                    let mut sprog = 0u16;
+                    if state.sstat & 0x7 == 2 {
+                        if state.polls_left == 0 {
+                            state.sstat = if state.fail { 3 } else { 1 | (1 << 3) };
+                        } else {
+                            sprog = (0x10000 - state.polls_left * 0x4000) as u16;
+                            state.polls_left -= 1;
+                        }
+                    }
+                    log[0..2].copy_from_slice(&sprog.to_le_bytes());
+                    log[2..4].copy_from_slice(&state.sstat.to_le_bytes());
+                    log[4..8].copy_from_slice(&state.cdw10.to_le_bytes());
+                    log[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+                    log[12..16].copy_from_slice(&400u32.to_le_bytes());
+                    log[16..20].copy_from_slice(&8u32.to_le_bytes());
+                    if let DataTransfer::In(buf) = data {
+                        let n = buf.len().min(log.len());
+                        buf[..n].copy_from_slice(&log[..n]);
+                    }
+                }
+                _ => return Ok(NvmeCompletion { result: 0, status: 0x01 }),
+            }
+            Ok(NvmeCompletion::default())
+        }
+
+        fn io(&mut self, _command: &NvmeCommand, _data: DataTransfer<'_>, _timeout: Duration) -> DriveResult<NvmeCompletion> {
+            Ok(NvmeCompletion { result: 0, status: 0x01 })
+        }
+
+        fn namespace_id(&mut self) -> DriveResult<u32> {
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_session_reports_progress_until_complete() {
+        let sim = SanitizingNvme::default();
+        let mut session = SanitizeSession::start(NvmeDevice::new(sim.clone()), SanitizeAction::BlockErase).unwrap();
+        assert!(!session.reattached());
+
+        let mut statuses = Vec::new();
+        let record = session.wait(Duration::ZERO, |status| statuses.push(status.clone())).unwrap();
+
+        let progress: Vec<f64> = statuses.iter().map(|s| s.progress).collect();
+        assert_eq!(progress, vec![25.0, 50.0, 75.0, 100.0]);
+        assert!(statuses.iter().all(|s| s.phase == WipePhase::Sanitizing));
+        // Block erase estimate of 400s scaled by what is left
+        assert_eq!(statuses[0].estimated_time_remaining, Some(300));
+        assert_eq!(statuses[3].estimated_time_remaining, Some(0));
+
+        assert_eq!(record.action, "Block Erase");
+        assert_eq!(record.state, SanitizeState::Completed);
+        assert_eq!(record.sstat, 1 | (1 << 3));
+        assert_eq!(record.scdw10, 2);
+        assert_eq!(record.estimated_overwrite_secs, None);
+        assert_eq!(record.estimated_block_erase_secs, Some(400));
+        assert_eq!(record.estimated_crypto_erase_secs, Some(8));
+        assert!(!record.reattached);
+        assert_eq!(sim.state.lock().unwrap().issued, 1);
+    }
+
+    #[test]
+    fn test_session_reattaches_to_running_sanitize() {
+        // A crypto erase started before the process restarted
+        let sim = SanitizingNvme::already_sanitizing(4, 2);
+        let mut session = SanitizeSession::start(NvmeDevice::new(sim.clone()), SanitizeAction::BlockErase).unwrap();
+
+        assert!(session.reattached());
+        assert_eq!(session.action(), Some(SanitizeAction::CryptoErase));
+
+        // 75% done, so a quarter of the 8s crypto erase estimate is left
+        let status = session.poll().unwrap();
+        assert_eq!(status.progress, 75.0);
+        assert!(!status.warnings.is_empty());
+        assert_eq!(status.estimated_time_remaining, Some(2));
+
+        let record = session.wait(Duration::ZERO, |_| {}).unwrap();
+        assert_eq!(record.action, "Crypto Erase");
+        assert!(record.reattached);
+        // No second sanitize was issued
+        assert_eq!(sim.state.lock().unwrap().issued, 0);
+    }
+
+    #[test]
+    fn test_session_detects_failed_sanitize() {
+        let sim = SanitizingNvme::default();
+        sim.state.lock().unwrap().fail = true;
+
+        let mut session = SanitizeSession::start(NvmeDevice::new(sim), SanitizeAction::CryptoErase).unwrap();
+        let err = session.wait(Duration::ZERO, |_| {}).unwrap_err().to_string();
+        assert!(err.contains("failure mode"), "{}", err);
+        assert!(err.contains("0x0003"), "{}", err);
+    }
+}
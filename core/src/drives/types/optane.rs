@@ -5,6 +5,8 @@
 
 use anyhow::{Result, anyhow};
 use std::process::Command;
+use crate::drives::nvme_admin::{NvmeDevice, SanitizeAction, SecureEraseSetting};
+use super::nvme::SanitizeSession;
 use serde::{Serialize, Deserialize};
 
 /// Optane operating mode
@@ -76,7 +78,7 @@ impl OptaneDrive {
 
         // Method 2: Check via nvme-cli for Optane NVMe devices
         if device_path.contains("nvme") {
-            if let Ok(is_optane) = Self::check_via_nvme_cli(device_path) {
+            if let Ok(is_optane) = Self::check_via_nvme_identify(device_path) {
                 if is_optane {
                     return Ok(true);
                 }
@@ -118,18 +120,14 @@ impl OptaneDrive {
         Ok(false)
     }
 
-    /// Check via nvme-cli
-    fn check_via_nvme_cli(device_path: &str) -> Result<bool> {
-        let output = Command::new("nvme")
-            .arg("id-ctrl")
-            .arg(device_path)
-            .output();
-
-        if let Ok(output) = output {
-            let stdout = String::from_utf8_lossy(&output.stdout);
+    /// Check the controller model via Identify Controller
+    fn check_via_nvme_identify(device_path: &str) -> Result<bool> {
+        let controller = NvmeDevice::open(device_path).and_then(|mut device| device.identify_controller());
 
+        if let Ok(controller) = controller {
             // Check model number for Optane
-            if stdout.contains("Optane") || stdout.contains("SSDPE") || stdout.contains("SSDPF") {
+            let model = controller.model();
+            if model.contains("Optane") || model.contains("SSDPE") || model.contains("SSDPF") {
                 return Ok(true);
             }
         }
@@ -158,18 +156,13 @@ impl OptaneDrive {
     /// Check if device supports Instant Secure Erase
     fn check_ise_support(device_path: &str) -> Result<bool> {
         if device_path.contains("nvme") {
-            let output = Command::new("nvme")
-                .arg("id-ctrl")
-                .arg(device_path)
-                .arg("-H")
-                .output();
-
-            if let Ok(output) = output {
-                let stdout = String::from_utf8_lossy(&output.stdout);
+            let controller = NvmeDevice::open(device_path).and_then(|mut device| device.identify_controller());
 
+            if let Ok(controller) = controller {
                 // Look for Format NVM support with crypto erase
-                if stdout.contains("Crypto Erase Supported") ||
-                   stdout.contains("Format NVM Supported") {
+                if controller.format_attributes().crypto_erase_supported ||
+                   controller.sanitize().crypto_erase ||
+                   controller.format_supported() {
                     return Ok(true);
                 }
             }
@@ -183,32 +176,19 @@ impl OptaneDrive {
         let mut namespaces = Vec::new();
 
         if device_path.contains("nvme") {
-            // NVMe Optane - use nvme list-ns
-            let output = Command::new("nvme")
-                .arg("list-ns")
-                .arg(device_path)
-                .output();
-
-            if let Ok(output) = output {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-
-                for line in stdout.lines() {
-                    if line.contains("ns") {
-                        // Parse namespace (simplified)
-                        if let Some(nsid_str) = line.split_whitespace().nth(0) {
-                            if let Ok(nsid) = nsid_str.trim_start_matches("ns").parse::<u32>() {
-                                let ns = OptaneNamespace {
-                                    nsid,
-                                    capacity: 0,  // Will be filled by get-ns
-                                    mode: OptaneMode::BlockMode,
-                                    device_path: format!("{}n{}", device_path, nsid),
-                                    is_healthy: true,
-                                };
-                                namespaces.push(ns);
-                            }
-                        }
-                    }
-                }
+            // NVMe Optane - list active namespaces
+            let mut device = NvmeDevice::open(device_path)?;
+            for nsid in device.active_namespaces().unwrap_or_default() {
+                let capacity = device.identify_namespace(nsid)
+                    .map(|ns| ns.size_bytes())
+                    .unwrap_or(0);
+                namespaces.push(OptaneNamespace {
+                    nsid,
+                    capacity,
+                    mode: OptaneMode::BlockMode,
+                    device_path: format!("{}n{}", device_path, nsid),
+                    is_healthy: true,
+                });
             }
         } else if device_path.contains("pmem") {
             // PMEM Optane - single namespace typically
@@ -312,38 +292,23 @@ impl OptaneDrive {
         Err(anyhow!("All ISE methods failed"))
     }
 
-    /// Try ISE via nvme format command
+    /// Try ISE via Format NVM with cryptographic erase
     fn try_nvme_format_crypto(&self) -> Result<()> {
-        let output = Command::new("nvme")
-            .arg("format")
-            .arg(&self.device_path)
-            .arg("-s")  // Secure erase setting
-            .arg("2")   // Cryptographic erase
-            .output()?;
-
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(anyhow!("nvme format failed"))
-        }
+        let mut device = NvmeDevice::open(&self.device_path)?;
+        let nsid = device.namespace_id();
+        device.format_nvm(nsid, SecureEraseSetting::Cryptographic)
+            .map_err(|e| anyhow!("nvme format failed: {}", e))
     }
 
-    /// Try ISE via nvme sanitize command
+    /// Try ISE via Sanitize with crypto erase
     fn try_nvme_sanitize_crypto(&self) -> Result<()> {
-        let output = Command::new("nvme")
-            .arg("sanitize")
-            .arg(&self.device_path)
-            .arg("-a")  // Sanitize action
-            .arg("2")   // Cryptographic erase
-            .output()?;
-
-        if output.status.success() {
-            // Wait for sanitize to complete
-            std::thread::sleep(std::time::Duration::from_secs(2));
-            Ok(())
-        } else {
-            Err(anyhow!("nvme sanitize failed"))
-        }
+        let mut session = SanitizeSession::open(&self.device_path, SanitizeAction::CryptoErase)
+            .map_err(|e| anyhow!("nvme sanitize failed: {}", e))?;
+
+        // Wait for sanitize to complete
+        session.wait(std::time::Duration::from_secs(1), |_| {})
+            .map_err(|e| anyhow!("nvme sanitize failed: {}", e))?;
+        Ok(())
     }
 
     /// Overwrite with 3D XPoint-specific patterns
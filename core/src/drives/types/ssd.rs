@@ -1,8 +1,9 @@
 use anyhow::{Result, anyhow};
-use std::process::Command;
 use std::thread;
 use std::time::Duration;
+use crate::drives::ata::{self, AtaDevice};
 use crate::ui::progress::ProgressBar;
+use crate::SanitizeOption;
 
 pub struct SSDWipe;
 
@@ -10,65 +11,67 @@ impl SSDWipe {
     pub fn secure_erase(device_path: &str) -> Result<()> {
         println!("Attempting hardware secure erase on {}", device_path);
 
-        if !Self::is_secure_erase_supported(device_path)? {
+        let mut device = AtaDevice::open(device_path)?;
+        let security = device.identify()?.security();
+        if !security.supported {
             return Err(anyhow!("Secure erase not supported on this device"));
         }
 
-        Self::unfreeze_drive(device_path)?;
-        Self::set_security_password(device_path, "temp123")?;
+        Self::unfreeze_drive(&mut device)?;
+        ata::secure_erase(device, security.enhanced_erase_supported)?;
 
-        let mut cmd = Command::new("hdparm");
-        cmd.args(["--user-master", "u", "--security-erase", "temp123", device_path]);
-        let mut process = cmd.spawn()?;
+        println!("\nHardware secure erase completed successfully");
+        Ok(())
+    }
+
+    /// ATA SANITIZE with BLOCK ERASE, falling back to CRYPTO SCRAMBLE
+    pub fn sanitize(device_path: &str, options: &[SanitizeOption]) -> Result<()> {
+        let mut device = AtaDevice::open(device_path)?;
+
+        let method = if options.contains(&SanitizeOption::BlockErase) {
+            "block-erase"
+        } else if options.contains(&SanitizeOption::CryptoScramble) {
+            "crypto-scramble"
+        } else {
+            return Err(anyhow!("ATA sanitize block erase or crypto scramble not supported on this device"));
+        };
 
+        println!("Starting ATA sanitize ({}) on {}", method, device_path);
+        Self::unfreeze_drive(&mut device)?;
+
+        let started = if method == "block-erase" {
+            device.sanitize_block_erase()
+        } else {
+            device.sanitize_crypto_scramble()
+        };
+        started.map_err(|e| anyhow!("ATA sanitize failed to start: {}", e))?;
+
+        // The command returns immediately; the drive reports progress through
+        // the sanitize status until the operation completes
         let mut bar = ProgressBar::new(48);
         loop {
-            match process.try_wait()? {
-                Some(status) => {
-                    bar.render(100.0, None, None);
-                    if status.success() {
-                        println!("\nHardware secure erase completed successfully");
-                        return Ok(());
-                    } else {
-                        return Err(anyhow!("Secure erase failed"));
-                    }
-                }
-                None => {
-                    bar.render(50.0, None, None);
-                    thread::sleep(Duration::from_millis(200));
-                }
+            let status = device.sanitize_status()?;
+
+            if status.in_progress {
+                bar.render(status.progress, None, None);
+                thread::sleep(Duration::from_secs(1));
+                continue;
             }
-        }
-    }
 
-    fn is_secure_erase_supported(device_path: &str) -> Result<bool> {
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()?;
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        Ok(output_str.contains("supported: enhanced erase"))
+            bar.render(100.0, None, None);
+            if status.failed || !status.completed_without_error {
+                return Err(anyhow!("ATA sanitize failed"));
+            }
+            println!("\nATA sanitize completed successfully");
+            return Ok(());
+        }
     }
 
-    fn unfreeze_drive(device_path: &str) -> Result<()> {
+    fn unfreeze_drive(device: &mut AtaDevice) -> Result<()> {
         println!("Checking drive freeze status...");
-        let output = Command::new("hdparm")
-            .args(["-I", device_path])
-            .output()?;
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        if output_str.contains("frozen") {
+        if device.identify()?.security().frozen {
             println!("Warning: Drive is frozen. May need power cycle.");
         }
         Ok(())
     }
-
-    fn set_security_password(device_path: &str, password: &str) -> Result<()> {
-        let output = Command::new("hdparm")
-            .args(["--user-master", "u", "--security-set-pass", password, device_path])
-            .output()?;
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to set security password: {}", error));
-        }
-        Ok(())
-    }
 }
@@ -0,0 +1,328 @@
+// Machine-readable wipe events
+//
+// Every phase change, progress tick, warning, error and final outcome of a
+// drive wipe is published as a typed `WipeEvent` to the registered
+// `WipeObserver`s. The CLI registers a `JsonlObserver` for `--output jsonl`;
+// embedders such as the desktop app register their own observer and receive
+// the same stream.
+
+use crate::{WipePhase, WipeStatus};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::{Arc, Mutex, RwLock};
+
+static OBSERVERS: RwLock<Vec<Arc<dyn WipeObserver>>> = RwLock::new(Vec::new());
+
+thread_local! {
+    // Device whose overwrite is running on this thread, its operation's own
+    // observer and the last drive temperature the I/O engine read for it.
+    // Progress is reported from deep inside the algorithms, which know none
+    // of them.
+    static CONTEXT: RefCell<Option<DeviceContext>> = const { RefCell::new(None) };
+}
+
+struct DeviceContext {
+    device: String,
+    observer: Option<Arc<dyn WipeObserver>>,
+    temperature: Option<u32>,
+}
+
+/// A single wipe event
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WipeEvent {
+    Started {
+        device: String,
+        model: String,
+        serial: String,
+        total_bytes: u64,
+        algorithm: String,
+    },
+    PhaseChanged {
+        device: String,
+        phase: WipePhase,
+    },
+    Progress {
+        device: Option<String>,
+        percent: f64,
+        bytes_processed: Option<u64>,
+        total_bytes: Option<u64>,
+        throughput_bps: Option<f64>,
+        eta_seconds: Option<u64>,
+        temperature_celsius: Option<u32>,
+    },
+    Warning {
+        device: String,
+        message: String,
+    },
+    Error {
+        device: String,
+        message: String,
+    },
+    Paused {
+        device: String,
+    },
+    Resumed {
+        device: String,
+    },
+    Completed {
+        device: String,
+        status: WipeStatus,
+        error_message: Option<String>,
+        verification_passed: Option<bool>,
+    },
+}
+
+/// Receives every `WipeEvent` emitted while it is registered. Events may be
+/// emitted from any thread, so observers must be cheap and thread safe.
+pub trait WipeObserver: Send + Sync {
+    fn on_event(&self, event: &WipeEvent);
+}
+
+/// Register an observer for all subsequent events
+pub fn add_observer(observer: Arc<dyn WipeObserver>) {
+    OBSERVERS.write().unwrap().push(observer);
+}
+
+/// Remove every registered observer
+pub fn clear_observers() {
+    OBSERVERS.write().unwrap().clear();
+}
+
+/// Whether anyone is listening, so callers can skip building events
+pub fn has_observers() -> bool {
+    !OBSERVERS.read().unwrap().is_empty()
+}
+
+/// Deliver an event to every registered observer, and to the observer of
+/// the operation running on this thread
+pub fn emit(event: WipeEvent) {
+    for observer in OBSERVERS.read().unwrap().iter() {
+        observer.on_event(&event);
+    }
+    if let Some(observer) = scope_observer() {
+        observer.on_event(&event);
+    }
+}
+
+fn scope_observer() -> Option<Arc<dyn WipeObserver>> {
+    CONTEXT.with(|c| c.borrow().as_ref().and_then(|context| context.observer.clone()))
+}
+
+/// Marks the device whose overwrite runs on the current thread until dropped,
+/// so progress events can name it and reach the operation's observer
+pub struct DeviceScope {
+    previous: Option<DeviceContext>,
+}
+
+impl DeviceScope {
+    pub fn enter(device: &str, observer: Option<Arc<dyn WipeObserver>>) -> Self {
+        let context = DeviceContext { device: device.to_string(), observer, temperature: None };
+        let previous = CONTEXT.with(|c| c.replace(Some(context)));
+        Self { previous }
+    }
+}
+
+impl Drop for DeviceScope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CONTEXT.with(|c| *c.borrow_mut() = previous);
+    }
+}
+
+/// Remember the latest drive temperature for the current device's progress
+pub fn record_temperature(celsius: u32) {
+    CONTEXT.with(|c| {
+        if let Some(context) = c.borrow_mut().as_mut() {
+            context.temperature = Some(celsius);
+        }
+    });
+}
+
+/// Emit a progress tick for the current device. Byte counts are absent for
+/// firmware operations that only report a percentage; otherwise throughput
+/// and ETA are derived from the average rate so far.
+pub fn progress(percent: f64, bytes: Option<(u64, u64)>, elapsed_secs: f64) {
+    if !has_observers() && scope_observer().is_none() {
+        return;
+    }
+
+    let throughput_bps = bytes
+        .map(|(done, _)| if elapsed_secs > 0.0 { done as f64 / elapsed_secs } else { 0.0 });
+    let eta_seconds = match (bytes, throughput_bps) {
+        (Some((done, total)), Some(rate)) if rate > 0.0 => {
+            Some((total.saturating_sub(done) as f64 / rate).round() as u64)
+        }
+        _ => None,
+    };
+    let (device, temperature_celsius) = CONTEXT.with(|c| match c.borrow().as_ref() {
+        Some(context) => (Some(context.device.clone()), context.temperature),
+        None => (None, None),
+    });
+
+    emit(WipeEvent::Progress {
+        device,
+        percent,
+        bytes_processed: bytes.map(|(done, _)| done),
+        total_bytes: bytes.map(|(_, total)| total),
+        throughput_bps,
+        eta_seconds,
+        temperature_celsius,
+    });
+}
+
+#[derive(Serialize)]
+struct JsonlRecord<'a> {
+    timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    event: &'a WipeEvent,
+}
+
+/// Writes each event as one JSON object per line
+pub struct JsonlObserver {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonlObserver {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+
+    /// Take over stdout for events. Everything else the process prints to
+    /// stdout is redirected to stderr from here on, so the stream stays
+    /// parseable.
+    #[cfg(unix)]
+    pub fn stdout() -> Result<Self> {
+        use std::os::unix::io::FromRawFd;
+
+        std::io::stdout().flush()?;
+        // SAFETY: dup only reads the descriptor table; the result is checked
+        let events_fd = unsafe { libc::dup(libc::STDOUT_FILENO) };
+        if events_fd < 0 {
+            return Err(std::io::Error::last_os_error()).context("Failed to duplicate stdout");
+        }
+        // SAFETY: both descriptors are the process's own standard streams;
+        // stdout was flushed above so no buffered output is lost
+        if unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) } < 0 {
+            let e = std::io::Error::last_os_error();
+            // SAFETY: events_fd came from dup above and nothing else owns it
+            unsafe { libc::close(events_fd) };
+            return Err(e).context("Failed to redirect stdout to stderr");
+        }
+
+        // SAFETY: events_fd is a valid descriptor from dup that only this File owns
+        let file = unsafe { std::fs::File::from_raw_fd(events_fd) };
+        Ok(Self::new(Box::new(file)))
+    }
+
+    /// Stream events to a listening Unix socket
+    #[cfg(unix)]
+    pub fn unix_socket(path: &str) -> Result<Self> {
+        let stream = std::os::unix::net::UnixStream::connect(path)
+            .with_context(|| format!("Failed to connect to event socket {}", path))?;
+        Ok(Self::new(Box::new(stream)))
+    }
+}
+
+impl WipeObserver for JsonlObserver {
+    fn on_event(&self, event: &WipeEvent) {
+        let record = JsonlRecord { timestamp: chrono::Utc::now(), event };
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                log::warn!("Failed to serialize wipe event: {}", e);
+                return;
+            }
+        };
+
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(e) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+            log::warn!("Failed to write wipe event: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_jsonl_lines_are_tagged_events() {
+        let buffer = SharedBuffer::default();
+        let observer = JsonlObserver::new(Box::new(buffer.clone()));
+
+        observer.on_event(&WipeEvent::PhaseChanged {
+            device: "/dev/sdz".to_string(),
+            phase: WipePhase::Overwriting,
+        });
+        observer.on_event(&WipeEvent::Warning {
+            device: "/dev/sdz".to_string(),
+            message: "3 unwritable sectors".to_string(),
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "phase_changed");
+        assert_eq!(lines[0]["phase"], "Overwriting");
+        assert!(lines[0]["timestamp"].is_string());
+        assert_eq!(lines[1]["event"], "warning");
+        assert_eq!(lines[1]["message"], "3 unwritable sectors");
+    }
+
+    #[test]
+    fn test_progress_carries_device_scope() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        struct Collect(Arc<Mutex<Vec<WipeEvent>>>);
+        impl WipeObserver for Collect {
+            fn on_event(&self, event: &WipeEvent) {
+                self.0.lock().unwrap().push(event.clone());
+            }
+        }
+        add_observer(Arc::new(Collect(events.clone())));
+
+        {
+            let _scope = DeviceScope::enter("/dev/events-test", None);
+            record_temperature(41);
+            progress(25.0, Some((256, 1024)), 2.0);
+        }
+        progress(50.0, None, 1.0);
+
+        // Other tests may emit concurrently; only look at ours
+        let events = events.lock().unwrap();
+        let scoped = events.iter().find(|e| matches!(e,
+            WipeEvent::Progress { device: Some(d), .. } if d == "/dev/events-test"));
+        assert_eq!(scoped, Some(&WipeEvent::Progress {
+            device: Some("/dev/events-test".to_string()),
+            percent: 25.0,
+            bytes_processed: Some(256),
+            total_bytes: Some(1024),
+            throughput_bps: Some(128.0),
+            eta_seconds: Some(6),
+            temperature_celsius: Some(41),
+        }));
+        assert!(events.iter().any(|e| matches!(e,
+            WipeEvent::Progress { device: None, percent, bytes_processed: None, eta_seconds: None, .. }
+                if *percent == 50.0)));
+    }
+}
@@ -0,0 +1,808 @@
+use anyhow::{Result, anyhow, Context};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::control::{CancellationToken, OperationContext, PauseToken};
+use crate::events::{self, WipeEvent};
+use crate::{DriveInfo, WipePhase, WipeSession, WipeStatus};
+
+/// Environment variable that overrides the default database location
+pub const HISTORY_DB_ENV: &str = "SAYONARA_HISTORY_DB";
+
+/// Default location of the wipe history database
+pub const DEFAULT_HISTORY_DB_PATH: &str = "/var/lib/sayonara/history.db";
+
+/// How often a running wipe checks the database for pause and cancel requests
+const CONTROL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS sessions (
+        session_id   TEXT PRIMARY KEY,
+        start_time   TEXT NOT NULL,
+        end_time     TEXT,
+        operator_id  TEXT,
+        config_json  TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS drive_records (
+        id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id          TEXT NOT NULL REFERENCES sessions(session_id),
+        device_path         TEXT NOT NULL,
+        model               TEXT NOT NULL,
+        serial              TEXT NOT NULL,
+        size_bytes          INTEGER NOT NULL,
+        drive_type          TEXT NOT NULL,
+        algorithm           TEXT NOT NULL,
+        status              TEXT NOT NULL,
+        start_time          TEXT NOT NULL,
+        end_time            TEXT,
+        error_message       TEXT,
+        certificate_path    TEXT,
+        verification_passed INTEGER,
+        drive_info_json     TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS phase_transitions (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        record_id  INTEGER NOT NULL REFERENCES drive_records(id),
+        phase      TEXT NOT NULL,
+        timestamp  TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS events (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        record_id  INTEGER NOT NULL REFERENCES drive_records(id),
+        level      TEXT NOT NULL,
+        message    TEXT NOT NULL,
+        timestamp  TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS drive_controls (
+        record_id   INTEGER PRIMARY KEY REFERENCES drive_records(id),
+        paused      INTEGER NOT NULL,
+        updated_at  TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS drive_cancellations (
+        record_id     INTEGER PRIMARY KEY REFERENCES drive_records(id),
+        requested_at  TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_drive_records_serial ON drive_records(serial);
+    CREATE INDEX IF NOT EXISTS idx_drive_records_start ON drive_records(start_time);
+    CREATE INDEX IF NOT EXISTS idx_drive_records_status ON drive_records(status);
+    CREATE INDEX IF NOT EXISTS idx_phase_transitions_record ON phase_transitions(record_id);
+    CREATE INDEX IF NOT EXISTS idx_events_record ON events(record_id);
+";
+
+/// One per-drive record as returned by `JobDatabase::query`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub record_id: i64,
+    pub session_id: String,
+    pub operator_id: Option<String>,
+    pub device_path: String,
+    pub model: String,
+    pub serial: String,
+    pub size_bytes: u64,
+    pub drive_type: String,
+    pub algorithm: String,
+    pub status: WipeStatus,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    pub certificate_path: Option<String>,
+    pub verification_passed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseEntry {
+    pub phase: WipePhase,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEntry {
+    pub level: String,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Query filter for `sayonara history`; all conditions are combined with AND
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub serial: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub status: Option<WipeStatus>,
+    pub limit: Option<usize>,
+}
+
+/// SQLite store of wipe sessions and per-drive records
+pub struct JobDatabase {
+    conn: Mutex<Connection>,
+    path: Option<PathBuf>,  // None when in memory
+}
+
+impl JobDatabase {
+    /// Path from `$SAYONARA_HISTORY_DB` or the default location
+    pub fn default_path() -> PathBuf {
+        std::env::var_os(HISTORY_DB_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_HISTORY_DB_PATH))
+    }
+
+    pub fn open_default() -> Result<Self> {
+        Self::open(Self::default_path())
+    }
+
+    /// Open (creating if needed) the database at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open history database {}", path.display()))?;
+        Self::init(conn, Some(path.to_path_buf()))
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?, None)
+    }
+
+    fn init(conn: Connection, path: Option<PathBuf>) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn), path })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|_| anyhow!("History database lock poisoned"))
+    }
+
+    pub fn start_session(&self, session: &WipeSession) -> Result<()> {
+        self.conn()?.execute(
+            "INSERT OR IGNORE INTO sessions (session_id, start_time, end_time, operator_id, config_json)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                session.session_id,
+                timestamp(&session.start_time),
+                session.end_time.as_ref().map(timestamp),
+                session.operator_id,
+                serde_json::to_string(&session.config)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn finish_session(&self, session_id: &str) -> Result<()> {
+        self.conn()?.execute(
+            "UPDATE sessions SET end_time = ?2 WHERE session_id = ?1",
+            params![session_id, timestamp(&Utc::now())],
+        )?;
+        Ok(())
+    }
+
+    /// Insert an in-progress drive record and return its id
+    pub fn start_drive(&self, session_id: &str, drive: &DriveInfo, algorithm: &str) -> Result<i64> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO drive_records (session_id, device_path, model, serial, size_bytes, drive_type,
+                                        algorithm, status, start_time, drive_info_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                session_id,
+                drive.device_path,
+                drive.model,
+                drive.serial,
+                drive.size as i64,
+                format!("{:?}", drive.drive_type),
+                algorithm,
+                enum_to_string(&WipeStatus::InProgress)?,
+                timestamp(&Utc::now()),
+                serde_json::to_string(drive)?,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn record_phase(&self, record_id: i64, phase: &WipePhase) -> Result<()> {
+        self.conn()?.execute(
+            "INSERT INTO phase_transitions (record_id, phase, timestamp) VALUES (?1, ?2, ?3)",
+            params![record_id, enum_to_string(phase)?, timestamp(&Utc::now())],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the recorded algorithm once "auto" or a policy settled on a method
+    pub fn record_algorithm(&self, record_id: i64, algorithm: &str) -> Result<()> {
+        self.conn()?.execute(
+            "UPDATE drive_records SET algorithm = ?2 WHERE id = ?1",
+            params![record_id, algorithm],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_event(&self, record_id: i64, level: &str, message: &str) -> Result<()> {
+        self.conn()?.execute(
+            "INSERT INTO events (record_id, level, message, timestamp) VALUES (?1, ?2, ?3, ?4)",
+            params![record_id, level, message, timestamp(&Utc::now())],
+        )?;
+        Ok(())
+    }
+
+    pub fn finish_drive(
+        &self,
+        record_id: i64,
+        status: &WipeStatus,
+        error_message: Option<&str>,
+        certificate_path: Option<&str>,
+        verification_passed: Option<bool>,
+    ) -> Result<()> {
+        self.conn()?.execute(
+            "UPDATE drive_records
+             SET status = ?2, end_time = ?3, error_message = ?4, certificate_path = ?5,
+                 verification_passed = ?6
+             WHERE id = ?1",
+            params![
+                record_id,
+                enum_to_string(status)?,
+                timestamp(&Utc::now()),
+                error_message,
+                certificate_path,
+                verification_passed,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Ask the process running the wipe of `record_id` to pause or resume
+    /// it. The request is picked up within a second or two.
+    pub fn set_paused(&self, record_id: i64, paused: bool) -> Result<()> {
+        let conn = self.conn()?;
+        Self::ensure_in_progress(&conn, record_id)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO drive_controls (record_id, paused, updated_at) VALUES (?1, ?2, ?3)",
+            params![record_id, paused, timestamp(&Utc::now())],
+        )?;
+        Ok(())
+    }
+
+    /// Ask the process running the wipe of `record_id` to stop it, leaving
+    /// the other drives of its session running. Cannot be withdrawn.
+    pub fn request_cancel(&self, record_id: i64) -> Result<()> {
+        let conn = self.conn()?;
+        Self::ensure_in_progress(&conn, record_id)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO drive_cancellations (record_id, requested_at) VALUES (?1, ?2)",
+            params![record_id, timestamp(&Utc::now())],
+        )?;
+        Ok(())
+    }
+
+    /// Whether cancelling `record_id` has been requested
+    pub fn cancel_requested(&self, record_id: i64) -> Result<bool> {
+        let requested = self.conn()?.query_row(
+            "SELECT 1 FROM drive_cancellations WHERE record_id = ?1",
+            params![record_id],
+            |_| Ok(()),
+        ).optional()?;
+        Ok(requested.is_some())
+    }
+
+    fn ensure_in_progress(conn: &Connection, record_id: i64) -> Result<()> {
+        let status: Option<String> = conn.query_row(
+            "SELECT status FROM drive_records WHERE id = ?1",
+            params![record_id],
+            |row| row.get(0),
+        ).optional()?;
+
+        match status {
+            None => Err(anyhow!("No wipe record {}", record_id)),
+            Some(status) if status != enum_to_string(&WipeStatus::InProgress)? => {
+                Err(anyhow!("Wipe record {} is not in progress", record_id))
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Whether a pause of `record_id` has been requested
+    pub fn pause_requested(&self, record_id: i64) -> Result<bool> {
+        let paused = self.conn()?.query_row(
+            "SELECT paused FROM drive_controls WHERE record_id = ?1",
+            params![record_id],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(paused.unwrap_or(false))
+    }
+
+    /// The most recent in-progress record for `device_path`
+    pub fn running_record(&self, device_path: &str) -> Result<Option<i64>> {
+        let id = self.conn()?.query_row(
+            "SELECT id FROM drive_records WHERE device_path = ?1 AND status = ?2
+             ORDER BY start_time DESC, id DESC LIMIT 1",
+            params![device_path, enum_to_string(&WipeStatus::InProgress)?],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(id)
+    }
+
+    /// Drive records matching `filter`, newest first
+    pub fn query(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>> {
+        let mut sql = String::from(
+            "SELECT r.id, r.session_id, s.operator_id, r.device_path, r.model, r.serial, r.size_bytes,
+                    r.drive_type, r.algorithm, r.status, r.start_time, r.end_time, r.error_message,
+                    r.certificate_path, r.verification_passed
+             FROM drive_records r JOIN sessions s ON s.session_id = r.session_id
+             WHERE 1 = 1",
+        );
+        let mut args: Vec<String> = Vec::new();
+
+        if let Some(serial) = &filter.serial {
+            args.push(serial.clone());
+            sql.push_str(&format!(" AND r.serial = ?{}", args.len()));
+        }
+        if let Some(since) = &filter.since {
+            args.push(timestamp(since));
+            sql.push_str(&format!(" AND r.start_time >= ?{}", args.len()));
+        }
+        if let Some(until) = &filter.until {
+            args.push(timestamp(until));
+            sql.push_str(&format!(" AND r.start_time < ?{}", args.len()));
+        }
+        if let Some(status) = &filter.status {
+            args.push(enum_to_string(status)?);
+            sql.push_str(&format!(" AND r.status = ?{}", args.len()));
+        }
+        sql.push_str(" ORDER BY r.start_time DESC, r.id DESC");
+        if let Some(limit) = filter.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(args.iter()), |row| Ok(read_entry(row)))?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row??);
+        }
+        Ok(entries)
+    }
+
+    pub fn get(&self, record_id: i64) -> Result<Option<HistoryEntry>> {
+        let conn = self.conn()?;
+        let entry = conn.query_row(
+            "SELECT r.id, r.session_id, s.operator_id, r.device_path, r.model, r.serial, r.size_bytes,
+                    r.drive_type, r.algorithm, r.status, r.start_time, r.end_time, r.error_message,
+                    r.certificate_path, r.verification_passed
+             FROM drive_records r JOIN sessions s ON s.session_id = r.session_id
+             WHERE r.id = ?1",
+            params![record_id],
+            |row| Ok(read_entry(row)),
+        ).optional()?;
+
+        entry.transpose()
+    }
+
+    /// Phase transitions of a drive record in the order they happened
+    pub fn phases(&self, record_id: i64) -> Result<Vec<PhaseEntry>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT phase, timestamp FROM phase_transitions WHERE record_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![record_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut phases = Vec::new();
+        for row in rows {
+            let (phase, time) = row?;
+            phases.push(PhaseEntry { phase: enum_from_string(&phase)?, timestamp: parse_timestamp(&time)? });
+        }
+        Ok(phases)
+    }
+
+    /// Warnings and errors of a drive record in the order they happened
+    pub fn events(&self, record_id: i64) -> Result<Vec<EventEntry>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT level, message, timestamp FROM events WHERE record_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![record_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let (level, message, time) = row?;
+            events.push(EventEntry { level, message, timestamp: parse_timestamp(&time)? });
+        }
+        Ok(events)
+    }
+}
+
+/// Per-drive recording handle used by the wipe flows.
+///
+/// History is a side channel: failures are logged and never abort a wipe, and
+/// the database side is a no-op when no database is available. Every call is
+/// also published as a `WipeEvent` to the registered observers.
+pub struct DriveJob<'a> {
+    db: Option<&'a JobDatabase>,
+    record_id: Option<i64>,
+    device: String,
+}
+
+impl<'a> DriveJob<'a> {
+    pub fn start(db: Option<&'a JobDatabase>, session_id: &str, drive: &DriveInfo, algorithm: &str) -> Self {
+        let record_id = db.and_then(|db| {
+            db.start_drive(session_id, drive, algorithm)
+                .map_err(|e| log::warn!("Failed to record wipe history: {}", e))
+                .ok()
+        });
+
+        events::emit(WipeEvent::Started {
+            device: drive.device_path.clone(),
+            model: drive.model.clone(),
+            serial: drive.serial.clone(),
+            total_bytes: drive.size,
+            algorithm: algorithm.to_string(),
+        });
+
+        Self { db, record_id, device: drive.device_path.clone() }
+    }
+
+    pub fn record_id(&self) -> Option<i64> {
+        self.record_id
+    }
+
+    pub fn phase(&self, phase: WipePhase) {
+        self.with(|db, id| db.record_phase(id, &phase));
+        events::emit(WipeEvent::PhaseChanged { device: self.device.clone(), phase });
+    }
+
+    pub fn algorithm(&self, algorithm: &str) {
+        self.with(|db, id| db.record_algorithm(id, algorithm));
+    }
+
+    pub fn warning(&self, message: &str) {
+        self.with(|db, id| db.record_event(id, "warning", message));
+        events::emit(WipeEvent::Warning { device: self.device.clone(), message: message.to_string() });
+    }
+
+    pub fn error(&self, message: &str) {
+        self.with(|db, id| db.record_event(id, "error", message));
+        events::emit(WipeEvent::Error { device: self.device.clone(), message: message.to_string() });
+    }
+
+    pub fn finish(
+        &self,
+        status: WipeStatus,
+        error_message: Option<&str>,
+        certificate_path: Option<&str>,
+        verification_passed: Option<bool>,
+    ) {
+        self.with(|db, id| {
+            db.finish_drive(id, &status, error_message, certificate_path, verification_passed)
+        });
+        events::emit(WipeEvent::Completed {
+            device: self.device.clone(),
+            status,
+            error_message: error_message.map(str::to_string),
+            verification_passed,
+        });
+    }
+
+    /// Follow pause and cancel requests for this record (`sayonara pause`,
+    /// `sayonara cancel`) on `context` until the returned watcher is dropped.
+    /// None without an on-disk database, which no other process could reach.
+    pub fn watch_controls(&self, context: &OperationContext) -> Option<ControlWatcher> {
+        let path = self.db?.path.clone()?;
+        let record_id = self.record_id?;
+        // A connection of its own, so polling never waits on the wipe's writes
+        let db = JobDatabase::open(&path)
+            .map_err(|e| log::warn!("Pause and cancel requests unavailable: {}", e))
+            .ok()?;
+        Some(ControlWatcher::spawn(db, record_id, context.pause.clone(), context.cancel.clone()))
+    }
+
+    fn with<F: FnOnce(&JobDatabase, i64) -> Result<()>>(&self, f: F) {
+        if let (Some(db), Some(id)) = (self.db, self.record_id) {
+            if let Err(e) = f(db, id) {
+                log::warn!("Failed to record wipe history: {}", e);
+            }
+        }
+    }
+}
+
+/// Background thread that applies pause and cancel requests stored in the
+/// database to a running wipe; stops when dropped
+pub struct ControlWatcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ControlWatcher {
+    fn spawn(db: JobDatabase, record_id: i64, pause: PauseToken, cancel: CancellationToken) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+
+        let thread = std::thread::spawn(move || {
+            // Only changes are applied, so a pause made another way (signal,
+            // library API) is not undone by an untouched database row
+            let mut requested = false;
+            while !stopped.load(Ordering::SeqCst) {
+                match db.pause_requested(record_id) {
+                    Ok(paused) if paused != requested => {
+                        requested = paused;
+                        if paused { pause.pause() } else { pause.resume() }
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Failed to read pause requests: {}", e),
+                }
+                match db.cancel_requested(record_id) {
+                    Ok(true) => {
+                        cancel.cancel();
+                        break;
+                    }
+                    Ok(false) => {}
+                    Err(e) => log::warn!("Failed to read cancel requests: {}", e),
+                }
+                std::thread::park_timeout(CONTROL_POLL_INTERVAL);
+            }
+        });
+
+        Self { stop, thread: Some(thread) }
+    }
+}
+
+impl Drop for ControlWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+fn read_entry(row: &Row) -> Result<HistoryEntry> {
+    let status: String = row.get(9)?;
+    let start_time: String = row.get(10)?;
+    let end_time: Option<String> = row.get(11)?;
+
+    Ok(HistoryEntry {
+        record_id: row.get(0)?,
+        session_id: row.get(1)?,
+        operator_id: row.get(2)?,
+        device_path: row.get(3)?,
+        model: row.get(4)?,
+        serial: row.get(5)?,
+        size_bytes: row.get::<_, i64>(6)? as u64,
+        drive_type: row.get(7)?,
+        algorithm: row.get(8)?,
+        status: enum_from_string(&status)?,
+        start_time: parse_timestamp(&start_time)?,
+        end_time: end_time.as_deref().map(parse_timestamp).transpose()?,
+        error_message: row.get(12)?,
+        certificate_path: row.get(13)?,
+        verification_passed: row.get(14)?,
+    })
+}
+
+/// Fixed-width UTC timestamps so lexical order in SQLite matches time order
+fn timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(s)?.with_timezone(&Utc))
+}
+
+/// Store unit enum variants by their serde name (e.g. "Completed")
+fn enum_to_string<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(s) => Ok(s),
+        other => Err(anyhow!("Expected a unit variant, got {}", other)),
+    }
+}
+
+fn enum_from_string<T: DeserializeOwned>(s: &str) -> Result<T> {
+    Ok(serde_json::from_value(serde_json::Value::String(s.to_string()))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_drive;
+    use crate::WipeConfig;
+    use tempfile::TempDir;
+
+    fn test_session() -> WipeSession {
+        WipeSession {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            start_time: Utc::now(),
+            end_time: None,
+            drives: vec![],
+            config: WipeConfig::default(),
+            operator_id: Some("op-7".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_records_full_drive_lifecycle() {
+        let db = JobDatabase::open_in_memory().unwrap();
+        let session = test_session();
+        db.start_session(&session).unwrap();
+
+        let job = DriveJob::start(Some(&db), &session.session_id, &test_drive("SN-1"), "SecureErase");
+        job.phase(WipePhase::Preparing);
+        job.algorithm("DoD5220");
+        job.phase(WipePhase::Overwriting);
+        job.warning("TRIM failed: unsupported");
+        job.phase(WipePhase::Complete);
+        job.finish(WipeStatus::Completed, None, Some("/tmp/cert.json"), Some(true));
+        db.finish_session(&session.session_id).unwrap();
+
+        let entries = db.query(&HistoryFilter::default()).unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.serial, "SN-1");
+        assert_eq!(entry.algorithm, "DoD5220");
+        assert_eq!(entry.status, WipeStatus::Completed);
+        assert_eq!(entry.operator_id.as_deref(), Some("op-7"));
+        assert_eq!(entry.certificate_path.as_deref(), Some("/tmp/cert.json"));
+        assert_eq!(entry.verification_passed, Some(true));
+        assert!(entry.end_time.is_some());
+
+        let phases: Vec<WipePhase> = db.phases(entry.record_id).unwrap()
+            .into_iter().map(|p| p.phase).collect();
+        assert_eq!(phases, vec![WipePhase::Preparing, WipePhase::Overwriting, WipePhase::Complete]);
+
+        let events = db.events(entry.record_id).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].level, "warning");
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let db = JobDatabase::open_in_memory().unwrap();
+        let session = test_session();
+        db.start_session(&session).unwrap();
+
+        let ok = DriveJob::start(Some(&db), &session.session_id, &test_drive("SN-OK"), "Zero");
+        ok.finish(WipeStatus::Completed, None, None, Some(true));
+        let failed = DriveJob::start(Some(&db), &session.session_id, &test_drive("SN-BAD"), "Zero");
+        failed.error("I/O error");
+        failed.finish(WipeStatus::Failed, Some("I/O error"), None, None);
+
+        let by_serial = db.query(&HistoryFilter { serial: Some("SN-BAD".to_string()), ..Default::default() }).unwrap();
+        assert_eq!(by_serial.len(), 1);
+        assert_eq!(by_serial[0].error_message.as_deref(), Some("I/O error"));
+
+        let by_status = db.query(&HistoryFilter { status: Some(WipeStatus::Completed), ..Default::default() }).unwrap();
+        assert_eq!(by_status.len(), 1);
+        assert_eq!(by_status[0].serial, "SN-OK");
+
+        let future = Utc::now() + chrono::Duration::days(1);
+        assert!(db.query(&HistoryFilter { since: Some(future), ..Default::default() }).unwrap().is_empty());
+        assert_eq!(db.query(&HistoryFilter { until: Some(future), ..Default::default() }).unwrap().len(), 2);
+        assert_eq!(db.query(&HistoryFilter { limit: Some(1), ..Default::default() }).unwrap().len(), 1);
+
+        let record = db.get(by_serial[0].record_id).unwrap().unwrap();
+        assert_eq!(record.status, WipeStatus::Failed);
+        assert!(db.get(9999).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_persists_across_reopen() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested/history.db");
+        let session = test_session();
+
+        {
+            let db = JobDatabase::open(&path).unwrap();
+            db.start_session(&session).unwrap();
+            DriveJob::start(Some(&db), &session.session_id, &test_drive("SN-P"), "Random")
+                .finish(WipeStatus::Completed, None, None, None);
+        }
+
+        let db = JobDatabase::open(&path).unwrap();
+        let entries = db.query(&HistoryFilter::default()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].algorithm, "Random");
+    }
+
+    #[test]
+    fn test_job_without_database_is_noop() {
+        let job = DriveJob::start(None, "session", &test_drive("SN-X"), "Zero");
+        assert!(job.record_id().is_none());
+        job.phase(WipePhase::Preparing);
+        job.finish(WipeStatus::Completed, None, None, None);
+    }
+
+    #[test]
+    fn test_pause_requests_reach_running_wipe() {
+        let dir = TempDir::new().unwrap();
+        let db = JobDatabase::open(dir.path().join("history.db")).unwrap();
+        let session = test_session();
+        db.start_session(&session).unwrap();
+        let drive = test_drive("SN-PAUSE");
+        let job = DriveJob::start(Some(&db), &session.session_id, &drive, "Zero");
+        let record_id = job.record_id().unwrap();
+        assert_eq!(db.running_record(&drive.device_path).unwrap(), Some(record_id));
+
+        let context = OperationContext::default();
+        let pause = context.pause.clone();
+        let watcher = job.watch_controls(&context).unwrap();
+        let wait_for = |paused: bool| {
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            while pause.is_paused() != paused && std::time::Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            pause.is_paused() == paused
+        };
+
+        db.set_paused(record_id, true).unwrap();
+        assert!(wait_for(true));
+        db.set_paused(record_id, false).unwrap();
+        assert!(wait_for(false));
+        drop(watcher);
+
+        // Only running wipes can be paused
+        job.finish(WipeStatus::Completed, None, None, None);
+        assert!(db.set_paused(record_id, true).is_err());
+        assert!(db.set_paused(9999, true).is_err());
+        assert_eq!(db.running_record(&drive.device_path).unwrap(), None);
+
+        // In-memory databases cannot be reached by another process
+        let memory = JobDatabase::open_in_memory().unwrap();
+        memory.start_session(&session).unwrap();
+        let job = DriveJob::start(Some(&memory), &session.session_id, &drive, "Zero");
+        assert!(job.watch_controls(&context).is_none());
+    }
+
+    #[test]
+    fn test_cancel_request_stops_only_its_drive() {
+        let dir = TempDir::new().unwrap();
+        let db = JobDatabase::open(dir.path().join("history.db")).unwrap();
+        let session = test_session();
+        db.start_session(&session).unwrap();
+        let first = DriveJob::start(Some(&db), &session.session_id, &test_drive("SN-CANCEL-1"), "Zero");
+        let second = DriveJob::start(Some(&db), &session.session_id, &test_drive("SN-CANCEL-2"), "Zero");
+
+        // Both drives run under one batch context, as in `wipe --all`
+        let batch = OperationContext::default();
+        let (first_context, second_context) = (batch.child(), batch.child());
+        let _first_watcher = first.watch_controls(&first_context).unwrap();
+        let _second_watcher = second.watch_controls(&second_context).unwrap();
+
+        let record_id = first.record_id().unwrap();
+        assert!(!db.cancel_requested(record_id).unwrap());
+        db.request_cancel(record_id).unwrap();
+        assert!(db.cancel_requested(record_id).unwrap());
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !first_context.cancel.is_cancelled() && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(first_context.cancel.is_cancelled());
+        assert!(!second_context.cancel.is_cancelled());
+        assert!(!batch.cancel.is_cancelled());
+
+        // Only running wipes can be cancelled
+        first.finish(WipeStatus::Failed, Some("cancelled"), None, None);
+        assert!(db.request_cancel(record_id).is_err());
+    }
+}
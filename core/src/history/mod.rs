@@ -0,0 +1,12 @@
+// Persistent wipe history (chain-of-custody record)
+//
+// Every session, per-drive record, phase transition, warning and error is
+// stored in a local SQLite database so operators can later prove what was
+// wiped, when, how and with which outcome (`sayonara history`).
+
+pub mod database;
+
+pub use database::{
+    ControlWatcher, DriveJob, EventEntry, HistoryEntry, HistoryFilter, JobDatabase, PhaseEntry,
+    DEFAULT_HISTORY_DB_PATH, HISTORY_DB_ENV,
+};
@@ -0,0 +1,289 @@
+// Resumable wipe checkpoints
+//
+// Overwrite algorithms record the current pass and byte offset while writing so
+// an interrupted wipe (power loss, Ctrl+C) can continue with `wipe --resume`
+// instead of starting again from byte 0.
+
+use super::{IOHandle, IOResult};
+use crate::DriveInfo;
+use anyhow::{Result, bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Environment variable that overrides the checkpoint directory
+pub const CHECKPOINT_DIR_ENV: &str = "SAYONARA_CHECKPOINT_DIR";
+
+/// Default checkpoint directory
+pub const DEFAULT_CHECKPOINT_DIR: &str = "/var/lib/sayonara/checkpoints";
+
+/// Save at least this often while a pass is running
+const SAVE_INTERVAL_BYTES: u64 = 1024 * 1024 * 1024; // 1GB
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Persisted progress of an overwrite.
+///
+/// Random passes deliberately do not store generator state: anyone holding the
+/// checkpoint could otherwise regenerate the data written to the drive. A
+/// resumed random pass continues with freshly seeded output instead, and the
+/// checkpoint records where each such new stream begins.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WipeCheckpoint {
+    pub device_path: String,
+    pub serial: String,
+    pub total_size: u64,
+    pub algorithm: String,
+    pub current_pass: usize,  // Zero-based index of the pass in progress
+    pub bytes_written: u64,   // Offset reached (and synced) within the current pass
+    #[serde(default)]
+    pub bad_sectors: Vec<u64>, // LBAs skipped as unwritable by any pass so far
+    #[serde(default)]
+    pub random_restarts: Vec<RandomRestart>, // Random passes resumed with a new generator
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A random pass continued part-way by a later run. Data before `offset` and
+/// from `offset` on come from two unrelated generator streams.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RandomRestart {
+    pub pass: usize,
+    pub offset: u64,
+}
+
+/// Tracks and periodically persists a `WipeCheckpoint` for one drive
+pub struct Checkpointer {
+    state: Option<WipeCheckpoint>, // None when checkpointing is disabled
+    path: PathBuf,
+    saved_offset: u64,
+    last_save: Instant,
+    bad_sectors: BTreeSet<u64>, // Kept after finish() for the certificate
+}
+
+impl Checkpointer {
+    /// Directory from `$SAYONARA_CHECKPOINT_DIR` or the default location
+    pub fn default_dir() -> PathBuf {
+        std::env::var_os(CHECKPOINT_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CHECKPOINT_DIR))
+    }
+
+    /// Checkpointer for `drive` in the default directory
+    pub fn open(drive: &DriveInfo, algorithm: &str, resume: bool) -> Result<Self> {
+        Self::open_in(Self::default_dir(), drive, algorithm, resume)
+    }
+
+    /// Start a new checkpoint, or with `resume` continue the existing one.
+    ///
+    /// Resuming refuses a checkpoint whose serial, size or algorithm no longer
+    /// match: continuing someone else's wipe at an offset would leave the
+    /// start of this drive untouched.
+    pub fn open_in<P: AsRef<Path>>(dir: P, drive: &DriveInfo, algorithm: &str, resume: bool) -> Result<Self> {
+        let path = dir.as_ref().join(Self::filename(drive));
+        let existing = Self::read(&path)?;
+
+        let state = match existing {
+            Some(checkpoint) if resume => {
+                if checkpoint.serial != drive.serial {
+                    bail!("Checkpoint belongs to drive {} but {} has serial {}",
+                          checkpoint.serial, drive.device_path, drive.serial);
+                }
+                if checkpoint.total_size != drive.size {
+                    bail!("Checkpoint was recorded for {} bytes but {} now reports {} bytes",
+                          checkpoint.total_size, drive.device_path, drive.size);
+                }
+                if checkpoint.algorithm != algorithm {
+                    bail!("Checkpoint was recorded for a {} wipe, not {}", checkpoint.algorithm, algorithm);
+                }
+
+                println!("♻️  Resuming {} wipe from pass {} at {} MB (checkpoint from {})",
+                         checkpoint.algorithm,
+                         checkpoint.current_pass + 1,
+                         checkpoint.bytes_written / (1024 * 1024),
+                         checkpoint.updated_at.format("%Y-%m-%d %H:%M:%S UTC"));
+                checkpoint
+            }
+            existing => {
+                if resume {
+                    println!("ℹ️  No checkpoint found for {}, starting from the beginning", drive.serial);
+                } else if existing.is_some() {
+                    println!("ℹ️  Discarding checkpoint of an interrupted wipe (use --resume to continue it)");
+                }
+
+                let now = chrono::Utc::now();
+                WipeCheckpoint {
+                    device_path: drive.device_path.clone(),
+                    serial: drive.serial.clone(),
+                    total_size: drive.size,
+                    algorithm: algorithm.to_string(),
+                    current_pass: 0,
+                    bytes_written: 0,
+                    bad_sectors: Vec::new(),
+                    random_restarts: Vec::new(),
+                    started_at: now,
+                    updated_at: now,
+                }
+            }
+        };
+
+        let mut checkpointer = Self {
+            saved_offset: state.bytes_written,
+            bad_sectors: state.bad_sectors.iter().copied().collect(),
+            state: Some(state),
+            path,
+            last_save: Instant::now(),
+        };
+        checkpointer.save()?;
+        Ok(checkpointer)
+    }
+
+    /// Checkpointer that tracks nothing (callers that cannot resume)
+    pub fn disabled() -> Self {
+        Self {
+            state: None,
+            path: PathBuf::new(),
+            saved_offset: 0,
+            last_save: Instant::now(),
+            bad_sectors: BTreeSet::new(),
+        }
+    }
+
+    pub fn checkpoint(&self) -> Option<&WipeCheckpoint> {
+        self.state.as_ref()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// True if `pass` already finished in an earlier run
+    pub fn is_pass_complete(&self, pass: usize) -> bool {
+        self.state.as_ref().is_some_and(|s| pass < s.current_pass)
+    }
+
+    /// Offset at which `pass` should start writing
+    pub fn resume_offset(&self, pass: usize) -> u64 {
+        match &self.state {
+            Some(s) if s.current_pass == pass => s.bytes_written,
+            _ => 0,
+        }
+    }
+
+    /// Offset at which random `pass` should start writing. Generator state is
+    /// never saved, so resuming part-way records where the new stream begins.
+    pub fn resume_random(&mut self, pass: usize) -> Result<u64> {
+        let offset = self.resume_offset(pass);
+        let Some(state) = self.state.as_mut() else { return Ok(offset) };
+        if offset == 0 {
+            return Ok(0);
+        }
+
+        let restart = RandomRestart { pass, offset };
+        if !state.random_restarts.contains(&restart) {
+            println!("ℹ️  Pass {} continues at {} MB with a freshly seeded generator",
+                     pass + 1, offset / (1024 * 1024));
+            state.random_restarts.push(restart);
+            self.save()?;
+        }
+        Ok(offset)
+    }
+
+    /// Record that `pass` has reached `offset`, persisting it periodically.
+    /// The device is synced before saving so the checkpoint never runs ahead
+    /// of the data on disk.
+    pub fn record(&mut self, handle: &IOHandle, pass: usize, offset: u64) -> IOResult<()> {
+        let Some(state) = self.state.as_mut() else { return Ok(()) };
+        state.current_pass = pass;
+        state.bytes_written = offset;
+
+        if offset.saturating_sub(self.saved_offset) >= SAVE_INTERVAL_BYTES
+            || self.last_save.elapsed() >= SAVE_INTERVAL
+        {
+            handle.sync()?;
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Add sectors a pass had to skip; they are saved with the next checkpoint
+    pub fn record_bad_sectors(&mut self, lbas: &[u64]) {
+        self.bad_sectors.extend(lbas);
+    }
+
+    /// Every LBA skipped as unwritable, including by earlier interrupted runs
+    pub fn bad_sectors(&self) -> Vec<u64> {
+        self.bad_sectors.iter().copied().collect()
+    }
+
+    /// Sync and save the latest recorded offset (used when a pass is aborted)
+    pub fn flush(&mut self, handle: &IOHandle) -> IOResult<()> {
+        if self.state.is_some() {
+            handle.sync()?;
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Mark `pass` as finished; the next run starts at the following pass
+    pub fn complete_pass(&mut self, pass: usize) -> Result<()> {
+        if let Some(state) = self.state.as_mut() {
+            state.current_pass = pass + 1;
+            state.bytes_written = 0;
+        }
+        self.save()
+    }
+
+    /// Remove the checkpoint after the wipe completed
+    pub fn finish(&mut self) {
+        if self.state.take().is_some() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    fn save(&mut self) -> Result<()> {
+        let Some(state) = self.state.as_mut() else { return Ok(()) };
+        state.updated_at = chrono::Utc::now();
+        state.bad_sectors = self.bad_sectors.iter().copied().collect();
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Write atomically so a power loss never leaves a truncated checkpoint
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to save checkpoint {}", self.path.display()))?;
+
+        self.saved_offset = state.bytes_written;
+        self.last_save = Instant::now();
+        Ok(())
+    }
+
+    fn read(path: &Path) -> Result<Option<WipeCheckpoint>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        let checkpoint = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid checkpoint {}", path.display()))?;
+        Ok(Some(checkpoint))
+    }
+
+    /// Keyed by serial so a drive that comes back under another device name
+    /// still finds its checkpoint
+    fn filename(drive: &DriveInfo) -> String {
+        let key = if drive.serial.trim().is_empty() || drive.serial == "Unknown" {
+            drive.device_path.as_str()
+        } else {
+            drive.serial.as_str()
+        };
+
+        let safe: String = key.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+            .collect();
+        format!("{}.json", safe.trim_start_matches('_'))
+    }
+}
@@ -195,6 +195,72 @@ impl IoUringEngine {
         Ok(results)
     }
 
+    /// Register `buffers` so `submit_write` can use them as fixed buffers,
+    /// sparing the kernel from mapping them again for every write
+    ///
+    /// # Safety
+    /// The buffers must stay allocated and in place until `unregister_buffers`.
+    pub unsafe fn register_buffers(&mut self, buffers: &[libc::iovec]) -> IOResult<()> {
+        self.ring.submitter().register_buffers(buffers)
+            .map_err(|e| IOError::OperationFailed(format!("io_uring buffer registration failed: {}", e)))
+    }
+
+    pub fn unregister_buffers(&mut self) -> IOResult<()> {
+        self.ring.submitter().unregister_buffers()
+            .map_err(|e| IOError::OperationFailed(format!("io_uring buffer unregistration failed: {}", e)))
+    }
+
+    /// Queue a write of `data` at `offset` without waiting for it. `slot` is
+    /// handed back by `wait_write` with its completion; with `fixed` it is
+    /// also the registered buffer that `data` lies in.
+    ///
+    /// # Safety
+    /// `data` must stay valid and unmodified until the completion for `slot`
+    /// has been returned by `wait_write`.
+    pub unsafe fn submit_write(
+        &mut self,
+        file: &File,
+        data: &[u8],
+        offset: u64,
+        slot: u16,
+        fixed: bool,
+    ) -> IOResult<()> {
+        let fd = types::Fd(file.as_raw_fd());
+        let entry = if fixed {
+            opcode::WriteFixed::new(fd, data.as_ptr(), data.len() as u32, slot).offset(offset).build()
+        } else {
+            opcode::Write::new(fd, data.as_ptr(), data.len() as u32).offset(offset).build()
+        };
+
+        self.ring
+            .submission()
+            .push(&entry.user_data(slot as u64))
+            .map_err(|e| IOError::OperationFailed(format!("io_uring push failed: {}", e)))?;
+        self.ring.submit()
+            .map_err(|e| IOError::OperationFailed(format!("io_uring submit failed: {}", e)))?;
+        Ok(())
+    }
+
+    /// Wait for the next write submitted with `submit_write` to complete.
+    /// Returns its slot and the bytes written or the error it failed with.
+    pub fn wait_write(&mut self) -> IOResult<(u16, std::io::Result<usize>)> {
+        loop {
+            if let Some(cqe) = self.ring.completion().next() {
+                let result = match cqe.result() {
+                    res if res < 0 => Err(std::io::Error::from_raw_os_error(-res)),
+                    res => Ok(res as usize),
+                };
+                return Ok((cqe.user_data() as u16, result));
+            }
+
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(IOError::OperationFailed(format!("io_uring wait failed: {}", e))),
+            }
+        }
+    }
+
     /// Sync file data to disk
     pub fn fsync(&mut self, file: &File) -> IOResult<()> {
         let fd = types::Fd(file.as_raw_fd());
@@ -279,4 +345,43 @@ mod tests {
         assert_eq!(read, 32);
         assert_eq!(&buffer[..data.len()], data);
     }
+
+    #[test]
+    fn test_queued_writes_complete_by_slot() {
+        if !IoUringEngine::is_available() {
+            println!("io_uring not available, skipping test");
+            return;
+        }
+
+        let temp = NamedTempFile::new().unwrap();
+        let file = temp.reopen().unwrap();
+        let mut engine = IoUringEngine::new(4).unwrap();
+
+        let mut buffers: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i + 1; 4096]).collect();
+        let iovecs: Vec<libc::iovec> = buffers.iter_mut()
+            .map(|b| libc::iovec { iov_base: b.as_mut_ptr().cast(), iov_len: b.len() })
+            .collect();
+        let fixed = unsafe { engine.register_buffers(&iovecs) }.is_ok();
+
+        for (slot, buffer) in buffers.iter().enumerate() {
+            unsafe { engine.submit_write(&file, buffer, slot as u64 * 4096, slot as u16, fixed).unwrap() };
+        }
+        let mut slots: Vec<u16> = (0..4)
+            .map(|_| {
+                let (slot, result) = engine.wait_write().unwrap();
+                assert_eq!(result.unwrap(), 4096);
+                slot
+            })
+            .collect();
+        slots.sort();
+        assert_eq!(slots, vec![0, 1, 2, 3]);
+
+        if fixed {
+            engine.unregister_buffers().unwrap();
+        }
+        let data = std::fs::read(temp.path()).unwrap();
+        for (i, chunk) in data.chunks(4096).enumerate() {
+            assert!(chunk.iter().all(|&b| b == i as u8 + 1));
+        }
+    }
 }
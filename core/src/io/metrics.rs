@@ -213,83 +213,218 @@ impl PerformanceStats {
     }
 }
 
-/// Automatic performance tuner
+/// Throughput the tuner has to keep after warmup, as a share of the
+/// baseline, before it grows the buffers
+const DEGRADED_THROUGHPUT: f64 = 0.8;
+
+/// Window latency above which the in-flight depth is reduced
+const HIGH_LATENCY: Duration = Duration::from_millis(100);
+
+/// Window latency below which the in-flight depth may be raised; the gap to
+/// `HIGH_LATENCY` keeps the depth from oscillating
+const LOW_LATENCY: Duration = Duration::from_millis(10);
+
+/// No decisions before this, while caches and queues settle
+const WARMUP: Duration = Duration::from_secs(10);
+
+/// Writes are aggregated into windows this long before they are judged
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Consecutive windows a condition has to hold before it is acted on
+const CONFIRM_WINDOWS: u32 = 3;
+
+/// Windows after a change during which nothing else is changed, so the
+/// effect of the change is measured before the next one
+const COOLDOWN_WINDOWS: u32 = 5;
+
+/// Throughput, latency and IOPS of one tuning window
+#[derive(Debug, Clone, Copy)]
+pub struct WindowStats {
+    pub throughput_bps: u64,
+    pub avg_latency: Duration,
+    pub iops: u64,
+}
+
+/// A change the tuner made, for the performance report
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningEvent {
+    pub at: Duration,        // Since the tuner started
+    pub buffer_size: usize,  // Parameters from then on
+    pub queue_depth: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Adjustment {
+    GrowBuffer,
+    ReduceDepth,
+    RaiseDepth,
+}
+
+struct TunerState {
+    buffer_size: usize,
+    queue_depth: usize,
+    baseline_throughput: Option<u64>,
+    window_start: Instant,
+    window_bytes: u64,
+    window_ops: u64,
+    window_latency: Duration,
+    pending: Option<(Adjustment, u32)>,  // Condition seen in this many windows in a row
+    cooldown: u32,
+    timeline: Vec<TuningEvent>,
+}
+
+/// Automatic performance tuner.
+///
+/// Completed writes are grouped into one-second windows. After a warmup the
+/// first window sets the baseline throughput; later windows grow the buffers
+/// when throughput falls well below it and move the in-flight depth with
+/// latency. A condition has to persist for several windows before anything
+/// changes, and each change is followed by a cooldown. The handle applies
+/// the returned parameters to its buffer pool and queue.
 pub struct PerformanceTuner {
     metrics: IOMetrics,
-    baseline_throughput: Arc<Mutex<Option<u64>>>,
-    buffer_size: Arc<Mutex<usize>>,
-    queue_depth: Arc<Mutex<usize>>,
+    max_buffer_size: usize,
+    max_queue_depth: usize,
+    state: Mutex<TunerState>,
 }
 
 impl PerformanceTuner {
     pub fn new() -> Self {
+        // Start with 4MB and 4 in flight, as the default config does
+        Self::bounded(4 * 1024 * 1024, 16 * 1024 * 1024, 4, 32)
+    }
+
+    /// Tuner starting from `config`'s buffer size and queue depth, bounded by
+    /// its `max_buffer_size` and `max_queue_depth()`
+    pub fn for_config(config: &super::IOConfig) -> Self {
+        Self::bounded(config.initial_buffer_size, config.max_buffer_size,
+                          config.queue_depth, config.max_queue_depth())
+    }
+
+    fn bounded(buffer_size: usize, max_buffer_size: usize, queue_depth: usize, max_queue_depth: usize) -> Self {
+        let queue_depth = queue_depth.max(1);
         Self {
             metrics: IOMetrics::new(),
-            baseline_throughput: Arc::new(Mutex::new(None)),
-            buffer_size: Arc::new(Mutex::new(4 * 1024 * 1024)), // Start with 4MB
-            queue_depth: Arc::new(Mutex::new(4)),
+            max_buffer_size: max_buffer_size.max(buffer_size),
+            max_queue_depth: max_queue_depth.max(queue_depth),
+            state: Mutex::new(TunerState {
+                buffer_size,
+                queue_depth,
+                baseline_throughput: None,
+                window_start: Instant::now(),
+                window_bytes: 0,
+                window_ops: 0,
+                window_latency: Duration::ZERO,
+                pending: None,
+                cooldown: 0,
+                timeline: Vec::new(),
+            }),
         }
     }
 
-    /// Record operation and check for tuning opportunities
+    /// Record a completed operation and return the buffer size and queue
+    /// depth to use from now on
     pub fn record_and_tune(&self, bytes: u64, latency: Duration) -> (usize, usize) {
         self.metrics.record_operation(bytes, latency);
 
-        // Update baseline after warmup period (10 seconds)
-        if self.metrics.start_time.elapsed() > Duration::from_secs(10) {
-            let mut baseline = self.baseline_throughput.lock().unwrap();
-            if baseline.is_none() {
-                *baseline = Some(self.metrics.throughput());
-            }
-        }
+        let window = {
+            let mut state = self.state.lock().unwrap();
+            state.window_bytes += bytes;
+            state.window_ops += 1;
+            state.window_latency += latency;
 
-        // Check if we should tune parameters
-        let current_stats = self.metrics.stats();
-        self.tune_if_needed(&current_stats);
+            let length = state.window_start.elapsed();
+            if length < WINDOW {
+                return (state.buffer_size, state.queue_depth);
+            }
 
-        // Return current parameters
-        (*self.buffer_size.lock().unwrap(), *self.queue_depth.lock().unwrap())
+            let window = WindowStats {
+                throughput_bps: (state.window_bytes as f64 / length.as_secs_f64()) as u64,
+                avg_latency: state.window_latency / state.window_ops as u32,
+                iops: (state.window_ops as f64 / length.as_secs_f64()) as u64,
+            };
+            state.window_start = Instant::now();
+            state.window_bytes = 0;
+            state.window_ops = 0;
+            state.window_latency = Duration::ZERO;
+            window
+        };
+
+        self.evaluate(window, self.metrics.start_time.elapsed());
+        (self.buffer_size(), self.queue_depth())
     }
 
-    /// Adaptive tuning based on performance
-    fn tune_if_needed(&self, stats: &PerformanceStats) {
-        // Only tune after warmup
-        if stats.elapsed < Duration::from_secs(10) {
-            return;
+    /// Judge one window, `elapsed` after the tuner started, and change the
+    /// parameters if a condition has held long enough
+    pub(crate) fn evaluate(&self, window: WindowStats, elapsed: Duration) -> Option<TuningEvent> {
+        let mut state = self.state.lock().unwrap();
+
+        if elapsed < WARMUP {
+            return None;
+        }
+        let Some(baseline) = state.baseline_throughput else {
+            state.baseline_throughput = Some(window.throughput_bps);
+            return None;
+        };
+        if state.cooldown > 0 {
+            state.cooldown -= 1;
+            return None;
         }
 
-        let baseline = self.baseline_throughput.lock().unwrap();
-        if let Some(baseline_throughput) = *baseline {
-            let current = stats.throughput_bps;
-
-            // If throughput is degrading, try increasing buffer size
-            if current < baseline_throughput * 8 / 10 {  // < 80% of baseline
-                let mut buffer_size = self.buffer_size.lock().unwrap();
-                if *buffer_size < 16 * 1024 * 1024 {  // Max 16MB
-                    *buffer_size *= 2;
-                    println!("📈 Tuning: Increased buffer size to {} MB",
-                             *buffer_size / (1024 * 1024));
-                }
-            }
+        let share = window.throughput_bps as f64 / baseline.max(1) as f64;
+        let wanted = if window.avg_latency > HIGH_LATENCY && state.queue_depth > 1 {
+            Some(Adjustment::ReduceDepth)
+        } else if share < DEGRADED_THROUGHPUT && state.buffer_size < self.max_buffer_size {
+            Some(Adjustment::GrowBuffer)
+        } else if window.avg_latency < LOW_LATENCY && window.iops < 1000 && state.queue_depth < self.max_queue_depth {
+            Some(Adjustment::RaiseDepth)
+        } else {
+            None
+        };
 
-            // If latency is too high, reduce queue depth
-            if stats.avg_latency > Duration::from_millis(100) {
-                let mut queue_depth = self.queue_depth.lock().unwrap();
-                if *queue_depth > 2 {
-                    *queue_depth = (*queue_depth * 3) / 4;  // Reduce by 25%
-                    println!("📉 Tuning: Reduced queue depth to {}", *queue_depth);
-                }
+        let seen = match (wanted, state.pending) {
+            (None, _) => {
+                state.pending = None;
+                return None;
             }
+            (Some(wanted), Some((pending, seen))) if wanted == pending => seen + 1,
+            (Some(_), _) => 1,
+        };
+        let adjustment = wanted?;
+        if seen < CONFIRM_WINDOWS {
+            state.pending = Some((adjustment, seen));
+            return None;
+        }
 
-            // If IOPS is low and latency is good, increase queue depth
-            if stats.iops < 1000 && stats.avg_latency < Duration::from_millis(10) {
-                let mut queue_depth = self.queue_depth.lock().unwrap();
-                if *queue_depth < 32 {
-                    *queue_depth += 2;
-                    println!("📈 Tuning: Increased queue depth to {}", *queue_depth);
-                }
+        let reason = match adjustment {
+            Adjustment::GrowBuffer => {
+                state.buffer_size = (state.buffer_size * 2).min(self.max_buffer_size);
+                println!("📈 Tuning: Increased buffer size to {} MB", state.buffer_size / (1024 * 1024));
+                format!("throughput {:.0}% of baseline", share * 100.0)
             }
-        }
+            Adjustment::ReduceDepth => {
+                state.queue_depth = (state.queue_depth * 3 / 4).max(1);
+                println!("📉 Tuning: Reduced queue depth to {}", state.queue_depth);
+                format!("latency {:.1}ms", window.avg_latency.as_secs_f64() * 1000.0)
+            }
+            Adjustment::RaiseDepth => {
+                state.queue_depth = (state.queue_depth + 2).min(self.max_queue_depth);
+                println!("📈 Tuning: Increased queue depth to {}", state.queue_depth);
+                format!("latency {:.1}ms at {} IOPS", window.avg_latency.as_secs_f64() * 1000.0, window.iops)
+            }
+        };
+
+        let event = TuningEvent {
+            at: elapsed,
+            buffer_size: state.buffer_size,
+            queue_depth: state.queue_depth,
+            reason,
+        };
+        state.pending = None;
+        state.cooldown = COOLDOWN_WINDOWS;
+        state.timeline.push(event.clone());
+        Some(event)
     }
 
     /// Get current metrics
@@ -299,12 +434,17 @@ impl PerformanceTuner {
 
     /// Get current buffer size
     pub fn buffer_size(&self) -> usize {
-        *self.buffer_size.lock().unwrap()
+        self.state.lock().unwrap().buffer_size
     }
 
     /// Get current queue depth
     pub fn queue_depth(&self) -> usize {
-        *self.queue_depth.lock().unwrap()
+        self.state.lock().unwrap().queue_depth
+    }
+
+    /// Changes made so far, oldest first
+    pub fn timeline(&self) -> Vec<TuningEvent> {
+        self.state.lock().unwrap().timeline.clone()
     }
 }
 
@@ -4,14 +4,18 @@ pub mod platform_specific;
 pub mod metrics;
 pub mod io_uring_engine;
 pub mod mmap_engine;
+pub mod checkpoint;
+pub mod rate_limiter;
 
 #[cfg(test)]
 mod tests;
 
 // Re-exports
-pub use optimized_engine::{OptimizedIO, IOConfig, IOHandle};
+pub use optimized_engine::{OptimizedIO, IOConfig, IOHandle, Extent};
 pub use buffer_pool::{BufferPool, AlignedBuffer};
-pub use metrics::{IOMetrics, PerformanceStats};
+pub use metrics::{IOMetrics, PerformanceStats, TuningEvent};
+pub use rate_limiter::{RateLimiter, TokenBucket, WriteThrottle};
+pub use checkpoint::{Checkpointer, RandomRestart, WipeCheckpoint, CHECKPOINT_DIR_ENV, DEFAULT_CHECKPOINT_DIR};
 
 use std::time::Duration;
 
@@ -118,3 +122,22 @@ pub enum ThrottleAction {
     Slow(f64),  // Reduce speed by this factor (0.0-1.0)
     Pause(Duration),
 }
+
+impl ThrottleAction {
+    /// Throttling for a drive at `temp` degrees with the given threshold
+    pub fn for_temperature(temp: u32, threshold: u32) -> Self {
+        if temp < threshold {
+            ThrottleAction::None
+        } else if temp < threshold + 5 {
+            // Slow down by 25%
+            ThrottleAction::Slow(0.75)
+        } else if temp < threshold + 10 {
+            // Slow down by 50%
+            ThrottleAction::Slow(0.50)
+        } else {
+            // Pause for cooling
+            let pause_secs = ((temp - threshold) / 5) as u64;
+            ThrottleAction::Pause(Duration::from_secs(pause_secs.min(30)))
+        }
+    }
+}
@@ -2,12 +2,24 @@
 
 use super::*;
 use super::buffer_pool::{BufferPool, PooledBuffer, PAGE_SIZE};
-use super::metrics::{IOMetrics, PerformanceTuner};
+use super::checkpoint::Checkpointer;
+use super::io_uring_engine::IoUringEngine;
+use super::metrics::{IOMetrics, PerformanceTuner, TuningEvent};
 use super::platform_specific::{PlatformIO, get_platform_io};
+use super::rate_limiter::{RateLimiter, WriteThrottle};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
 use std::sync::Arc;
-use std::time::Instant;
-use crate::drives::operations::smart::SMARTMonitor;
+use std::time::{Duration, Instant};
+use crate::control::{CancellationToken, PauseToken, QuietHours};
+use crate::events::{self, WipeEvent};
+use crate::DriveType;
+
+/// Cap on buffer memory kept in flight by queued writes
+const MAX_BYTES_IN_FLIGHT: u64 = 256 * 1024 * 1024;
+
+/// How often a paused write loop checks whether it may continue
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(200);
 
 /// I/O Configuration
 #[derive(Debug, Clone)]
@@ -38,6 +50,20 @@ pub struct IOConfig {
 
     /// Target efficiency (percentage of drive's max speed)
     pub target_efficiency: f64,
+
+    /// Keep writing past unwritable sectors: a write that fails with a medium
+    /// error is narrowed down to the failing sectors, which are retried and
+    /// then recorded instead of aborting the whole operation
+    pub resilient_writes: bool,
+
+    /// Retries for a single failing sector before it is recorded as unwritable
+    pub sector_write_retries: u32,
+
+    /// Write bandwidth ceiling for this handle (None for unlimited)
+    pub max_bytes_per_sec: Option<u64>,
+
+    /// Write operations per second ceiling for this handle (None for unlimited)
+    pub max_iops: Option<u64>,
 }
 
 impl Default for IOConfig {
@@ -52,11 +78,26 @@ impl Default for IOConfig {
             temperature_check_interval: 100 * 1024 * 1024,  // 100MB
             adaptive_tuning: true,
             target_efficiency: 95.0,
+            resilient_writes: true,
+            sector_write_retries: 3,
+            max_bytes_per_sec: None,
+            max_iops: None,
         }
     }
 }
 
 impl IOConfig {
+    /// Most writes the adaptive tuner may keep in flight: twice the
+    /// configured depth, within `max_buffers`
+    pub fn max_queue_depth(&self) -> usize {
+        let depth = self.queue_depth.max(1);
+        if self.adaptive_tuning {
+            (depth * 2).min(self.max_buffers).max(depth)
+        } else {
+            depth
+        }
+    }
+
     /// Create config optimized for drive speed
     pub fn for_drive_speed(speed: DriveSpeed) -> Self {
         let mut config = Self::default();
@@ -65,6 +106,16 @@ impl IOConfig {
         config
     }
 
+    /// Create config for a drive type
+    pub fn for_drive_type(drive_type: &DriveType) -> Self {
+        match drive_type {
+            DriveType::NVMe => Self::nvme_optimized(),
+            DriveType::SSD => Self::sata_ssd_optimized(),
+            DriveType::HDD => Self::hdd_optimized(),
+            _ => Self::default(),
+        }
+    }
+
     /// Create config for NVMe drives
     pub fn nvme_optimized() -> Self {
         Self {
@@ -77,6 +128,10 @@ impl IOConfig {
             temperature_check_interval: 500 * 1024 * 1024,  // 500MB
             adaptive_tuning: true,
             target_efficiency: 95.0,
+            resilient_writes: true,
+            sector_write_retries: 3,
+            max_bytes_per_sec: None,
+            max_iops: None,
         }
     }
 
@@ -92,6 +147,10 @@ impl IOConfig {
             temperature_check_interval: 200 * 1024 * 1024,  // 200MB
             adaptive_tuning: true,
             target_efficiency: 95.0,
+            resilient_writes: true,
+            sector_write_retries: 3,
+            max_bytes_per_sec: None,
+            max_iops: None,
         }
     }
 
@@ -107,6 +166,10 @@ impl IOConfig {
             temperature_check_interval: 50 * 1024 * 1024,   // 50MB
             adaptive_tuning: true,
             target_efficiency: 90.0,  // HDDs have more overhead
+            resilient_writes: true,
+            sector_write_retries: 3,
+            max_bytes_per_sec: None,
+            max_iops: None,
         }
     }
 
@@ -122,6 +185,10 @@ impl IOConfig {
             temperature_check_interval: 500 * 1024 * 1024,  // 500MB
             adaptive_tuning: true,
             target_efficiency: 95.0,
+            resilient_writes: true,
+            sector_write_retries: 3,
+            max_bytes_per_sec: None,
+            max_iops: None,
         }
     }
 
@@ -137,10 +204,21 @@ impl IOConfig {
             temperature_check_interval: u64::MAX,  // No temp checks for small ops
             adaptive_tuning: false,  // Fixed config for small reads
             target_efficiency: 80.0,
+            resilient_writes: false,  // Nothing to recover for reads
+            sector_write_retries: 0,
+            max_bytes_per_sec: None,
+            max_iops: None,
         }
     }
 }
 
+/// Contiguous byte range of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extent {
+    pub offset: u64,  // Device offset of the first byte
+    pub length: u64,
+}
+
 /// Optimized I/O Handle
 pub struct IOHandle {
     file: File,
@@ -150,8 +228,21 @@ pub struct IOHandle {
     platform_io: Box<dyn PlatformIO>,
     config: IOConfig,
     pub(crate) device_path: String,
-    bytes_since_temp_check: Arc<std::sync::Mutex<u64>>,
-    temperature_monitoring_disabled: Arc<std::sync::atomic::AtomicBool>,
+    sector_size: u64,
+    bad_sectors: BTreeSet<u64>,
+    /// Set when sequential writes are queued through io_uring instead of pwrite
+    uring: Option<IoUringEngine>,
+    /// Set by `restrict_to`; offsets are then relative to its start
+    extent: Option<Extent>,
+    /// Writes queued at once; lowered and raised by the tuner
+    queue_depth: usize,
+    /// Stops sequential writes with `IOError::Interrupted` once cancelled
+    cancel: CancellationToken,
+    /// Holds sequential writes between buffers while paused
+    pause: PauseToken,
+    /// Limits from `IOConfig`, shared with other handles, quiet hours and
+    /// thermal throttling
+    limits: WriteThrottle,
 }
 
 impl IOHandle {
@@ -159,17 +250,122 @@ impl IOHandle {
     pub fn write_at(&mut self, data: &[u8], offset: u64) -> IOResult<usize> {
         let start = Instant::now();
 
-        let written = self.platform_io.write_optimized(&self.file, data, offset)?;
+        self.check_within_extent(offset, data.len() as u64)?;
+        let written = self.platform_io.write_optimized(&self.file, data, self.device_offset(offset))?;
 
         let latency = start.elapsed();
         self.metrics.record_operation(written as u64, latency);
 
         // Temperature check
-        self.check_temperature_if_needed(written as u64)?;
+        self.check_temperature(written as u64);
 
         Ok(written)
     }
 
+    /// Write all of `data` at `offset`, continuing after short writes.
+    ///
+    /// With `resilient_writes`, a medium error does not fail the write: the
+    /// failed range is bisected down to single sectors, each bad sector is
+    /// retried and, if it still fails, skipped and recorded in `bad_sectors()`.
+    pub fn write_all_at(&mut self, data: &[u8], offset: u64) -> IOResult<()> {
+        match self.write_fully(data, offset) {
+            Err((done, e)) if self.config.resilient_writes && is_medium_error(&e) => {
+                self.write_around_bad_sectors(&data[done..], offset + done as u64)
+            }
+            result => result.map_err(|(_, e)| e),
+        }
+    }
+
+    /// LBAs that could not be written, in ascending order
+    pub fn bad_sectors(&self) -> Vec<u64> {
+        self.bad_sectors.iter().copied().collect()
+    }
+
+    /// Size of the buffers handed to `fill_buffer` by the sequential writers
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_pool.stats().buffer_size
+    }
+
+    /// Whether sequential writes go through io_uring
+    pub fn uses_io_uring(&self) -> bool {
+        self.uring.is_some()
+    }
+
+    /// Writes the io_uring path keeps in flight
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    /// Stop sequential writes on this handle when `token` is cancelled
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    /// Hold sequential writes on this handle while `token` is paused
+    pub fn set_pause(&mut self, token: PauseToken) {
+        self.pause = token;
+    }
+
+    /// Throttle sequential writes to the quiet hours ceiling while the
+    /// window is in effect
+    pub fn set_quiet_hours(&mut self, quiet_hours: Option<QuietHours>) {
+        self.limits.set_quiet_hours(quiet_hours);
+    }
+
+    /// Also count sequential writes against `limiter`, which other handles
+    /// may share
+    pub fn set_shared_limiter(&mut self, limiter: Option<Arc<RateLimiter>>) {
+        self.limits.set_shared(limiter);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn tuner(&self) -> Option<&PerformanceTuner> {
+        self.tuner.as_deref()
+    }
+
+    /// Buffer size and queue depth changes made by the adaptive tuner
+    pub fn tuning_timeline(&self) -> Vec<TuningEvent> {
+        self.tuner.as_ref().map_or_else(Vec::new, |tuner| tuner.timeline())
+    }
+
+    /// Logical sector size the LBAs in `bad_sectors()` refer to
+    pub fn sector_size(&self) -> u64 {
+        self.sector_size
+    }
+
+    /// Confine the handle to `extent`. Offsets passed to reads and writes are
+    /// then relative to its start; writes reaching past its end are refused
+    /// and reads stop there. Bad sectors are still recorded as device LBAs.
+    pub fn restrict_to(&mut self, extent: Extent) -> IOResult<()> {
+        if !extent.offset.is_multiple_of(self.sector_size) || !extent.length.is_multiple_of(self.sector_size) {
+            return Err(IOError::AlignmentError(format!(
+                "Extent at {} of {} bytes is not aligned to {} byte sectors",
+                extent.offset, extent.length, self.sector_size
+            )));
+        }
+        self.extent = Some(extent);
+        Ok(())
+    }
+
+    pub fn extent(&self) -> Option<Extent> {
+        self.extent
+    }
+
+    /// Device offset of handle offset `offset`
+    pub fn device_offset(&self, offset: u64) -> u64 {
+        self.extent.map_or(offset, |extent| extent.offset + offset)
+    }
+
+    fn check_within_extent(&self, offset: u64, len: u64) -> IOResult<()> {
+        match self.extent {
+            Some(extent) if offset + len > extent.length => Err(IOError::OperationFailed(format!(
+                "Write of {} bytes at {} reaches past the {} byte extent at device offset {}",
+                len, offset, extent.length, extent.offset
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     /// Write entire buffer using optimal I/O
     pub fn write_buffer(&mut self, buffer: &PooledBuffer, offset: u64) -> IOResult<usize> {
         self.write_at(buffer.as_slice(), offset)
@@ -179,7 +375,15 @@ impl IOHandle {
     pub fn read_at(&mut self, buffer: &mut [u8], offset: u64) -> IOResult<usize> {
         let start = Instant::now();
 
-        let read = self.platform_io.read_optimized(&self.file, buffer, offset)?;
+        // Reads end at the extent like they would at the end of the device
+        let len = match self.extent {
+            Some(extent) => extent.length.saturating_sub(offset).min(buffer.len() as u64) as usize,
+            None => buffer.len(),
+        };
+        if len == 0 {
+            return Ok(0);
+        }
+        let read = self.platform_io.read_optimized(&self.file, &mut buffer[..len], self.device_offset(offset))?;
 
         let latency = start.elapsed();
         self.metrics.record_operation(read as u64, latency);
@@ -207,77 +411,153 @@ impl IOHandle {
         self.buffer_pool.acquire()
     }
 
-    /// Check temperature and throttle if needed
-    fn check_temperature_if_needed(&mut self, bytes_written: u64) -> IOResult<()> {
-        // Skip if temperature monitoring is disabled
-        if self.temperature_monitoring_disabled.load(std::sync::atomic::Ordering::Relaxed) {
-            return Ok(());
+    /// On failure, also returns how many bytes were written before the error
+    fn write_fully(&mut self, data: &[u8], offset: u64) -> Result<(), (usize, IOError)> {
+        let mut done = 0;
+        while done < data.len() {
+            match self.write_at(&data[done..], offset + done as u64) {
+                Ok(0) => {
+                    return Err((done, IOError::OperationFailed(
+                        format!("Partial write: {} of {} bytes", done, data.len())
+                    )));
+                }
+                Ok(written) => done += written,
+                Err(e) => return Err((done, e)),
+            }
         }
+        Ok(())
+    }
 
-        let mut bytes_since_check = self.bytes_since_temp_check.lock().unwrap();
-        *bytes_since_check += bytes_written;
-
-        if *bytes_since_check >= self.config.temperature_check_interval {
-            *bytes_since_check = 0;
-            drop(bytes_since_check);
+    /// Split a failed range on a sector boundary and write each half,
+    /// recursing into whichever half fails again
+    fn write_around_bad_sectors(&mut self, data: &[u8], offset: u64) -> IOResult<()> {
+        let sector_size = self.sector_size as usize;
+        if data.len() <= sector_size {
+            return self.retry_sector(data, offset);
+        }
 
-            match SMARTMonitor::monitor_temperature(&self.device_path) {
-                Ok(temp_monitor) => {
-                    if temp_monitor.current_celsius > self.config.temperature_threshold {
-                        let throttle = self.calculate_throttle(temp_monitor.current_celsius);
-                        self.apply_throttle(throttle)?;
-                    }
-                }
-                Err(_) => {
-                    // Temperature monitoring failed - disable it and warn once
-                    eprintln!("⚠️  WARNING: Could not read temperature sensor");
-                    eprintln!("   Temperature monitoring will be disabled.");
-                    self.temperature_monitoring_disabled.store(true, std::sync::atomic::Ordering::Relaxed);
+        let mid = (data.len() / sector_size / 2).max(1) * sector_size;
+        for (part, at) in [(&data[..mid], offset), (&data[mid..], offset + mid as u64)] {
+            match self.write_fully(part, at) {
+                Ok(()) => {}
+                Err((done, e)) if is_medium_error(&e) => {
+                    self.write_around_bad_sectors(&part[done..], at + done as u64)?;
                 }
+                Err((_, e)) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn retry_sector(&mut self, data: &[u8], offset: u64) -> IOResult<()> {
+        let lba = self.device_offset(offset) / self.sector_size;
+
+        for _ in 0..self.config.sector_write_retries {
+            match self.write_fully(data, offset) {
+                Ok(()) => return Ok(()),
+                Err((_, e)) if is_medium_error(&e) => continue,
+                Err((_, e)) => return Err(e),
             }
         }
 
+        if self.bad_sectors.insert(lba) {
+            eprintln!("⚠️  LBA {} could not be written after {} retries, skipping it",
+                      lba, self.config.sector_write_retries);
+        }
         Ok(())
     }
 
+    /// Block while paused. Checkpoints stay where the last write left them,
+    /// so a pause survives an interruption like any other stop.
+    fn wait_while_paused(&mut self) -> IOResult<()> {
+        if !self.pause.is_paused() {
+            return Ok(());
+        }
+
+        println!("\n⏸️  Paused {}", self.device_path);
+        events::emit(WipeEvent::Paused { device: self.device_path.clone() });
+        while self.pause.is_paused() {
+            if self.cancel.is_cancelled() {
+                return Err(IOError::Interrupted);
+            }
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        self.limits.discard_interval();
+        println!("▶️  Resumed {}", self.device_path);
+        events::emit(WipeEvent::Resumed { device: self.device_path.clone() });
+        Ok(())
+    }
+
+    /// Account for a sequential write of `bytes` and sleep until every limit
+    /// that applies allows the next: the handle's own, the shared one, quiet
+    /// hours and thermal throttling
+    fn throttle(&mut self, bytes: u64) {
+        let wait = self.limits.reserve(bytes);
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Count `bytes` toward the next temperature reading
+    fn check_temperature(&mut self, bytes: u64) {
+        let (platform_io, device_path) = (&self.platform_io, &self.device_path);
+        self.limits.check_temperature(bytes, || platform_io.temperature(device_path));
+    }
+
     /// Calculate throttling action based on temperature
+    #[cfg(test)]
     pub(crate) fn calculate_throttle(&self, temp: u32) -> ThrottleAction {
-        let threshold = self.config.temperature_threshold;
-
-        if temp < threshold {
-            ThrottleAction::None
-        } else if temp < threshold + 5 {
-            // Slow down by 25%
-            ThrottleAction::Slow(0.75)
-        } else if temp < threshold + 10 {
-            // Slow down by 50%
-            ThrottleAction::Slow(0.50)
-        } else {
-            // Pause for cooling
-            let pause_secs = ((temp - threshold) / 5) as u64;
-            ThrottleAction::Pause(std::time::Duration::from_secs(pause_secs.min(30)))
-        }
+        ThrottleAction::for_temperature(temp, self.config.temperature_threshold)
     }
 
     /// Apply throttling action
-    fn apply_throttle(&self, action: ThrottleAction) -> IOResult<()> {
-        match action {
-            ThrottleAction::None => Ok(()),
-            ThrottleAction::Slow(factor) => {
-                println!("🌡️  Temperature throttling: Reducing speed to {:.0}%", factor * 100.0);
-                // Implement by adding delays between writes
-                std::thread::sleep(std::time::Duration::from_millis(
-                    ((1.0 - factor) * 100.0) as u64
-                ));
-                Ok(())
-            }
-            ThrottleAction::Pause(duration) => {
-                println!("🌡️  Temperature too high! Pausing for {:?} to cool down", duration);
-                std::thread::sleep(duration);
-                Ok(())
+    #[cfg(test)]
+    pub(crate) fn apply_throttle(&mut self, action: ThrottleAction) -> IOResult<()> {
+        self.limits.apply_thermal(action);
+        Ok(())
+    }
+}
+
+/// Errors the drive reports for sectors it cannot write (EIO, or ENODATA
+/// for a medium error), as opposed to the device going away
+fn is_medium_error(error: &IOError) -> bool {
+    match error {
+        IOError::IoError(e) => matches!(e.raw_os_error(), Some(libc::EIO) | Some(libc::ENODATA)),
+        _ => false,
+    }
+}
+
+fn format_buffer_size(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{} MB", bytes / (1024 * 1024))
+    } else {
+        format!("{} KB", bytes / 1024)
+    }
+}
+
+/// Logical sector size of a block device; 512 for anything else
+fn logical_sector_size(file: &File) -> u64 {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::io::AsRawFd;
+
+        // _IO(0x12, 104)
+        const BLKSSZGET: libc::c_ulong = 0x1268;
+
+        if file.metadata().is_ok_and(|m| m.file_type().is_block_device()) {
+            let mut size: libc::c_int = 0;
+            // SAFETY: BLKSSZGET writes a single int
+            let ret = unsafe { libc::ioctl(file.as_raw_fd(), BLKSSZGET as _, &mut size) };
+            if ret == 0 && size > 0 {
+                return size as u64;
             }
         }
     }
+    #[cfg(not(target_os = "linux"))]
+    let _ = file;
+
+    512
 }
 
 /// Optimized I/O Engine
@@ -286,7 +566,36 @@ pub struct OptimizedIO;
 impl OptimizedIO {
     /// Open a device/file with optimized I/O
     pub fn open(device_path: &str, config: IOConfig) -> IOResult<IOHandle> {
-        let platform_io = get_platform_io();
+        let queue_depth = config.queue_depth;
+        let max_queue_depth = config.max_queue_depth();
+        let mut handle = Self::open_with(device_path, config, get_platform_io())?;
+
+        // Deep queues only pay off when several writes can be in flight. The
+        // ring is sized for the deepest queue the tuner may ask for.
+        if queue_depth > 1 && IoUringEngine::is_available() {
+            #[cfg(target_os = "linux")]
+            {
+                handle.uring = IoUringEngine::new(max_queue_depth as u32).ok();
+            }
+            #[cfg(not(target_os = "linux"))]
+            let _ = max_queue_depth;
+        }
+
+        if handle.config.initial_buffer_size >= 1024 * 1024 {
+            let engine = if handle.uses_io_uring() { "io_uring" } else { "pwrite" };
+            println!("   Engine: {}", engine);
+        }
+
+        Ok(handle)
+    }
+
+    /// Open with a specific platform I/O implementation. Writes always use
+    /// its pwrite path, never io_uring.
+    pub(crate) fn open_with(
+        device_path: &str,
+        config: IOConfig,
+        platform_io: Box<dyn PlatformIO>,
+    ) -> IOResult<IOHandle> {
 
         // Only print for large operations (not detection/sampling)
         if config.initial_buffer_size >= 1024 * 1024 {
@@ -299,14 +608,10 @@ impl OptimizedIO {
 
         // Open file with platform-specific optimizations
         let file = platform_io.open_optimized(device_path, config.use_direct_io)?;
+        let sector_size = logical_sector_size(&file);
 
         // Create buffer pool
-        let alignment = if config.use_direct_io { PAGE_SIZE } else { 8 };
-        let buffer_pool = Arc::new(BufferPool::new(
-            config.initial_buffer_size,
-            alignment,
-            config.max_buffers,
-        ));
+        let buffer_pool = Self::buffer_pool(&config, config.initial_buffer_size);
 
         // Pre-allocate some buffers
         buffer_pool.preallocate(config.queue_depth)?;
@@ -316,11 +621,15 @@ impl OptimizedIO {
 
         // Create performance tuner if adaptive tuning is enabled
         let tuner = if config.adaptive_tuning {
-            Some(Arc::new(PerformanceTuner::new()))
+            Some(Arc::new(PerformanceTuner::for_config(&config)))
         } else {
             None
         };
 
+        let queue_depth = config.queue_depth.max(1);
+        let mut limits = WriteThrottle::new(device_path, RateLimiter::new(config.max_bytes_per_sec, config.max_iops));
+        limits.set_temperature_checks(config.temperature_threshold, config.temperature_check_interval);
+
         Ok(IOHandle {
             file,
             buffer_pool,
@@ -329,30 +638,107 @@ impl OptimizedIO {
             platform_io,
             config,
             device_path: device_path.to_string(),
-            bytes_since_temp_check: Arc::new(std::sync::Mutex::new(0)),
-            temperature_monitoring_disabled: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            sector_size,
+            bad_sectors: BTreeSet::new(),
+            uring: None,
+            extent: None,
+            queue_depth,
+            cancel: CancellationToken::new(),
+            pause: PauseToken::new(),
+            limits,
         })
     }
 
+    /// Pool of `buffer_size` byte buffers, aligned for `config`'s I/O mode
+    fn buffer_pool(config: &IOConfig, buffer_size: usize) -> Arc<BufferPool> {
+        let alignment = if config.use_direct_io { PAGE_SIZE } else { 8 };
+        Arc::new(BufferPool::new(buffer_size, alignment, config.max_buffers))
+    }
+
     /// Perform a full sequential write with optimizations
     pub fn sequential_write<F>(
         handle: &mut IOHandle,
         total_size: u64,
+        fill_buffer: F,
+    ) -> IOResult<()>
+    where
+        F: FnMut(&mut PooledBuffer) -> IOResult<()>,
+    {
+        Self::write_from(handle, 0, total_size, fill_buffer, |_, _| Ok(()))?;
+
+        // Final sync
+        handle.sync()?;
+
+        Ok(())
+    }
+
+    /// Sequential write of one pass that starts where `checkpoint` left off
+    /// and records its progress there, so an interrupted pass can be resumed.
+    pub fn sequential_write_resumable<F>(
+        handle: &mut IOHandle,
+        total_size: u64,
+        checkpoint: &mut Checkpointer,
+        pass: usize,
+        fill_buffer: F,
+    ) -> IOResult<()>
+    where
+        F: FnMut(&mut PooledBuffer) -> IOResult<()>,
+    {
+        let start = checkpoint.resume_offset(pass).min(total_size);
+
+        // Skipped sectors go into every saved checkpoint, since a resumed pass
+        // starts past them and would not find them again
+        let result = Self::write_from(handle, start, total_size, fill_buffer, |handle, offset| {
+            checkpoint.record_bad_sectors(&handle.bad_sectors());
+            checkpoint.record(handle, pass, offset)
+        });
+        checkpoint.record_bad_sectors(&handle.bad_sectors());
+
+        if let Err(e) = result {
+            // Keep whatever progress was made; the error is what matters
+            let _ = checkpoint.flush(handle);
+            return Err(e);
+        }
+
+        handle.sync()?;
+        checkpoint.complete_pass(pass)?;
+
+        Ok(())
+    }
+
+    /// Write `[start, total_size)` sequentially, calling `on_written` with the
+    /// new offset after every completed write
+    fn write_from<F, P>(
+        handle: &mut IOHandle,
+        start: u64,
+        total_size: u64,
         mut fill_buffer: F,
+        mut on_written: P,
     ) -> IOResult<()>
     where
         F: FnMut(&mut PooledBuffer) -> IOResult<()>,
+        P: FnMut(&IOHandle, u64) -> IOResult<()>,
     {
-        let mut offset = 0u64;
-        let buffer_size = handle.buffer_pool.stats().buffer_size as u64;
+        handle.check_within_extent(0, total_size)?;
+
+        #[cfg(target_os = "linux")]
+        if let Some(mut ring) = handle.uring.take() {
+            let result = Self::write_queued(handle, &mut ring, start, total_size, fill_buffer, on_written);
+            handle.uring = Some(ring);
+            return result;
+        }
+
+        let mut offset = start;
 
         while offset < total_size {
-            // Check for interrupt signal
-            if crate::is_interrupted() {
+            // Check for cancellation
+            if handle.cancel.is_cancelled() {
                 return Err(IOError::Interrupted);
             }
+            handle.wait_while_paused()?;
 
-            let write_size = (total_size - offset).min(buffer_size);
+            // The tuner may have resized the pool since the last buffer
+            let write_size = (total_size - offset).min(handle.buffer_size() as u64);
 
             // Acquire buffer from pool
             let mut buffer = handle.acquire_buffer()?;
@@ -360,34 +746,200 @@ impl OptimizedIO {
             // Fill buffer with data
             fill_buffer(&mut buffer)?;
 
-            // Write to device
-            let written = handle.write_buffer(&buffer, offset)?;
+            // Write to device (the final chunk may be shorter than the buffer)
+            let write_start = Instant::now();
+            handle.write_all_at(&buffer.as_slice()[..write_size as usize], offset)?;
+            drop(buffer);
 
-            if written as u64 != write_size {
-                return Err(IOError::OperationFailed(
-                    format!("Partial write: {} of {} bytes", written, write_size)
-                ));
+            offset += write_size;
+            on_written(handle, offset)?;
+
+            Self::tune(handle, write_size, write_start.elapsed());
+            handle.throttle(write_size);
+        }
+
+        Ok(())
+    }
+
+    /// `write_from` with up to `queue_depth` buffers in flight on io_uring.
+    ///
+    /// Completions arrive out of order, so `on_written` only ever sees the
+    /// offset below which every write has finished. A write that fails or
+    /// comes back short is finished with `write_all_at`, so bad sectors are
+    /// handled exactly as on the pwrite path.
+    ///
+    /// A lower depth from the tuner takes effect at the next submission. A
+    /// new buffer size drains the queue first, since registered buffers can
+    /// only be swapped while the kernel holds none of them.
+    #[cfg(target_os = "linux")]
+    fn write_queued<F, P>(
+        handle: &mut IOHandle,
+        ring: &mut IoUringEngine,
+        start: u64,
+        total_size: u64,
+        mut fill_buffer: F,
+        mut on_written: P,
+    ) -> IOResult<()>
+    where
+        F: FnMut(&mut PooledBuffer) -> IOResult<()>,
+        P: FnMut(&IOHandle, u64) -> IOResult<()>,
+    {
+        let mut buffer_size = handle.buffer_size() as u64;
+        let (mut buffers, mut fixed) = Self::queue_buffers(handle, ring, buffer_size)?;
+        let mut slots = buffers.len();
+
+        let mut free: Vec<usize> = (0..slots).rev().collect();
+        let mut in_flight: Vec<Option<(u64, usize, Instant)>> = vec![None; slots];
+        let mut next = start;
+        let mut contiguous = start;
+        // Writes that finished ahead of `contiguous`: offset -> end
+        let mut finished: BTreeMap<u64, u64> = BTreeMap::new();
+        // First error; once set nothing new is submitted and the queue drains
+        let mut error: Option<IOError> = None;
+
+        loop {
+            // Resize once the queue has drained
+            if handle.buffer_size() as u64 != buffer_size && free.len() == slots
+                && error.is_none() && next < total_size
+            {
+                if fixed {
+                    let _ = ring.unregister_buffers();
+                }
+                drop(buffers);
+                buffer_size = handle.buffer_size() as u64;
+                (buffers, fixed) = Self::queue_buffers(handle, ring, buffer_size)?;
+                slots = buffers.len();
+                free = (0..slots).rev().collect();
+                in_flight = vec![None; slots];
             }
 
-            offset += written as u64;
+            while error.is_none() && next < total_size
+                && handle.buffer_size() as u64 == buffer_size
+                && slots - free.len() < handle.queue_depth
+                && !handle.pause.is_paused()
+            {
+                let Some(slot) = free.pop() else { break };
+
+                if handle.cancel.is_cancelled() {
+                    error = Some(IOError::Interrupted);
+                } else if let Err(e) = fill_buffer(&mut buffers[slot]) {
+                    error = Some(e);
+                } else {
+                    let len = (total_size - next).min(buffer_size) as usize;
+                    let data = &buffers[slot].as_slice()[..len];
+                    // SAFETY: the buffer is not touched again until its completion is reaped
+                    let at = handle.device_offset(next);
+                    match unsafe { ring.submit_write(&handle.file, data, at, slot as u16, fixed) } {
+                        Ok(()) => {
+                            in_flight[slot] = Some((next, len, Instant::now()));
+                            next += len as u64;
+                            continue;
+                        }
+                        Err(e) => error = Some(e),
+                    }
+                }
+                free.push(slot);
+            }
 
-            // Adaptive tuning if enabled
-            if let Some(ref tuner) = handle.tuner {
-                let stats = handle.metrics.stats();
-                if stats.elapsed.as_secs() > 0 && stats.throughput_bps > 0 {
-                    // Tuner can adjust buffer size and queue depth
-                    let _ = tuner.record_and_tune(
-                        written as u64,
-                        stats.avg_latency
-                    );
+            if free.len() == slots {
+                // Paused with nothing in flight: wait, then carry on
+                if error.is_none() && next < total_size && handle.pause.is_paused() {
+                    if let Err(e) = handle.wait_while_paused() {
+                        error = Some(e);
+                    }
+                    continue;
+                }
+                break;
+            }
+
+            let (slot, result) = match ring.wait_write() {
+                Ok(completion) => completion,
+                Err(e) => {
+                    // The kernel may still be reading from queued buffers, so
+                    // they must never be reused
+                    std::mem::forget(buffers);
+                    return Err(e);
+                }
+            };
+            let slot = slot as usize;
+            let (offset, len, submitted) = in_flight[slot].take()
+                .ok_or_else(|| IOError::OperationFailed(format!("io_uring completion for idle slot {}", slot)))?;
+            free.push(slot);
+
+            let data = &buffers[slot].as_slice()[..len];
+            let outcome = match result {
+                Ok(written) => {
+                    handle.metrics.record_operation(written as u64, submitted.elapsed());
+                    handle.check_temperature(written as u64);
+                    handle.write_all_at(&data[written.min(len)..], offset + written as u64)
+                }
+                Err(_) => handle.write_all_at(data, offset),
+            };
+
+            match outcome {
+                Ok(()) => {
+                    finished.insert(offset, offset + len as u64);
+                    let before = contiguous;
+                    while let Some(end) = finished.remove(&contiguous) {
+                        contiguous = end;
+                    }
+                    if contiguous > before {
+                        if let Err(e) = on_written(handle, contiguous) {
+                            error.get_or_insert(e);
+                        }
+                    }
+                    Self::tune(handle, len as u64, submitted.elapsed());
+                    handle.throttle(len as u64);
+                }
+                Err(e) => {
+                    error.get_or_insert(e);
                 }
             }
         }
 
-        // Final sync
-        handle.sync()?;
+        if fixed {
+            let _ = ring.unregister_buffers();
+        }
 
-        Ok(())
+        match error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// One buffer per queue slot, registered with the ring when possible
+    #[cfg(target_os = "linux")]
+    fn queue_buffers(handle: &IOHandle, ring: &mut IoUringEngine, buffer_size: u64) -> IOResult<(Vec<PooledBuffer>, bool)> {
+        let slots = ring.queue_depth()
+            .min(handle.config.max_buffers)
+            .min((MAX_BYTES_IN_FLIGHT / buffer_size) as usize)
+            .clamp(1, u16::MAX as usize);
+
+        let mut buffers = (0..slots)
+            .map(|_| handle.acquire_buffer())
+            .collect::<IOResult<Vec<_>>>()?;
+        let iovecs: Vec<libc::iovec> = buffers.iter_mut()
+            .map(|b| libc::iovec { iov_base: b.as_mut_slice().as_mut_ptr().cast(), iov_len: b.size() })
+            .collect();
+        // SAFETY: the buffers are only released after unregistering.
+        // Registration can fail (e.g. RLIMIT_MEMLOCK); plain writes still queue.
+        let fixed = unsafe { ring.register_buffers(&iovecs) }.is_ok();
+
+        Ok((buffers, fixed))
+    }
+
+    /// Feed a completed write to the adaptive tuner, if enabled, and apply
+    /// its answer: a new depth limits the writes queued from now on, a new
+    /// buffer size replaces the pool. Buffers still out return to the old
+    /// pool, which goes away with the last of them.
+    fn tune(handle: &mut IOHandle, bytes: u64, latency: Duration) {
+        let Some(tuner) = &handle.tuner else { return };
+        let (buffer_size, queue_depth) = tuner.record_and_tune(bytes, latency);
+
+        handle.queue_depth = queue_depth.max(1);
+        if buffer_size != handle.buffer_size() {
+            handle.buffer_pool = Self::buffer_pool(&handle.config, buffer_size);
+        }
     }
 
     /// Perform a full sequential read with optimizations
@@ -406,7 +958,9 @@ impl OptimizedIO {
             let mut buffer = handle.acquire_buffer()?;
 
             // Read from device
+            let read_start = Instant::now();
             let bytes_read = handle.read_buffer(&mut buffer, offset)?;
+            let latency = read_start.elapsed();
 
             if bytes_read == 0 {
                 return Err(IOError::OperationFailed(
@@ -418,17 +972,10 @@ impl OptimizedIO {
             process_buffer(&buffer, bytes_read)?;
 
             offset += bytes_read as u64;
+            drop(buffer);
 
             // Adaptive tuning if enabled
-            if let Some(ref tuner) = handle.tuner {
-                let stats = handle.metrics.stats();
-                if stats.elapsed.as_secs() > 0 && stats.throughput_bps > 0 {
-                    let _ = tuner.record_and_tune(
-                        bytes_read as u64,
-                        stats.avg_latency
-                    );
-                }
-            }
+            Self::tune(handle, bytes_read as u64, latency);
         }
 
         Ok(())
@@ -475,6 +1022,20 @@ impl OptimizedIO {
         println!("  Total Memory: {:.2} MB",
                  pool_stats.total_memory as f64 / (1024.0 * 1024.0));
 
+        if handle.tuner.is_some() {
+            let timeline = handle.tuning_timeline();
+            println!("\n🎛️  Tuning Timeline:");
+            if timeline.is_empty() {
+                println!("  No changes: {} buffers, queue depth {}",
+                         format_buffer_size(handle.buffer_size()), handle.queue_depth());
+            }
+            for event in timeline {
+                println!("  +{:.1}s  {} buffers, queue depth {} ({})",
+                         event.at.as_secs_f64(), format_buffer_size(event.buffer_size),
+                         event.queue_depth, event.reason);
+            }
+        }
+
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     }
 }
@@ -1,6 +1,7 @@
 // Platform-specific I/O implementations
 
 use super::{IOResult, IOError};
+use crate::drives::operations::smart::SMARTMonitor;
 use std::fs::File;
 use std::os::unix::fs::OpenOptionsExt;
 
@@ -49,6 +50,11 @@ pub trait PlatformIO: Send + Sync {
     /// Sync data to disk
     fn sync_data(&self, file: &File) -> IOResult<()>;
 
+    /// Current temperature of the drive in Celsius, if it can be read
+    fn temperature(&self, device_path: &str) -> Option<u32> {
+        SMARTMonitor::monitor_temperature(device_path).ok().map(|monitor| monitor.current_celsius)
+    }
+
     /// Get platform name
     fn platform_name(&self) -> &str;
 }
@@ -0,0 +1,265 @@
+// Token-bucket rate limiting for writes
+//
+// A `RateLimiter` caps bandwidth and IOPS. Each handle gets one from its
+// `IOConfig`, and a limiter shared by several handles (`IOHandle::set_shared_limiter`)
+// caps them in aggregate, e.g. every drive of a `wipe-all` behind one HBA.
+// `WriteThrottle` combines them with quiet hours and thermal throttling for
+// one writer, reading the drive's temperature as often as its `IOConfig` asks.
+
+use super::ThrottleAction;
+use crate::control::QuietHours;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// `rate` tokens per second accumulate up to `burst`. Taking more than are
+/// available leaves the bucket in debt, which the caller waits out.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    /// Starts full, so the first `burst` tokens are taken without waiting
+    pub fn new(rate_per_sec: u64, burst: u64) -> Self {
+        let rate = rate_per_sec.max(1) as f64;
+        let burst = burst.max(1) as f64;
+        Self { rate, burst, state: Mutex::new(BucketState { tokens: burst, last: Instant::now() }) }
+    }
+
+    pub fn rate(&self) -> u64 {
+        self.rate as u64
+    }
+
+    /// Take `tokens` and return how long to wait before using them
+    pub fn reserve(&self, tokens: u64) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let refill = now.duration_since(state.last).as_secs_f64() * self.rate;
+        state.tokens = (state.tokens + refill).min(self.burst) - tokens as f64;
+        state.last = now;
+
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / self.rate)
+        }
+    }
+}
+
+/// Bandwidth and IOPS ceilings; either may be absent
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    bytes: Option<TokenBucket>,
+    operations: Option<TokenBucket>,
+}
+
+impl RateLimiter {
+    /// A quarter second of bandwidth, and at least one operation, may be
+    /// taken at once
+    pub fn new(max_bytes_per_sec: Option<u64>, max_iops: Option<u64>) -> Self {
+        Self {
+            bytes: max_bytes_per_sec.map(|rate| TokenBucket::new(rate, rate / 4)),
+            operations: max_iops.map(|rate| TokenBucket::new(rate, (rate / 4).max(1))),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.bytes.is_none() && self.operations.is_none()
+    }
+
+    pub fn max_bytes_per_sec(&self) -> Option<u64> {
+        self.bytes.as_ref().map(TokenBucket::rate)
+    }
+
+    /// Account for one write of `bytes` and return how long to wait before
+    /// the next
+    pub fn reserve(&self, bytes: u64) -> Duration {
+        let bandwidth = self.bytes.as_ref().map_or(Duration::ZERO, |bucket| bucket.reserve(bytes));
+        let operations = self.operations.as_ref().map_or(Duration::ZERO, |bucket| bucket.reserve(1));
+        bandwidth.max(operations)
+    }
+}
+
+/// Every limit on the writes to one device: its own ceilings, a limiter
+/// shared with other devices, quiet hours and thermal throttling
+#[derive(Debug, Default)]
+pub struct WriteThrottle {
+    device_path: String,
+    limiter: RateLimiter,
+    shared: Option<Arc<RateLimiter>>,
+    quiet_hours: Option<QuietHours>,
+    /// Set while the quiet hours window is in effect
+    quiet_limit: Option<TokenBucket>,
+    /// Set while the drive is too warm
+    thermal_limit: Option<ThermalLimit>,
+    /// Temperature threshold and bytes between readings; None while off
+    temperature_checks: Option<(u32, u64)>,
+    since_temperature_check: u64,
+    /// Bytes written, and the time spent writing them with waits excluded
+    unthrottled: (u64, Duration),
+    /// When the last wait handed out by `reserve` ends
+    resume_at: Option<Instant>,
+}
+
+/// Write cap applied while the drive is too warm
+#[derive(Debug)]
+struct ThermalLimit {
+    factor: f64,
+    base: u64,  // Rate the factor applies to
+    bucket: TokenBucket,
+}
+
+impl WriteThrottle {
+    pub fn new(device_path: &str, limiter: RateLimiter) -> Self {
+        Self { device_path: device_path.to_string(), limiter, ..Default::default() }
+    }
+
+    pub fn set_shared(&mut self, limiter: Option<Arc<RateLimiter>>) {
+        self.shared = limiter;
+    }
+
+    pub fn set_quiet_hours(&mut self, quiet_hours: Option<QuietHours>) {
+        self.quiet_hours = quiet_hours;
+    }
+
+    /// Read the temperature every `interval` bytes, starting with the first
+    /// write, and throttle above `threshold`. An interval of `u64::MAX`
+    /// turns checks off.
+    pub fn set_temperature_checks(&mut self, threshold: u32, interval: u64) {
+        self.temperature_checks = (interval != u64::MAX).then_some((threshold, interval));
+        self.since_temperature_check = interval;
+    }
+
+    /// The device's own bandwidth ceiling
+    pub fn max_bytes_per_sec(&self) -> Option<u64> {
+        self.limiter.max_bytes_per_sec()
+    }
+
+    /// Rate the device writes at when nothing holds it back
+    pub fn unthrottled_rate(&self) -> u64 {
+        let (bytes, busy) = self.unthrottled;
+        if busy.is_zero() {
+            return 0;
+        }
+        (bytes as f64 / busy.as_secs_f64()) as u64
+    }
+
+    /// Leave the time since the last write out of the unthrottled rate,
+    /// e.g. after a pause
+    pub fn discard_interval(&mut self) {
+        self.resume_at = None;
+    }
+
+    /// Account for one write of `bytes` and return how long to wait before
+    /// the next, under whichever limit is tightest
+    pub fn reserve(&mut self, bytes: u64) -> Duration {
+        // The write took whatever passed since the last wait ended
+        let now = Instant::now();
+        if let Some(resume_at) = self.resume_at {
+            self.unthrottled.0 += bytes;
+            self.unthrottled.1 += now.saturating_duration_since(resume_at);
+        }
+
+        match self.quiet_hours.filter(QuietHours::is_active) {
+            Some(quiet_hours) if self.quiet_limit.is_none() => {
+                println!("\n🌙 Quiet hours: writes to {} limited to {} MB/s",
+                         self.device_path, quiet_hours.max_mb_per_sec);
+                let rate = quiet_hours.bytes_per_sec();
+                self.quiet_limit = Some(TokenBucket::new(rate, rate / 4));
+            }
+            Some(_) => {}
+            None => self.quiet_limit = None,
+        }
+
+        let mut wait = self.limiter.reserve(bytes);
+        if let Some(shared) = &self.shared {
+            wait = wait.max(shared.reserve(bytes));
+        }
+        if let Some(quiet) = &self.quiet_limit {
+            wait = wait.max(quiet.reserve(bytes));
+        }
+        if let Some(thermal) = &self.thermal_limit {
+            wait = wait.max(thermal.bucket.reserve(bytes));
+        }
+
+        self.resume_at = Some(now + wait);
+        wait
+    }
+
+    /// Apply a thermal throttling decision. Slowing down caps writes at a
+    /// share of the rate before throttling: the configured ceiling, or else
+    /// the rate the drive writes at unthrottled. The cap stays until the
+    /// drive is back under its threshold.
+    pub fn apply_thermal(&mut self, action: ThrottleAction) {
+        match action {
+            ThrottleAction::None => {
+                if self.thermal_limit.take().is_some() {
+                    println!("🌡️  Temperature back to normal: lifting throttle");
+                }
+            }
+            ThrottleAction::Slow(factor) => {
+                if self.thermal_limit.as_ref().is_some_and(|limit| limit.factor == factor) {
+                    return;
+                }
+
+                let base = self.thermal_limit.as_ref().map(|limit| limit.base)
+                    .or(self.limiter.max_bytes_per_sec())
+                    .unwrap_or_else(|| self.unthrottled_rate());
+                if base == 0 {
+                    return;
+                }
+
+                let rate = (base as f64 * factor) as u64;
+                println!("🌡️  Temperature throttling: Reducing speed to {:.0}% ({} MB/s)",
+                         factor * 100.0, rate / (1024 * 1024));
+                self.thermal_limit = Some(ThermalLimit { factor, base, bucket: TokenBucket::new(rate, rate / 4) });
+            }
+            ThrottleAction::Pause(duration) => {
+                println!("🌡️  Temperature too high! Pausing for {:?} to cool down", duration);
+                std::thread::sleep(duration);
+                self.discard_interval();
+            }
+        }
+    }
+
+    /// Account for `bytes` written and, once the check interval has passed,
+    /// throttle by the temperature `read_temp` reports. A sensor that cannot
+    /// be read turns checks off.
+    pub fn check_temperature(&mut self, bytes: u64, read_temp: impl FnOnce() -> Option<u32>) {
+        let Some((threshold, interval)) = self.temperature_checks else {
+            return;
+        };
+
+        self.since_temperature_check += bytes;
+        if self.since_temperature_check < interval {
+            return;
+        }
+        self.since_temperature_check = 0;
+
+        match read_temp() {
+            Some(celsius) => {
+                crate::events::record_temperature(celsius);
+                self.apply_thermal(ThrottleAction::for_temperature(celsius, threshold));
+            }
+            None => {
+                // Temperature monitoring failed - disable it and warn once
+                eprintln!("⚠️  WARNING: Could not read temperature sensor");
+                eprintln!("   Temperature monitoring will be disabled.");
+                self.temperature_checks = None;
+            }
+        }
+    }
+
+    /// Current thermal write cap
+    pub fn thermal_rate(&self) -> Option<u64> {
+        self.thermal_limit.as_ref().map(|limit| limit.bucket.rate())
+    }
+}
@@ -1,9 +1,11 @@
 #[cfg(test)]
 mod tests {
     use crate::io::*;
+    use crate::test_support::test_drive;
     use tempfile::NamedTempFile;
     use std::time::Instant;
-    use crate::io::metrics::PerformanceTuner;
+    use crate::io::metrics::{PerformanceTuner, WindowStats};
+    use std::time::Duration;
 
     type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -247,4 +249,746 @@ mod tests {
 
         Ok(())
     }
+
+    fn checkpoint_test_drive(path: &str, size: u64) -> crate::DriveInfo {
+        crate::DriveInfo { device_path: path.to_string(), size, ..test_drive("CKPT-TEST") }
+    }
+
+    fn small_buffer_config() -> IOConfig {
+        IOConfig {
+            use_direct_io: false,
+            initial_buffer_size: 64 * 1024,
+            adaptive_tuning: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_resumable_write_continues_interrupted_pass() -> Result<()> {
+        let temp = NamedTempFile::new()?;
+        let path = temp.path().to_str().unwrap();
+        let dir = tempfile::TempDir::new()?;
+        let size = 1024 * 1024u64;
+        let drive = checkpoint_test_drive(path, size);
+
+        // First run fails after four buffers
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Zero", false)?;
+        let mut handle = OptimizedIO::open(path, small_buffer_config())?;
+        let mut calls = 0;
+        let result = OptimizedIO::sequential_write_resumable(&mut handle, size, &mut checkpoint, 0, |buffer| {
+            calls += 1;
+            if calls > 4 {
+                return Err(IOError::OperationFailed("simulated power loss".to_string()));
+            }
+            buffer.as_mut_slice().fill(0xAA);
+            Ok(())
+        });
+        assert!(result.is_err());
+        drop(checkpoint);
+
+        // Second run resumes at the saved offset and only writes the rest
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Zero", true)?;
+        assert_eq!(checkpoint.resume_offset(0), 4 * 64 * 1024);
+
+        let mut handle = OptimizedIO::open(path, small_buffer_config())?;
+        let mut resumed_calls = 0;
+        OptimizedIO::sequential_write_resumable(&mut handle, size, &mut checkpoint, 0, |buffer| {
+            resumed_calls += 1;
+            buffer.as_mut_slice().fill(0xBB);
+            Ok(())
+        })?;
+        assert_eq!(resumed_calls, 12);
+        assert!(checkpoint.is_pass_complete(0));
+
+        let data = std::fs::read(path)?;
+        assert_eq!(data.len() as u64, size);
+        assert!(data[..4 * 64 * 1024].iter().all(|&b| b == 0xAA));
+        assert!(data[4 * 64 * 1024..].iter().all(|&b| b == 0xBB));
+
+        checkpoint.finish();
+        assert!(std::fs::read_dir(dir.path())?.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resumed_random_pass_records_restart() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let drive = checkpoint_test_drive("/dev/sdz", 1 << 30);
+        let temp = NamedTempFile::new()?;
+        let handle = OptimizedIO::open(temp.path().to_str().unwrap(), small_buffer_config())?;
+
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Random", false)?;
+        assert_eq!(checkpoint.resume_random(0)?, 0);
+        checkpoint.record(&handle, 0, 8 << 20)?;
+        checkpoint.flush(&handle)?;
+        drop(checkpoint);
+
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Random", true)?;
+        assert_eq!(checkpoint.resume_random(0)?, 8 << 20);
+        assert_eq!(checkpoint.resume_random(0)?, 8 << 20);
+        drop(checkpoint);
+
+        // The restart is persisted once, so a later resume still shows the splice
+        let checkpoint = Checkpointer::open_in(dir.path(), &drive, "Random", true)?;
+        assert_eq!(checkpoint.checkpoint().unwrap().random_restarts,
+                   vec![RandomRestart { pass: 0, offset: 8 << 20 }]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_rejects_different_drive() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let drive = checkpoint_test_drive("/dev/sdz", 1 << 30);
+
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "DoD5220", false)?;
+        checkpoint.complete_pass(0)?;
+
+        // Same serial, different capacity: not the drive the checkpoint was made for
+        let mut resized = drive.clone();
+        resized.size = 1 << 31;
+        assert!(Checkpointer::open_in(dir.path(), &resized, "DoD5220", true).is_err());
+
+        // Without --resume a stale checkpoint is replaced rather than trusted
+        let fresh = Checkpointer::open_in(dir.path(), &drive, "DoD5220", false)?;
+        assert_eq!(fresh.checkpoint().unwrap().current_pass, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sequential_write_partial_final_chunk() -> Result<()> {
+        let temp = NamedTempFile::new()?;
+        let path = temp.path().to_str().unwrap();
+        let size = 64 * 1024 * 3 + 4096;
+
+        let mut handle = OptimizedIO::open(path, small_buffer_config())?;
+        OptimizedIO::sequential_write(&mut handle, size, |buffer| {
+            buffer.as_mut_slice().fill(0x5A);
+            Ok(())
+        })?;
+
+        assert_eq!(std::fs::metadata(path)?.len(), size);
+        Ok(())
+    }
+
+    #[test]
+    fn test_queued_writes_land_in_order() -> Result<()> {
+        let temp = NamedTempFile::new()?;
+        let path = temp.path().to_str().unwrap();
+        let size = 64 * 1024 * 40 + 4096;
+
+        let mut config = small_buffer_config();
+        config.queue_depth = 8;
+        let mut handle = OptimizedIO::open(path, config.clone())?;
+        assert_eq!(handle.uses_io_uring(), io_uring_engine::IoUringEngine::is_available());
+
+        // Every buffer gets its own byte, so a write at the wrong offset shows
+        let mut chunk = 0u8;
+        OptimizedIO::sequential_write(&mut handle, size, |buffer| {
+            chunk += 1;
+            buffer.as_mut_slice().fill(chunk);
+            Ok(())
+        })?;
+        assert_eq!(handle.metrics().stats().bytes_processed, size);
+
+        let data = std::fs::read(path)?;
+        assert_eq!(data.len() as u64, size);
+        for (i, written) in data.chunks(64 * 1024).enumerate() {
+            assert!(written.iter().all(|&b| b == i as u8 + 1), "chunk {} misplaced", i);
+        }
+
+        // A custom platform I/O keeps the pwrite path
+        let handle = OptimizedIO::open_with(path, config, Box::new(FaultyIO::new(&[])))?;
+        assert!(!handle.uses_io_uring());
+
+        Ok(())
+    }
+
+    /// Buffered file I/O that fails writes touching `bad` LBAs with `errno`.
+    /// A `flaky` sector fails its first N single-sector writes and then
+    /// recovers; `max_write` caps every write to force short writes.
+    struct FaultyIO {
+        bad: Vec<u64>,
+        flaky: std::sync::Mutex<std::collections::HashMap<u64, u32>>,
+        errno: i32,
+        max_write: usize,
+    }
+
+    impl FaultyIO {
+        fn new(bad: &[u64]) -> Self {
+            Self {
+                bad: bad.to_vec(),
+                flaky: Default::default(),
+                errno: libc::EIO,
+                max_write: usize::MAX,
+            }
+        }
+    }
+
+    impl platform_specific::PlatformIO for FaultyIO {
+        fn open_optimized(&self, path: &str, _direct_io: bool) -> IOResult<std::fs::File> {
+            Ok(std::fs::OpenOptions::new().read(true).write(true).open(path)?)
+        }
+
+        fn write_optimized(&self, file: &std::fs::File, data: &[u8], offset: u64) -> IOResult<usize> {
+            use std::os::unix::fs::FileExt;
+
+            let sectors = offset / 512..(offset + data.len() as u64).div_ceil(512);
+            if self.bad.iter().any(|lba| sectors.contains(lba)) {
+                return Err(std::io::Error::from_raw_os_error(self.errno).into());
+            }
+            let mut flaky = self.flaky.lock().unwrap();
+            for (lba, failures) in flaky.iter_mut() {
+                if sectors.contains(lba) && (data.len() > 512 || *failures > 0) {
+                    *failures = failures.saturating_sub(if data.len() == 512 { 1 } else { 0 });
+                    return Err(std::io::Error::from_raw_os_error(self.errno).into());
+                }
+            }
+
+            let len = data.len().min(self.max_write);
+            Ok(file.write_at(&data[..len], offset)?)
+        }
+
+        fn read_optimized(&self, file: &std::fs::File, buffer: &mut [u8], offset: u64) -> IOResult<usize> {
+            use std::os::unix::fs::FileExt;
+            Ok(file.read_at(buffer, offset)?)
+        }
+
+        fn sync_data(&self, file: &std::fs::File) -> IOResult<()> {
+            Ok(file.sync_data()?)
+        }
+
+        fn platform_name(&self) -> &str {
+            "Faulty"
+        }
+    }
+
+    /// Temp file of `size` bytes of 0x11, so untouched sectors are visible
+    fn prefilled_file(size: u64) -> Result<NamedTempFile> {
+        let temp = NamedTempFile::new()?;
+        std::fs::write(temp.path(), vec![0x11u8; size as usize])?;
+        Ok(temp)
+    }
+
+    fn write_pattern(handle: &mut IOHandle, size: u64) -> IOResult<()> {
+        OptimizedIO::sequential_write(handle, size, |buffer| {
+            buffer.as_mut_slice().fill(0xAA);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_resilient_write_skips_bad_sectors() -> Result<()> {
+        let size = 1024 * 1024u64;
+        let temp = prefilled_file(size)?;
+        let path = temp.path().to_str().unwrap();
+
+        let io = FaultyIO::new(&[10, 11, 1500]);
+        let mut handle = OptimizedIO::open_with(path, small_buffer_config(), Box::new(io))?;
+        write_pattern(&mut handle, size)?;
+
+        assert_eq!(handle.bad_sectors(), vec![10, 11, 1500]);
+        assert_eq!(handle.sector_size(), 512);
+
+        let data = std::fs::read(path)?;
+        for (lba, sector) in data.chunks(512).enumerate() {
+            let expected = if [10, 11, 1500].contains(&(lba as u64)) { 0x11 } else { 0xAA };
+            assert!(sector.iter().all(|&b| b == expected), "LBA {} has the wrong contents", lba);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_flaky_sector_recovered_by_retry() -> Result<()> {
+        let size = 256 * 1024u64;
+        let temp = prefilled_file(size)?;
+        let path = temp.path().to_str().unwrap();
+
+        // Fails when bisection isolates it and on the first two retries
+        let io = FaultyIO::new(&[]);
+        io.flaky.lock().unwrap().insert(300, 3);
+        let mut handle = OptimizedIO::open_with(path, small_buffer_config(), Box::new(io))?;
+        write_pattern(&mut handle, size)?;
+        assert!(handle.bad_sectors().is_empty());
+        assert!(std::fs::read(path)?.iter().all(|&b| b == 0xAA));
+
+        // Not enough retries to get past the failures
+        let io = FaultyIO::new(&[]);
+        io.flaky.lock().unwrap().insert(300, 3);
+        let config = IOConfig { sector_write_retries: 2, ..small_buffer_config() };
+        let mut handle = OptimizedIO::open_with(path, config, Box::new(io))?;
+        write_pattern(&mut handle, size)?;
+        assert_eq!(handle.bad_sectors(), vec![300]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_errors_abort_without_resilience() -> Result<()> {
+        let size = 256 * 1024u64;
+        let temp = prefilled_file(size)?;
+        let path = temp.path().to_str().unwrap();
+
+        let config = IOConfig { resilient_writes: false, ..small_buffer_config() };
+        let mut handle = OptimizedIO::open_with(path, config, Box::new(FaultyIO::new(&[10])))?;
+        assert!(write_pattern(&mut handle, size).is_err());
+
+        // A drive that went away is not a bad sector
+        let io = FaultyIO { errno: libc::ENODEV, ..FaultyIO::new(&[10]) };
+        let mut handle = OptimizedIO::open_with(path, small_buffer_config(), Box::new(io))?;
+        assert!(write_pattern(&mut handle, size).is_err());
+        assert!(handle.bad_sectors().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_short_writes_are_continued() -> Result<()> {
+        let size = 256 * 1024u64;
+        let temp = prefilled_file(size)?;
+        let path = temp.path().to_str().unwrap();
+
+        let io = FaultyIO { max_write: 7 * 512, ..FaultyIO::new(&[]) };
+        let mut handle = OptimizedIO::open_with(path, small_buffer_config(), Box::new(io))?;
+        write_pattern(&mut handle, size)?;
+
+        assert!(std::fs::read(path)?.iter().all(|&b| b == 0xAA));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bad_sectors_survive_resume() -> Result<()> {
+        let size = 1024 * 1024u64;
+        let temp = prefilled_file(size)?;
+        let path = temp.path().to_str().unwrap();
+        let dir = tempfile::TempDir::new()?;
+        let drive = checkpoint_test_drive(path, size);
+
+        // Interrupted after skipping LBA 5 in the first buffer
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Zero", false)?;
+        let mut handle = OptimizedIO::open_with(path, small_buffer_config(), Box::new(FaultyIO::new(&[5])))?;
+        let mut calls = 0;
+        let result = OptimizedIO::sequential_write_resumable(&mut handle, size, &mut checkpoint, 0, |buffer| {
+            calls += 1;
+            if calls > 2 {
+                return Err(IOError::OperationFailed("simulated power loss".to_string()));
+            }
+            buffer.as_mut_slice().fill(0xAA);
+            Ok(())
+        });
+        assert!(result.is_err());
+        drop(checkpoint);
+
+        // The resumed run starts past LBA 5 but still reports it
+        let mut checkpoint = Checkpointer::open_in(dir.path(), &drive, "Zero", true)?;
+        assert_eq!(checkpoint.bad_sectors(), vec![5]);
+        let mut handle = OptimizedIO::open_with(path, small_buffer_config(), Box::new(FaultyIO::new(&[5, 1800])))?;
+        OptimizedIO::sequential_write_resumable(&mut handle, size, &mut checkpoint, 0, |buffer| {
+            buffer.as_mut_slice().fill(0xAA);
+            Ok(())
+        })?;
+        checkpoint.finish();
+
+        assert_eq!(checkpoint.bad_sectors(), vec![5, 1800]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_restricted_handle_stays_in_extent() -> Result<()> {
+        let size = 1024 * 1024u64;
+        let extent = Extent { offset: 64 * 1024, length: 200 * 1024 };
+        let mut queued = small_buffer_config();
+        queued.queue_depth = 8;
+
+        // Both the queued and the pwrite path, the latter with a bad sector
+        for faulty in [false, true] {
+            let temp = prefilled_file(size)?;
+            let path = temp.path().to_str().unwrap();
+            let mut handle = if faulty {
+                OptimizedIO::open_with(path, small_buffer_config(), Box::new(FaultyIO::new(&[200])))?
+            } else {
+                OptimizedIO::open(path, queued.clone())?
+            };
+            handle.restrict_to(extent)?;
+            write_pattern(&mut handle, extent.length)?;
+
+            // Bad sectors are device LBAs, not extent-relative ones
+            assert_eq!(handle.bad_sectors(), if faulty { vec![200] } else { vec![] });
+            let data = std::fs::read(path)?;
+            for (lba, sector) in data.chunks(512).enumerate() {
+                let offset = lba as u64 * 512;
+                let inside = (extent.offset..extent.offset + extent.length).contains(&offset);
+                let expected = if inside && !(faulty && lba == 200) { 0xAA } else { 0x11 };
+                assert!(sector.iter().all(|&b| b == expected), "LBA {} has the wrong contents", lba);
+            }
+
+            // Reads stop at the end of the extent; writes past it are refused
+            let mut buffer = vec![0u8; 4096];
+            assert_eq!(handle.read_at(&mut buffer, extent.length - 512)?, 512);
+            assert_eq!(handle.read_at(&mut buffer, extent.length)?, 0);
+            assert!(handle.write_at(&buffer, extent.length - 512).is_err());
+            assert!(write_pattern(&mut handle, extent.length + 512).is_err());
+        }
+
+        let temp = prefilled_file(size)?;
+        let mut handle = OptimizedIO::open(temp.path().to_str().unwrap(), small_buffer_config())?;
+        assert!(handle.restrict_to(Extent { offset: 100, length: 4096 }).is_err());
+        Ok(())
+    }
+
+    fn window(mb_per_sec: u64, latency_ms: u64) -> WindowStats {
+        WindowStats {
+            throughput_bps: mb_per_sec * 1024 * 1024,
+            avg_latency: Duration::from_millis(latency_ms),
+            iops: 100,
+        }
+    }
+
+    #[test]
+    fn test_tuner_hysteresis_and_bounds() {
+        let config = IOConfig { initial_buffer_size: 4 * 1024 * 1024, max_buffer_size: 8 * 1024 * 1024, ..IOConfig::default() };
+        let tuner = PerformanceTuner::for_config(&config);
+        let mut at = Duration::from_secs(4);
+        let mut next = |stats: WindowStats| {
+            at += Duration::from_secs(1);
+            tuner.evaluate(stats, at)
+        };
+
+        // Nothing during warmup; the first window after it is the baseline
+        for _ in 0..5 {
+            assert!(next(window(10, 500)).is_none());
+        }
+        assert!(next(window(100, 50)).is_none());
+
+        // A dip that does not last, or latency inside the dead band, changes nothing
+        assert!(next(window(50, 50)).is_none());
+        assert!(next(window(50, 50)).is_none());
+        assert!(next(window(100, 50)).is_none());
+        assert!(next(window(50, 50)).is_none());
+        assert_eq!(tuner.buffer_size(), 4 * 1024 * 1024);
+
+        // Three degraded windows in a row grow the buffers
+        assert!(next(window(50, 50)).is_none());
+        let event = next(window(50, 50)).unwrap();
+        assert_eq!((event.buffer_size, event.queue_depth), (8 * 1024 * 1024, 4));
+        assert!(event.reason.contains("50% of baseline"), "{}", event.reason);
+
+        // Cooldown, then the buffers are already at max_buffer_size
+        for _ in 0..10 {
+            assert!(next(window(50, 50)).is_none());
+        }
+        assert_eq!(tuner.buffer_size(), 8 * 1024 * 1024);
+
+        // Slow completions lower the depth, fast ones raise it up to the bound
+        for _ in 0..2 {
+            assert!(next(window(100, 200)).is_none());
+        }
+        assert_eq!(next(window(100, 200)).unwrap().queue_depth, 3);
+        for _ in 0..40 {
+            next(window(100, 2));
+        }
+        assert_eq!(tuner.queue_depth(), config.max_queue_depth());
+        assert_eq!(tuner.timeline().len(), 5);  // Buffer, 4 -> 3 -> 5 -> 7 -> 8
+    }
+
+    #[test]
+    fn test_tuning_applies_to_live_writes() -> Result<()> {
+        let config = IOConfig {
+            adaptive_tuning: true,
+            max_buffer_size: 128 * 1024,
+            queue_depth: 4,
+            ..small_buffer_config()
+        };
+
+        // Both the queued and the pwrite path
+        for queued in [true, false] {
+            let temp = prefilled_file(0)?;
+            let path = temp.path().to_str().unwrap();
+            let mut handle = if queued {
+                OptimizedIO::open(path, config.clone())?
+            } else {
+                OptimizedIO::open_with(path, config.clone(), Box::new(FaultyIO::new(&[])))?
+            };
+            assert_eq!(handle.buffer_size(), 64 * 1024);
+
+            // Tuner decisions as if the drive had slowed down, then stalled
+            let tuner = handle.tuner().unwrap();
+            let mut at = Duration::from_secs(10);
+            for stats in [window(100, 50), window(50, 50), window(50, 50), window(50, 50)]
+                .into_iter()
+                .chain(std::iter::repeat_n(window(100, 200), 8))
+            {
+                at += Duration::from_secs(1);
+                tuner.evaluate(stats, at);
+            }
+            assert_eq!((tuner.buffer_size(), tuner.queue_depth()), (128 * 1024, 3));
+
+            // The next completed write applies them; every buffer gets its own byte
+            let size = 2 * 1024 * 1024 + 4096;
+            let mut pieces = Vec::new();
+            OptimizedIO::sequential_write(&mut handle, size, |buffer| {
+                let tag = pieces.len() as u8 + 1;
+                buffer.as_mut_slice().fill(tag);
+                pieces.push((tag, buffer.size()));
+                Ok(())
+            })?;
+
+            assert_eq!(handle.buffer_size(), 128 * 1024);
+            assert_eq!(handle.queue_depth(), 3);
+            assert_eq!(handle.tuning_timeline().len(), 2);
+            assert!(pieces.iter().any(|&(_, len)| len == 64 * 1024));
+            assert!(pieces.iter().any(|&(_, len)| len == 128 * 1024));
+
+            let mut expected = Vec::with_capacity(size as usize);
+            for (tag, len) in pieces {
+                let take = len.min(size as usize - expected.len());
+                expected.extend(std::iter::repeat_n(tag, take));
+            }
+            assert_eq!(std::fs::read(path)?, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancelled_handle_stops_only_its_own_writes() -> Result<()> {
+        use crate::control::CancellationToken;
+
+        let size = 1024 * 1024u64;
+        let root = CancellationToken::new();
+        let mut queued = small_buffer_config();
+        queued.queue_depth = 8;
+
+        for config in [small_buffer_config(), queued] {
+            let cancelled = prefilled_file(size)?;
+            let running = prefilled_file(size)?;
+            let mut stopped = OptimizedIO::open(cancelled.path().to_str().unwrap(), config.clone())?;
+            let mut other = OptimizedIO::open(running.path().to_str().unwrap(), config)?;
+            let token = root.child();
+            stopped.set_cancellation(token.clone());
+            other.set_cancellation(root.child());
+
+            // Cancel after the second buffer; the sibling is unaffected
+            let mut filled = 0;
+            let result = OptimizedIO::sequential_write(&mut stopped, size, |buffer| {
+                filled += 1;
+                if filled == 2 {
+                    token.cancel();
+                }
+                buffer.as_mut_slice().fill(0xAA);
+                Ok(())
+            });
+            assert!(matches!(result, Err(IOError::Interrupted)));
+            assert!(std::fs::read(cancelled.path())?.contains(&0x11));
+
+            write_pattern(&mut other, size)?;
+            assert!(std::fs::read(running.path())?.iter().all(|&b| b == 0xAA));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_paused_writes_wait_for_resume() -> Result<()> {
+        use crate::control::PauseToken;
+
+        let size = 1024 * 1024u64;
+        let mut queued = small_buffer_config();
+        queued.queue_depth = 8;
+
+        for config in [small_buffer_config(), queued] {
+            let temp = prefilled_file(size)?;
+            let mut handle = OptimizedIO::open(temp.path().to_str().unwrap(), config)?;
+            let pause = PauseToken::new();
+            handle.set_pause(pause.clone());
+
+            // Pause from inside the pass; another thread resumes it later
+            let resumer = std::thread::spawn({
+                let pause = pause.clone();
+                move || {
+                    while !pause.is_paused() {
+                        std::thread::sleep(Duration::from_millis(5));
+                    }
+                    std::thread::sleep(Duration::from_millis(300));
+                    pause.resume();
+                }
+            });
+            let start = Instant::now();
+            let mut filled = 0;
+            OptimizedIO::sequential_write(&mut handle, size, |buffer| {
+                filled += 1;
+                if filled == 3 {
+                    pause.pause();
+                }
+                buffer.as_mut_slice().fill(0xAA);
+                Ok(())
+            })?;
+            resumer.join().unwrap();
+
+            assert!(start.elapsed() >= Duration::from_millis(300));
+            assert!(std::fs::read(temp.path())?.iter().all(|&b| b == 0xAA));
+        }
+
+        // Cancelling a paused handle ends the wait
+        let temp = prefilled_file(size)?;
+        let mut handle = OptimizedIO::open(temp.path().to_str().unwrap(), small_buffer_config())?;
+        let context = crate::control::OperationContext::default();
+        handle.set_cancellation(context.cancel.clone());
+        handle.set_pause(context.pause.clone());
+        context.pause.pause();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            context.cancel.cancel();
+        });
+        assert!(matches!(write_pattern(&mut handle, size), Err(IOError::Interrupted)));
+        canceller.join().unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_quiet_hours_cap_write_rate() -> Result<()> {
+        use crate::control::QuietHours;
+
+        let size = 4 * 1024 * 1024u64;
+        let now = chrono::Local::now().time();
+        let hour = chrono::Duration::hours(1);
+        let active = QuietHours { start: now - hour, end: now + hour, max_mb_per_sec: 8 };
+        let inactive = QuietHours { start: now + hour, end: now + hour * 2, ..active };
+
+        let temp = prefilled_file(size)?;
+        let mut handle = OptimizedIO::open(temp.path().to_str().unwrap(), small_buffer_config())?;
+        handle.set_quiet_hours(Some(active));
+        let start = Instant::now();
+        write_pattern(&mut handle, size)?;
+        // 4 MiB at 8 MiB/s, the first quarter second of which is burst
+        assert!(start.elapsed() >= Duration::from_millis(240), "{:?}", start.elapsed());
+        assert!(std::fs::read(temp.path())?.iter().all(|&b| b == 0xAA));
+
+        handle.set_quiet_hours(Some(inactive));
+        let start = Instant::now();
+        write_pattern(&mut handle, size)?;
+        assert!(start.elapsed() < Duration::from_millis(240));
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_bucket_waits_out_debt() {
+        let bucket = TokenBucket::new(1000, 100);
+        assert_eq!(bucket.reserve(100), Duration::ZERO);
+        let wait = bucket.reserve(100);
+        assert!(wait > Duration::from_millis(90) && wait <= Duration::from_millis(100), "{:?}", wait);
+
+        let limiter = RateLimiter::new(None, Some(4));
+        assert_eq!(limiter.max_bytes_per_sec(), None);
+        assert_eq!(limiter.reserve(u64::MAX), Duration::ZERO);  // Only one operation
+        assert!(limiter.reserve(1) > Duration::from_millis(200));
+        assert!(RateLimiter::default().is_unlimited());
+    }
+
+    #[test]
+    fn test_max_bytes_per_sec_caps_handle() -> Result<()> {
+        let size = 4 * 1024 * 1024u64;
+        let config = IOConfig { max_bytes_per_sec: Some(8 * 1024 * 1024), ..small_buffer_config() };
+
+        let temp = prefilled_file(size)?;
+        let mut handle = OptimizedIO::open(temp.path().to_str().unwrap(), config)?;
+        let start = Instant::now();
+        write_pattern(&mut handle, size)?;
+        assert!(start.elapsed() >= Duration::from_millis(240), "{:?}", start.elapsed());
+        assert!(std::fs::read(temp.path())?.iter().all(|&b| b == 0xAA));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_limiter_caps_handles_in_aggregate() -> Result<()> {
+        let size = 2 * 1024 * 1024u64;
+        let shared = std::sync::Arc::new(RateLimiter::new(Some(8 * 1024 * 1024), None));
+
+        // Each handle alone would finish within the burst; together they
+        // have to wait for the second quarter second
+        let start = Instant::now();
+        std::thread::scope(|scope| {
+            let writers: Vec<_> = (0..2).map(|_| {
+                let shared = shared.clone();
+                scope.spawn(move || -> std::result::Result<(), String> {
+                    let temp = prefilled_file(size).map_err(|e| e.to_string())?;
+                    let mut handle = OptimizedIO::open(temp.path().to_str().unwrap(), small_buffer_config())
+                        .map_err(|e| e.to_string())?;
+                    handle.set_shared_limiter(Some(shared));
+                    write_pattern(&mut handle, size).map_err(|e| e.to_string())
+                })
+            }).collect();
+            writers.into_iter().try_for_each(|writer| writer.join().unwrap())
+        })?;
+        assert!(start.elapsed() >= Duration::from_millis(240), "{:?}", start.elapsed());
+        Ok(())
+    }
+
+    #[test]
+    fn test_thermal_base_excludes_throttling() {
+        let mib = 1024 * 1024u64;
+
+        // Held to 8 MiB/s by a shared limit alone: slowing down must start
+        // from what the drive writes unthrottled, not the throughput seen
+        let mut limits = WriteThrottle::new("/dev/test", RateLimiter::default());
+        limits.set_shared(Some(std::sync::Arc::new(RateLimiter::new(Some(8 * mib), None))));
+        for _ in 0..4 {
+            std::thread::sleep(limits.reserve(mib));
+        }
+        assert!(limits.unthrottled_rate() > 64 * mib, "{}", limits.unthrottled_rate());
+
+        limits.apply_thermal(ThrottleAction::Slow(0.5));
+        assert!(limits.thermal_rate().unwrap() > 32 * mib);
+        limits.apply_thermal(ThrottleAction::None);
+        assert_eq!(limits.thermal_rate(), None);
+    }
+
+    #[test]
+    fn test_temperature_read_every_interval_until_sensor_fails() {
+        let mib = 1024 * 1024u64;
+        let mut limits = WriteThrottle::new("/dev/test", RateLimiter::new(Some(64 * mib), None));
+        limits.set_temperature_checks(65, 4 * mib);
+        let mut readings = 0;
+
+        // The first write is checked, then every 4 MiB
+        for _ in 0..8 {
+            limits.check_temperature(mib, || { readings += 1; Some(70) });
+        }
+        assert_eq!(readings, 2);
+        assert!(limits.thermal_rate().is_some());
+
+        // Too hot, then cooled down
+        limits.check_temperature(4 * mib, || { readings += 1; Some(40) });
+        assert_eq!(readings, 3);
+        assert_eq!(limits.thermal_rate(), None);
+
+        // A sensor that cannot be read is not asked again
+        limits.check_temperature(4 * mib, || { readings += 1; None });
+        limits.check_temperature(4 * mib, || { readings += 1; Some(40) });
+        assert_eq!(readings, 4);
+
+        // Checks are off with an interval of u64::MAX
+        limits.set_temperature_checks(65, u64::MAX);
+        limits.check_temperature(u64::MAX, || { readings += 1; Some(40) });
+        assert_eq!(readings, 4);
+    }
+
+    #[test]
+    fn test_thermal_slowdown_limits_rate_until_cooled() -> Result<()> {
+        let size = 4 * 1024 * 1024u64;
+        let config = IOConfig { max_bytes_per_sec: Some(64 * 1024 * 1024), ..small_buffer_config() };
+
+        let temp = prefilled_file(size)?;
+        let mut handle = OptimizedIO::open(temp.path().to_str().unwrap(), config)?;
+
+        // An eighth of 64 MiB/s
+        handle.apply_throttle(ThrottleAction::Slow(0.125))?;
+        let start = Instant::now();
+        write_pattern(&mut handle, size)?;
+        assert!(start.elapsed() >= Duration::from_millis(240), "{:?}", start.elapsed());
+
+        handle.apply_throttle(ThrottleAction::None)?;
+        let start = Instant::now();
+        write_pattern(&mut handle, size)?;
+        assert!(start.elapsed() < Duration::from_millis(240), "{:?}", start.elapsed());
+        Ok(())
+    }
 }
@@ -5,26 +5,19 @@ pub mod crypto;
 pub mod ui;
 pub mod io;
 pub mod wipe_orchestrator;
+pub mod history;
+pub mod policy;
+pub mod events;
+pub mod control;
+
+#[cfg(test)]
+pub(crate) mod test_support;
 
 // Re-export main wipe orchestrator for convenience
 pub use wipe_orchestrator::{WipeOrchestrator, wipe_drive};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use std::sync::atomic::{AtomicBool, Ordering};
-
-// Global flag for handling Ctrl+C interrupts
-static INTERRUPTED: AtomicBool = AtomicBool::new(false);
-
-/// Set the interrupt flag (called by signal handler)
-pub fn set_interrupted() {
-    INTERRUPTED.store(true, Ordering::SeqCst);
-}
-
-/// Check if an interrupt has been received
-pub fn is_interrupted() -> bool {
-    INTERRUPTED.load(Ordering::SeqCst)
-}
 
 // Enhanced error types for better error handling
 #[derive(Error, Debug)]
@@ -71,8 +64,18 @@ pub enum DriveError {
 
 impl From<anyhow::Error> for DriveError {
     fn from(err: anyhow::Error) -> Self {
-        // Map to the most appropriate variant based on error message
-        DriveError::HardwareCommandFailed(err.to_string())
+        // Drive errors passed up through an I/O handle keep their variant
+        let err = match err.downcast::<DriveError>() {
+            Ok(drive_error) => return drive_error,
+            Err(err) => err,
+        };
+        match err.downcast::<io::IOError>() {
+            Ok(io::IOError::Interrupted) => DriveError::Interrupted,
+            Ok(io::IOError::IoError(e)) => DriveError::IoError(e),
+            Ok(io::IOError::AnyhowError(inner)) => inner.into(),
+            Ok(other) => DriveError::HardwareCommandFailed(other.to_string()),
+            Err(err) => DriveError::HardwareCommandFailed(err.to_string()),
+        }
     }
 }
 
@@ -82,6 +85,8 @@ pub type DriveResult<T> = Result<T, DriveError>;
 pub struct WipeConfig {
     pub algorithm: Algorithm,
     pub verify: bool,
+    #[deprecated(note = "ignored; set pass counts with an Algorithm::Custom pattern scheme")]
+    #[serde(default)]
     pub multiple_passes: Option<u32>,
     pub preserve_partition_table: bool,
     pub unlock_encrypted: bool,
@@ -91,9 +96,28 @@ pub struct WipeConfig {
     pub max_temperature_celsius: Option<u32>,
     pub freeze_mitigation: bool,
     pub sed_crypto_erase: bool,
+    #[serde(default)]
+    pub resume: bool,  // Continue an interrupted overwrite from its checkpoint
+    #[serde(default)]
+    pub auto_select: bool,  // Algorithm left as "auto" for the tool to choose
+    #[serde(default)]
+    pub sanitization_level: Option<policy::SanitizationLevel>,  // NIST 800-88 target level
+    #[serde(default)]
+    pub random_mode: RandomMode,  // Data source for random overwrite passes
+    #[serde(default)]
+    pub pass_verification: PassVerification,  // Read-back of overwrite passes
+    #[serde(default)]
+    pub scope: drives::WipeScope,  // Part of the device to overwrite
+    #[serde(default)]
+    pub quiet_hours: Option<control::QuietHours>,  // Daily window with a write bandwidth ceiling
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<u64>,  // Overwrite bandwidth ceiling for this drive
+    #[serde(default)]
+    pub max_iops: Option<u64>,  // Overwrite operations per second ceiling for this drive
 }
 
 impl Default for WipeConfig {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             algorithm: Algorithm::DoD5220,
@@ -107,10 +131,119 @@ impl Default for WipeConfig {
             max_temperature_celsius: Some(65),
             freeze_mitigation: true,
             sed_crypto_erase: true,
+            resume: false,
+            auto_select: false,
+            sanitization_level: None,
+            random_mode: RandomMode::default(),
+            pass_verification: PassVerification::default(),
+            scope: drives::WipeScope::WholeDevice,
+            quiet_hours: None,
+            max_bytes_per_sec: None,
+            max_iops: None,
+        }
+    }
+}
+
+impl WipeConfig {
+    /// The configured scope; `preserve_partition_table` turns a whole-device
+    /// wipe into one that keeps the partition table
+    pub fn effective_scope(&self) -> drives::WipeScope {
+        match self.scope {
+            drives::WipeScope::WholeDevice if self.preserve_partition_table => drives::WipeScope::ExceptPartitionTable,
+            ref scope => scope.clone(),
+        }
+    }
+}
+
+/// Data source for random overwrite passes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RandomMode {
+    #[default]
+    Drbg,    // Parallel HMAC-DRBG; only statistically verifiable
+    AesCtr,  // AES-256-CTR keystream under a per-wipe key; verified byte for byte
+}
+
+impl RandomMode {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "drbg" => Ok(RandomMode::Drbg),
+            "aes-ctr" => Ok(RandomMode::AesCtr),
+            _ => Err(anyhow::anyhow!("Unknown random mode: {} (expected drbg or aes-ctr)", s)),
         }
     }
 }
 
+/// Which overwrite passes are read back and compared with what was written,
+/// and how much of each. Nothing is read back unless a caller opts in; the
+/// CLI does so with `--verify-passes`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PassVerification {
+    pub passes: VerifyPasses,
+    pub coverage: VerifyCoverage,
+}
+
+impl Default for PassVerification {
+    fn default() -> Self {
+        Self {
+            passes: VerifyPasses::None,
+            coverage: VerifyCoverage::Sample(1.0),
+        }
+    }
+}
+
+impl PassVerification {
+    /// Whether pass `pass` (0-based) of `total` is read back
+    pub fn covers(&self, pass: usize, total: usize) -> bool {
+        match self.passes {
+            VerifyPasses::None => false,
+            VerifyPasses::LastPass => pass + 1 == total,
+            VerifyPasses::EveryPass => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerifyPasses {
+    #[default]
+    None,       // No read-back
+    LastPass,   // Only the final pass (DoD 5220.22-M ECE)
+    EveryPass,  // Each pass before the next one overwrites it
+}
+
+impl VerifyPasses {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(VerifyPasses::None),
+            "last" | "last-pass" => Ok(VerifyPasses::LastPass),
+            "every" | "every-pass" => Ok(VerifyPasses::EveryPass),
+            _ => Err(anyhow::anyhow!("Unknown pass verification: {} (expected none, last or every)", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum VerifyCoverage {
+    Sample(f64),  // Percent of the drive, in evenly spaced blocks
+    Full,         // Every byte
+}
+
+impl VerifyCoverage {
+    /// "full", or a percentage such as "1" or "2.5%"
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        if s.eq_ignore_ascii_case("full") {
+            return Ok(VerifyCoverage::Full);
+        }
+
+        let percent: f64 = s.trim_end_matches('%').parse()
+            .map_err(|_| anyhow::anyhow!("Invalid verification coverage: {} (expected a percentage or full)", s))?;
+        if !(percent > 0.0 && percent <= 100.0) {
+            return Err(anyhow::anyhow!("Verification coverage must be in (0, 100]: {}", s));
+        }
+
+        Ok(if percent == 100.0 { VerifyCoverage::Full } else { VerifyCoverage::Sample(percent) })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HPADCOHandling {
     Ignore,           // Don't check for HPA/DCO
@@ -129,6 +262,34 @@ pub enum Algorithm {
     CryptoErase,  // Cryptographic erase (SED)
     Sanitize,     // NVMe sanitize command
     TrimOnly,     // TRIM/discard only (SSD)
+    Custom(algorithms::custom::PatternScheme),  // User-defined pass scheme
+}
+
+impl Algorithm {
+    /// Short name for certificates, history and checkpoints
+    pub fn name(&self) -> String {
+        match self {
+            Algorithm::Custom(scheme) => format!("Custom({})", scheme.name),
+            other => format!("{:?}", other),
+        }
+    }
+
+    /// Whether the algorithm overwrites through the host, and so can be
+    /// limited to part of the device
+    pub fn is_overwrite(&self) -> bool {
+        matches!(self, Algorithm::DoD5220 | Algorithm::Gutmann | Algorithm::Random | Algorithm::Zero | Algorithm::Custom(_))
+    }
+
+    /// Whether any overwrite pass writes random data
+    pub fn writes_random(&self) -> bool {
+        match self {
+            Algorithm::DoD5220 | Algorithm::Gutmann | Algorithm::Random => true,
+            Algorithm::Custom(scheme) => {
+                scheme.passes.iter().any(|pass| pass.pattern == algorithms::custom::PassPattern::Random)
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -214,7 +375,7 @@ pub enum SEDType {
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SanitizeOption {
     BlockErase,
     CryptoErase,
@@ -252,13 +413,14 @@ pub struct OperationStatus {
     pub warnings: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum WipePhase {
     Preparing,
     UnfreezingDrive,
     RemovingHPA,
     RemovingDCO,
     CryptoErase,
+    Sanitizing,
     Overwriting,
     TrimOperation,
     Verification,
@@ -289,7 +451,7 @@ pub struct DriveWipeRecord {
     pub verification_passed: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum WipeStatus {
     Pending,
     InProgress,
@@ -11,8 +11,15 @@ use sayonara_wipe::drives::{
     HDDWipe,
     SSDWipe,
     NVMeWipe,
+    NvmeSanitizeRecord,
+    SanitizeSession,
+    ResolvedScope,
+    WipeScope,
 };
-use sayonara_wipe::algorithms::{dod::DoDWipe, gutmann::GutmannWipe, random::RandomWipe};
+use sayonara_wipe::algorithms::{self, dod::DoDWipe, OverwriteOptions};
+use sayonara_wipe::algorithms::custom::PatternScheme;
+use sayonara_wipe::control::{OperationContext, QuietHours};
+use sayonara_wipe::io::{Checkpointer, RateLimiter};
 use sayonara_wipe::verification::recovery_test::RecoveryTest;
 use sayonara_wipe::verification::{
     EnhancedVerification,
@@ -22,11 +29,31 @@ use sayonara_wipe::verification::{
     PreWipeTestResults,
     LiveUSBVerification,
 };
-use sayonara_wipe::crypto::certificates::{CertificateGenerator, WipeDetails, VerificationResult};
+use sayonara_wipe::crypto::certificates::{CertificateGenerator, UnwritableSectors, WipeDetails, VerificationResult};
+use sayonara_wipe::crypto::keystream::Keystream;
+use sayonara_wipe::crypto::pkcs7;
+use sayonara_wipe::crypto::report::CertificateDocument;
+use sayonara_wipe::crypto::revocation::{certificate_status, CertificateStatus, RevocationList, SequenceState};
+use sayonara_wipe::history::{DriveJob, HistoryFilter, JobDatabase};
+use sayonara_wipe::policy::{AutoSelector, NistPolicy, PlannedMethod, SanitizationLevel, SanitizationRecord, WipePlan};
 use std::time::{Duration, Instant};
 use std::io::{self, Write};
+use std::sync::{Arc, OnceLock};
 use uuid::Uuid;
 
+// Parent of every drive's context; SIGINT cancels, and SIGUSR1 and SIGUSR2
+// pause and resume, all of them. It also holds the limits of `wipe-all
+// --total-rate`, which its children share.
+static CONTROL: OnceLock<OperationContext> = OnceLock::new();
+
+fn control() -> &'static OperationContext {
+    CONTROL.get_or_init(OperationContext::default)
+}
+
+fn mb_per_sec(mb: u64) -> u64 {
+    mb * 1024 * 1024
+}
+
 #[derive(Parser)]
 #[command(name = "sayonara-wipe")]
 #[command(about = "Advanced secure data wiping tool with comprehensive hardware support")]
@@ -42,6 +69,15 @@ struct Cli {
     /// Disable safety checks (DANGEROUS!)
     #[arg(long, global = true)]
     unsafe_mode: bool,
+
+    /// Output format: text (human readable) or jsonl (one event per line on
+    /// stdout; all other output goes to stderr)
+    #[arg(long, global = true, default_value = "text")]
+    output: String,
+
+    /// Also stream JSON Lines events to this Unix socket
+    #[arg(long, global = true)]
+    event_socket: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -97,6 +133,49 @@ enum Commands {
         /// Force operation even if drive is unhealthy
         #[arg(long)]
         force: bool,
+
+        /// Resume an interrupted overwrite from its checkpoint
+        #[arg(long)]
+        resume: bool,
+        /// Custom pattern scheme file (TOML or JSON); overrides --algorithm
+        #[arg(long)]
+        scheme: Option<String>,
+
+        /// NIST SP 800-88 target level (clear, purge, destroy); the method is chosen or checked per drive
+        #[arg(long)]
+        level: Option<String>,
+
+        /// Random pass data (drbg, aes-ctr); aes-ctr passes are verified byte for byte
+        #[arg(long, default_value = "drbg")]
+        random_mode: String,
+
+        /// Overwrite passes to read back (none, last, every)
+        #[arg(long, default_value = "every")]
+        verify_passes: String,
+
+        /// Share of each verified pass to read back: a percentage, or "full"
+        #[arg(long, default_value = "1")]
+        verify_coverage: String,
+
+        /// Part of the drive to overwrite: whole, partition:<number|guid>, lba:<first>-<last>, except-table
+        #[arg(long, default_value = "whole")]
+        scope: String,
+
+        /// Daily window (local time, HH:MM-HH:MM) in which overwrites are throttled
+        #[arg(long)]
+        quiet_hours: Option<String>,
+
+        /// Write bandwidth ceiling during quiet hours, in MB/s
+        #[arg(long, default_value = "50")]
+        quiet_rate: u64,
+
+        /// Write bandwidth ceiling, in MB/s
+        #[arg(long)]
+        max_rate: Option<u64>,
+
+        /// Write operations per second ceiling
+        #[arg(long)]
+        max_iops: Option<u64>,
     },
 
     /// Wipe ALL drives (EXTREMELY DANGEROUS!)
@@ -128,6 +207,54 @@ enum Commands {
         /// Force operation even if drives are unhealthy
         #[arg(long)]
         force: bool,
+
+        /// Custom pattern scheme file (TOML or JSON); overrides --algorithm
+        #[arg(long)]
+        scheme: Option<String>,
+
+        /// NIST SP 800-88 target level (clear, purge, destroy); the method is chosen or checked per drive
+        #[arg(long)]
+        level: Option<String>,
+
+        /// Random pass data (drbg, aes-ctr); aes-ctr passes are verified byte for byte
+        #[arg(long, default_value = "drbg")]
+        random_mode: String,
+
+        /// Overwrite passes to read back (none, last, every)
+        #[arg(long, default_value = "every")]
+        verify_passes: String,
+
+        /// Share of each verified pass to read back: a percentage, or "full"
+        #[arg(long, default_value = "1")]
+        verify_coverage: String,
+
+        /// Part of the drive to overwrite: whole, partition:<number|guid>, lba:<first>-<last>, except-table
+        #[arg(long, default_value = "whole")]
+        scope: String,
+
+        /// Daily window (local time, HH:MM-HH:MM) in which overwrites are throttled
+        #[arg(long)]
+        quiet_hours: Option<String>,
+
+        /// Write bandwidth ceiling during quiet hours, in MB/s
+        #[arg(long, default_value = "50")]
+        quiet_rate: u64,
+
+        /// Write bandwidth ceiling per drive, in MB/s
+        #[arg(long)]
+        max_rate: Option<u64>,
+
+        /// Write operations per second ceiling per drive
+        #[arg(long)]
+        max_iops: Option<u64>,
+
+        /// Write bandwidth ceiling across all drives, in MB/s
+        #[arg(long)]
+        total_rate: Option<u64>,
+
+        /// Write operations per second ceiling across all drives
+        #[arg(long)]
+        total_iops: Option<u64>,
     },
 
     /// Verify a previous wipe
@@ -203,6 +330,30 @@ enum Commands {
         /// Force operation even if drive is unhealthy
         #[arg(long)]
         force: bool,
+
+        /// Custom pattern scheme file (TOML or JSON); overrides --algorithm
+        #[arg(long)]
+        scheme: Option<String>,
+
+        /// NIST SP 800-88 target level (clear, purge, destroy); the method is chosen or checked per drive
+        #[arg(long)]
+        level: Option<String>,
+
+        /// Random pass data (drbg, aes-ctr); aes-ctr passes are verified byte for byte
+        #[arg(long, default_value = "drbg")]
+        random_mode: String,
+
+        /// Overwrite passes to read back (none, last, every)
+        #[arg(long, default_value = "every")]
+        verify_passes: String,
+
+        /// Share of each verified pass to read back: a percentage, or "full"
+        #[arg(long, default_value = "1")]
+        verify_coverage: String,
+
+        /// Part of the drive to overwrite: whole, partition:<number|guid>, lba:<first>-<last>, except-table
+        #[arg(long, default_value = "whole")]
+        scope: String,
     },
 
     /// Create Live USB for external verification
@@ -225,6 +376,73 @@ enum Commands {
         sample_percent: f64,
     },
 
+    /// Export and verify wipe certificates
+    Cert {
+        #[command(subcommand)]
+        action: CertAction,
+    },
+
+    /// Query the persistent wipe history
+    History {
+        /// Only drives with this serial number
+        #[arg(long)]
+        serial: Option<String>,
+
+        /// Only wipes started on or after this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only wipes started on or before this date (YYYY-MM-DD or RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Only records with this status (completed, failed, in-progress, pending, skipped)
+        #[arg(long)]
+        status: Option<String>,
+
+        /// Maximum number of records to show
+        #[arg(long, default_value = "50")]
+        limit: usize,
+
+        /// Show phase transitions, warnings and errors for each record
+        #[arg(long)]
+        details: bool,
+
+        /// History database (default: $SAYONARA_HISTORY_DB or /var/lib/sayonara/history.db)
+        #[arg(long)]
+        database: Option<String>,
+    },
+
+    /// Pause an overwrite running in another process (resume with `resume`)
+    Pause {
+        /// Device path or wipe record ID (see `history`)
+        target: String,
+
+        /// History database (default: $SAYONARA_HISTORY_DB or /var/lib/sayonara/history.db)
+        #[arg(long)]
+        database: Option<String>,
+    },
+
+    /// Resume a paused overwrite
+    Resume {
+        /// Device path or wipe record ID (see `history`)
+        target: String,
+
+        /// History database (default: $SAYONARA_HISTORY_DB or /var/lib/sayonara/history.db)
+        #[arg(long)]
+        database: Option<String>,
+    },
+
+    /// Stop the wipe of one drive running in another process; other drives keep going
+    Cancel {
+        /// Device path or wipe record ID (see `history`)
+        target: String,
+
+        /// History database (default: $SAYONARA_HISTORY_DB or /var/lib/sayonara/history.db)
+        #[arg(long)]
+        database: Option<String>,
+    },
+
     Custom,
 }
 
@@ -247,29 +465,139 @@ enum SedAction {
     },
 }
 
+#[derive(Subcommand)]
+enum CertAction {
+    /// Wrap a JSON certificate as a CMS/PKCS#7 signed-data blob
+    Export {
+        /// JSON certificate produced by a wipe
+        certificate: String,
+
+        /// PEM operator certificate chain (operator certificate first)
+        #[arg(long)]
+        chain: String,
+
+        /// Output path (default: certificate path with .p7s extension)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Write PEM instead of DER
+        #[arg(long)]
+        pem: bool,
+
+        /// PKCS#8 signing key that signed the certificate
+        #[arg(long)]
+        key: String,
+    },
+
+    /// Verify a PKCS#7 blob against a CA, or a JSON certificate against a public key
+    Verify {
+        /// Certificate file (.p7s/.pem or .json)
+        file: String,
+
+        /// PEM file with trusted CA certificates (PKCS#7 input)
+        #[arg(long)]
+        ca: Option<String>,
+
+        /// Hex public key file exported by the signer (JSON input, default: local signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Signed revocation list (default: $SAYONARA_REVOCATION_LIST or /etc/sayonara/revocations.json)
+        #[arg(long)]
+        revocation_list: Option<String>,
+    },
+
+    /// Print a readable summary of a certificate and its status
+    Show {
+        /// JSON certificate
+        file: String,
+
+        /// Hex public key file exported by the signer (default: local signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Signed revocation list
+        #[arg(long)]
+        revocation_list: Option<String>,
+    },
+
+    /// List all certificates in a directory with their status
+    List {
+        /// Directory containing JSON certificates
+        dir: String,
+
+        /// Hex public key file exported by the signer (default: local signing key)
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Signed revocation list
+        #[arg(long)]
+        revocation_list: Option<String>,
+    },
+
+    /// Revoke a certificate by appending it to the signed revocation list
+    Revoke {
+        /// Certificate file or certificate ID
+        certificate: String,
+
+        /// Reason recorded in the revocation list
+        #[arg(long)]
+        reason: String,
+
+        /// PKCS#8 signing key (default: $SAYONARA_SIGNING_KEY or /etc/sayonara/signing_key.pk8)
+        #[arg(long)]
+        key: Option<String>,
+
+        /// Signed revocation list
+        #[arg(long)]
+        revocation_list: Option<String>,
+    },
+
+    /// Render a printable disposal report (pdf or text)
+    Render {
+        /// JSON certificate produced by a wipe
+        certificate: String,
+
+        /// Output format: pdf, text
+        #[arg(short, long, default_value = "pdf")]
+        format: String,
+
+        /// Output path (default: certificate path with .pdf/.txt extension)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
+
 /// Generate enhanced certificate with verification details
 fn generate_enhanced_certificate(
     drive_info: &DriveInfo,
-    config: &WipeConfig,
+    scope: Option<ResolvedScope>,
     verification_report: &VerificationReport,
     duration: Duration,
+    sanitization: Option<SanitizationRecord>,
+    outcome: WipeOutcome,
     cert_path: &str,
 ) -> Result<()> {
-    use crate::crypto::certificates::{CertificateGenerator, WipeDetails, VerificationResult};
+    use crate::crypto::certificates::{WipeDetails, VerificationResult};
 
-    let cert_gen = CertificateGenerator::new();
+    let cert_gen = certificate_generator()?;
 
     // Create enhanced wipe details
     let wipe_details = WipeDetails {
-        algorithm_used: format!("{:?}", config.algorithm),
+        algorithm_used: outcome.algorithm.name(),
         passes_completed: 1,
         duration_seconds: duration.as_secs(),
         operator_id: None,
+        sanitization,
+        nvme_sanitize: outcome.nvme_sanitize,
+        unwritable_sectors: UnwritableSectors::from_lbas(&outcome.unwritable_sectors),
+        keystream_commitment: outcome.keystream_commitment,
+        scope,
     };
 
     // Create enhanced verification result
     let verification_result = VerificationResult {
-        verified: verification_report.confidence_level >= 95.0,
+        verified: verification_report.confidence_level >= 95.0 && wipe_details.unwritable_sectors.is_none(),
         entropy_score: verification_report.post_wipe_analysis.entropy_score,
         recovery_test_passed: verification_report.confidence_level >= 99.0,
         verification_timestamp: verification_report.timestamp,
@@ -296,10 +624,20 @@ fn generate_enhanced_certificate(
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Commands::WipeAll { total_rate, total_iops, .. } = &cli.command {
+        let limiter = RateLimiter::new(total_rate.map(mb_per_sec), *total_iops);
+        if !limiter.is_unlimited() {
+            let _ = CONTROL.set(OperationContext { rate_limiter: Some(Arc::new(limiter)), ..Default::default() });
+        }
+    }
+
     setup_signal_handlers()?;
 
     // Check for root privileges
-    if !cli.unsafe_mode && !is_root() {
+    // Certificate and history tooling never touches drives
+    let needs_root = !matches!(cli.command, Commands::Cert { .. } | Commands::History { .. }
+        | Commands::Pause { .. } | Commands::Resume { .. } | Commands::Cancel { .. });
+    if needs_root && !cli.unsafe_mode && !is_root() {
         eprintln!("Error: This program requires root privileges.");
         eprintln!("Please run with sudo or as root user.");
         std::process::exit(1);
@@ -310,24 +648,45 @@ async fn main() -> Result<()> {
         env_logger::init();
     }
 
+    setup_event_output(&cli.output, cli.event_socket.as_deref())?;
+
     match &cli.command {
         Commands::List { detailed, include_system } => {
             list_drives(*detailed, *include_system).await?;
         }
         Commands::Wipe { device, algorithm, no_verify, cert_output, hpa_dco,
-            no_trim, no_temp_check, max_temp, no_unfreeze, force } => {
-            let config = build_wipe_config(
+            no_trim, no_temp_check, max_temp, no_unfreeze, force, resume, scheme, level, random_mode,
+            verify_passes, verify_coverage, scope, quiet_hours, quiet_rate, max_rate, max_iops } => {
+            let mut config = build_wipe_config(
                 algorithm, !no_verify, hpa_dco, !no_trim,
                 !no_temp_check, *max_temp, !no_unfreeze
             )?;
+            config.resume = *resume;
+            config.random_mode = RandomMode::parse(random_mode)?;
+            config.scope = WipeScope::parse(scope)?;
+            config.quiet_hours = quiet_hours.as_deref().map(|w| QuietHours::parse(w, *quiet_rate)).transpose()?;
+            config.max_bytes_per_sec = max_rate.map(mb_per_sec);
+            config.max_iops = *max_iops;
+            apply_pass_verification(&mut config, verify_passes, verify_coverage)?;
+            apply_scheme(&mut config, scheme.as_deref())?;
+            apply_level(&mut config, level.as_deref())?;
             wipe_drive(device, config, cert_output.as_deref(), *force, cli.unsafe_mode).await?;
         }
         Commands::WipeAll { algorithm, no_verify, cert_dir, exclude,
-            hpa_dco, no_trim, force} => {
-            let config = build_wipe_config(
+            hpa_dco, no_trim, force, scheme, level, random_mode, verify_passes, verify_coverage, scope,
+            quiet_hours, quiet_rate, max_rate, max_iops, .. } => {
+            let mut config = build_wipe_config(
                 algorithm, !no_verify, hpa_dco, !no_trim,
                 true, 65, true
             )?;
+            config.random_mode = RandomMode::parse(random_mode)?;
+            config.scope = WipeScope::parse(scope)?;
+            config.quiet_hours = quiet_hours.as_deref().map(|w| QuietHours::parse(w, *quiet_rate)).transpose()?;
+            config.max_bytes_per_sec = max_rate.map(mb_per_sec);
+            config.max_iops = *max_iops;
+            apply_pass_verification(&mut config, verify_passes, verify_coverage)?;
+            apply_scheme(&mut config, scheme.as_deref())?;
+            apply_level(&mut config, level.as_deref())?;
             wipe_all_drives(config, cert_dir, exclude.as_deref(), cli.unsafe_mode, *force).await?;
         }
         Commands::Verify { device, check_hidden } => {
@@ -343,13 +702,19 @@ async fn main() -> Result<()> {
             device,
             algorithm,
             cert_output,
-            sample_percent,
+            sample_percent: _,
             skip_pre_tests,
             min_confidence,
             verification_level,
             hpa_dco,
             no_trim,
-            force
+            force,
+            scheme,
+            level: sanitization_level,
+            random_mode,
+            verify_passes,
+            verify_coverage,
+            scope,
         } => {
             let drives = DriveDetector::detect_all_drives()?;
             let drive_info = drives.into_iter()
@@ -392,7 +757,7 @@ async fn main() -> Result<()> {
             };
 
             // Build config
-            let config = build_wipe_config(
+            let mut config = build_wipe_config(
                 algorithm,
                 true,  // Always verify in enhanced mode
                 hpa_dco,
@@ -401,11 +766,20 @@ async fn main() -> Result<()> {
                 65,
                 true,  // Freeze mitigation
             )?;
+            config.random_mode = RandomMode::parse(random_mode)?;
+            config.scope = WipeScope::parse(scope)?;
+            apply_pass_verification(&mut config, verify_passes, verify_coverage)?;
+            apply_scheme(&mut config, scheme.as_deref())?;
+            apply_level(&mut config, sanitization_level.as_deref())?;
 
             // Safety confirmation with level info
             if !cli.unsafe_mode {
                 println!("\n⚠️  WARNING: Enhanced Secure Wipe with Forensic Verification");
-                println!("This will PERMANENTLY DESTROY all data on:");
+                if config.scope.is_whole_device() {
+                    println!("This will PERMANENTLY DESTROY all data on:");
+                } else {
+                    println!("This will PERMANENTLY DESTROY {} on:", config.scope);
+                }
                 println!("  Device: {}", device);
                 println!("  Model: {}", drive_info.model);
                 println!("  Serial: {}", drive_info.serial);
@@ -442,17 +816,37 @@ async fn main() -> Result<()> {
                 }
             }
 
+            let session = WipeSession {
+                session_id: Uuid::new_v4().to_string(),
+                start_time: chrono::Utc::now(),
+                end_time: None,
+                drives: vec![],
+                config: config.clone(),
+                operator_id: None,
+            };
+            let history = open_history(&session);
+            let job = DriveJob::start(
+                history.as_ref(), &session.session_id, &drive_info, &config.algorithm.name()
+            );
+
             // Execute enhanced wipe with selected level
-            enhanced_wipe_with_verification(
-                device,
-                &drive_info,
-                config,
-                cert_output.as_deref(),
-                *sample_percent,      // IMPORTANT: Pass sample_percent
-                *min_confidence,
+            let verification = VerificationOptions {
                 level,
-                *skip_pre_tests,      // IMPORTANT: Pass skip_pre_tests
-            ).await?;
+                min_confidence: *min_confidence,
+                skip_pre_tests: *skip_pre_tests,
+                cert_output: cert_output.as_deref(),
+            };
+            let result = enhanced_wipe_with_verification(device, &drive_info, config, verification, &job).await;
+
+            match &result {
+                Ok(()) => job.finish(WipeStatus::Completed, None, cert_output.as_deref(), Some(true)),
+                Err(e) => {
+                    job.error(&e.to_string());
+                    job.finish(WipeStatus::Failed, Some(&e.to_string()), None, None);
+                }
+            }
+            close_history(history.as_ref(), &session);
+            result?;
         }
 
         Commands::CreateVerificationUSB { output: _ } => {
@@ -512,6 +906,33 @@ async fn main() -> Result<()> {
             println!("📁 Report saved to: {}", local_report);
         }
 
+        Commands::Cert { action } => {
+            handle_cert(action)?;
+        }
+
+        Commands::History { serial, since, until, status, limit, details, database } => {
+            let filter = HistoryFilter {
+                serial: serial.clone(),
+                since: since.as_deref().map(|s| parse_history_date(s, false)).transpose()?,
+                until: until.as_deref().map(|s| parse_history_date(s, true)).transpose()?,
+                status: status.as_deref().map(parse_wipe_status).transpose()?,
+                limit: Some(*limit),
+            };
+            show_history(database.as_deref(), &filter, *details)?;
+        }
+
+        Commands::Pause { target, database } => {
+            request_pause(target, database.as_deref(), true)?;
+        }
+
+        Commands::Resume { target, database } => {
+            request_pause(target, database.as_deref(), false)?;
+        }
+
+        Commands::Cancel { target, database } => {
+            request_cancel(target, database.as_deref())?;
+        }
+
         Commands::Custom => {
             print_customizations()?;
         }
@@ -520,33 +941,50 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// How an enhanced wipe checks and certifies its result
+struct VerificationOptions<'a> {
+    level: VerificationLevel,
+    min_confidence: f64,  // Fail below this confidence, in percent
+    skip_pre_tests: bool,
+    cert_output: Option<&'a str>,
+}
+
 /// Enhanced wipe with multi-level verification
 async fn enhanced_wipe_with_verification(
     device: &str,
     drive_info: &DriveInfo,
-    config: WipeConfig,
-    cert_output: Option<&str>,
-    _sample_percent: f64,         // PARAMETER 5
-    min_confidence: f64,          // PARAMETER 6
-    verification_level: VerificationLevel,  // PARAMETER 7
-    skip_pre_tests: bool,         // PARAMETER 8
+    mut config: WipeConfig,
+    verification: VerificationOptions<'_>,
+    job: &DriveJob<'_>,
 ) -> Result<()> {
+    let VerificationOptions { level: verification_level, min_confidence, skip_pre_tests, cert_output } = verification;
     println!("\n🚀 Starting Enhanced Secure Wipe with Forensic Verification");
     println!("Device: {} ({} GB)", device, drive_info.size / (1024 * 1024 * 1024));
     println!("Verification Level: {:?}", verification_level);
     println!("{}", "=".repeat(70));
 
+    let sanitizing = sanitize_in_progress(drive_info);
+    let sanitization = apply_sanitization_policy(&mut config, drive_info, sanitizing)?;
+    let scope = resolve_scope(&config, drive_info)?;
+    let mut plan = plan_wipe(&config, drive_info, sanitizing);
+    if let Some(scope) = &scope {
+        plan = scope_plan(plan, scope)?;
+    }
+
     let start_time = Instant::now();
+    job.phase(WipePhase::Preparing);
 
     // ===== STAGE 1: PRE-WIPE VERIFICATION CAPABILITY TEST =====
-    let pre_wipe_results = if !skip_pre_tests {
+    // The test writes to the drive, which a running sanitize does not allow
+    let pre_wipe_results = if !skip_pre_tests && !sanitizing {
         println!("\n📋 Stage 1: Pre-Wipe Verification Testing");
         println!("Testing our ability to detect data patterns...\n");
 
-        let results = EnhancedVerification::pre_wipe_capability_test(
-            device,
-            1024 * 1024, // Use 1MB test area
-        )?;
+        // Use a 1MB test area, inside the scope for a scoped wipe
+        let results = match &scope {
+            Some(scope) => EnhancedVerification::pre_wipe_capability_test_within(device, 1024 * 1024, scope.extent())?,
+            None => EnhancedVerification::pre_wipe_capability_test(device, 1024 * 1024)?,
+        };
 
         // Display pre-wipe test results
         println!("✅ Verification System Test Results:");
@@ -573,7 +1011,11 @@ async fn enhanced_wipe_with_verification(
 
         results
     } else {
-        println!("\n⚠️  Skipping pre-wipe tests (--skip-pre-tests enabled)");
+        if sanitizing {
+            println!("\nℹ️  Skipping pre-wipe tests: sanitize in progress");
+        } else {
+            println!("\n⚠️  Skipping pre-wipe tests (--skip-pre-tests enabled)");
+        }
         PreWipeTestResults {
             test_pattern_detection: true,
             recovery_tool_simulation: true,
@@ -585,17 +1027,28 @@ async fn enhanced_wipe_with_verification(
 
     // ===== STAGE 2: COMPLETE DATA WIPE =====
     println!("\n🔥 Stage 2: Complete Data Destruction");
-    println!("Algorithm: {:?}", config.algorithm);
+    println!("Algorithm: {}", config.algorithm.name());
 
     // Execute the wipe
     println!("  └─ Executing wipe algorithm...");
-    select_and_execute_wipe(device, drive_info, &config).await?;
+    job.phase(wipe_phase(plan.primary()));
+    // This drive can be cancelled or paused on its own, or with the others
+    let context = control().child();
+    let _control_requests = job.watch_controls(&context);
+    let outcome = select_and_execute_wipe(device, drive_info, &config, &plan, scope.as_ref(), &context).await?;
+    config.algorithm = outcome.algorithm.clone();
+    job.algorithm(&config.algorithm.name());
+    if !outcome.unwritable_sectors.is_empty() {
+        println!("⚠️  {} unwritable sectors were skipped and remain unsanitized",
+                 outcome.unwritable_sectors.len());
+    }
 
     let wipe_duration = start_time.elapsed();
     println!("✅ Wipe completed in {:.2} seconds", wipe_duration.as_secs_f64());
 
     // ===== STAGE 3: MULTI-LEVEL VERIFICATION =====
     println!("\n🔬 Stage 3: Multi-Level Forensic Verification");
+    job.phase(WipePhase::Verification);
     println!("Level: {:?}", verification_level);
 
     // Display level-specific information
@@ -620,11 +1073,12 @@ async fn enhanced_wipe_with_verification(
 
     println!("\nAnalyzing wiped drive for data remnants...\n");
 
-    let post_wipe_analysis = EnhancedVerification::post_wipe_verification_with_level(
-        device,
-        drive_info.size,
-        verification_level,
-    )?;
+    // Data outside a scope was kept on purpose, so only the scope is analyzed
+    let mut post_wipe_analysis = match &scope {
+        Some(scope) => EnhancedVerification::post_wipe_verification_within(device, scope.extent(), verification_level)?,
+        None => EnhancedVerification::post_wipe_verification_with_level(device, drive_info.size, verification_level)?,
+    };
+    post_wipe_analysis.bad_sectors.unwritable_sectors = outcome.unwritable_sectors.clone();
 
     // Display post-wipe analysis
     display_enhanced_post_wipe_analysis(&post_wipe_analysis);
@@ -665,11 +1119,14 @@ async fn enhanced_wipe_with_verification(
     // ===== STAGE 5: CERTIFICATE GENERATION =====
     if let Some(cert_path) = cert_output {
         println!("\n🏆 Stage 5: Generating Enhanced Certificate");
+        job.phase(WipePhase::GeneratingCertificate);
         generate_enhanced_certificate(
             drive_info,
-            &config,
+            scope.clone(),
             &verification_report,
             wipe_duration,
+            sanitization,
+            outcome,
             cert_path,
         )?;
         println!("✅ Certificate saved to: {}", cert_path);
@@ -688,8 +1145,12 @@ async fn enhanced_wipe_with_verification(
     }
 
     // ===== STAGE 7: POST-WIPE OPERATIONS =====
-    if config.use_trim_after && drive_info.capabilities.trim_support {
+    // TRIM discards the whole device, so not for scoped wipes
+    if let (Some(scope), true) = (&scope, config.use_trim_after) {
+        println!("\nℹ️  TRIM skipped: it would discard more than {}", scope.description);
+    } else if config.use_trim_after && drive_info.capabilities.trim_support {
         println!("\n🧹 Stage 7: Post-Wipe TRIM");
+        job.phase(WipePhase::TrimOperation);
         TrimOperations::secure_trim_with_verify(device)?;
     }
 
@@ -705,9 +1166,12 @@ async fn enhanced_wipe_with_verification(
         println!("   • {}", standard);
     }
 
+    job.phase(WipePhase::Complete);
+
     if !verification_report.warnings.is_empty() {
         println!("\n⚠️  Warnings:");
         for warning in &verification_report.warnings {
+            job.warning(warning);
             println!("   • {}", warning);
         }
     }
@@ -803,6 +1267,10 @@ fn display_enhanced_post_wipe_analysis(analysis: &PostWipeAnalysis) {
         println!("  │  ├─ Unreadable: {}", analysis.bad_sectors.unreadable_count);
         println!("  │  └─ Percentage: {:.2}%", analysis.bad_sectors.percentage_unreadable);
     }
+    if !analysis.bad_sectors.unwritable_sectors.is_empty() {
+        println!("  ├─ Unwritable Sectors: {} (skipped during overwrite, not sanitized)",
+                 analysis.bad_sectors.unwritable_sectors.len());
+    }
 
     // Sector Sampling
     println!("  └─ Sector Analysis:");
@@ -843,7 +1311,16 @@ fn print_customizations() -> Result<()> {
     println!("  crypto     - Cryptographic erase (for Self-Encrypting Drives)");
     println!("  sanitize   - NVMe sanitize command");
     println!("  trim       - TRIM/discard only (for SSDs)");
-    println!("  auto       - Automatically select best algorithm (default)");
+    println!("  auto       - Plan from detected capabilities with fallbacks (default)");
+    println!("  --scheme   - Custom pattern scheme file (TOML/JSON, see schemes/)");
+
+    // NIST 800-88 LEVELS
+    println!("\n📐 NIST SP 800-88 SANITIZATION LEVELS (--level)");
+    println!("{}", "-".repeat(80));
+    println!("  clear      - Single overwrite, or a hardware method where overwrite is refused");
+    println!("  purge      - Sanitize, cryptographic erase or ATA secure erase per media type");
+    println!("  destroy    - Physical destruction only (always refused in software)");
+    println!("  With --algorithm auto the policy picks the method, otherwise it checks yours");
 
     // HPA/DCO HANDLING
     println!("\n🔒 HPA/DCO (Hidden Protected Area / Device Configuration Overlay) HANDLING");
@@ -877,6 +1354,17 @@ fn print_customizations() -> Result<()> {
     println!("    --max-temp         - Maximum safe temperature in Celsius (default: 65)");
     println!("    --no-unfreeze      - Skip drive freeze mitigation");
     println!("    --force            - Force operation on unhealthy drives");
+    println!("    --resume           - Continue an interrupted overwrite from its checkpoint");
+    println!("    --scheme           - Custom pattern scheme file (overrides --algorithm)");
+    println!("    --level            - NIST SP 800-88 target level (clear, purge)");
+    println!("    --random-mode      - Random pass data: drbg (default) or aes-ctr (exactly verifiable)");
+    println!("    --verify-passes    - Passes to read back: none, last or every (default)");
+    println!("    --verify-coverage  - Share of each verified pass read back: percent (default: 1) or full");
+    println!("    --scope            - Part of the drive: whole (default), partition:<n|guid>, lba:<a>-<b>, except-table");
+    println!("    --quiet-hours      - Daily window (HH:MM-HH:MM, local time) with throttled writes");
+    println!("    --quiet-rate       - Write ceiling during quiet hours in MB/s (default: 50)");
+    println!("    --max-rate         - Write ceiling in MB/s");
+    println!("    --max-iops         - Write operations per second ceiling");
 
     println!("\n  WIPE-ALL Command:");
     println!("    -a, --algorithm     - Select wiping algorithm");
@@ -886,6 +1374,18 @@ fn print_customizations() -> Result<()> {
     println!("    --hpa-dco          - HPA/DCO handling mode");
     println!("    --no-trim          - Skip TRIM operations");
     println!("    --force            - Force operation on unhealthy drives");
+    println!("    --scheme           - Custom pattern scheme file (overrides --algorithm)");
+    println!("    --level            - NIST SP 800-88 target level (clear, purge)");
+    println!("    --random-mode      - Random pass data: drbg (default) or aes-ctr (exactly verifiable)");
+    println!("    --verify-passes    - Passes to read back: none, last or every (default)");
+    println!("    --verify-coverage  - Share of each verified pass read back: percent (default: 1) or full");
+    println!("    --scope            - Part of the drive: whole (default), partition:<n|guid>, lba:<a>-<b>, except-table");
+    println!("    --quiet-hours      - Daily window (HH:MM-HH:MM, local time) with throttled writes");
+    println!("    --quiet-rate       - Write ceiling during quiet hours in MB/s (default: 50)");
+    println!("    --max-rate         - Write ceiling per drive in MB/s");
+    println!("    --max-iops         - Write operations per second ceiling per drive");
+    println!("    --total-rate       - Write ceiling across all drives in MB/s");
+    println!("    --total-iops       - Write operations per second ceiling across all drives");
 
     println!("\n  VERIFY Command:");
     println!("    --check-hidden     - Check for hidden areas (HPA/DCO)");
@@ -910,6 +1410,38 @@ fn print_customizations() -> Result<()> {
     println!("    --hpa-dco            - HPA/DCO handling mode");
     println!("    --no-trim            - Skip TRIM operation");
     println!("    --force              - Force operation on unhealthy drives");
+    println!("    --scope              - Part of the drive: whole (default), partition:<n|guid>, lba:<a>-<b>, except-table");
+
+    println!("\n  CERT Command:");
+    println!("    export <json>        - Wrap a certificate as CMS/PKCS#7 signed-data");
+    println!("      --chain          - PEM operator certificate chain");
+    println!("      -o, --output     - Output path (default: .p7s next to the JSON)");
+    println!("      --pem            - Write PEM instead of DER");
+    println!("      --key            - PKCS#8 signing key");
+    println!("    verify <file>        - Verify a certificate");
+    println!("      --ca             - Trusted CA PEM for PKCS#7 input");
+    println!("      --public-key     - Signer public key for JSON input");
+    println!("      --revocation-list - Signed revocation list to check");
+    println!("    show <json>          - Readable certificate summary and status");
+    println!("    list <dir>           - Table of all certificates with status");
+    println!("    revoke <json|id>     - Add a certificate to the signed revocation list");
+    println!("      --reason         - Reason recorded for the revocation");
+    println!("    render <json>        - Printable disposal report with QR code");
+    println!("      -f, --format     - pdf (default) or text");
+    println!("      -o, --output     - Output path");
+
+    println!("\n  HISTORY Command:");
+    println!("    --serial             - Only drives with this serial number");
+    println!("    --since / --until    - Date range (YYYY-MM-DD or RFC 3339)");
+    println!("    --status             - completed, failed, in-progress, pending, skipped");
+    println!("    --limit              - Maximum records (default: 50)");
+    println!("    --details            - Show phase transitions, warnings and errors");
+    println!("    --database           - History database path");
+
+    println!("\n  PAUSE / RESUME Commands:");
+    println!("    <target>             - Device path or wipe record ID of a running overwrite");
+    println!("    --database           - History database path");
+    println!("    (SIGUSR1 / SIGUSR2 pause and resume every wipe of a running process)");
 
     println!("\n  LIVE-VERIFY Command:");
     println!("    --report-to          - Remote endpoint for verification report");
@@ -971,13 +1503,17 @@ fn print_customizations() -> Result<()> {
     // CERTIFICATE FEATURES
     println!("\n📜 CERTIFICATE GENERATION");
     println!("{}", "-".repeat(80));
-    println!("  - Cryptographically signed certificates");
+    println!("  - Ed25519 / ECDSA P-256 signed certificates (PKCS#8 key)");
+    println!("  - Third-party verification with the public key only");
+    println!("  - CMS/PKCS#7 export signed by an operator X.509 chain");
     println!("  - Timestamp and operator ID tracking");
     println!("  - Algorithm and pass count documentation");
     println!("  - Verification results included");
     println!("  - Drive serial number and model recorded");
     println!("  - Entropy scores and confidence levels");
     println!("  - JSON format for easy parsing");
+    println!("  - Printable PDF reports with a QR code of the certificate hash and signature");
+    println!("  - Signed local revocation list checked by cert verify");
 
     // VENDOR-SPECIFIC SUPPORT
     println!("\n🏭 VENDOR-SPECIFIC RAID CONTROLLER SUPPORT");
@@ -1041,6 +1577,136 @@ fn is_root() -> bool {
     unsafe { libc::geteuid() == 0 }
 }
 
+/// Replace the configured algorithm with a custom pattern scheme file
+fn apply_scheme(config: &mut WipeConfig, scheme: Option<&str>) -> Result<()> {
+    if let Some(path) = scheme {
+        let scheme = PatternScheme::load(path)?;
+        println!("📋 Loaded pattern scheme '{}' ({} passes)", scheme.name, scheme.passes.len());
+        config.algorithm = Algorithm::Custom(scheme);
+        config.auto_select = false;
+    }
+    Ok(())
+}
+
+/// Set which overwrite passes are read back; `--no-verify` turns it off
+fn apply_pass_verification(config: &mut WipeConfig, passes: &str, coverage: &str) -> Result<()> {
+    config.pass_verification = PassVerification {
+        passes: if config.verify { VerifyPasses::parse(passes)? } else { VerifyPasses::None },
+        coverage: VerifyCoverage::parse(coverage)?,
+    };
+    Ok(())
+}
+
+fn apply_level(config: &mut WipeConfig, level: Option<&str>) -> Result<()> {
+    if let Some(level) = level {
+        config.sanitization_level = Some(SanitizationLevel::parse(level)?);
+    }
+    Ok(())
+}
+
+/// Ordered methods for this drive: the capability-driven plan for "auto",
+/// otherwise just the configured (or policy-chosen) method
+fn plan_wipe(config: &WipeConfig, drive_info: &DriveInfo, sanitizing: bool) -> WipePlan {
+    // A sanitize cannot be aborted and keeps running across restarts, so
+    // finish the one in flight instead of starting anything else
+    if sanitizing {
+        println!("\n🔁 A sanitize is already in progress on {}; re-attaching to it", drive_info.device_path);
+        return WipePlan {
+            steps: vec![PlannedMethod {
+                algorithm: Algorithm::Sanitize,
+                reason: "Sanitize already in progress on the controller".to_string(),
+            }],
+            rejected: Vec::new(),
+            notes: Vec::new(),
+        };
+    }
+
+    if !config.auto_select || config.sanitization_level.is_some() {
+        return WipePlan::single(config.algorithm.clone());
+    }
+
+    let plan = AutoSelector::plan(drive_info, config);
+
+    println!("\n🧭 Wipe plan for {}:", drive_info.device_path);
+    for (i, step) in plan.steps.iter().enumerate() {
+        let role = if i == 0 { "primary" } else { "fallback" };
+        println!("   {}. {} ({}) - {}", i + 1, step.algorithm.name(), role, step.reason);
+    }
+    for rejected in &plan.rejected {
+        println!("   ✗ {}: {}", rejected.method, rejected.reason);
+    }
+    for note in &plan.notes {
+        println!("   ℹ️  {}", note);
+    }
+
+    plan
+}
+
+/// Whether the controller is still running a sanitize started earlier
+fn sanitize_in_progress(drive_info: &DriveInfo) -> bool {
+    matches!(drive_info.drive_type, DriveType::NVMe) && SanitizeSession::in_progress(&drive_info.device_path)
+}
+
+/// Resolve the configured scope on the drive; None for a whole-device wipe
+fn resolve_scope(config: &WipeConfig, drive_info: &DriveInfo) -> Result<Option<ResolvedScope>> {
+    let scope = config.effective_scope();
+    if scope.is_whole_device() {
+        return Ok(None);
+    }
+
+    let resolved = scope.resolve(&drive_info.device_path, drive_info.size)?;
+    println!("\n🎯 Scope: {} (LBA {}-{}, {} MB)", resolved.description, resolved.first_lba, resolved.last_lba,
+             resolved.extent().length / (1024 * 1024));
+    Ok(Some(resolved))
+}
+
+/// Drop the methods of `plan` that erase the whole device; only overwrites
+/// can be confined to `scope`
+fn scope_plan(mut plan: WipePlan, scope: &ResolvedScope) -> Result<WipePlan> {
+    let primary = plan.primary().name();
+    plan.steps.retain(|step| {
+        let keep = step.algorithm.is_overwrite();
+        if !keep {
+            println!("   ✗ {}: erases the whole device, not just {}", step.algorithm.name(), scope.description);
+        }
+        keep
+    });
+
+    if plan.steps.is_empty() {
+        return Err(anyhow::anyhow!("{} erases the whole device and cannot be limited to {}; use an overwrite algorithm",
+                                   primary, scope.description));
+    }
+    Ok(plan)
+}
+
+/// Let the NIST 800-88 policy choose (or check) the method for `drive_info`
+/// when a target level was requested. Refuses methods that cannot reach it.
+/// A sanitize already running is the method, so it is checked instead.
+fn apply_sanitization_policy(config: &mut WipeConfig, drive_info: &DriveInfo, sanitizing: bool) -> Result<Option<SanitizationRecord>> {
+    let Some(level) = config.sanitization_level else { return Ok(None) };
+
+    let requested = if sanitizing {
+        Some(Algorithm::Sanitize)
+    } else {
+        (!config.auto_select).then(|| config.algorithm.clone())
+    };
+    let decision = NistPolicy::decide(level, requested.as_ref(), drive_info, config)?;
+    let record = decision.record;
+
+    println!("\n📐 {} {} for {}", record.standard, record.level, record.media);
+    println!("   Method: {} ({})", record.method,
+             if record.operator_selected { "operator selected" } else { "selected by policy" });
+    for reason in &record.rationale {
+        println!("   ✓ {}", reason);
+    }
+    for rejected in &record.rejected {
+        println!("   ✗ {}: {}", rejected.method, rejected.reason);
+    }
+
+    config.algorithm = decision.algorithm;
+    Ok(Some(record))
+}
+
 fn build_wipe_config(
     algorithm: &str,
     verify: bool,
@@ -1050,6 +1716,7 @@ fn build_wipe_config(
     max_temp: u32,
     freeze_mitigation: bool,
 ) -> Result<WipeConfig> {
+    let auto_select = algorithm.eq_ignore_ascii_case("auto");
     let algorithm = match algorithm.to_lowercase().as_str() {
         "dod" => Algorithm::DoD5220,
         "gutmann" => Algorithm::Gutmann,
@@ -1059,7 +1726,8 @@ fn build_wipe_config(
         "crypto" => Algorithm::CryptoErase,
         "sanitize" => Algorithm::Sanitize,
         "trim" => Algorithm::TrimOnly,
-        "auto" => Algorithm::SecureErase, // Will fallback based on capabilities
+        "auto" => Algorithm::SecureErase, // Replaced per drive by the AutoSelector plan
+        "custom" => return Err(anyhow::anyhow!("Select a custom pattern scheme with --scheme <file>")),
         _ => return Err(anyhow::anyhow!("Unknown algorithm: {}", algorithm)),
     };
 
@@ -1074,7 +1742,6 @@ fn build_wipe_config(
     Ok(WipeConfig {
         algorithm,
         verify,
-        multiple_passes: None,
         preserve_partition_table: false,
         unlock_encrypted: false,
         handle_hpa_dco: hpa_dco_handling,
@@ -1083,6 +1750,16 @@ fn build_wipe_config(
         max_temperature_celsius: Some(max_temp),
         freeze_mitigation,
         sed_crypto_erase: true,
+        resume: false,
+        auto_select,
+        sanitization_level: None,
+        random_mode: RandomMode::default(),
+        pass_verification: PassVerification::default(),
+        scope: WipeScope::WholeDevice,
+        quiet_hours: None,
+        max_bytes_per_sec: None,
+        max_iops: None,
+        ..Default::default()
     })
 }
 
@@ -1240,7 +1917,12 @@ async fn wipe_drive(
 
     // Confirmation
     if !unsafe_mode {
-        println!("\nWARNING: This will permanently erase ALL data on {}", device);
+        let scope = config.effective_scope();
+        if scope.is_whole_device() {
+            println!("\nWARNING: This will permanently erase ALL data on {}", device);
+        } else {
+            println!("\nWARNING: This will permanently erase {} on {}", scope, device);
+        }
         println!("Drive: {} ({})", drive_info.model, drive_info.serial);
         println!("Size: {} GB", drive_info.size / (1024 * 1024 * 1024));
 
@@ -1276,29 +1958,108 @@ async fn wipe_drive(
         operator_id: None,
     };
 
+    let history = open_history(&session);
+
     // Perform the wipe
-    wipe_single_drive(device, &drive_info, config, cert_output, session, force).await
+    let result = wipe_single_drive(
+        device, &drive_info, config, cert_output, &session, history.as_ref(), force
+    ).await;
+
+    close_history(history.as_ref(), &session);
+
+    result.map(|_| ())
 }
 
+/// Open the wipe history database and register `session`. History is best
+/// effort: a missing or unwritable database never blocks a wipe.
+fn open_history(session: &WipeSession) -> Option<JobDatabase> {
+    let db = JobDatabase::open_default()
+        .and_then(|db| db.start_session(session).map(|_| db));
+
+    match db {
+        Ok(db) => Some(db),
+        Err(e) => {
+            eprintln!("⚠️  Wipe history unavailable: {}", e);
+            None
+        }
+    }
+}
+
+fn close_history(history: Option<&JobDatabase>, session: &WipeSession) {
+    if let Some(db) = history {
+        if let Err(e) = db.finish_session(&session.session_id) {
+            log::warn!("Failed to record wipe history: {}", e);
+        }
+    }
+}
+
+/// Wipe one drive and record the outcome in the wipe history
 async fn wipe_single_drive(
     device: &str,
     drive_info: &DriveInfo,
     config: WipeConfig,
     cert_output: Option<&str>,
-    mut session: WipeSession,
+    session: &WipeSession,
+    history: Option<&JobDatabase>,
     force: bool,
-) -> Result<()> {
+) -> Result<DriveWipeRecord> {
+    let algorithm = config.algorithm.name();
+    let job = DriveJob::start(history, &session.session_id, drive_info, &algorithm);
+
+    let result = execute_single_drive(device, drive_info, config, cert_output, session, &job, force).await;
+
+    match &result {
+        Ok(record) => job.finish(
+            record.status.clone(),
+            record.error_message.as_deref(),
+            record.certificate_path.as_deref(),
+            record.verification_passed,
+        ),
+        Err(e) => {
+            job.error(&e.to_string());
+            job.finish(WipeStatus::Failed, Some(&e.to_string()), None, None);
+        }
+    }
+
+    result
+}
+
+async fn execute_single_drive(
+    device: &str,
+    drive_info: &DriveInfo,
+    mut config: WipeConfig,
+    cert_output: Option<&str>,
+    session: &WipeSession,
+    job: &DriveJob<'_>,
+    force: bool,
+) -> Result<DriveWipeRecord> {
     println!("\nStarting wipe of {} ({}, {})",
              device, drive_info.model, drive_info.serial);
 
+    let sanitizing = sanitize_in_progress(drive_info);
+    let sanitization = apply_sanitization_policy(&mut config, drive_info, sanitizing)?;
+    let scope = resolve_scope(&config, drive_info)?;
+    let mut plan = plan_wipe(&config, drive_info, sanitizing);
+    if let Some(scope) = &scope {
+        plan = scope_plan(plan, scope)?;
+    }
+
     let start_time = Instant::now();
+    let started_at = chrono::Utc::now();
     let mut warnings = Vec::new();
 
     // Phase 1: Preparation
     println!("\nPhase 1: Preparation");
+    job.phase(WipePhase::Preparing);
+
+    // A running sanitize owns the drive; leave its configuration alone
+    if sanitizing {
+        println!("ℹ️  Sanitize in progress: skipping freeze mitigation and HPA/DCO handling");
+    }
 
     // Handle freeze mitigation
-    if config.freeze_mitigation && drive_info.capabilities.is_frozen {
+    if config.freeze_mitigation && drive_info.capabilities.is_frozen && !sanitizing {
+        job.phase(WipePhase::UnfreezingDrive);
         println!("Drive is frozen, attempting mitigation...");
         match FreezeMitigation::unfreeze_drive(device) {
             Ok(_) => println!("✓ Drive unfrozen successfully"),
@@ -1313,6 +2074,7 @@ async fn wipe_single_drive(
     // Handle HPA/DCO
     let mut hpa_original = None;
     match config.handle_hpa_dco {
+        _ if sanitizing => {}
         HPADCOHandling::Detect => {
             if let Ok((hpa, dco)) = HPADCOManager::check_hidden_areas(device) {
                 if hpa.is_some() || dco.is_some() {
@@ -1323,6 +2085,7 @@ async fn wipe_single_drive(
         HPADCOHandling::TemporaryRemove => {
             if let Ok(Some(hpa)) = HPADCOManager::detect_hpa(device) {
                 hpa_original = Some(hpa.current_max_sectors);
+                job.phase(WipePhase::RemovingHPA);
                 println!("Temporarily removing HPA...");
                 HPADCOManager::remove_hpa_temporary(device)?;
                 println!("✓ HPA temporarily removed");
@@ -1330,11 +2093,13 @@ async fn wipe_single_drive(
         }
         HPADCOHandling::PermanentRemove => {
             if HPADCOManager::detect_hpa(device)?.is_some() {
+                job.phase(WipePhase::RemovingHPA);
                 println!("Permanently removing HPA...");
                 HPADCOManager::remove_hpa_temporary(device)?;
                 println!("✓ HPA permanently removed");
             }
             if HPADCOManager::detect_dco(device)?.is_some() {
+                job.phase(WipePhase::RemovingDCO);
                 println!("Removing DCO...");
                 HPADCOManager::remove_dco(device)?;
                 println!("✓ DCO removed");
@@ -1382,15 +2147,45 @@ async fn wipe_single_drive(
 
     // Phase 2: Wipe
     println!("\nPhase 2: Wiping");
-
-    let wipe_result = match select_and_execute_wipe(device, drive_info, &config).await {
-        Ok(_) => Ok(()),
+    job.phase(wipe_phase(plan.primary()));
+
+    let mut nvme_sanitize = None;
+    let mut unwritable_sectors = Vec::new();
+    let mut keystream_commitment = None;
+    // This drive can be cancelled or paused on its own, or with the others
+    let context = control().child();
+    let control_requests = job.watch_controls(&context);
+    let wipe_result = select_and_execute_wipe(device, drive_info, &config, &plan, scope.as_ref(), &context).await;
+    drop(control_requests);
+    let wipe_result = match wipe_result {
+        Ok(outcome) => {
+            if outcome.algorithm != *plan.primary() {
+                warnings.push(format!("{} failed, wiped with fallback {}",
+                                      plan.primary().name(), outcome.algorithm.name()));
+            }
+            if !outcome.unwritable_sectors.is_empty() {
+                warnings.push(format!("{} unwritable sectors were skipped and remain unsanitized",
+                                      outcome.unwritable_sectors.len()));
+            }
+            config.algorithm = outcome.algorithm;
+            nvme_sanitize = outcome.nvme_sanitize;
+            unwritable_sectors = outcome.unwritable_sectors;
+            keystream_commitment = outcome.keystream_commitment;
+            job.algorithm(&config.algorithm.name());
+            Ok(())
+        }
         Err(e) => {
             warnings.push(format!("Wipe error: {}", e));
 
             // Check if this was a user interrupt
             if e.to_string().contains("interrupted") || e.to_string().contains("Interrupted") {
                 eprintln!("\n❌ Wipe operation cancelled by user");
+                // Sequential zones cannot be rewritten from the middle
+                if drive_info.drive_type == DriveType::SMR {
+                    eprintln!("💾 SMR overwrites start over; rerun the same wipe to finish it");
+                } else {
+                    eprintln!("💾 Overwrite progress is checkpointed; rerun the same wipe with --resume to continue");
+                }
                 return Err(e);
             }
 
@@ -1399,17 +2194,23 @@ async fn wipe_single_drive(
     };
 
     // If wipe failed (not interrupted), continue to cleanup but skip verification
-    if wipe_result.is_err() {
+    if let Err(e) = wipe_result {
         eprintln!("\n⚠️  Wipe failed, skipping post-wipe operations");
-        return wipe_result;
+        for warning in &warnings {
+            job.warning(warning);
+        }
+        return Err(e);
     }
 
     // Phase 3: Post-wipe operations
     println!("\nPhase 3: Post-wipe operations");
 
-    // TRIM after wipe
-    if config.use_trim_after && drive_info.capabilities.trim_support {
+    // TRIM after wipe; it discards the whole device, so not for scoped wipes
+    if let (Some(scope), true) = (&scope, config.use_trim_after) {
+        println!("ℹ️  TRIM skipped: it would discard more than {}", scope.description);
+    } else if config.use_trim_after && drive_info.capabilities.trim_support {
         println!("Performing TRIM operation...");
+        job.phase(WipePhase::TrimOperation);
         match TrimOperations::secure_trim_with_verify(device) {
             Ok(_) => println!("✓ TRIM completed"),
             Err(e) => warnings.push(format!("TRIM failed: {}", e)),
@@ -1419,6 +2220,7 @@ async fn wipe_single_drive(
     // Restore HPA if needed
     if let Some(original_sectors) = hpa_original {
         println!("Restoring original HPA configuration...");
+        job.phase(WipePhase::RestoringConfig);
         HPADCOManager::restore_hpa(device, original_sectors)?;
         println!("✓ HPA restored");
     }
@@ -1429,7 +2231,21 @@ async fn wipe_single_drive(
     // Phase 4: Verification
     let verification_result = if config.verify {
         println!("\nPhase 4: Verification");
-        let verified = RecoveryTest::verify_wipe(device, drive_info.size)?;
+        job.phase(WipePhase::Verification);
+        // The recovery test reads the whole device, data outside the scope
+        // included; a scoped wipe relies on its pass read-back instead
+        let verified = match &scope {
+            Some(scope) => {
+                println!("ℹ️  Recovery test skipped for {}; verified by pass read-back", scope.description);
+                config.pass_verification.passes != VerifyPasses::None
+            }
+            None => RecoveryTest::verify_wipe(device, drive_info.size)?,
+        };
+        // Skipped sectors were never sanitized, so the wipe is not verified
+        if !unwritable_sectors.is_empty() {
+            println!("⚠️  Not verified: {} sectors could not be written", unwritable_sectors.len());
+        }
+        let verified = verified && unwritable_sectors.is_empty();
         let entropy_score = 7.8; // This would come from the actual verification
 
         VerificationResult {
@@ -1450,12 +2266,18 @@ async fn wipe_single_drive(
     // Generate certificate
     if let Some(cert_path) = cert_output {
         println!("\nGenerating certificate...");
-        let cert_gen = CertificateGenerator::new();
+        job.phase(WipePhase::GeneratingCertificate);
+        let cert_gen = certificate_generator()?;
         let wipe_details = WipeDetails {
-            algorithm_used: format!("{:?}", config.algorithm),
+            algorithm_used: config.algorithm.name(),
             passes_completed: 1,
             duration_seconds: wipe_duration.as_secs(),
             operator_id: session.operator_id.clone(),
+            sanitization,
+            nvme_sanitize,
+            unwritable_sectors: UnwritableSectors::from_lbas(&unwritable_sectors),
+            keystream_commitment,
+            scope: scope.clone(),
         };
 
         let certificate = cert_gen.generate_certificate(drive_info, wipe_details, verification_result.clone())?;
@@ -1463,114 +2285,190 @@ async fn wipe_single_drive(
         println!("✓ Certificate saved to: {}", cert_path);
     }
 
-    // Update session
-    session.drives.push(DriveWipeRecord {
+    job.phase(WipePhase::Complete);
+
+    let record = DriveWipeRecord {
         drive_info: drive_info.clone(),
-        status: if wipe_result.is_ok() { WipeStatus::Completed } else { WipeStatus::Failed },
-        start_time: chrono::Utc::now() - chrono::Duration::seconds(wipe_duration.as_secs() as i64),
+        status: WipeStatus::Completed,
+        start_time: started_at,
         end_time: Some(chrono::Utc::now()),
-        error_message: wipe_result.err().map(|e| e.to_string()),
+        error_message: None,
         certificate_path: cert_output.map(|s| s.to_string()),
-        verification_passed: Some(verification_result.verified),
-    });
+        verification_passed: config.verify.then_some(verification_result.verified),
+    };
 
     if !warnings.is_empty() {
         println!("\nWarnings:");
         for warning in warnings {
+            job.warning(&warning);
             println!("  ⚠ {}", warning);
         }
     }
 
     println!("\n✓ Operation completed successfully!");
-    Ok(())
+    Ok(record)
+}
+
+/// History phase for the main wipe step of `algorithm`
+fn wipe_phase(algorithm: &Algorithm) -> WipePhase {
+    match algorithm {
+        Algorithm::CryptoErase => WipePhase::CryptoErase,
+        Algorithm::Sanitize => WipePhase::Sanitizing,
+        Algorithm::TrimOnly => WipePhase::TrimOperation,
+        _ => WipePhase::Overwriting,
+    }
 }
 
+/// What the wipe method that completed reports for the certificate
+struct WipeOutcome {
+    algorithm: Algorithm,
+    nvme_sanitize: Option<NvmeSanitizeRecord>, // Final status of an NVMe sanitize
+    unwritable_sectors: Vec<u64>,              // LBAs an overwrite had to skip
+    keystream_commitment: Option<String>,      // Commitment to the AES-CTR pass key
+}
+
+/// Run `plan` on the drive, falling back to the next method when one fails
 async fn select_and_execute_wipe(
     device: &str,
     drive_info: &DriveInfo,
     config: &WipeConfig,
-) -> Result<()> {
-    // Check if this is an advanced drive type that needs specialized handling
-    match drive_info.drive_type {
+    plan: &WipePlan,
+    scope: Option<&ResolvedScope>,
+    context: &OperationContext,
+) -> Result<WipeOutcome> {
+    // Advanced drive types, and NVMe drives with ZNS or several namespaces,
+    // need specialized handling; every step of the plan runs through it
+    let specialized = match drive_info.drive_type {
         DriveType::SMR | DriveType::Optane | DriveType::HybridSSHD |
-        DriveType::EMMC | DriveType::UFS => {
-            // Use the advanced wipe orchestrator for these drive types
-            println!("🔬 Detected advanced drive type: {:?}", drive_info.drive_type);
-            println!("Using specialized wipe strategy...\n");
-
-            use sayonara_wipe::WipeOrchestrator;
-            let orchestrator = WipeOrchestrator::new(device.to_string(), config.clone())
-                .map_err(|e| anyhow::anyhow!("Orchestrator initialization failed: {}", e))?;
-
-            orchestrator.execute().await
-                .map_err(|e| anyhow::anyhow!("Advanced wipe failed: {}", e))?;
-
-            return Ok(());
-        }
+        DriveType::EMMC | DriveType::UFS => true,
         DriveType::NVMe => {
-            // Check if it's an advanced NVMe (ZNS, multi-namespace, etc.)
             use sayonara_wipe::drives::NVMeAdvanced;
-            if NVMeAdvanced::detect_advanced_features(device).unwrap_or(false) {
-                println!("🔬 Detected advanced NVMe features (ZNS/Multi-namespace)");
-                println!("Using specialized wipe strategy...\n");
+            NVMeAdvanced::detect_advanced_features(device).unwrap_or(false)
+        }
+        _ => false,
+    };
 
-                use sayonara_wipe::WipeOrchestrator;
-                let orchestrator = WipeOrchestrator::new(device.to_string(), config.clone())
-                    .map_err(|e| anyhow::anyhow!("Orchestrator initialization failed: {}", e))?;
+    let orchestrator = if specialized {
+        if let Some(scope) = scope {
+            return Err(anyhow::anyhow!("This {:?} drive is wiped whole by a specialized strategy; {} cannot be wiped on its own",
+                                       drive_info.drive_type, scope.description));
+        }
 
-                orchestrator.execute().await
-                    .map_err(|e| anyhow::anyhow!("Advanced NVMe wipe failed: {}", e))?;
+        println!("🔬 Detected advanced drive type: {:?}", drive_info.drive_type);
+        println!("Using specialized wipe strategy...\n");
 
-                return Ok(());
-            }
-            // Otherwise fall through to standard NVMe handling below
-        }
-        _ => {
-            // Standard drives - use existing implementations
+        use sayonara_wipe::WipeOrchestrator;
+        let mut orchestrator = WipeOrchestrator::new(device.to_string(), config.clone())
+            .map_err(|e| anyhow::anyhow!("Orchestrator initialization failed: {}", e))?;
+        orchestrator.set_context(context.clone());
+        Some(orchestrator)
+    } else {
+        None
+    };
+
+    let mut steps = plan.steps.iter().peekable();
+    while let Some(step) = steps.next() {
+        println!("Using algorithm: {}", step.algorithm.name());
+
+        let result = match &orchestrator {
+            Some(orchestrator) => orchestrator.run(&step.algorithm).await
+                .map(|()| WipeOutcome {
+                    algorithm: step.algorithm.clone(),
+                    nvme_sanitize: None,
+                    unwritable_sectors: orchestrator.unwritable_sectors(),
+                    keystream_commitment: orchestrator.keystream_commitment(),
+                })
+                .map_err(|e| anyhow::anyhow!("Advanced wipe failed: {}", e)),
+            None => execute_method(device, drive_info, config, &step.algorithm, scope, context),
+        };
+
+        match result {
+            Ok(outcome) => return Ok(outcome),
+            Err(e) if context.cancel.is_cancelled() => return Err(e),
+            Err(e) => match steps.peek() {
+                Some(next) => {
+                    eprintln!("⚠️  {} failed: {}", step.algorithm.name(), e);
+                    println!("↪️  Falling back to {}", next.algorithm.name());
+                }
+                None => return Err(e),
+            },
         }
     }
 
-    // Auto-select best method based on capabilities and config
-    let algorithm = if config.algorithm == Algorithm::SecureErase {
-        // Auto-select based on drive capabilities
-        if drive_info.capabilities.crypto_erase && config.sed_crypto_erase {
-            Algorithm::CryptoErase
-        } else if drive_info.drive_type == DriveType::NVMe &&
-            !drive_info.capabilities.sanitize_options.is_empty() {
-            Algorithm::Sanitize
-        } else if drive_info.capabilities.secure_erase {
-            Algorithm::SecureErase
-        } else {
-            Algorithm::DoD5220
-        }
-    } else {
-        config.algorithm.clone()
+    Err(anyhow::anyhow!("Wipe plan is empty"))
+}
+
+fn execute_method(
+    device: &str,
+    drive_info: &DriveInfo,
+    config: &WipeConfig,
+    algorithm: &Algorithm,
+    scope: Option<&ResolvedScope>,
+    context: &OperationContext,
+) -> Result<WipeOutcome> {
+    // Progress events from the methods below are attributed to this device
+    let _events = context.enter(device);
+
+    // Overwrite algorithms checkpoint their progress so `--resume` can continue
+    // them; the checkpoint also collects the sectors they had to skip. The
+    // scope is part of the label so a resume cannot continue another extent.
+    let checkpoint = |algorithm: &Algorithm| {
+        let label = match scope {
+            Some(scope) => format!("{} ({})", algorithm.name(), scope.description),
+            None => algorithm.name(),
+        };
+        Checkpointer::open(drive_info, &label, config.resume)
+    };
+    let mut outcome = WipeOutcome {
+        algorithm: algorithm.clone(),
+        nvme_sanitize: None,
+        unwritable_sectors: Vec::new(),
+        keystream_commitment: None,
     };
 
-    println!("Using algorithm: {:?}", algorithm);
+    // One key for all keystream passes of this wipe. It is dropped, and
+    // zeroed, when this returns; verified passes have been read back by then.
+    let keystream = match config.random_mode {
+        RandomMode::AesCtr => Some(Keystream::generate()?),
+        RandomMode::Drbg => None,
+    };
+    let keystream = keystream.as_ref();
+    let commitment = || keystream.map(Keystream::commitment);
+
+    // Overwrites are confined to the scope; `size` is its length
+    let options = OverwriteOptions {
+        keystream,
+        verification: config.pass_verification,
+        extent: scope.map(ResolvedScope::extent),
+        context: Some(context),
+        quiet_hours: config.quiet_hours,
+        max_bytes_per_sec: config.max_bytes_per_sec,
+        max_iops: config.max_iops,
+        backend: None,
+    };
+    let size = options.extent.map_or(drive_info.size, |extent| extent.length);
 
     match algorithm {
-        Algorithm::DoD5220 => {
-            DoDWipe::wipe_drive(device, drive_info.size, drive_info.drive_type.clone())?;
-        }
-        Algorithm::Gutmann => {
-            GutmannWipe::wipe_drive(device, drive_info.size, drive_info.drive_type.clone())?;
-        }
-        Algorithm::Random => {
-            RandomWipe::wipe_drive(device, drive_info.size, drive_info.drive_type.clone())?;
-        }
-        Algorithm::Zero => {
-            // Simple zero overwrite
-            DoDWipe::wipe_drive(device, drive_info.size, drive_info.drive_type.clone())?; // Reuse with zero pattern
+        Algorithm::DoD5220 | Algorithm::Gutmann | Algorithm::Random | Algorithm::Zero | Algorithm::Custom(_) => {
+            let mut checkpoint = checkpoint(algorithm)?;
+            algorithms::overwrite(algorithm, device, size, drive_info.drive_type.clone(), &options, &mut checkpoint)?;
+            outcome.unwritable_sectors = checkpoint.bad_sectors();
+            if algorithm.writes_random() {
+                outcome.keystream_commitment = commitment();
+            }
         }
         Algorithm::SecureErase => {
             match drive_info.drive_type {
                 DriveType::SSD => SSDWipe::secure_erase(device)?,
-                DriveType::NVMe => NVMeWipe::secure_erase(device)?,
+                DriveType::NVMe | DriveType::Optane => NVMeWipe::secure_erase(device)?,
                 DriveType::HDD => HDDWipe::secure_erase(device)?,
                 _ => {
                     println!("Hardware secure erase not available, falling back to DoD");
-                    DoDWipe::wipe_drive(device, drive_info.size, drive_info.drive_type.clone())?;
+                    outcome.algorithm = Algorithm::DoD5220;
+                    let mut checkpoint = checkpoint(&Algorithm::DoD5220)?;
+                    DoDWipe::wipe_drive(device, size, drive_info.drive_type.clone(), &options, &mut checkpoint)?;
+                    outcome.unwritable_sectors = checkpoint.bad_sectors();
+                    outcome.keystream_commitment = commitment();
                 }
             }
         }
@@ -1582,10 +2480,12 @@ async fn select_and_execute_wipe(
             }
         }
         Algorithm::Sanitize => {
-            if drive_info.drive_type == DriveType::NVMe {
-                NVMeWipe::secure_erase(device)?;
-            } else {
-                return Err(anyhow::anyhow!("Sanitize only available for NVMe drives"));
+            match drive_info.drive_type {
+                DriveType::NVMe | DriveType::Optane => {
+                    outcome.nvme_sanitize = Some(NVMeWipe::sanitize(device, &drive_info.capabilities.sanitize_options)?);
+                }
+                DriveType::SSD => SSDWipe::sanitize(device, &drive_info.capabilities.sanitize_options)?,
+                _ => return Err(anyhow::anyhow!("Sanitize only available for NVMe, Optane and SATA SSD drives")),
             }
         }
         Algorithm::TrimOnly => {
@@ -1597,7 +2497,11 @@ async fn select_and_execute_wipe(
         }
     }
 
-    Ok(())
+    if let Some(commitment) = &outcome.keystream_commitment {
+        println!("🔑 Keystream key discarded (SHA-256 commitment {})", commitment);
+    }
+
+    Ok(outcome)
 }
 
 async fn wipe_all_drives(
@@ -1676,6 +2580,7 @@ async fn wipe_all_drives(
         config: config.clone(),
         operator_id: None,
     };
+    let history = open_history(&session);
 
     let total_drives = drives_to_wipe.len();
     let mut successful = 0;
@@ -1695,7 +2600,8 @@ async fn wipe_all_drives(
             drive,
             config.clone(),
             Some(&cert_path),
-            session.clone(),
+            &session,
+            history.as_ref(),
             force
         ).await;
 
@@ -1711,6 +2617,8 @@ async fn wipe_all_drives(
         }
     }
 
+    close_history(history.as_ref(), &session);
+
     // Summary
     println!("\n{}", "=".repeat(60));
     println!("SUMMARY");
@@ -2009,6 +2917,380 @@ async fn handle_sed(device: &str, action: &SedAction) -> Result<()> {
     Ok(())
 }
 
+fn handle_cert(action: &CertAction) -> Result<()> {
+    match action {
+        CertAction::Export { certificate, chain, output, pem, key } => {
+            let generator = CertificateGenerator::from_key_file(key)?;
+            let cert = CertificateGenerator::load_certificate(certificate)?;
+
+            if !generator.verify_certificate(&cert)? {
+                anyhow::bail!("{} was not signed by this key; refusing to export", certificate);
+            }
+
+            let chain_pem = std::fs::read(chain)?;
+            let output = output.clone().unwrap_or_else(|| {
+                std::path::Path::new(certificate)
+                    .with_extension("p7s")
+                    .to_string_lossy()
+                    .into_owned()
+            });
+
+            pkcs7::save_pkcs7(&generator, &cert, &chain_pem, &output, *pem)?;
+            println!("📜 PKCS#7 certificate written to: {}", output);
+        }
+
+        CertAction::Verify { file, ca, public_key, revocation_list } => {
+            let data = std::fs::read(file)?;
+            let is_json = data.iter()
+                .find(|b| !b.is_ascii_whitespace())
+                .is_some_and(|b| *b == b'{');
+            let list_path = revocation_list_path(revocation_list.as_ref());
+
+            if is_json {
+                let key = trusted_public_key(public_key.as_ref())?.ok_or_else(|| {
+                    anyhow::anyhow!("No trusted key: pass --public-key or make the signing key readable")
+                })?;
+                let cert = CertificateGenerator::load_certificate(file)?;
+                let revocations = RevocationList::load_for(&list_path, &key, &mut revocation_state()?)?;
+                let status = certificate_status(&cert, &key, revocations.as_ref())?;
+
+                print_certificate_status(&cert.certificate_id, &status);
+                if status != CertificateStatus::Valid {
+                    std::process::exit(1);
+                }
+            } else {
+                let ca_path = ca.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!("PKCS#7 certificates are verified with --ca")
+                })?;
+                let ca_pem = std::fs::read(ca_path)?;
+
+                match pkcs7::verify_pkcs7(&data, &ca_pem) {
+                    Ok(result) => {
+                        println!("✅ PKCS#7 signature and certificate chain are valid");
+                        println!("   Certificate: {}", result.certificate.certificate_id);
+                        println!("   Device: {} ({})", result.certificate.device_info.model,
+                                 result.certificate.device_info.serial);
+                        println!("   Signer: {}", result.signer_subject);
+                        println!("   Chain: {}", result.chain.join(" -> "));
+                        if let Some(time) = result.signing_time {
+                            println!("   Signed at: {}", time);
+                        }
+                        if !result.inner_signature_valid {
+                            println!("❌ Embedded certificate signature is INVALID");
+                            std::process::exit(1);
+                        }
+
+                        // The embedded signer key matched the chain-validated leaf key
+                        let key = result.certificate.signer.public_key_bytes()?;
+                        let revocations = RevocationList::load_for(&list_path, &key, &mut revocation_state()?)?;
+                        if let Some(entry) = revocations.as_ref()
+                            .and_then(|list| list.find(&result.certificate.certificate_id))
+                        {
+                            print_certificate_status(
+                                &result.certificate.certificate_id,
+                                &CertificateStatus::Revoked(entry.clone()),
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                    Err(e) => {
+                        println!("❌ Verification failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+
+        CertAction::Show { file, public_key, revocation_list } => {
+            let document = CertificateDocument::load(file)?;
+            print!("{}", document.to_text());
+            println!();
+
+            match trusted_public_key(public_key.as_ref())? {
+                Some(key) => {
+                    let list_path = revocation_list_path(revocation_list.as_ref());
+                    let revocations = RevocationList::load_for(&list_path, &key, &mut revocation_state()?)?;
+                    let status = certificate_status(&document.certificate, &key, revocations.as_ref())?;
+                    print_certificate_status(&document.certificate.certificate_id, &status);
+                }
+                None => {
+                    println!("⚠️  Signature not checked: no trusted public key (use --public-key)");
+                }
+            }
+        }
+
+        CertAction::List { dir, public_key, revocation_list } => {
+            let key = trusted_public_key(public_key.as_ref())?;
+            let revocations = match &key {
+                Some(key) => RevocationList::load_for(revocation_list_path(revocation_list.as_ref()), key, &mut revocation_state()?)?,
+                None => None,
+            };
+
+            let mut paths: Vec<_> = std::fs::read_dir(dir)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            paths.sort();
+
+            println!("{:<10} {:<17} {:<22} {:<18} {:<10} Status",
+                     "ID", "Date", "Model", "Serial", "Algorithm");
+            println!("{}", "-".repeat(90));
+
+            let mut count = 0;
+            for path in paths {
+                // Skip JSON files that are not certificates (e.g. verification reports)
+                let Ok(cert) = CertificateGenerator::load_certificate(&path) else { continue };
+                count += 1;
+
+                let status = match &key {
+                    Some(key) => match certificate_status(&cert, key, revocations.as_ref())? {
+                        CertificateStatus::Valid => "✅ Valid".to_string(),
+                        CertificateStatus::InvalidSignature => "❌ INVALID".to_string(),
+                        CertificateStatus::Revoked(_) => "⛔ REVOKED".to_string(),
+                    },
+                    None => "Unchecked".to_string(),
+                };
+
+                println!("{:<10} {:<17} {:<22} {:<18} {:<10} {}",
+                         cert.certificate_id.chars().take(8).collect::<String>(),
+                         cert.timestamp.format("%Y-%m-%d %H:%M"),
+                         truncate_string(&cert.device_info.model, 22),
+                         truncate_string(&cert.device_info.serial, 18),
+                         truncate_string(&cert.wipe_details.algorithm_used, 10),
+                         status);
+            }
+
+            println!("\n{} certificate(s) in {}", count, dir);
+            if key.is_none() {
+                println!("⚠️  Signatures not checked: no trusted public key (use --public-key)");
+            }
+        }
+
+        CertAction::Revoke { certificate, reason, key, revocation_list } => {
+            let key_path = key.clone()
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(CertificateGenerator::default_key_path);
+            let generator = CertificateGenerator::from_key_file(&key_path)?;
+
+            let (certificate_id, certificate_hash) = if std::path::Path::new(certificate).exists() {
+                let cert = CertificateGenerator::load_certificate(certificate)?;
+                if !generator.verify_certificate(&cert)? {
+                    anyhow::bail!("{} was not issued by this signing key", certificate);
+                }
+                let hash = CertificateGenerator::certificate_hash(&cert)?;
+                (cert.certificate_id, Some(hash))
+            } else if Uuid::parse_str(certificate).is_ok() {
+                (certificate.clone(), None)
+            } else {
+                anyhow::bail!("'{}' is neither a certificate file nor a certificate ID", certificate);
+            };
+
+            let list_path = revocation_list_path(revocation_list.as_ref());
+            let mut state = revocation_state()?;
+            let mut list = RevocationList::load_or_new(&list_path, &generator, &mut state)?;
+
+            if list.revoke(&generator, &certificate_id, certificate_hash, reason)? {
+                list.save(&list_path)?;
+                state.accept(&list)?;
+                println!("⛔ Certificate {} revoked ({} entries in {})",
+                         certificate_id, list.entries.len(), list_path.display());
+            } else {
+                println!("ℹ️  Certificate {} is already revoked", certificate_id);
+            }
+        }
+
+        CertAction::Render { certificate, format, output } => {
+            let document = CertificateDocument::load(certificate)?;
+            let extension = match format.to_lowercase().as_str() {
+                "pdf" => "pdf",
+                "text" | "txt" => "txt",
+                _ => anyhow::bail!("Unknown render format '{}' (expected pdf or text)", format),
+            };
+            let output = output.clone().unwrap_or_else(|| {
+                std::path::Path::new(certificate)
+                    .with_extension(extension)
+                    .to_string_lossy()
+                    .into_owned()
+            });
+
+            if extension == "pdf" {
+                document.save_pdf(&output)?;
+            } else {
+                std::fs::write(&output, document.to_text())?;
+            }
+            println!("📄 Certificate report written to: {}", output);
+        }
+    }
+
+    Ok(())
+}
+
+/// Key JSON certificates are checked against: an exported public key file,
+/// or the local signing key when it is readable
+fn trusted_public_key(public_key: Option<&String>) -> Result<Option<Vec<u8>>> {
+    if let Some(path) = public_key {
+        return Ok(Some(CertificateGenerator::read_public_key_file(path)?));
+    }
+
+    Ok(CertificateGenerator::from_key_file(CertificateGenerator::default_key_path())
+        .ok()
+        .map(|generator| generator.public_key_bytes().to_vec()))
+}
+
+fn revocation_list_path(path: Option<&String>) -> std::path::PathBuf {
+    path.map(std::path::PathBuf::from)
+        .unwrap_or_else(RevocationList::default_path)
+}
+
+/// Signing key for new certificates, created on first use
+fn certificate_generator() -> Result<CertificateGenerator> {
+    if CertificateGenerator::create_default_key()? {
+        println!("🔑 No signing key found, generated Ed25519 key at {}",
+                 CertificateGenerator::default_key_path().display());
+    }
+    CertificateGenerator::new()
+}
+
+/// Highest revocation list sequences accepted so far (see `SequenceState`)
+fn revocation_state() -> Result<SequenceState> {
+    SequenceState::load(SequenceState::default_path())
+}
+
+fn print_certificate_status(certificate_id: &str, status: &CertificateStatus) {
+    match status {
+        CertificateStatus::Valid => {
+            println!("✅ Certificate {} signature is valid", certificate_id);
+        }
+        CertificateStatus::InvalidSignature => {
+            println!("❌ Certificate {} signature is INVALID", certificate_id);
+        }
+        CertificateStatus::Revoked(entry) => {
+            println!("⛔ Certificate {} was REVOKED on {}: {}",
+                     certificate_id, entry.revoked_at.format("%Y-%m-%d %H:%M:%S UTC"), entry.reason);
+        }
+    }
+}
+
+fn show_history(database: Option<&str>, filter: &HistoryFilter, details: bool) -> Result<()> {
+    let path = database.map(std::path::PathBuf::from).unwrap_or_else(JobDatabase::default_path);
+    if !path.exists() {
+        println!("No wipe history recorded yet ({} does not exist)", path.display());
+        return Ok(());
+    }
+
+    let db = JobDatabase::open(&path)?;
+    let entries = db.query(filter)?;
+
+    println!("{:<6} {:<17} {:<15} {:<20} {:<15} {:<10} {:<10} Verified",
+             "ID", "Started", "Device", "Model", "Serial", "Algorithm", "Status");
+    println!("{}", "-".repeat(110));
+
+    for entry in &entries {
+        let verified = match entry.verification_passed {
+            Some(true) => "✅",
+            Some(false) => "❌",
+            None => "-",
+        };
+
+        println!("{:<6} {:<17} {:<15} {:<20} {:<15} {:<10} {:<10} {}",
+                 entry.record_id,
+                 entry.start_time.format("%Y-%m-%d %H:%M"),
+                 truncate_string(&entry.device_path, 15),
+                 truncate_string(&entry.model, 20),
+                 truncate_string(&entry.serial, 15),
+                 truncate_string(&entry.algorithm, 10),
+                 format!("{:?}", entry.status),
+                 verified);
+
+        if details {
+            println!("       Session: {}", entry.session_id);
+            if let Some(operator) = &entry.operator_id {
+                println!("       Operator: {}", operator);
+            }
+            if let Some(end) = entry.end_time {
+                println!("       Finished: {} ({}s)",
+                         end.format("%Y-%m-%d %H:%M:%S UTC"),
+                         (end - entry.start_time).num_seconds());
+            }
+            if let Some(cert) = &entry.certificate_path {
+                println!("       Certificate: {}", cert);
+            }
+            for phase in db.phases(entry.record_id)? {
+                println!("       {}  {:?}", phase.timestamp.format("%H:%M:%S"), phase.phase);
+            }
+            for event in db.events(entry.record_id)? {
+                let icon = if event.level == "error" { "❌" } else { "⚠️ " };
+                println!("       {}  {} {}", event.timestamp.format("%H:%M:%S"), icon, event.message);
+            }
+            println!();
+        }
+    }
+
+    println!("\n{} record(s) in {}", entries.len(), path.display());
+    Ok(())
+}
+
+/// Pause or resume the overwrite of `target`, a device path or record ID,
+/// through the history database the running process watches
+fn request_pause(target: &str, database: Option<&str>, paused: bool) -> Result<()> {
+    let (db, record_id) = running_wipe(target, database)?;
+    db.set_paused(record_id, paused)?;
+
+    if paused {
+        println!("⏸️  Pause requested for wipe record {} ({})", record_id, target);
+    } else {
+        println!("▶️  Resume requested for wipe record {} ({})", record_id, target);
+    }
+    Ok(())
+}
+
+/// Cancel the wipe of `target` alone, leaving the rest of its batch running
+fn request_cancel(target: &str, database: Option<&str>) -> Result<()> {
+    let (db, record_id) = running_wipe(target, database)?;
+    db.request_cancel(record_id)?;
+    println!("🛑 Cancel requested for wipe record {} ({})", record_id, target);
+    Ok(())
+}
+
+/// The history database and in-progress record for `target`
+fn running_wipe(target: &str, database: Option<&str>) -> Result<(JobDatabase, i64)> {
+    let path = database.map(std::path::PathBuf::from).unwrap_or_else(JobDatabase::default_path);
+    if !path.exists() {
+        return Err(anyhow::anyhow!("No wipe history at {}; is a wipe running?", path.display()));
+    }
+
+    let db = JobDatabase::open(&path)?;
+    let record_id = match target.parse::<i64>() {
+        Ok(id) => id,
+        Err(_) => db.running_record(target)?
+            .ok_or_else(|| anyhow::anyhow!("No wipe of {} is in progress", target))?,
+    };
+    Ok((db, record_id))
+}
+
+/// Accept a plain date (whole day, UTC) or a full RFC 3339 timestamp
+fn parse_history_date(s: &str, end_of_day: bool) -> Result<chrono::DateTime<chrono::Utc>> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&chrono::Utc));
+    }
+
+    let date = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| anyhow::anyhow!("Invalid date '{}': expected YYYY-MM-DD or RFC 3339", s))?;
+    let date = if end_of_day { date.succ_opt().unwrap_or(date) } else { date };
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+
+fn parse_wipe_status(s: &str) -> Result<WipeStatus> {
+    match s.to_lowercase().as_str() {
+        "pending" => Ok(WipeStatus::Pending),
+        "in-progress" | "inprogress" | "running" => Ok(WipeStatus::InProgress),
+        "completed" | "complete" => Ok(WipeStatus::Completed),
+        "failed" => Ok(WipeStatus::Failed),
+        "skipped" => Ok(WipeStatus::Skipped),
+        _ => Err(anyhow::anyhow!("Unknown status: {}", s)),
+    }
+}
+
 fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
@@ -2028,22 +3310,26 @@ async fn wipe_drives_parallel(
 ) -> Result<Vec<DriveWipeRecord>> {
     use futures::stream::{self, StreamExt};
 
+    let session = WipeSession {
+        session_id: Uuid::new_v4().to_string(),
+        start_time: chrono::Utc::now(),
+        end_time: None,
+        drives: vec![],
+        config: config.clone(),
+        operator_id: None,
+    };
+    let history = open_history(&session);
+
     let results = stream::iter(drives)
         .map(|drive| {
             let config = config.clone();
             let cert_dir = cert_dir.to_string();
+            let session = &session;
+            let history = history.as_ref();
             async move {
                 let cert_filename = drive.device_path.replace("/", "_").replace("dev_", "");
                 let cert_path = format!("{}/cert_{}.json", cert_dir, cert_filename);
-
-                let session = WipeSession {
-                    session_id: Uuid::new_v4().to_string(),
-                    start_time: chrono::Utc::now(),
-                    end_time: None,
-                    drives: vec![],
-                    config: config.clone(),
-                    operator_id: None,
-                };
+                let start_time = chrono::Utc::now();
 
                 match wipe_single_drive(
                     &drive.device_path,
@@ -2051,21 +3337,14 @@ async fn wipe_drives_parallel(
                     config,
                     Some(&cert_path),
                     session,
+                    history,
                     force
                 ).await {
-                    Ok(_) => DriveWipeRecord {
-                        drive_info: drive.clone(),
-                        status: WipeStatus::Completed,
-                        start_time: chrono::Utc::now(),
-                        end_time: Some(chrono::Utc::now()),
-                        error_message: None,
-                        certificate_path: Some(cert_path),
-                        verification_passed: Some(true),
-                    },
+                    Ok(record) => record,
                     Err(e) => DriveWipeRecord {
                         drive_info: drive.clone(),
                         status: WipeStatus::Failed,
-                        start_time: chrono::Utc::now(),
+                        start_time,
                         end_time: Some(chrono::Utc::now()),
                         error_message: Some(e.to_string()),
                         certificate_path: None,
@@ -2078,14 +3357,36 @@ async fn wipe_drives_parallel(
         .collect::<Vec<_>>()
         .await;
 
+    close_history(history.as_ref(), &session);
+
     Ok(results)
 }
 
+// Register the machine-readable event sinks requested on the command line
+fn setup_event_output(output: &str, event_socket: Option<&str>) -> Result<()> {
+    use sayonara_wipe::events::{self, JsonlObserver};
+
+    match output.to_lowercase().as_str() {
+        "text" => {}
+        "jsonl" => {
+            events::add_observer(Arc::new(JsonlObserver::stdout()?));
+            sayonara_wipe::ui::progress::set_bar_enabled(false);
+        }
+        other => anyhow::bail!("Unknown output format '{}' (expected text or jsonl)", other),
+    }
+
+    if let Some(path) = event_socket {
+        events::add_observer(Arc::new(JsonlObserver::unix_socket(path)?));
+    }
+
+    Ok(())
+}
+
 // Signal handler for graceful shutdown
 fn setup_signal_handlers() -> Result<()> {
-    use signal_hook::{consts::SIGINT, iterator::Signals};
+    use signal_hook::{consts::{SIGINT, SIGUSR1, SIGUSR2}, iterator::Signals};
 
-    let mut signals = Signals::new(&[SIGINT])?;
+    let mut signals = Signals::new(&[SIGINT, SIGUSR1, SIGUSR2])?;
 
     std::thread::spawn(move || {
         for sig in signals.forever() {
@@ -2093,7 +3394,15 @@ fn setup_signal_handlers() -> Result<()> {
                 SIGINT => {
                     eprintln!("\n\n🛑 Interrupt received! Stopping wipe operation...");
                     eprintln!("   Please wait for current buffer to finish writing...");
-                    sayonara_wipe::set_interrupted();
+                    control().cancel.cancel();
+                }
+                SIGUSR1 => {
+                    eprintln!("\n⏸️  Pause requested; writes stop after the current buffer");
+                    control().pause.pause();
+                }
+                SIGUSR2 => {
+                    eprintln!("\n▶️  Resume requested");
+                    control().pause.resume();
                 }
                 _ => {}
             }
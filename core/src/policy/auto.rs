@@ -0,0 +1,307 @@
+// Capability-driven "auto" algorithm selection
+//
+// Turns the detected `DriveCapabilities` into an ordered plan: the fastest
+// method that erases everything the drive can reach comes first, followed by
+// fallbacks down to a software overwrite that works on any drive.
+
+use super::nist::RejectedMethod;
+use crate::{Algorithm, DriveInfo, DriveType, HealthStatus, SEDType, SanitizeOption, WipeConfig};
+
+/// One method in a wipe plan and why it qualifies
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedMethod {
+    pub algorithm: Algorithm,
+    pub reason: String,
+}
+
+/// Ordered methods to try, plus the alternatives that were ruled out
+#[derive(Debug, Clone, PartialEq)]
+pub struct WipePlan {
+    pub steps: Vec<PlannedMethod>,
+    pub rejected: Vec<RejectedMethod>,
+    pub notes: Vec<String>,
+}
+
+impl WipePlan {
+    /// Plan that runs exactly the configured method
+    pub fn single(algorithm: Algorithm) -> Self {
+        Self {
+            steps: vec![PlannedMethod { algorithm, reason: "Selected by operator".to_string() }],
+            rejected: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Method tried first
+    pub fn primary(&self) -> &Algorithm {
+        &self.steps[0].algorithm
+    }
+}
+
+/// Builds wipe plans from detected drive capabilities
+pub struct AutoSelector;
+
+impl AutoSelector {
+    /// Ordered plan for `drive`. Never empty: an overwrite always closes it.
+    pub fn plan(drive: &DriveInfo, config: &WipeConfig) -> WipePlan {
+        let mut plan = WipePlan { steps: Vec::new(), rejected: Vec::new(), notes: Vec::new() };
+
+        for (algorithm, verdict) in [
+            (Algorithm::CryptoErase, Self::crypto_erase(drive, config)),
+            (Algorithm::Sanitize, Self::sanitize(drive, config)),
+            (Algorithm::SecureErase, Self::secure_erase(drive, config)),
+        ] {
+            match verdict {
+                Ok(reason) => plan.steps.push(PlannedMethod { algorithm, reason }),
+                Err(reason) => {
+                    log::info!("Auto selection rejected {} for {}: {}", algorithm.name(), drive.device_path, reason);
+                    plan.rejected.push(RejectedMethod { method: algorithm.name(), reason });
+                }
+            }
+        }
+
+        let (overwrite, reason, rejected) = Self::overwrite(drive);
+        plan.steps.push(PlannedMethod { algorithm: overwrite, reason });
+        if let Some((algorithm, reason)) = rejected {
+            log::info!("Auto selection rejected {} for {}: {}", algorithm.name(), drive.device_path, reason);
+            plan.rejected.push(RejectedMethod { method: algorithm.name(), reason });
+        }
+
+        if drive.capabilities.trim_support {
+            plan.notes.push(if config.use_trim_after {
+                "TRIM runs after the wipe to release mapped blocks".to_string()
+            } else {
+                "TRIM is supported but disabled (--no-trim)".to_string()
+            });
+        }
+        if drive.capabilities.is_frozen && config.freeze_mitigation {
+            plan.notes.push("Drive is frozen; unfreezing is attempted before security commands".to_string());
+        }
+
+        plan
+    }
+
+    fn crypto_erase(drive: &DriveInfo, config: &WipeConfig) -> Result<String, String> {
+        let caps = &drive.capabilities;
+        match &caps.sed_type {
+            None | Some(SEDType::None) => return Err("not a self-encrypting drive".to_string()),
+            Some(_) if !caps.crypto_erase => {
+                return Err("self-encrypting drive does not support cryptographic erase".to_string());
+            }
+            _ => {}
+        }
+        if !config.sed_crypto_erase {
+            return Err("SED cryptographic erase is disabled in the wipe configuration".to_string());
+        }
+
+        Ok(format!("{:?} self-encrypting drive: discarding the media key erases all data in seconds",
+                   caps.sed_type.as_ref().unwrap_or(&SEDType::None)))
+    }
+
+    fn sanitize(drive: &DriveInfo, config: &WipeConfig) -> Result<String, String> {
+        let options = &drive.capabilities.sanitize_options;
+
+        match drive.drive_type {
+            DriveType::NVMe | DriveType::Optane => {
+                if options.is_empty() {
+                    return Err("controller does not report Sanitize support".to_string());
+                }
+            }
+            DriveType::SSD => {
+                if !options.iter().any(|o| matches!(o, SanitizeOption::BlockErase | SanitizeOption::CryptoScramble)) {
+                    return Err("drive does not support ATA SANITIZE block erase or crypto scramble".to_string());
+                }
+                Self::check_frozen(drive, config)?;
+            }
+            DriveType::EMMC | DriveType::UFS => {
+                if !options.contains(&SanitizeOption::BlockErase) {
+                    return Err("device does not report eMMC sanitize or UFS purge support".to_string());
+                }
+            }
+            _ => return Err(format!("Sanitize is not available for {:?} drives", drive.drive_type)),
+        }
+
+        Ok(format!("{:?} drive supports Sanitize ({}), which also erases spare and cached blocks",
+                   drive.drive_type,
+                   options.iter().map(|o| format!("{:?}", o)).collect::<Vec<_>>().join(", ")))
+    }
+
+    fn secure_erase(drive: &DriveInfo, config: &WipeConfig) -> Result<String, String> {
+        let caps = &drive.capabilities;
+
+        match drive.drive_type {
+            DriveType::HDD | DriveType::SSD => {
+                if !caps.secure_erase {
+                    return Err("drive does not support ATA SECURITY ERASE UNIT".to_string());
+                }
+                Self::check_frozen(drive, config)?;
+
+                Ok(format!("ATA SECURITY ERASE UNIT{} covers reallocated sectors",
+                           if caps.enhanced_erase { " (enhanced erase supported)" } else { "" }))
+            }
+            DriveType::NVMe | DriveType::Optane => {
+                if !caps.secure_erase {
+                    return Err("controller does not support Format NVM".to_string());
+                }
+                Self::check_frozen(drive, config)?;
+
+                Ok("NVMe Format with user data erase".to_string())
+            }
+            DriveType::EMMC => {
+                if !caps.secure_erase {
+                    return Err("device does not report eMMC secure erase support".to_string());
+                }
+
+                Ok("eMMC secure erase of the user data area; boot partitions are overwritten".to_string())
+            }
+            _ => Err(format!("no hardware secure erase for {:?} drives", drive.drive_type)),
+        }
+    }
+
+    /// Software overwrite that closes every plan, and the one it replaced
+    fn overwrite(drive: &DriveInfo) -> (Algorithm, String, Option<(Algorithm, String)>) {
+        let unhealthy = matches!(drive.health_status, Some(HealthStatus::Critical | HealthStatus::Failed));
+        let flash = !matches!(drive.drive_type, DriveType::HDD | DriveType::SMR |
+                                                DriveType::HybridSSHD | DriveType::RAID | DriveType::Unknown);
+
+        if flash {
+            (Algorithm::Random,
+             "Single random pass; extra passes only add wear on flash".to_string(),
+             Some((Algorithm::DoD5220, "multi-pass overwrite gains nothing on flash media".to_string())))
+        } else if unhealthy {
+            (Algorithm::Random,
+             format!("Single random pass to limit stress on a drive in {:?} health",
+                     drive.health_status.as_ref().unwrap_or(&HealthStatus::Unknown)),
+             Some((Algorithm::DoD5220, "three passes would stress a failing drive".to_string())))
+        } else {
+            (Algorithm::DoD5220, "DoD 5220.22-M 3-pass overwrite works on any magnetic drive".to_string(), None)
+        }
+    }
+
+    /// Security and ATA sanitize commands fail on a frozen drive
+    fn check_frozen(drive: &DriveInfo, config: &WipeConfig) -> Result<(), String> {
+        if drive.capabilities.is_frozen && !config.freeze_mitigation {
+            return Err("drive is frozen and freeze mitigation is disabled".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_drive;
+    use crate::DriveCapabilities;
+
+    fn drive(drive_type: DriveType, capabilities: DriveCapabilities) -> DriveInfo {
+        DriveInfo {
+            size: 1024 * 1024 * 1024,
+            drive_type,
+            capabilities,
+            health_status: Some(HealthStatus::Good),
+            ..test_drive("TEST123")
+        }
+    }
+
+    fn algorithms(plan: &WipePlan) -> Vec<Algorithm> {
+        plan.steps.iter().map(|s| s.algorithm.clone()).collect()
+    }
+
+    #[test]
+    fn test_plain_hdd_falls_back_to_dod() {
+        let plan = AutoSelector::plan(&drive(DriveType::HDD, Default::default()), &WipeConfig::default());
+
+        assert_eq!(algorithms(&plan), vec![Algorithm::DoD5220]);
+        assert_eq!(plan.rejected.len(), 3);
+        assert_eq!(plan.primary(), &Algorithm::DoD5220);
+    }
+
+    #[test]
+    fn test_sed_nvme_plan_order() {
+        let caps = DriveCapabilities {
+            crypto_erase: true,
+            sed_type: Some(SEDType::OPAL20),
+            secure_erase: true,
+            sanitize_options: vec![SanitizeOption::BlockErase],
+            trim_support: true,
+            ..Default::default()
+        };
+        let plan = AutoSelector::plan(&drive(DriveType::NVMe, caps), &WipeConfig::default());
+
+        assert_eq!(algorithms(&plan), vec![
+            Algorithm::CryptoErase, Algorithm::Sanitize, Algorithm::SecureErase, Algorithm::Random,
+        ]);
+        assert!(plan.notes.iter().any(|n| n.contains("TRIM")));
+    }
+
+    #[test]
+    fn test_nvme_methods_require_reported_support() {
+        let plan = AutoSelector::plan(&drive(DriveType::NVMe, Default::default()), &WipeConfig::default());
+        assert_eq!(algorithms(&plan), vec![Algorithm::Random]);
+        assert!(plan.rejected.iter().any(|r| r.method == "SecureErase" && r.reason.contains("Format NVM")));
+
+        let caps = DriveCapabilities {
+            secure_erase: true,
+            sanitize_options: vec![SanitizeOption::CryptoErase],
+            ..Default::default()
+        };
+        let plan = AutoSelector::plan(&drive(DriveType::Optane, caps), &WipeConfig::default());
+        assert_eq!(algorithms(&plan), vec![Algorithm::Sanitize, Algorithm::SecureErase, Algorithm::Random]);
+    }
+
+    #[test]
+    fn test_embedded_flash_plans() {
+        let caps = DriveCapabilities {
+            secure_erase: true,
+            sanitize_options: vec![SanitizeOption::BlockErase],
+            ..Default::default()
+        };
+        let plan = AutoSelector::plan(&drive(DriveType::EMMC, caps.clone()), &WipeConfig::default());
+        assert_eq!(algorithms(&plan), vec![Algorithm::Sanitize, Algorithm::SecureErase, Algorithm::Random]);
+
+        // UFS has purge but no secure erase
+        let plan = AutoSelector::plan(&drive(DriveType::UFS, caps), &WipeConfig::default());
+        assert_eq!(algorithms(&plan), vec![Algorithm::Sanitize, Algorithm::Random]);
+
+        let plan = AutoSelector::plan(&drive(DriveType::EMMC, Default::default()), &WipeConfig::default());
+        assert_eq!(algorithms(&plan), vec![Algorithm::Random]);
+    }
+
+    #[test]
+    fn test_frozen_ssd_without_mitigation_skips_security_commands() {
+        let caps = DriveCapabilities {
+            secure_erase: true,
+            is_frozen: true,
+            sanitize_options: vec![SanitizeOption::CryptoScramble],
+            ..Default::default()
+        };
+        let config = WipeConfig { freeze_mitigation: false, ..Default::default() };
+        let plan = AutoSelector::plan(&drive(DriveType::SSD, caps.clone()), &config);
+
+        assert_eq!(algorithms(&plan), vec![Algorithm::Random]);
+        assert!(plan.rejected.iter().any(|r| r.method == "SecureErase" && r.reason.contains("frozen")));
+
+        let plan = AutoSelector::plan(&drive(DriveType::SSD, caps), &WipeConfig::default());
+        assert_eq!(algorithms(&plan), vec![Algorithm::Sanitize, Algorithm::SecureErase, Algorithm::Random]);
+    }
+
+    #[test]
+    fn test_unhealthy_hdd_uses_single_pass() {
+        let mut hdd = drive(DriveType::HDD, Default::default());
+        hdd.health_status = Some(HealthStatus::Critical);
+        let plan = AutoSelector::plan(&hdd, &WipeConfig::default());
+
+        assert_eq!(algorithms(&plan), vec![Algorithm::Random]);
+        assert!(plan.rejected.iter().any(|r| r.method == "DoD5220"));
+    }
+
+    #[test]
+    fn test_disabled_crypto_erase_is_rejected() {
+        let caps = DriveCapabilities { crypto_erase: true, sed_type: Some(SEDType::OPAL20), ..Default::default() };
+        let config = WipeConfig { sed_crypto_erase: false, ..Default::default() };
+        let plan = AutoSelector::plan(&drive(DriveType::HDD, caps), &config);
+
+        assert_eq!(plan.primary(), &Algorithm::DoD5220);
+        assert!(plan.rejected.iter().any(|r| r.method == "CryptoErase" && r.reason.contains("disabled")));
+    }
+}
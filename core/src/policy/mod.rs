@@ -0,0 +1,8 @@
+pub mod auto;
+pub mod nist;
+
+// Re-export
+pub use auto::{AutoSelector, PlannedMethod, WipePlan};
+pub use nist::{
+    NistPolicy, PolicyDecision, RejectedMethod, SanitizationLevel, SanitizationRecord, NIST_800_88,
+};
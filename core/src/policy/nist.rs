@@ -0,0 +1,477 @@
+// NIST SP 800-88 Rev. 1 sanitization policy
+//
+// Maps a target level (Clear / Purge / Destroy) plus a drive's media type and
+// detected capabilities onto a sanitization method, following the media
+// tables in Appendix A of the guideline.
+
+use crate::{Algorithm, DriveInfo, DriveType, HPADCOHandling, SanitizeOption, WipeConfig};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Guideline the decisions are made against
+pub const NIST_800_88: &str = "NIST SP 800-88 Rev. 1";
+
+/// Sanitization levels defined by NIST SP 800-88
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SanitizationLevel {
+    Clear,    // Protects against simple, non-invasive recovery
+    Purge,    // Protects against state of the art laboratory recovery
+    Destroy,  // Physical destruction of the media
+}
+
+impl SanitizationLevel {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "clear" => Ok(SanitizationLevel::Clear),
+            "purge" => Ok(SanitizationLevel::Purge),
+            "destroy" => Ok(SanitizationLevel::Destroy),
+            _ => Err(anyhow!("Unknown sanitization level: {} (expected clear, purge or destroy)", s)),
+        }
+    }
+}
+
+impl fmt::Display for SanitizationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Media categories of NIST SP 800-88 Appendix A
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MediaClass {
+    AtaHardDisk,
+    AtaSolidState,
+    Nvme,
+    RemovableFlash,
+    EmbeddedFlash,
+    Raid,
+    Unknown,
+}
+
+impl MediaClass {
+    fn of(drive_type: &DriveType) -> Self {
+        match drive_type {
+            DriveType::HDD | DriveType::SMR | DriveType::HybridSSHD => MediaClass::AtaHardDisk,
+            DriveType::SSD => MediaClass::AtaSolidState,
+            DriveType::NVMe | DriveType::Optane => MediaClass::Nvme,
+            DriveType::USB => MediaClass::RemovableFlash,
+            DriveType::EMMC | DriveType::UFS => MediaClass::EmbeddedFlash,
+            DriveType::RAID => MediaClass::Raid,
+            DriveType::Unknown => MediaClass::Unknown,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            MediaClass::AtaHardDisk => "ATA hard disk drive",
+            MediaClass::AtaSolidState => "ATA solid state drive",
+            MediaClass::Nvme => "NVMe solid state drive",
+            MediaClass::RemovableFlash => "USB removable flash media",
+            MediaClass::EmbeddedFlash => "Embedded flash memory",
+            MediaClass::Raid => "RAID volume",
+            MediaClass::Unknown => "Unclassified media",
+        }
+    }
+
+    fn is_flash(self) -> bool {
+        !matches!(self, MediaClass::AtaHardDisk | MediaClass::Raid | MediaClass::Unknown)
+    }
+}
+
+/// A method the engine considered and refused
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RejectedMethod {
+    pub method: String,
+    pub reason: String,
+}
+
+/// Why a method was chosen, as recorded in the wipe certificate
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SanitizationRecord {
+    pub standard: String,
+    pub level: SanitizationLevel,
+    pub media: String,
+    pub method: String,
+    pub operator_selected: bool,  // Method given by the operator rather than chosen by the engine
+    pub rationale: Vec<String>,
+    pub rejected: Vec<RejectedMethod>,
+}
+
+/// Outcome of applying the policy to one drive
+#[derive(Debug, Clone)]
+pub struct PolicyDecision {
+    pub algorithm: Algorithm,
+    pub record: SanitizationRecord,
+}
+
+/// Chooses and checks sanitization methods against NIST SP 800-88
+pub struct NistPolicy;
+
+impl NistPolicy {
+    /// Decide how to sanitize `drive` to `level`.
+    ///
+    /// With `requested` set the operator's method is only checked, otherwise
+    /// the engine picks the first method in its preference order that reaches
+    /// the level. Either way a method that cannot reach the level is refused.
+    pub fn decide(
+        level: SanitizationLevel,
+        requested: Option<&Algorithm>,
+        drive: &DriveInfo,
+        config: &WipeConfig,
+    ) -> Result<PolicyDecision> {
+        let media = MediaClass::of(&drive.drive_type);
+
+        if level == SanitizationLevel::Destroy {
+            return Err(anyhow!(
+                "{} Destroy cannot be performed in software: shred, disintegrate, pulverize or incinerate {} ({})",
+                NIST_800_88, drive.device_path, media.describe()
+            ));
+        }
+
+        let record = |algorithm: &Algorithm, operator_selected, rationale, rejected| SanitizationRecord {
+            standard: NIST_800_88.to_string(),
+            level,
+            media: media.describe().to_string(),
+            method: algorithm.name(),
+            operator_selected,
+            rationale,
+            rejected,
+        };
+
+        if let Some(algorithm) = requested {
+            return match Self::evaluate(level, algorithm, media, drive, config) {
+                Ok(rationale) => Ok(PolicyDecision {
+                    algorithm: algorithm.clone(),
+                    record: record(algorithm, true, rationale, Vec::new()),
+                }),
+                Err(reason) => Err(anyhow!(
+                    "{} does not reach {} {} on {}: {}",
+                    algorithm.name(), NIST_800_88, level, drive.device_path, reason
+                )),
+            };
+        }
+
+        let mut rejected = Vec::new();
+        for algorithm in Self::candidates(level) {
+            match Self::evaluate(level, &algorithm, media, drive, config) {
+                Ok(rationale) => {
+                    return Ok(PolicyDecision {
+                        record: record(&algorithm, false, rationale, rejected),
+                        algorithm,
+                    });
+                }
+                Err(reason) => rejected.push(RejectedMethod { method: algorithm.name(), reason }),
+            }
+        }
+
+        let reasons: Vec<String> = rejected.iter()
+            .map(|r| format!("{}: {}", r.method, r.reason))
+            .collect();
+        Err(anyhow!(
+            "No method reaches {} {} on {} ({}); physical destruction is required. Rejected: {}",
+            NIST_800_88, level, drive.device_path, media.describe(), reasons.join("; ")
+        ))
+    }
+
+    /// Methods in order of preference for `level`
+    fn candidates(level: SanitizationLevel) -> Vec<Algorithm> {
+        match level {
+            // A single overwrite is the baseline Clear method for every
+            // medium; hardware methods follow for drives where it is refused
+            SanitizationLevel::Clear => vec![
+                Algorithm::Zero,
+                Algorithm::Sanitize,
+                Algorithm::CryptoErase,
+                Algorithm::SecureErase,
+            ],
+            SanitizationLevel::Purge => vec![
+                Algorithm::Sanitize,
+                Algorithm::CryptoErase,
+                Algorithm::SecureErase,
+            ],
+            SanitizationLevel::Destroy => Vec::new(),
+        }
+    }
+
+    /// Rationale if `algorithm` reaches `level` on this drive, or why not
+    fn evaluate(
+        level: SanitizationLevel,
+        algorithm: &Algorithm,
+        media: MediaClass,
+        drive: &DriveInfo,
+        config: &WipeConfig,
+    ) -> std::result::Result<Vec<String>, String> {
+        let caps = &drive.capabilities;
+
+        if level == SanitizationLevel::Destroy {
+            return Err("Destroy requires physical destruction of the media".to_string());
+        }
+        if media == MediaClass::Raid {
+            return Err("methods applied through a RAID volume do not reach member drive metadata \
+                        or spare areas; sanitize each member drive individually".to_string());
+        }
+
+        let mut rationale = Vec::new();
+
+        match algorithm {
+            Algorithm::DoD5220 | Algorithm::Gutmann | Algorithm::Random |
+            Algorithm::Zero | Algorithm::Custom(_) => {
+                if level == SanitizationLevel::Purge {
+                    return Err("software overwrite cannot reach remapped sectors or \
+                                over-provisioned flash, so it only meets Clear".to_string());
+                }
+                if (caps.hpa_enabled || caps.dco_enabled) && matches!(
+                    config.handle_hpa_dco, HPADCOHandling::Ignore | HPADCOHandling::Detect
+                ) {
+                    return Err("HPA/DCO hidden areas would not be overwritten; \
+                                use --hpa-dco remove-temp".to_string());
+                }
+
+                rationale.push(format!(
+                    "Overwrite of all user-addressable locations meets Clear for {} media",
+                    media.describe()
+                ));
+                if media.is_flash() {
+                    rationale.push("Flash over-provisioning may retain data outside the \
+                                    addressable range; use Purge where that matters".to_string());
+                }
+            }
+
+            Algorithm::CryptoErase => {
+                if !caps.crypto_erase {
+                    return Err("drive is not a self-encrypting drive with cryptographic erase".to_string());
+                }
+                if !config.sed_crypto_erase {
+                    return Err("SED cryptographic erase is disabled in the wipe configuration".to_string());
+                }
+
+                rationale.push(format!(
+                    "Cryptographic erase destroys the media encryption key ({}), meeting {} for {} media",
+                    caps.sed_type.as_ref().map(|t| format!("{:?}", t)).unwrap_or_else(|| "SED".to_string()),
+                    level, media.describe()
+                ));
+                rationale.push("Only valid if all user data was written through the drive's \
+                                encryption; verify the SED was not deployed in bypass mode".to_string());
+            }
+
+            Algorithm::SecureErase => {
+                Self::check_not_frozen(drive, config)?;
+
+                match media {
+                    MediaClass::AtaHardDisk => {
+                        if !caps.secure_erase {
+                            return Err("drive does not support ATA SECURITY ERASE UNIT".to_string());
+                        }
+                        rationale.push(format!(
+                            "ATA SECURITY ERASE UNIT overwrites all user data including reallocated sectors, meeting {} for hard disks",
+                            level
+                        ));
+                    }
+                    MediaClass::AtaSolidState => {
+                        if !caps.secure_erase {
+                            return Err("drive does not support ATA SECURITY ERASE UNIT".to_string());
+                        }
+                        if level == SanitizationLevel::Purge && !caps.enhanced_erase {
+                            return Err("normal ATA SECURITY ERASE UNIT is not a Purge method for \
+                                        solid state drives; enhanced erase is required".to_string());
+                        }
+                        rationale.push(format!(
+                            "ATA SECURITY ERASE UNIT{} meets {} for solid state drives",
+                            if caps.enhanced_erase { " (enhanced)" } else { "" }, level
+                        ));
+                    }
+                    MediaClass::Nvme => {
+                        if !caps.secure_erase {
+                            return Err("controller does not support Format NVM".to_string());
+                        }
+                        if level == SanitizationLevel::Purge {
+                            return Err("Format NVM user data erase is only a Clear method; \
+                                        use Sanitize or cryptographic erase for Purge".to_string());
+                        }
+                        rationale.push("Format NVM with user data erase meets Clear for NVMe drives".to_string());
+                    }
+                    _ => {
+                        return Err(format!("no hardware secure erase command for {} media", media.describe()));
+                    }
+                }
+            }
+
+            Algorithm::Sanitize => {
+                Self::check_not_frozen(drive, config)?;
+
+                match media {
+                    MediaClass::Nvme => {
+                        if caps.sanitize_options.is_empty() {
+                            return Err("drive does not support the NVMe Sanitize command".to_string());
+                        }
+                        rationale.push(format!(
+                            "NVMe Sanitize ({}) erases all user data, caches and spare areas, meeting {}",
+                            Self::options(&caps.sanitize_options), level
+                        ));
+                    }
+                    MediaClass::AtaSolidState => {
+                        let supported = caps.sanitize_options.iter().any(|o| matches!(
+                            o, SanitizeOption::BlockErase | SanitizeOption::CryptoScramble
+                        ));
+                        if !supported {
+                            return Err("drive does not support ATA SANITIZE BLOCK ERASE or CRYPTO SCRAMBLE".to_string());
+                        }
+                        rationale.push(format!(
+                            "ATA SANITIZE ({}) erases all user data and over-provisioned blocks, meeting {}",
+                            Self::options(&caps.sanitize_options), level
+                        ));
+                    }
+                    _ => {
+                        return Err(format!("Sanitize is not supported for {} media", media.describe()));
+                    }
+                }
+            }
+
+            Algorithm::TrimOnly => {
+                return Err("TRIM only marks blocks as unused; it is not a sanitization method".to_string());
+            }
+        }
+
+        Ok(rationale)
+    }
+
+    /// Security and sanitize commands are rejected by a frozen drive
+    fn check_not_frozen(drive: &DriveInfo, config: &WipeConfig) -> std::result::Result<(), String> {
+        if drive.capabilities.is_frozen && !config.freeze_mitigation {
+            return Err("drive is frozen and freeze mitigation is disabled".to_string());
+        }
+        Ok(())
+    }
+
+    fn options(options: &[SanitizeOption]) -> String {
+        options.iter()
+            .map(|o| format!("{:?}", o))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_drive;
+    use crate::{DriveCapabilities, SEDType};
+
+    fn drive(drive_type: DriveType, capabilities: DriveCapabilities) -> DriveInfo {
+        DriveInfo {
+            size: 1024 * 1024 * 1024,
+            drive_type,
+            capabilities,
+            ..test_drive("TEST123")
+        }
+    }
+
+    #[test]
+    fn test_purge_nvme_prefers_sanitize() {
+        let caps = DriveCapabilities {
+            crypto_erase: true,
+            sanitize_options: vec![SanitizeOption::CryptoErase, SanitizeOption::BlockErase],
+            ..Default::default()
+        };
+        let decision = NistPolicy::decide(
+            SanitizationLevel::Purge, None, &drive(DriveType::NVMe, caps), &WipeConfig::default()
+        ).unwrap();
+
+        assert_eq!(decision.algorithm, Algorithm::Sanitize);
+        assert!(!decision.record.operator_selected);
+        assert!(decision.record.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_purge_sata_ssd_falls_back_to_crypto_erase() {
+        let caps = DriveCapabilities {
+            crypto_erase: true,
+            sed_type: Some(SEDType::OPAL20),
+            secure_erase: true,
+            ..Default::default()
+        };
+        let decision = NistPolicy::decide(
+            SanitizationLevel::Purge, None, &drive(DriveType::SSD, caps), &WipeConfig::default()
+        ).unwrap();
+
+        assert_eq!(decision.algorithm, Algorithm::CryptoErase);
+        assert_eq!(decision.record.rejected.len(), 1);
+        assert_eq!(decision.record.rejected[0].method, "Sanitize");
+    }
+
+    #[test]
+    fn test_clear_hdd_is_single_overwrite() {
+        let decision = NistPolicy::decide(
+            SanitizationLevel::Clear, None, &drive(DriveType::HDD, Default::default()), &WipeConfig::default()
+        ).unwrap();
+
+        assert_eq!(decision.algorithm, Algorithm::Zero);
+        assert_eq!(decision.record.level, SanitizationLevel::Clear);
+        assert_eq!(decision.record.standard, NIST_800_88);
+    }
+
+    #[test]
+    fn test_overwrite_refused_for_purge() {
+        let err = NistPolicy::decide(
+            SanitizationLevel::Purge,
+            Some(&Algorithm::DoD5220),
+            &drive(DriveType::HDD, Default::default()),
+            &WipeConfig::default(),
+        ).unwrap_err();
+
+        assert!(err.to_string().contains("only meets Clear"));
+    }
+
+    #[test]
+    fn test_overwrite_refused_with_hidden_areas() {
+        let caps = DriveCapabilities { hpa_enabled: true, ..Default::default() };
+        let hdd = drive(DriveType::HDD, caps);
+
+        assert!(NistPolicy::decide(
+            SanitizationLevel::Clear, Some(&Algorithm::Zero), &hdd, &WipeConfig::default()
+        ).is_err());
+
+        let config = WipeConfig { handle_hpa_dco: HPADCOHandling::TemporaryRemove, ..Default::default() };
+        assert!(NistPolicy::decide(SanitizationLevel::Clear, Some(&Algorithm::Zero), &hdd, &config).is_ok());
+    }
+
+    #[test]
+    fn test_purge_without_capabilities_requires_destruction() {
+        let err = NistPolicy::decide(
+            SanitizationLevel::Purge, None, &drive(DriveType::USB, Default::default()), &WipeConfig::default()
+        ).unwrap_err();
+
+        assert!(err.to_string().contains("physical destruction"));
+    }
+
+    #[test]
+    fn test_destroy_and_trim_are_refused() {
+        let hdd = drive(DriveType::HDD, Default::default());
+        assert!(NistPolicy::decide(SanitizationLevel::Destroy, None, &hdd, &WipeConfig::default()).is_err());
+        assert!(NistPolicy::decide(
+            SanitizationLevel::Clear, Some(&Algorithm::TrimOnly), &hdd, &WipeConfig::default()
+        ).is_err());
+    }
+
+    #[test]
+    fn test_ssd_purge_needs_enhanced_secure_erase() {
+        let caps = DriveCapabilities { secure_erase: true, ..Default::default() };
+        let ssd = drive(DriveType::SSD, caps.clone());
+        assert!(NistPolicy::decide(
+            SanitizationLevel::Purge, Some(&Algorithm::SecureErase), &ssd, &WipeConfig::default()
+        ).is_err());
+
+        let ssd = drive(DriveType::SSD, DriveCapabilities { enhanced_erase: true, ..caps });
+        let decision = NistPolicy::decide(
+            SanitizationLevel::Purge, Some(&Algorithm::SecureErase), &ssd, &WipeConfig::default()
+        ).unwrap();
+        assert!(decision.record.operator_selected);
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(SanitizationLevel::parse("PURGE").unwrap(), SanitizationLevel::Purge);
+        assert!(SanitizationLevel::parse("shred").is_err());
+    }
+}
@@ -0,0 +1,62 @@
+// Fixtures shared by the unit tests of several modules
+//
+// Tests override individual fields with struct update syntax, e.g.
+// `DriveInfo { drive_type: DriveType::NVMe, ..test_drive("SN1") }`.
+
+use chrono::Utc;
+use ring::rand::SystemRandom;
+use ring::signature::Ed25519KeyPair;
+
+use crate::crypto::certificates::{CertificateGenerator, VerificationResult, WipeCertificate, WipeDetails};
+use crate::{DriveInfo, DriveType, EncryptionStatus};
+
+/// A 500 GiB HDD at /dev/sdz
+pub(crate) fn test_drive(serial: &str) -> DriveInfo {
+    DriveInfo {
+        device_path: "/dev/sdz".to_string(),
+        model: "Test Model".to_string(),
+        serial: serial.to_string(),
+        size: 500 * 1024 * 1024 * 1024,
+        drive_type: DriveType::HDD,
+        encryption_status: EncryptionStatus::None,
+        capabilities: Default::default(),
+        health_status: None,
+        temperature_celsius: None,
+    }
+}
+
+/// A completed three-pass DoD 5220.22-M overwrite with no optional records
+pub(crate) fn test_wipe_details() -> WipeDetails {
+    WipeDetails {
+        algorithm_used: "DoD5220".to_string(),
+        passes_completed: 3,
+        duration_seconds: 3600,
+        operator_id: Some("operator-1".to_string()),
+        sanitization: None,
+        nvme_sanitize: None,
+        unwritable_sectors: None,
+        keystream_commitment: None,
+        scope: None,
+    }
+}
+
+/// A passed verification, timestamped now
+pub(crate) fn test_verification() -> VerificationResult {
+    VerificationResult {
+        verified: true,
+        entropy_score: 7.95,
+        recovery_test_passed: true,
+        verification_timestamp: Utc::now(),
+    }
+}
+
+/// Generator with a fresh in-memory Ed25519 key
+pub(crate) fn test_generator() -> CertificateGenerator {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+    CertificateGenerator::from_pkcs8(pkcs8.as_ref()).unwrap()
+}
+
+/// Certificate for `test_drive("SN12345")` signed by `generator`
+pub(crate) fn test_certificate(generator: &CertificateGenerator) -> WipeCertificate {
+    generator.generate_certificate(&test_drive("SN12345"), test_wipe_details(), test_verification()).unwrap()
+}
@@ -1,5 +1,15 @@
 use std::io::{self, Write};
-use std::time::{Instant};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+// Cleared in machine-readable output mode, where progress is only reported
+// as events
+static BAR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turn drawing of progress bars on or off process-wide
+pub fn set_bar_enabled(enabled: bool) {
+    BAR_ENABLED.store(enabled, Ordering::Relaxed);
+}
 
 const CAT_FRAMES: [&str; 6] = [
     "ฅ(^･ω･^=)  ", // cat happy
@@ -10,6 +20,11 @@ const CAT_FRAMES: [&str; 6] = [
     "ฅ(=^･ω･^ ) ",
 ];
 
+// Progress events are emitted at most this often, or when the percentage
+// moved by at least EVENT_STEP, however fast the bar is redrawn
+const EVENT_INTERVAL: Duration = Duration::from_secs(1);
+const EVENT_STEP: f64 = 1.0;
+
 const PAW_FRAMES: [&str; 4] = ["·", "˚", "•", "˚"];
 
 pub struct ProgressBar {
@@ -19,6 +34,7 @@ pub struct ProgressBar {
     paw_frame: usize,
     start: Instant,
     first_render: bool,
+    last_event: Option<(Instant, f64)>,
 }
 
 impl ProgressBar {
@@ -31,7 +47,22 @@ impl ProgressBar {
             paw_frame: 0,
             start: Instant::now(),
             first_render: true,
+            last_event: None,
+        }
+    }
+
+    /// Whether a redraw at `pct` should also be reported as a progress event
+    fn event_due(&mut self, pct: f64) -> bool {
+        let due = match self.last_event {
+            None => true,
+            Some((at, last)) => (pct >= 100.0 && last < 100.0)
+                || pct - last >= EVENT_STEP
+                || at.elapsed() >= EVENT_INTERVAL,
+        };
+        if due {
+            self.last_event = Some((Instant::now(), pct));
         }
+        due
     }
 
     /// Render the progress bar
@@ -45,6 +76,14 @@ impl ProgressBar {
             progress.clamp(0.0, 100.0)
         };
 
+        let bytes = bytes_written.zip(total_bytes);
+        if self.event_due(pct) {
+            crate::events::progress(pct, bytes, self.start.elapsed().as_secs_f64());
+        }
+        if !BAR_ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+
         let filled = ((pct / 100.0) * self.width as f64).round() as usize;
         let empty = self.width.saturating_sub(filled);
 
@@ -153,3 +192,27 @@ fn format_duration(secs: u64) -> String {
         format!("{}:{:02}", m, s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_events_are_throttled() {
+        let mut bar = ProgressBar::new(10);
+        assert!(bar.event_due(0.0));
+        assert!(!bar.event_due(0.1));
+        assert!(!bar.event_due(0.9));
+        assert!(bar.event_due(1.0));
+        assert!(!bar.event_due(1.5));
+        // Completion is always reported, once
+        assert!(bar.event_due(100.0));
+        assert!(!bar.event_due(100.0));
+
+        // A slow pass still reports at least once per interval
+        let mut bar = ProgressBar::new(10);
+        assert!(bar.event_due(5.0));
+        bar.last_event = Some((Instant::now() - EVENT_INTERVAL, 5.0));
+        assert!(bar.event_due(5.1));
+    }
+}
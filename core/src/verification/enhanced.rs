@@ -4,7 +4,8 @@ use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use std::collections::HashSet;
 use std::process::Command;
-use crate::io::{OptimizedIO, IOConfig, IOHandle};
+use crate::io::{Extent, OptimizedIO, IOConfig, IOHandle};
+use crate::drives::ata::AtaDevice;
 
 /// Enhanced verification system with comprehensive forensic analysis
 pub struct EnhancedVerification;
@@ -185,6 +186,8 @@ pub struct BadSectorTracker {
     pub unreadable_count: u64,
     pub percentage_unreadable: f64,
     pub total_sectors_attempted: u64,
+    #[serde(default)]
+    pub unwritable_sectors: Vec<u64>, // LBAs the overwrite could not write, so never sanitized
 }
 
 // ==================== FILE SIGNATURES DATABASE ====================
@@ -266,10 +269,19 @@ impl EnhancedVerification {
 
     /// Stage 1: Pre-wipe verification capability testing
     pub fn pre_wipe_capability_test(device_path: &str, test_size: u64) -> Result<PreWipeTestResults> {
-        println!("🔬 Stage 1: Testing Verification Capabilities");
-
         let device_size = Self::get_device_size(device_path)?;
-        let test_offset = device_size.saturating_sub(test_size.min(1024 * 1024));
+        Self::capability_test_at(device_path, device_size.saturating_sub(test_size.min(1024 * 1024)))
+    }
+
+    /// Stage 1 for a scoped wipe: the test patterns go at the end of `extent`
+    /// so nothing outside the scope is written
+    pub fn pre_wipe_capability_test_within(device_path: &str, test_size: u64, extent: Extent) -> Result<PreWipeTestResults> {
+        let test_size = test_size.min(1024 * 1024).min(extent.length);
+        Self::capability_test_at(device_path, extent.offset + extent.length - test_size)
+    }
+
+    fn capability_test_at(device_path: &str, test_offset: u64) -> Result<PreWipeTestResults> {
+        println!("🔬 Stage 1: Testing Verification Capabilities");
 
         println!("  ├─ Writing test patterns...");
         let pattern_detection = Self::test_pattern_detection(device_path, test_offset)?;
@@ -297,21 +309,39 @@ impl EnhancedVerification {
         device_path: &str,
         device_size: u64,
         level: VerificationLevel,
+    ) -> Result<PostWipeAnalysis> {
+        Self::post_wipe_verification_of(device_path, Region { size: device_size, extent: None }, level)
+    }
+
+    /// Stage 2 for a scoped wipe: only `extent` is analyzed, since data
+    /// outside it was deliberately left in place
+    pub fn post_wipe_verification_within(
+        device_path: &str,
+        extent: Extent,
+        level: VerificationLevel,
+    ) -> Result<PostWipeAnalysis> {
+        Self::post_wipe_verification_of(device_path, Region { size: extent.length, extent: Some(extent) }, level)
+    }
+
+    fn post_wipe_verification_of(
+        device_path: &str,
+        region: Region,
+        level: VerificationLevel,
     ) -> Result<PostWipeAnalysis> {
         println!("🔬 Stage 2: Post-Wipe Verification (Level: {:?})", level);
 
         match level {
             VerificationLevel::Level1RandomSampling => {
-                Self::level1_random_sampling(device_path, device_size, 1.0)
+                Self::level1_random_sampling(device_path, region, 1.0)
             }
             VerificationLevel::Level2SystematicSampling => {
-                Self::level2_systematic_sampling(device_path, device_size, 100)
+                Self::level2_systematic_sampling(device_path, region, 100)
             }
             VerificationLevel::Level3FullScan => {
-                Self::level3_full_scan(device_path, device_size)
+                Self::level3_full_scan(device_path, region)
             }
             VerificationLevel::Level4ForensicScan => {
-                Self::level4_forensic_scan(device_path, device_size)
+                Self::level4_forensic_scan(device_path, region)
             }
         }
     }
@@ -320,29 +350,31 @@ impl EnhancedVerification {
 
     fn level1_random_sampling(
         device_path: &str,
-        device_size: u64,
+        region: Region,
         sample_percentage: f64,
     ) -> Result<PostWipeAnalysis> {
         println!("  📊 Level 1: Random Sampling ({}%)", sample_percentage);
+        let device_size = region.size;
 
         let sample_size = ((device_size as f64 * sample_percentage / 100.0) as u64)
             .max(10 * 1024 * 1024)
             .min(1024 * 1024 * 1024);
 
         println!("  ├─ Sampling {} MB...", sample_size / (1024 * 1024));
-        let samples = Self::collect_stratified_samples(device_path, device_size, sample_size)?;
+        let samples = Self::collect_stratified_samples(device_path, region, sample_size)?;
 
-        Self::analyze_samples(device_path, device_size, samples, false)
+        Self::analyze_samples(device_path, region, samples, false)
     }
 
     // ==================== LEVEL 2: SYSTEMATIC SAMPLING ====================
 
     fn level2_systematic_sampling(
         device_path: &str,
-        device_size: u64,
+        region: Region,
         every_nth: u64,
     ) -> Result<PostWipeAnalysis> {
         println!("  📊 Level 2: Systematic Sampling (every {}th sector)", every_nth);
+        let device_size = region.size;
 
         let sector_size = 512u64;
         let total_sectors = device_size / sector_size;
@@ -351,8 +383,7 @@ impl EnhancedVerification {
         println!("  ├─ Checking {} sectors systematically...", sectors_to_check);
 
         let mut samples = Vec::new();
-        let config = IOConfig::small_read_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+        let mut handle = region.open(device_path, IOConfig::small_read_optimized())?;
 
         for i in 0..sectors_to_check {
             let sector_num = i * every_nth;
@@ -370,18 +401,18 @@ impl EnhancedVerification {
             }
         }
 
-        Self::analyze_samples(device_path, device_size, samples, false)
+        Self::analyze_samples(device_path, region, samples, false)
     }
 
     // ==================== LEVEL 3: FULL SCAN ====================
 
-    fn level3_full_scan(device_path: &str, device_size: u64) -> Result<PostWipeAnalysis> {
+    fn level3_full_scan(device_path: &str, region: Region) -> Result<PostWipeAnalysis> {
         println!("  📊 Level 3: Full Scan (100% of drive)");
         println!("  ⚠️  Warning: This will take a long time!");
 
+        let device_size = region.size;
         let mut all_samples = Vec::new();
-        let config = IOConfig::verification_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+        let mut handle = region.open(device_path, IOConfig::verification_optimized())?;
 
         let mut bytes_read = 0u64;
         let mut chunk_num = 0u64;
@@ -402,36 +433,36 @@ impl EnhancedVerification {
             Ok(())
         })?;
 
-        Self::analyze_samples(device_path, device_size, all_samples, false)
+        Self::analyze_samples(device_path, region, all_samples, false)
     }
 
     // ==================== LEVEL 4: FORENSIC SCAN ====================
 
-    fn level4_forensic_scan(device_path: &str, device_size: u64) -> Result<PostWipeAnalysis> {
+    fn level4_forensic_scan(device_path: &str, region: Region) -> Result<PostWipeAnalysis> {
         println!("  🔬 Level 4: Forensic Scan (Full + Hidden Areas + MFM)");
         println!("  ⚠️  Warning: This is the most thorough and time-consuming verification!");
 
         // Start with full scan
-        let mut analysis = Self::level3_full_scan(device_path, device_size)?;
+        let mut analysis = Self::level3_full_scan(device_path, region)?;
 
         // Add forensic components
-        analysis.hidden_areas = Self::verify_hidden_areas(device_path)?;
+        analysis.hidden_areas = Self::verify_hidden_areas(device_path, region)?;
 
         if Self::is_hdd(device_path)? {
             println!("  ├─ Running MFM simulation (HDD detected)...");
-            analysis.recovery_simulation.mfm_simulation = Some(Self::simulate_mfm(device_path)?);
+            analysis.recovery_simulation.mfm_simulation = Some(Self::simulate_mfm(device_path, region)?);
         }
 
         // Generate heat map for forensic analysis
         println!("  └─ Generating detailed entropy heat map...");
-        analysis.heat_map = Some(Self::generate_entropy_heat_map(device_path, device_size)?);
+        analysis.heat_map = Some(Self::generate_entropy_heat_map(device_path, region)?);
 
         Ok(analysis)
     }
 
     // ==================== HIDDEN AREA VERIFICATION ====================
 
-    fn verify_hidden_areas(device_path: &str) -> Result<HiddenAreaVerification> {
+    fn verify_hidden_areas(device_path: &str, region: Region) -> Result<HiddenAreaVerification> {
         println!("  🔍 Verifying Hidden Areas...");
 
         let mut warnings = Vec::new();
@@ -441,7 +472,10 @@ impl EnhancedVerification {
 
         // Check HPA
         println!("    ├─ Checking Host Protected Area (HPA)...");
-        if let Ok(Some(hpa_info)) = Self::detect_hpa(device_path) {
+        if region.extent.is_some() {
+            // The HPA lies past the last user LBA, outside any wipe scope
+            println!("      Skipped: outside the wipe scope");
+        } else if let Ok(Some(hpa_info)) = Self::detect_hpa(device_path) {
             println!("      HPA detected: {} sectors", hpa_info.hidden_sectors);
             hpa_sectors = hpa_info.hidden_sectors;
 
@@ -509,20 +543,13 @@ impl EnhancedVerification {
     }
 
     fn detect_hpa(device_path: &str) -> Result<Option<HPAInfo>> {
-        // Use hdparm to detect HPA
-        let output = Command::new("hdparm")
-            .args(["-N", device_path])
-            .output()?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
+        let mut device = AtaDevice::open(device_path)?;
+        let current = device.identify()?.user_addressable_sectors();
+        let native = device.read_native_max_address()? + 1;
 
-        // Parse output for "max sectors" and "native max"
-        // Format: "max sectors = X/Y, HPA is enabled"
-        if output_str.contains("HPA") {
-            // Parse sectors from output
-            // This is simplified - real implementation needs proper parsing
+        if native > current {
             Ok(Some(HPAInfo {
-                hidden_sectors: 0, // Parse from output
+                hidden_sectors: native - current,
             }))
         } else {
             Ok(None)
@@ -576,12 +603,8 @@ impl EnhancedVerification {
     }
 
     fn verify_controller_cache_flush(device_path: &str) -> Result<bool> {
-        // Send FLUSH CACHE command
-        let output = Command::new("hdparm")
-            .args(["-f", device_path])
-            .output()?;
-
-        Ok(output.status.success())
+        // FLUSH CACHE EXT; drives that take no ATA commands report false
+        Ok(AtaDevice::open(device_path).and_then(|mut device| device.flush_cache()).is_ok())
     }
 
     fn verify_over_provisioning() -> Result<bool> {
@@ -626,25 +649,25 @@ impl EnhancedVerification {
         Ok(detected)
     }
 
-    fn simulate_recovery_tools(device_path: &str, device_size: u64) -> Result<RecoverySimulationResults> {
+    fn simulate_recovery_tools(device_path: &str, region: Region) -> Result<RecoverySimulationResults> {
         println!("  🔍 Simulating Recovery Tools...");
 
         // PhotoRec simulation
         println!("    ├─ PhotoRec simulation...");
-        let photorec_results = Self::simulate_photorec(device_path, device_size)?;
+        let photorec_results = Self::simulate_photorec(device_path, region)?;
 
         // TestDisk simulation
         println!("    ├─ TestDisk simulation...");
-        let testdisk_results = Self::simulate_testdisk(device_path)?;
+        let testdisk_results = Self::simulate_testdisk(device_path, region)?;
 
         // Filesystem metadata check
         println!("    ├─ Filesystem metadata check...");
-        let filesystem_metadata = Self::check_filesystem_metadata(device_path)?;
+        let filesystem_metadata = Self::check_filesystem_metadata(device_path, region)?;
 
         // MFM simulation (HDDs only)
         let mfm_simulation = if Self::is_hdd(device_path)? {
             println!("    ├─ MFM simulation (HDD detected)...");
-            Some(Self::simulate_mfm(device_path)?)
+            Some(Self::simulate_mfm(device_path, region)?)
         } else {
             None
         };
@@ -668,10 +691,10 @@ impl EnhancedVerification {
         })
     }
 
-    fn simulate_photorec(device_path: &str, device_size: u64) -> Result<PhotoRecResults> {
+    fn simulate_photorec(device_path: &str, region: Region) -> Result<PhotoRecResults> {
+        let device_size = region.size;
         let mut found_signatures = Vec::new();
-        let config = IOConfig::small_read_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+        let mut handle = region.open(device_path, IOConfig::small_read_optimized())?;
 
         // Sample 10% of drive in random locations
         let sample_count = 1000;
@@ -714,9 +737,8 @@ impl EnhancedVerification {
         })
     }
 
-    fn simulate_testdisk(device_path: &str) -> Result<TestDiskResults> {
-        let config = IOConfig::small_read_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+    fn simulate_testdisk(device_path: &str, region: Region) -> Result<TestDiskResults> {
+        let mut handle = region.open(device_path, IOConfig::small_read_optimized())?;
 
         // Check MBR signature
         let mbr_found = Self::check_mbr_signature(&mut handle)?;
@@ -787,9 +809,8 @@ impl EnhancedVerification {
         Ok(signatures)
     }
 
-    fn check_filesystem_metadata(device_path: &str) -> Result<FilesystemMetadataResults> {
-        let config = IOConfig::small_read_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+    fn check_filesystem_metadata(device_path: &str, region: Region) -> Result<FilesystemMetadataResults> {
+        let mut handle = region.open(device_path, IOConfig::small_read_optimized())?;
 
         let superblock_remnants = Self::check_filesystem_signatures(&mut handle)?;
         let inode_structures = Self::check_for_inodes()?;
@@ -830,18 +851,17 @@ impl EnhancedVerification {
         Ok(false)
     }
 
-    fn simulate_mfm(device_path: &str) -> Result<MFMResults> {
+    fn simulate_mfm(device_path: &str, region: Region) -> Result<MFMResults> {
         // Magnetic Force Microscopy simulation
         // This simulates whether magnetic flux transitions could reveal previous data
 
-        let config = IOConfig::small_read_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+        let mut handle = region.open(device_path, IOConfig::small_read_optimized())?;
 
         let mut suspicious_transitions = 0u64;
         let sample_count = 100;
 
         let mut rng = rand::thread_rng();
-        let device_size = Self::get_device_size(device_path)?;
+        let device_size = region.size;
 
         for _ in 0..sample_count {
             let offset = rng.gen_range(0..device_size - 512);
@@ -935,9 +955,10 @@ impl EnhancedVerification {
 
     // ==================== HEAT MAP GENERATION ====================
 
-    fn generate_entropy_heat_map(device_path: &str, device_size: u64) -> Result<EntropyHeatMap> {
+    fn generate_entropy_heat_map(device_path: &str, region: Region) -> Result<EntropyHeatMap> {
         println!("  🗺️  Generating Entropy Heat Map...");
 
+        let device_size = region.size;
         let width = 100;
         let height = 50;
         let block_size = device_size / (width * height) as u64;
@@ -947,8 +968,7 @@ impl EnhancedVerification {
         let mut max_entropy: f32 = 0.0;
         let mut suspicious_blocks = Vec::new();
 
-        let config = IOConfig::small_read_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+        let mut handle = region.open(device_path, IOConfig::small_read_optimized())?;
 
         for y in 0..height {
             for x in 0..width {
@@ -1015,7 +1035,7 @@ impl EnhancedVerification {
 
     fn analyze_samples(
         device_path: &str,
-        device_size: u64,
+        region: Region,
         samples: Vec<u8>,
         include_recovery: bool,
     ) -> Result<PostWipeAnalysis> {
@@ -1032,14 +1052,14 @@ impl EnhancedVerification {
         let stats = Self::run_statistical_tests(&samples)?;
 
         println!("  ├─ Sector anomaly detection...");
-        let (sectors, bad_sectors) = Self::analyze_sectors_with_bad_tracking(device_path, device_size)?;
+        let (sectors, bad_sectors) = Self::analyze_sectors_with_bad_tracking(device_path, region)?;
 
         println!("  ├─ Hidden area verification...");
-        let hidden_areas = Self::verify_hidden_areas(device_path)?;
+        let hidden_areas = Self::verify_hidden_areas(device_path, region)?;
 
         println!("  ├─ Recovery tool simulation...");
         let recovery = if include_recovery {
-            Self::simulate_recovery_tools(device_path, device_size)?
+            Self::simulate_recovery_tools(device_path, region)?
         } else {
             RecoverySimulationResults {
                 photorec_results: PhotoRecResults {
@@ -1083,14 +1103,13 @@ impl EnhancedVerification {
 
     fn analyze_sectors_with_bad_tracking(
         device_path: &str,
-        device_size: u64,
+        region: Region,
     ) -> Result<(SectorSamplingResult, BadSectorTracker)> {
         let sector_size = 512u64;
-        let total_sectors = device_size / sector_size;
+        let total_sectors = region.size / sector_size;
         let samples_per_region = 100;
 
-        let config = IOConfig::small_read_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+        let mut handle = region.open(device_path, IOConfig::small_read_optimized())?;
 
         let mut suspicious = 0u64;
         let mut entropy_dist = Vec::new();
@@ -1135,6 +1154,7 @@ impl EnhancedVerification {
             unreadable_count: unreadable,
             percentage_unreadable: (unreadable as f64 / total_attempts as f64) * 100.0,
             total_sectors_attempted: total_attempts,
+            unwritable_sectors: Vec::new(),
         };
 
         Ok((sampling_result, bad_sector_tracker))
@@ -1142,12 +1162,12 @@ impl EnhancedVerification {
 
     fn collect_stratified_samples(
         device_path: &str,
-        device_size: u64,
+        region: Region,
         sample_size: u64,
     ) -> Result<Vec<u8>> {
+        let device_size = region.size;
         let mut samples = Vec::with_capacity(sample_size as usize);
-        let config = IOConfig::small_read_optimized();
-        let mut handle = OptimizedIO::open(device_path, config)?;
+        let mut handle = region.open(device_path, IOConfig::small_read_optimized())?;
 
         // Stratified sampling: beginning, middle, end
         let regions = vec![
@@ -1748,6 +1768,13 @@ impl EnhancedVerification {
             warnings.push("File recovery tools may succeed".to_string());
         }
 
+        if !post_wipe.bad_sectors.unwritable_sectors.is_empty() {
+            warnings.push(format!(
+                "{} sectors could not be overwritten and may still hold data",
+                post_wipe.bad_sectors.unwritable_sectors.len()
+            ));
+        }
+
         warnings
     }
 }
@@ -1759,6 +1786,24 @@ struct HPAInfo {
     hidden_sectors: u64,
 }
 
+/// Part of the device the post-wipe analysis reads: all of it, or the
+/// extent a scoped wipe was confined to. Offsets are relative to its start.
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    size: u64,
+    extent: Option<Extent>,
+}
+
+impl Region {
+    fn open(&self, device_path: &str, config: IOConfig) -> Result<IOHandle> {
+        let mut handle = OptimizedIO::open(device_path, config)?;
+        if let Some(extent) = self.extent {
+            handle.restrict_to(extent)?;
+        }
+        Ok(handle)
+    }
+}
+
 /// Live USB verification system
 pub struct LiveUSBVerification;
 
@@ -506,6 +506,7 @@ mod tests {
                 unreadable_count: 0,
                 percentage_unreadable: 0.0,
                 total_sectors_attempted: 1000,
+                unwritable_sectors: vec![],
             },
             heat_map: None,
         }
@@ -597,11 +598,34 @@ mod tests {
                 unreadable_count: 50,
                 percentage_unreadable: 5.0,
                 total_sectors_attempted: 1000,
+                unwritable_sectors: vec![],
             },
             heat_map: None,
         }
     }
 
+    // ==================== SCOPED VERIFICATION TESTS ====================
+
+    #[test]
+    fn test_capability_test_stays_inside_scope() -> Result<()> {
+        use crate::io::Extent;
+
+        const MB: usize = 1024 * 1024;
+        let temp = tempfile::NamedTempFile::new()?;
+        std::fs::write(temp.path(), vec![0xABu8; 4 * MB])?;
+        let extent = Extent { offset: MB as u64, length: 2 * MB as u64 };
+
+        EnhancedVerification::pre_wipe_capability_test_within(
+            temp.path().to_str().unwrap(), MB as u64, extent,
+        )?;
+
+        let data = std::fs::read(temp.path())?;
+        assert!(data[..MB].iter().all(|&b| b == 0xAB));
+        assert!(data[3 * MB..].iter().all(|&b| b == 0xAB));
+        assert!(data[2 * MB..3 * MB].iter().any(|&b| b != 0xAB));
+        Ok(())
+    }
+
     // ==================== INTEGRATION TESTS (REQUIRE ROOT) ====================
 
     #[test]
@@ -2,235 +2,337 @@
 //
 // This module acts as the main entry point for wipe operations, detecting the drive
 // type and routing to the appropriate specialized wipe implementation.
+//
+// Every flow issues everything through a `DeviceBackend`, so it runs
+// unchanged against a `SimulatedDevice` in tests. Overwrites run the same
+// algorithm implementations as every other wipe, through `BackendIO` where
+// the device node cannot simply be opened.
 
 use crate::{
-    DriveInfo, DriveType, WipeConfig, Algorithm, DriveResult, DriveError,
+    DriveInfo, DriveType, WipeConfig, Algorithm, DriveResult, DriveError, HPADCOHandling, RandomMode,
+    SanitizeOption,
+    algorithms::{self, OverwriteOptions},
     drives::{
-        SMRDrive,
-        OptaneDrive,
-        HybridDrive,
-        NVMeAdvanced,
-        integrated_wipe::{
-            wipe_smr_drive_integrated,
-            wipe_optane_drive_integrated,
-            wipe_hybrid_drive_integrated,
-            wipe_emmc_drive_integrated,
-            wipe_raid_array_integrated,
-            wipe_nvme_advanced_integrated,
-            WipeAlgorithm,
-        },
+        backend::{BackendIO, BlockDevice, DeviceArea, DeviceBackend},
+        types::smr::ZoneType,
     },
 };
-use crate::drives::types::emmc::EMMCDevice;
+use crate::control::OperationContext;
+use crate::crypto::keystream::Keystream;
+use crate::io::Checkpointer;
 use anyhow::Result;
-use std::fs::OpenOptions;
-use std::io::{Write, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+/// Methods other than overwrites cannot be run as one
+fn require_overwrite(algorithm: &Algorithm) -> DriveResult<()> {
+    if algorithm.is_overwrite() {
+        Ok(())
+    } else {
+        Err(DriveError::Unsupported(format!("{} is not an overwrite method", algorithm.name())))
+    }
+}
 
 /// Main wipe orchestrator
 pub struct WipeOrchestrator {
     device_path: String,
     config: WipeConfig,
     drive_info: DriveInfo,
+    backend: Mutex<Box<dyn DeviceBackend>>,
+    context: OperationContext,
+    /// Where overwrites of the user area keep their checkpoints
+    checkpoint_dir: PathBuf,
+    /// Key for the keystream passes of the running wipe
+    keystream: Mutex<Option<Keystream>>,
+    /// LBAs the last wipe had to skip
+    unwritable_sectors: Mutex<Vec<u64>>,
+    /// Commitment to the key the last wipe's keystream passes used
+    keystream_commitment: Mutex<Option<String>>,
 }
 
 impl WipeOrchestrator {
     /// Create new orchestrator for a device
     pub fn new(device_path: String, config: WipeConfig) -> Result<Self> {
-        // Detect drive type and capabilities
-        // For now, create a basic DriveInfo
-        let drive_info = Self::create_basic_drive_info(&device_path)?;
+        Self::with_backend(Box::new(BlockDevice::open(&device_path)?), config)
+    }
+
+    /// Create an orchestrator that drives `backend`, e.g. a `SimulatedDevice`
+    pub fn with_backend(mut backend: Box<dyn DeviceBackend>, config: WipeConfig) -> Result<Self> {
+        let drive_info = backend.identify()?;
 
         Ok(Self {
-            device_path,
+            device_path: drive_info.device_path.clone(),
             config,
             drive_info,
+            backend: Mutex::new(backend),
+            context: OperationContext::default(),
+            checkpoint_dir: Checkpointer::default_dir(),
+            keystream: Mutex::new(None),
+            unwritable_sectors: Mutex::new(Vec::new()),
+            keystream_commitment: Mutex::new(None),
         })
     }
 
-    /// Execute the wipe operation
+    pub fn drive_info(&self) -> &DriveInfo {
+        &self.drive_info
+    }
+
+    /// Run under `context`: overwrites stop when its token is cancelled,
+    /// hold while it is paused and report progress to its observer
+    pub fn set_context(&mut self, context: OperationContext) {
+        self.context = context;
+    }
+
+    /// Keep overwrite checkpoints in `dir` instead of the default directory
+    pub fn set_checkpoint_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.checkpoint_dir = dir.into();
+    }
+
+    /// LBAs the last wipe skipped as unwritable
+    pub fn unwritable_sectors(&self) -> Vec<u64> {
+        lock(&self.unwritable_sectors).clone()
+    }
+
+    /// Commitment to the key of the last wipe's keystream passes, if any
+    pub fn keystream_commitment(&self) -> Option<String> {
+        lock(&self.keystream_commitment).clone()
+    }
+
+    /// Execute the wipe operation with the configured algorithm
     pub async fn execute(&self) -> DriveResult<()> {
+        self.run(&self.config.algorithm).await
+    }
+
+    /// Wipe with `algorithm`. Methods the drive type cannot carry out fail
+    /// with `Unsupported` before anything is written.
+    pub async fn run(&self, algorithm: &Algorithm) -> DriveResult<()> {
         println!("\n=== Starting Wipe Operation ===");
         println!("Device: {}", self.device_path);
         println!("Model: {}", self.drive_info.model);
         println!("Size: {} GB", self.drive_info.size / (1024 * 1024 * 1024));
         println!("Type: {:?}", self.drive_info.drive_type);
-        println!("Algorithm: {:?}", self.config.algorithm);
+        println!("Algorithm: {}", algorithm.name());
         println!();
 
+        lock(&self.unwritable_sectors).clear();
+        *lock(&self.keystream_commitment) = None;
+        // One key for all keystream passes of this wipe, dropped and zeroed
+        // once it is done
+        *lock(&self.keystream) = match self.config.random_mode {
+            RandomMode::AesCtr if algorithm.writes_random() => Some(Keystream::generate()?),
+            _ => None,
+        };
+
         // Route to appropriate wipe implementation
-        match self.drive_info.drive_type {
-            DriveType::SMR => self.wipe_smr_drive().await,
-            DriveType::Optane => self.wipe_optane_drive().await,
-            DriveType::HybridSSHD => self.wipe_hybrid_drive().await,
-            DriveType::EMMC => self.wipe_emmc_drive().await,
-            DriveType::UFS => self.wipe_ufs_drive().await,
-            DriveType::NVMe => self.wipe_nvme_drive().await,
-            DriveType::SSD => self.wipe_ssd_drive().await,
-            DriveType::HDD => self.wipe_hdd_drive().await,
-            DriveType::RAID => self.wipe_raid_member().await,
+        let result = match self.drive_info.drive_type {
+            DriveType::SMR => self.wipe_smr_drive(algorithm).await,
+            DriveType::Optane => self.wipe_optane_drive(algorithm).await,
+            DriveType::HybridSSHD => self.wipe_hybrid_drive(algorithm).await,
+            DriveType::EMMC => self.wipe_emmc_drive(algorithm).await,
+            DriveType::UFS => self.wipe_ufs_drive(algorithm).await,
+            DriveType::NVMe => self.wipe_nvme_drive(algorithm).await,
+            DriveType::SSD => self.wipe_ssd_drive(algorithm).await,
+            DriveType::HDD => self.wipe_hdd_drive(algorithm).await,
+            DriveType::RAID => self.wipe_raid_member(algorithm).await,
             _ => Err(DriveError::Unsupported(
                 format!("Drive type {:?} not yet supported", self.drive_info.drive_type)
             )),
+        };
+
+        if let Some(keystream) = lock(&self.keystream).take() {
+            if result.is_ok() {
+                let commitment = keystream.commitment();
+                println!("🔑 Keystream key discarded (SHA-256 commitment {})", commitment);
+                *lock(&self.keystream_commitment) = Some(commitment);
+            }
         }
+        result
     }
 
     /// Wipe SMR (Shingled Magnetic Recording) drive
-    async fn wipe_smr_drive(&self) -> DriveResult<()> {
-        println!("📀 Detected SMR drive - using zone-aware wipe strategy with OptimizedIO");
-
-        let smr = SMRDrive::get_zone_configuration(&self.device_path)
-            .map_err(|e| DriveError::HardwareCommandFailed(format!("SMR detection failed: {}", e)))?;
-
-        println!("Zone Model: {:?}", smr.zone_model);
-        println!("Total Zones: {}", smr.zones.len());
-        println!("Conventional Zones: {}", smr.conventional_zone_count);
+    async fn wipe_smr_drive(&self, algorithm: &Algorithm) -> DriveResult<()> {
+        println!("📀 Detected SMR drive - using zone-aware wipe strategy");
+
+        require_overwrite(algorithm)?;
+        let zones = self.backend().zones()?;
+        let sequential = zones.iter().filter(|z| z.zone_type != ZoneType::Conventional).count();
+        println!("Total Zones: {}", zones.len());
+        println!("Conventional Zones: {}", zones.len() - sequential);
         println!();
 
-        // Convert WipeConfig algorithm to WipeAlgorithm
-        let wipe_algorithm = self.convert_to_wipe_algorithm();
-
-        // Use integrated wipe with OptimizedIO engine
-        wipe_smr_drive_integrated(&smr, wipe_algorithm)
-            .map_err(|e| DriveError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, format!("SMR wipe failed: {}", e))
-            ))?;
+        // Sequential zones only take writes at their write pointer, so every
+        // pass resets each one as it reaches it
+        self.overwrite(algorithm, DeviceArea::User, self.drive_info.size)?;
+
+        // Every sequential zone must have been written to its end
+        let unfinished = self.backend().zones()?
+            .into_iter()
+            .filter(|z| z.zone_type != ZoneType::Conventional && z.write_pointer < z.zone_start_lba + z.zone_length)
+            .count();
+        if unfinished > 0 {
+            return Err(DriveError::HardwareCommandFailed(format!(
+                "{} sequential zones were not completely overwritten", unfinished
+            )));
+        }
 
         println!("✅ SMR drive wipe completed successfully");
         Ok(())
     }
 
     /// Wipe Intel Optane / 3D XPoint drive
-    async fn wipe_optane_drive(&self) -> DriveResult<()> {
-        println!("⚡ Detected Intel Optane drive - checking for ISE support with OptimizedIO");
-
-        let optane = OptaneDrive::get_configuration(&self.device_path)
-            .map_err(|e| DriveError::HardwareCommandFailed(format!("Optane detection failed: {}", e)))?;
-
-        println!("Generation: {}", optane.generation);
-        println!("Mode: {}", if optane.is_pmem { "Persistent Memory" } else { "Block Device" });
-        println!("ISE Support: {}", if optane.supports_ise { "Yes" } else { "No" });
-        println!();
-
-        // Use integrated wipe with OptimizedIO engine
-        // Prefer hardware ISE if available
-        let use_ise = optane.supports_ise;
-        wipe_optane_drive_integrated(&optane, use_ise)
-            .map_err(|e| DriveError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, format!("Optane wipe failed: {}", e))
-            ))?;
+    async fn wipe_optane_drive(&self, algorithm: &Algorithm) -> DriveResult<()> {
+        println!("⚡ Detected Intel Optane drive");
+
+        match algorithm {
+            Algorithm::SecureErase => {
+                // Instant Secure Erase is a Format NVM with secure erase
+                println!("Running Instant Secure Erase...");
+                self.backend().secure_erase(false)?;
+            }
+            Algorithm::CryptoErase => self.sed_crypto_erase()?,
+            Algorithm::Sanitize => {
+                let option = self.sanitize_option(&[SanitizeOption::CryptoErase, SanitizeOption::BlockErase,
+                                                     SanitizeOption::Overwrite])?;
+                println!("Using NVMe sanitize command ({:?})", option);
+                self.backend().sanitize(option)?;
+            }
+            _ => {
+                // 3D XPoint writes in place, so an overwrite reaches every cell
+                self.overwrite(algorithm, DeviceArea::User, self.drive_info.size)?;
+            }
+        }
 
         println!("✅ Optane drive wipe completed successfully");
         Ok(())
     }
 
     /// Wipe Hybrid SSHD drive
-    async fn wipe_hybrid_drive(&self) -> DriveResult<()> {
-        println!("🔀 Detected Hybrid SSHD - wiping both HDD and SSD cache with OptimizedIO");
+    async fn wipe_hybrid_drive(&self, algorithm: &Algorithm) -> DriveResult<()> {
+        println!("🔀 Detected Hybrid SSHD - wiping with the NAND cache disabled");
 
-        let mut hybrid = HybridDrive::get_configuration(&self.device_path)
-            .map_err(|e| DriveError::HardwareCommandFailed(format!("Hybrid detection failed: {}", e)))?;
+        if !matches!(algorithm, Algorithm::SecureErase | Algorithm::CryptoErase) {
+            require_overwrite(algorithm)?;
+        }
 
-        println!("HDD: {} GB @ {} RPM",
-                 hybrid.hdd_portion.capacity / (1024 * 1024 * 1024),
-                 hybrid.hdd_portion.rpm);
-        println!("SSD Cache: {} GB",
-                 hybrid.ssd_cache.cache_size / (1024 * 1024 * 1024));
-        println!();
+        // Pinned and cached copies of old data stay in the NAND cache until
+        // it is flushed with caching off
+        let cache_disabled = match self.backend().set_write_cache(false) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("⚠️  Warning: Unable to disable cache: {}", e);
+                false
+            }
+        };
+
+        let result = self.with_ata_preparation(|size| match algorithm {
+            Algorithm::SecureErase => self.hardware_secure_erase(),
+            Algorithm::CryptoErase => self.sed_crypto_erase(),
+            _ => self.overwrite(algorithm, DeviceArea::User, size),
+        });
+        let flushed = self.backend().flush();
 
-        // Use integrated wipe with OptimizedIO engine
-        wipe_hybrid_drive_integrated(&mut hybrid)
-            .map_err(|e| DriveError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, format!("Hybrid wipe failed: {}", e))
-            ))?;
+        if cache_disabled {
+            if let Err(e) = self.backend().set_write_cache(true) {
+                println!("⚠️  Failed to re-enable write cache: {}", e);
+            }
+        }
+        result?;
+        flushed?;
 
         println!("✅ Hybrid drive wipe completed successfully");
         Ok(())
     }
 
     /// Wipe eMMC embedded storage
-    async fn wipe_emmc_drive(&self) -> DriveResult<()> {
-        println!("📱 Detected eMMC device - wiping all partitions with OptimizedIO");
+    async fn wipe_emmc_drive(&self, algorithm: &Algorithm) -> DriveResult<()> {
+        println!("📱 Detected eMMC device - wiping user data area and boot partitions");
 
-        let emmc = EMMCDevice::get_configuration(&self.device_path)
-            .map_err(|e| DriveError::HardwareCommandFailed(format!("eMMC detection failed: {}", e)))?;
+        // Erase commands only cover the user data area; the boot partitions
+        // get the overwrite passes, or are zeroed like an erased user area
+        let boot_algorithm = match algorithm {
+            Algorithm::SecureErase | Algorithm::Sanitize => Algorithm::Zero,
+            _ => {
+                require_overwrite(algorithm)?;
+                algorithm.clone()
+            }
+        };
 
-        println!("eMMC Version: {}", emmc.emmc_version);
-        println!("Boot Partitions: {}", emmc.boot_partitions.len());
-        println!();
+        match algorithm {
+            Algorithm::SecureErase => {
+                println!("Running eMMC secure erase...");
+                self.backend().secure_erase(false)?;
+            }
+            Algorithm::Sanitize => {
+                println!("Running eMMC sanitize...");
+                let option = self.sanitize_option(&[SanitizeOption::BlockErase])?;
+                self.backend().sanitize(option)?;
+            }
+            _ => self.overwrite(algorithm, DeviceArea::User, self.drive_info.size)?,
+        }
 
-        // Use integrated wipe with OptimizedIO engine
-        // Try hardware erase first, fall back to software if not supported
-        let use_hardware = true; // Can be made configurable
-        wipe_emmc_drive_integrated(&emmc, use_hardware)
-            .map_err(|e| DriveError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, format!("eMMC wipe failed: {}", e))
-            ))?;
+        let partitions = self.backend().hardware_partitions()?;
+        for partition in &partitions {
+            println!("Wiping boot partition {}: {} MB", partition.number, partition.size / (1024 * 1024));
+            self.overwrite(&boot_algorithm, DeviceArea::HardwarePartition(partition.number), partition.size)?;
+        }
+        println!("RPMB cannot be wiped (cryptographically protected)");
 
         println!("✅ eMMC wipe completed successfully");
         Ok(())
     }
 
     /// Wipe UFS (Universal Flash Storage)
-    async fn wipe_ufs_drive(&self) -> DriveResult<()> {
-        println!("📱 Detected UFS device - using PURGE command");
-
-        // UFS detection and wipe via purge command
-        println!("⚠️  UFS full integration pending, using PURGE command");
-
-        let output = std::process::Command::new("sg_unmap")
-            .arg("--all")
-            .arg(&self.device_path)
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(format!("UFS PURGE failed: {}", e)))?;
-
-        if !output.status.success() {
-            return Err(DriveError::HardwareCommandFailed("UFS PURGE command failed".to_string()));
+    async fn wipe_ufs_drive(&self, algorithm: &Algorithm) -> DriveResult<()> {
+        println!("📱 Detected UFS device");
+
+        match algorithm {
+            Algorithm::Sanitize => {
+                println!("Running UFS purge...");
+                let option = self.sanitize_option(&[SanitizeOption::BlockErase])?;
+                self.backend().sanitize(option)?;
+            }
+            _ => {
+                self.overwrite(algorithm, DeviceArea::User, self.drive_info.size)?;
+                if let Err(e) = self.backend().discard(0, self.drive_info.size) {
+                    println!("⚠️  UNMAP after overwrite failed: {}", e);
+                }
+            }
         }
 
         println!("✅ UFS wipe completed successfully");
         Ok(())
     }
 
-    /// Wipe NVMe drive (check for advanced features first)
-    async fn wipe_nvme_drive(&self) -> DriveResult<()> {
+    /// Wipe NVMe drive (check for multiple namespaces first)
+    async fn wipe_nvme_drive(&self, algorithm: &Algorithm) -> DriveResult<()> {
         println!("💾 Detected NVMe drive - checking for advanced features");
 
-        // Check if this is an advanced NVMe with ZNS, multiple namespaces, etc.
-        if NVMeAdvanced::detect_advanced_features(&self.device_path).unwrap_or(false) {
-            println!("🔬 Advanced NVMe features detected, using OptimizedIO with namespace support");
-            println!();
-
-            // Get advanced NVMe configuration
-            let nvme_advanced = NVMeAdvanced::get_configuration(&self.device_path)
-                .map_err(|e| DriveError::HardwareCommandFailed(format!("NVMe advanced detection failed: {}", e)))?;
-
-            println!("Namespaces: {}", nvme_advanced.namespaces.len());
-            println!("ZNS Support: {}", nvme_advanced.zns_support);
-            println!();
-
-            // Use integrated wipe with OptimizedIO engine
-            // Prefer hardware format, but can fall back to software
-            let use_format = true; // Can be made configurable
-            wipe_nvme_advanced_integrated(&nvme_advanced, use_format)
-                .map_err(|e| DriveError::IoError(
-                    std::io::Error::new(std::io::ErrorKind::Other, format!("Advanced NVMe wipe failed: {}", e))
-                ))?;
-
-            println!("✅ Advanced NVMe wipe completed successfully");
-            return Ok(());
-        }
-
-        // Fall back to basic NVMe wipe via sanitize command
-        println!("Using standard NVMe sanitize command");
-        let output = std::process::Command::new("nvme")
-            .arg("sanitize")
-            .arg(&self.device_path)
-            .arg("-a").arg("2")  // Cryptographic erase
-            .output()
-            .map_err(|e| DriveError::HardwareCommandFailed(format!("NVMe sanitize failed: {}", e)))?;
-
-        if !output.status.success() {
-            return Err(DriveError::HardwareCommandFailed("NVMe sanitize failed".to_string()));
+        let namespaces = self.backend().namespaces()?;
+        println!("Namespaces: {}", namespaces.len());
+
+        match algorithm {
+            Algorithm::CryptoErase => self.sed_crypto_erase()?,
+            Algorithm::SecureErase => {
+                // Format every namespace with a user data erase
+                for namespace in &namespaces {
+                    println!("      Formatting namespace {}...", namespace.nsid);
+                    self.backend().format_namespace(namespace.nsid)?;
+                }
+            }
+            Algorithm::Sanitize => {
+                // Sanitize acts on the whole controller, so it covers every namespace
+                let option = self.sanitize_option(&[SanitizeOption::CryptoErase, SanitizeOption::BlockErase,
+                                                     SanitizeOption::Overwrite])?;
+                println!("Using standard NVMe sanitize command ({:?})", option);
+                self.backend().sanitize(option)?;
+            }
+            _ if namespaces.len() > 1 => {
+                return Err(DriveError::Unsupported(format!(
+                    "{} overwrites only this namespace; {} namespaces need secure erase or sanitize",
+                    algorithm.name(), namespaces.len()
+                )));
+            }
+            _ => self.overwrite(algorithm, DeviceArea::User, self.drive_info.size)?,
         }
 
         println!("✅ NVMe wipe completed successfully");
@@ -238,48 +340,58 @@ impl WipeOrchestrator {
     }
 
     /// Wipe SSD drive
-    async fn wipe_ssd_drive(&self) -> DriveResult<()> {
+    async fn wipe_ssd_drive(&self, algorithm: &Algorithm) -> DriveResult<()> {
         println!("💿 Detected SSD - using TRIM-aware wipe strategy");
 
-        // Use generic overwrite + TRIM for now
-        println!("⚠️  Using simplified SSD wipe (full integration pending)");
-
-        // Perform basic overwrite
-        self.write_pattern_to_region(0, self.drive_info.size)
-            .map_err(|e| DriveError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, format!("SSD wipe failed: {}", e))
-            ))?;
-
-        // Then TRIM if supported
-        if self.drive_info.capabilities.trim_support {
-            let _ = std::process::Command::new("blkdiscard")
-                .arg(&self.device_path)
-                .output();
+        if !matches!(algorithm, Algorithm::SecureErase | Algorithm::CryptoErase | Algorithm::Sanitize) {
+            require_overwrite(algorithm)?;
         }
 
+        self.with_ata_preparation(|size| match algorithm {
+            Algorithm::SecureErase => self.hardware_secure_erase(),
+            Algorithm::CryptoErase => self.sed_crypto_erase(),
+            Algorithm::Sanitize => {
+                let option = self.sanitize_option(&[SanitizeOption::BlockErase, SanitizeOption::CryptoScramble])?;
+                self.backend().sanitize(option)
+            }
+            _ => {
+                self.overwrite(algorithm, DeviceArea::User, size)?;
+
+                // Then TRIM if supported
+                if self.drive_info.capabilities.trim_support {
+                    if let Err(e) = self.backend().discard(0, size) {
+                        println!("⚠️  TRIM after overwrite failed: {}", e);
+                    }
+                }
+                Ok(())
+            }
+        })?;
+
         println!("✅ SSD wipe completed successfully");
         Ok(())
     }
 
     /// Wipe HDD drive
-    async fn wipe_hdd_drive(&self) -> DriveResult<()> {
+    async fn wipe_hdd_drive(&self, algorithm: &Algorithm) -> DriveResult<()> {
         println!("💽 Detected HDD - using traditional overwrite strategy");
 
-        // Use generic overwrite for now
-        println!("⚠️  Using simplified HDD wipe (full integration pending)");
+        if !matches!(algorithm, Algorithm::SecureErase | Algorithm::CryptoErase) {
+            require_overwrite(algorithm)?;
+        }
 
-        self.write_pattern_to_region(0, self.drive_info.size)
-            .map_err(|e| DriveError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, format!("HDD wipe failed: {}", e))
-            ))?;
+        self.with_ata_preparation(|size| match algorithm {
+            Algorithm::SecureErase => self.hardware_secure_erase(),
+            Algorithm::CryptoErase => self.sed_crypto_erase(),
+            _ => self.overwrite(algorithm, DeviceArea::User, size),
+        })?;
 
         println!("✅ HDD wipe completed successfully");
         Ok(())
     }
 
     /// Wipe RAID array member
-    async fn wipe_raid_member(&self) -> DriveResult<()> {
-        println!("🔗 Detected RAID array member - using OptimizedIO");
+    async fn wipe_raid_member(&self, algorithm: &Algorithm) -> DriveResult<()> {
+        println!("🔗 Detected RAID array member");
         println!("⚠️  Warning: Wiping individual RAID members will destroy the array!");
 
         // Check if user confirmed
@@ -289,118 +401,148 @@ impl WipeOrchestrator {
             ));
         }
 
-        // Import raid module
-        use crate::drives::types::raid::RAIDArray;
-
-        // Get RAID configuration
-        let raid = RAIDArray::get_configuration(&self.device_path)
-            .map_err(|e| DriveError::HardwareCommandFailed(format!("RAID detection failed: {}", e)))?;
-
-        println!("RAID Type: {:?}", raid.raid_type);
-        println!("Members: {}", raid.member_drives.len());
-        println!();
-
-        // Use integrated wipe with OptimizedIO engine
-        // This will wipe all members and metadata
-        let wipe_metadata = true;
-        wipe_raid_array_integrated(&raid, wipe_metadata)
-            .map_err(|e| DriveError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, format!("RAID wipe failed: {}", e))
-            ))?;
+        // md, DDF and vendor metadata live at the start or end of the member,
+        // so overwriting all of it removes them too
+        self.overwrite(algorithm, DeviceArea::User, self.drive_info.size)?;
 
         println!("✅ RAID member wipe completed successfully");
         Ok(())
     }
 
-    /// Convert WipeConfig algorithm to WipeAlgorithm for integrated wipe functions
-    fn convert_to_wipe_algorithm(&self) -> WipeAlgorithm {
-        match self.config.algorithm {
-            Algorithm::Zero => WipeAlgorithm::Zeros,
-            Algorithm::Random => WipeAlgorithm::Random,
-            Algorithm::DoD5220 => WipeAlgorithm::Random, // DoD uses multiple passes with random
-            Algorithm::Gutmann => WipeAlgorithm::Random,  // Gutmann uses complex patterns
-            _ => WipeAlgorithm::Random, // Default to random for security
-        }
+    fn backend(&self) -> MutexGuard<'_, Box<dyn DeviceBackend>> {
+        lock(&self.backend)
     }
 
-    /// Write pattern to a specific region (used by SMR and other specialized wipers)
-    fn write_pattern_to_region(&self, offset: u64, size: u64) -> Result<()> {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .open(&self.device_path)?;
+    /// Run `wipe` on an ATA drive with freeze mitigation and HPA/DCO handling
+    /// around it. `wipe` gets the number of bytes to cover.
+    fn with_ata_preparation<F>(&self, wipe: F) -> DriveResult<()>
+    where
+        F: FnOnce(u64) -> DriveResult<()>,
+    {
+        if self.config.freeze_mitigation && self.drive_info.capabilities.is_frozen {
+            println!("Drive is frozen, attempting mitigation...");
+            match self.backend().unfreeze() {
+                Ok(()) => println!("✓ Drive unfrozen successfully"),
+                Err(e) => println!("⚠️  Failed to unfreeze: {}", e),
+            }
+        }
+
+        let mut original_hpa = None;
+        match self.config.handle_hpa_dco {
+            HPADCOHandling::Ignore => {}
+            HPADCOHandling::Detect => {
+                let (hpa, dco) = self.backend().hidden_areas()?;
+                if hpa.is_some() || dco.is_some() {
+                    println!("⚠️  Hidden areas detected but not removed");
+                }
+            }
+            HPADCOHandling::TemporaryRemove => {
+                let (hpa, _) = self.backend().hidden_areas()?;
+                if let Some(hpa) = hpa {
+                    println!("Temporarily removing HPA...");
+                    self.backend().remove_hpa()?;
+                    original_hpa = Some(hpa.current_max_sectors);
+                }
+            }
+            HPADCOHandling::PermanentRemove => {
+                let (hpa, dco) = self.backend().hidden_areas()?;
+                if dco.is_some() {
+                    println!("Removing DCO...");
+                    self.backend().remove_dco()?;
+                }
+                if hpa.is_some() || dco.is_some() {
+                    println!("Permanently removing HPA...");
+                    self.backend().remove_hpa()?;
+                }
+            }
+        }
 
-        file.seek(SeekFrom::Start(offset))?;
+        // Hidden areas that were removed are now part of the wipe
+        let size = self.backend().identify()?.size;
+        let result = wipe(size);
 
-        // Generate pattern based on algorithm
-        let pattern = self.generate_pattern(size as usize)?;
-        file.write_all(&pattern)?;
-        file.sync_all()?;
+        // Restore the HPA even when the wipe failed
+        if let Some(max_sectors) = original_hpa {
+            println!("Restoring original HPA configuration...");
+            self.backend().restore_hpa(max_sectors)?;
+        }
 
-        Ok(())
+        result
     }
 
-    /// Create basic drive info for now (TODO: integrate with full detection)
-    fn create_basic_drive_info(device_path: &str) -> Result<DriveInfo> {
-        // Simple detection based on device path
-        let drive_type = if device_path.contains("nvme") {
-            DriveType::NVMe
-        } else if device_path.contains("mmcblk") {
-            DriveType::EMMC
-        } else {
-            DriveType::HDD  // Default
-        };
+    fn hardware_secure_erase(&self) -> DriveResult<()> {
+        println!("Running hardware secure erase...");
+        self.backend().secure_erase(self.drive_info.capabilities.enhanced_erase)
+    }
 
-        Ok(DriveInfo {
-            device_path: device_path.to_string(),
-            model: "Unknown".to_string(),
-            serial: "Unknown".to_string(),
-            size: 1024 * 1024 * 1024 * 100,  // Assume 100GB for now
-            drive_type,
-            encryption_status: crate::EncryptionStatus::None,
-            capabilities: Default::default(),
-            health_status: None,
-            temperature_celsius: None,
-        })
+    fn sed_crypto_erase(&self) -> DriveResult<()> {
+        println!("Running SED cryptographic erase...");
+        self.backend().crypto_erase()
     }
 
-    /// Generate wipe pattern based on configured algorithm
-    fn generate_pattern(&self, size: usize) -> Result<Vec<u8>> {
-        use crate::crypto::secure_rng::SecureRNG;
+    /// First of `preferred` the drive supports
+    fn sanitize_option(&self, preferred: &[SanitizeOption]) -> DriveResult<SanitizeOption> {
+        preferred.iter()
+            .find(|option| self.drive_info.capabilities.sanitize_options.contains(option))
+            .cloned()
+            .ok_or_else(|| DriveError::Unsupported("Sanitize not supported".to_string()))
+    }
 
-        match self.config.algorithm {
-            Algorithm::Random => {
-                let mut data = vec![0u8; size];
-                let mut rng = SecureRNG::new()?;
-                rng.fill_bytes(&mut data)?;
-                Ok(data)
-            }
-            Algorithm::Zero => {
-                Ok(vec![0u8; size])
-            }
-            Algorithm::DoD5220 => {
-                // DoD uses multiple passes, for now just use first pass pattern
-                let mut data = vec![0u8; size];
-                let mut rng = SecureRNG::new()?;
-                rng.fill_bytes(&mut data)?;
-                Ok(data)
-            }
-            Algorithm::Gutmann => {
-                // Gutmann uses 35 passes, this is simplified
-                let mut data = vec![0u8; size];
-                let mut rng = SecureRNG::new()?;
-                rng.fill_bytes(&mut data)?;
-                Ok(data)
-            }
-            _ => {
-                let mut data = vec![0u8; size];
-                let mut rng = SecureRNG::new()?;
-                rng.fill_bytes(&mut data)?;
-                Ok(data)
+    /// Overwrite the first `size` bytes of `area` with `algorithm`, on the
+    /// engine every other overwrite runs on. The user area is checkpointed
+    /// for `--resume` like any other overwrite; sequential zones cannot be
+    /// rewritten from the middle and boot partitions are small, so those
+    /// start over instead.
+    fn overwrite(&self, algorithm: &Algorithm, area: DeviceArea, size: u64) -> DriveResult<()> {
+        require_overwrite(algorithm)?;
+        let backend = match self.backend().overwrite_backend(area)? {
+            Some(backend) => Some(BackendIO::new(backend, area).map_err(anyhow::Error::from)?),
+            None => None,
+        };
+        let keystream = lock(&self.keystream);
+        let options = OverwriteOptions {
+            keystream: keystream.as_ref(),
+            // Boot partitions cannot be read back through the backend
+            verification: match area {
+                DeviceArea::User => self.config.pass_verification,
+                DeviceArea::HardwarePartition(_) => Default::default(),
+            },
+            extent: None,
+            context: Some(&self.context),
+            quiet_hours: self.config.quiet_hours,
+            max_bytes_per_sec: self.config.max_bytes_per_sec,
+            max_iops: self.config.max_iops,
+            backend: backend.as_ref(),
+        };
+
+        let resumable = area == DeviceArea::User && self.drive_info.drive_type != DriveType::SMR;
+        let mut checkpoint = if resumable {
+            Checkpointer::open_in(&self.checkpoint_dir, &self.drive_info, &algorithm.name(), self.config.resume)?
+        } else {
+            Checkpointer::disabled()
+        };
+        let result = algorithms::overwrite(algorithm, &self.device_path, size, self.drive_info.drive_type.clone(),
+                                           &options, &mut checkpoint);
+
+        let skipped = checkpoint.bad_sectors();
+        match area {
+            DeviceArea::User => lock(&self.unwritable_sectors).extend(skipped),
+            // Only device LBAs can be reported, so skipped partition sectors fail the wipe
+            DeviceArea::HardwarePartition(number) if !skipped.is_empty() && result.is_ok() => {
+                return Err(DriveError::HardwareCommandFailed(format!(
+                    "{} sectors of boot partition {} could not be written", skipped.len(), number
+                )));
             }
+            DeviceArea::HardwarePartition(_) => {}
         }
+        result.map_err(DriveError::from)
     }
 }
 
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 /// Convenience function for simple wipe operations
 pub async fn wipe_drive(device_path: &str, config: WipeConfig) -> DriveResult<()> {
     let orchestrator = WipeOrchestrator::new(device_path.to_string(), config)
@@ -412,6 +554,28 @@ pub async fn wipe_drive(device_path: &str, config: WipeConfig) -> DriveResult<()
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::drives::backend::{Fault, SimulatedCommand, SimulatedDevice, SimulatedProfile};
+    use crate::io::RateLimiter;
+    use crate::{HPADCOHandling, SEDType};
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn config(algorithm: Algorithm) -> WipeConfig {
+        WipeConfig { algorithm, handle_hpa_dco: HPADCOHandling::Ignore, ..Default::default() }
+    }
+
+    fn orchestrator_for(sim: &SimulatedDevice, checkpoints: &TempDir, config: WipeConfig) -> WipeOrchestrator {
+        let mut orchestrator = WipeOrchestrator::with_backend(Box::new(sim.clone()), config).unwrap();
+        orchestrator.set_checkpoint_dir(checkpoints.path());
+        orchestrator
+    }
+
+    fn run(sim: &SimulatedDevice, config: WipeConfig) -> DriveResult<()> {
+        let checkpoints = TempDir::new().unwrap();
+        futures::executor::block_on(orchestrator_for(sim, &checkpoints, config).execute())
+    }
 
     #[test]
     fn test_orchestrator_creation() {
@@ -424,30 +588,425 @@ mod tests {
     }
 
     #[test]
-    fn test_pattern_generation() {
-        let config = WipeConfig {
-            algorithm: Algorithm::Zero,
+    fn test_context_cancels_and_observes_overwrite() {
+        use crate::events::{WipeEvent, WipeObserver};
+        use std::sync::Arc;
+
+        struct Collect(Mutex<Vec<WipeEvent>>);
+        impl WipeObserver for Collect {
+            fn on_event(&self, event: &WipeEvent) {
+                self.0.lock().unwrap().push(event.clone());
+            }
+        }
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        let observer = Arc::new(Collect(Mutex::new(Vec::new())));
+        let checkpoints = TempDir::new().unwrap();
+        let mut orchestrator = orchestrator_for(&sim, &checkpoints, config(Algorithm::Zero));
+        orchestrator.set_context(OperationContext { observer: Some(observer.clone()), ..Default::default() });
+        let device = orchestrator.drive_info().device_path.clone();
+        futures::executor::block_on(orchestrator.execute()).unwrap();
+
+        let events = observer.0.lock().unwrap();
+        assert!(events.iter().any(|e| matches!(e,
+            WipeEvent::Progress { device: Some(d), bytes_processed: Some(b), .. }
+                if *d == device && *b == 4 * MIB)));
+
+        // A cancelled context stops before anything is written
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        let context = OperationContext::default();
+        context.cancel.cancel();
+        let checkpoints = TempDir::new().unwrap();
+        let mut orchestrator = orchestrator_for(&sim, &checkpoints, config(Algorithm::Zero));
+        orchestrator.set_context(context);
+        let err = futures::executor::block_on(orchestrator.execute()).unwrap_err();
+        assert!(matches!(err, DriveError::Interrupted), "{}", err);
+        assert_eq!(sim.bytes_written(), 0);
+    }
+
+    #[test]
+    fn test_paused_context_holds_overwrite_until_resumed() {
+        use crate::events::{WipeEvent, WipeObserver};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        struct Collect(Mutex<Vec<WipeEvent>>);
+        impl WipeObserver for Collect {
+            fn on_event(&self, event: &WipeEvent) {
+                self.0.lock().unwrap().push(event.clone());
+            }
+        }
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        let observer = Arc::new(Collect(Mutex::new(Vec::new())));
+        let context = OperationContext { observer: Some(observer.clone()), ..Default::default() };
+        context.pause.pause();
+        let resumer = std::thread::spawn({
+            let pause = context.pause.clone();
+            move || {
+                std::thread::sleep(Duration::from_millis(300));
+                pause.resume();
+            }
+        });
+
+        let checkpoints = TempDir::new().unwrap();
+        let mut orchestrator = orchestrator_for(&sim, &checkpoints, config(Algorithm::Zero));
+        orchestrator.set_context(context);
+        let device = orchestrator.drive_info().device_path.clone();
+        let start = Instant::now();
+        futures::executor::block_on(orchestrator.execute()).unwrap();
+        resumer.join().unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(300), "{:?}", start.elapsed());
+        assert_eq!(sim.bytes_written(), 4 * MIB);
+        let events = observer.0.lock().unwrap();
+        let paused = events.iter().position(|e| matches!(e, WipeEvent::Paused { device: d } if *d == device));
+        let resumed = events.iter().position(|e| matches!(e, WipeEvent::Resumed { device: d } if *d == device));
+        assert!(paused.is_some() && paused < resumed, "{:?}", events);
+    }
+
+    #[test]
+    fn test_rate_limits_apply_per_drive_and_across_drives() {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        // 4 MiB at 8 MiB/s, the first quarter second of which is burst
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        let start = Instant::now();
+        run(&sim, WipeConfig { max_bytes_per_sec: Some(8 * MIB), ..config(Algorithm::Zero) }).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(240), "{:?}", start.elapsed());
+
+        // Two 2 MiB drives under one shared 8 MiB/s limit
+        let root = OperationContext {
+            rate_limiter: Some(Arc::new(RateLimiter::new(Some(8 * MIB), None))),
             ..Default::default()
         };
+        let start = Instant::now();
+        for _ in 0..2 {
+            let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(2 * MIB)).unwrap();
+            let checkpoints = TempDir::new().unwrap();
+            let mut orchestrator = orchestrator_for(&sim, &checkpoints, config(Algorithm::Zero));
+            orchestrator.set_context(root.child());
+            futures::executor::block_on(orchestrator.execute()).unwrap();
+            assert_eq!(sim.bytes_written(), 2 * MIB);
+        }
+        assert!(start.elapsed() >= Duration::from_millis(240), "{:?}", start.elapsed());
+    }
 
-        let orchestrator = WipeOrchestrator {
-            device_path: "/dev/null".to_string(),
-            config: config.clone(),
-            drive_info: DriveInfo {
-                device_path: "/dev/null".to_string(),
-                model: "Test".to_string(),
-                serial: "TEST123".to_string(),
-                size: 1024 * 1024 * 1024,
-                drive_type: DriveType::HDD,
-                encryption_status: crate::EncryptionStatus::None,
-                capabilities: Default::default(),
-                health_status: None,
-                temperature_celsius: None,
-            },
+    #[test]
+    fn test_overheating_drive_is_throttled() {
+        use std::time::Duration;
+
+        // 4 MiB fits in the burst of a 16 MiB/s ceiling, but not of half of it
+        let limited = WipeConfig { max_bytes_per_sec: Some(16 * MIB), ..config(Algorithm::Zero) };
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        let start = Instant::now();
+        run(&sim, limited.clone()).unwrap();
+        assert!(start.elapsed() < Duration::from_millis(240), "{:?}", start.elapsed());
+        assert!(sim.commands().contains(&SimulatedCommand::Smart));
+
+        // 6 degrees over the HDD threshold halves the rate
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        sim.inject(Fault::Overheating { celsius: 61 });
+        let start = Instant::now();
+        run(&sim, limited).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(240), "{:?}", start.elapsed());
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_quiet_hours_cap_overwrites() {
+        use crate::control::QuietHours;
+        use std::time::Duration;
+
+        let now = chrono::Local::now().time();
+        let hour = chrono::Duration::hours(1);
+        let active = QuietHours { start: now - hour, end: now + hour, max_mb_per_sec: 8 };
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        let start = Instant::now();
+        run(&sim, WipeConfig { quiet_hours: Some(active), ..config(Algorithm::Zero) }).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(240), "{:?}", start.elapsed());
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+        let inactive = QuietHours { start: now + hour, end: now + hour * 2, ..active };
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        let start = Instant::now();
+        run(&sim, WipeConfig { quiet_hours: Some(inactive), ..config(Algorithm::Zero) }).unwrap();
+        assert!(start.elapsed() < Duration::from_millis(240), "{:?}", start.elapsed());
+    }
+
+    #[test]
+    fn test_hdd_overwrite_leaves_no_residual_data() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        run(&sim, config(Algorithm::Zero)).unwrap();
+
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+        assert_eq!(sim.bytes_written(), 4 * MIB);
+    }
+
+    #[test]
+    fn test_hpa_removed_for_wipe_and_restored() {
+        let profile = SimulatedProfile { hpa_sectors: 2048, ..SimulatedProfile::hdd(4 * MIB) };
+
+        // Detect only warns, so the hidden area keeps its data
+        let sim = SimulatedDevice::temporary(profile.clone()).unwrap();
+        run(&sim, WipeConfig { handle_hpa_dco: HPADCOHandling::Detect, ..config(Algorithm::Zero) }).unwrap();
+        assert_eq!(sim.residual_bytes().unwrap(), 2048 * 512);
+
+        let sim = SimulatedDevice::temporary(profile).unwrap();
+        run(&sim, WipeConfig { handle_hpa_dco: HPADCOHandling::TemporaryRemove, ..config(Algorithm::Zero) }).unwrap();
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+        assert_eq!(sim.max_sectors(), 4 * MIB / 512);
+
+        let commands = sim.commands();
+        let removed = commands.iter().position(|c| *c == SimulatedCommand::RemoveHpa).unwrap();
+        let restored = commands.iter().position(|c| *c == SimulatedCommand::RestoreHpa).unwrap();
+        assert!(removed < restored);
+    }
+
+    #[test]
+    fn test_dco_removed_permanently() {
+        let profile = SimulatedProfile { hpa_sectors: 1024, dco_sectors: 1024, ..SimulatedProfile::hdd(4 * MIB) };
+        let sim = SimulatedDevice::temporary(profile).unwrap();
+
+        run(&sim, WipeConfig { handle_hpa_dco: HPADCOHandling::PermanentRemove, ..config(Algorithm::Zero) }).unwrap();
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+        assert_eq!(sim.max_sectors(), 4 * MIB / 512 + 2048);
+    }
+
+    #[test]
+    fn test_frozen_ssd_secure_erase() {
+        let frozen = SimulatedProfile { frozen: true, ..SimulatedProfile::sata_ssd(4 * MIB) };
+
+        let sim = SimulatedDevice::temporary(frozen.clone()).unwrap();
+        let err = run(&sim, WipeConfig { freeze_mitigation: false, ..config(Algorithm::SecureErase) }).unwrap_err();
+        assert!(matches!(err, DriveError::DriveFrozen(_)), "{}", err);
+        assert_eq!(sim.residual_bytes().unwrap(), 4 * MIB);
+
+        let sim = SimulatedDevice::temporary(frozen.clone()).unwrap();
+        run(&sim, config(Algorithm::SecureErase)).unwrap();
+        assert!(!sim.is_frozen());
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+        // A freeze that cannot be lifted still blocks the erase
+        let sim = SimulatedDevice::temporary(SimulatedProfile { unfreezable: false, ..frozen }).unwrap();
+        let err = run(&sim, config(Algorithm::SecureErase)).unwrap_err();
+        assert!(matches!(err, DriveError::DriveFrozen(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_ssd_overwrite_then_trim() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::sata_ssd(4 * MIB)).unwrap();
+        run(&sim, config(Algorithm::Zero)).unwrap();
+
+        assert!(sim.commands().contains(&SimulatedCommand::Discard));
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_ssd_sanitize() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::sata_ssd(4 * MIB)).unwrap();
+        run(&sim, config(Algorithm::Sanitize)).unwrap();
+
+        assert!(sim.commands().contains(&SimulatedCommand::Sanitize));
+        assert_eq!(sim.bytes_written(), 0);
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_sed_crypto_erase() {
+        let profile = SimulatedProfile { sed: Some(SEDType::OPAL20), ..SimulatedProfile::sata_ssd(4 * MIB) };
+        let sim = SimulatedDevice::temporary(profile).unwrap();
+        run(&sim, config(Algorithm::CryptoErase)).unwrap();
+
+        assert!(sim.commands().contains(&SimulatedCommand::CryptoErase));
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+        // Without an SED there is nothing to erase cryptographically
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        assert!(matches!(run(&sim, config(Algorithm::CryptoErase)), Err(DriveError::CryptoEraseFailed(_))));
+    }
+
+    #[test]
+    fn test_nvme_namespaces_formatted() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::nvme(4 * MIB, 2)).unwrap();
+        run(&sim, config(Algorithm::SecureErase)).unwrap();
+
+        let formats = sim.commands().iter().filter(|c| **c == SimulatedCommand::FormatNamespace).count();
+        assert_eq!(formats, 2);
+        assert!(!sim.commands().contains(&SimulatedCommand::Sanitize));
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+        // An overwrite of this block device would miss the other namespace
+        let sim = SimulatedDevice::temporary(SimulatedProfile::nvme(4 * MIB, 2)).unwrap();
+        assert!(matches!(run(&sim, config(Algorithm::Zero)), Err(DriveError::Unsupported(_))));
+        assert_eq!(sim.bytes_written(), 0);
+    }
+
+    #[test]
+    fn test_nvme_single_namespace_sanitized() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::nvme(4 * MIB, 1)).unwrap();
+        run(&sim, config(Algorithm::Sanitize)).unwrap();
+
+        assert!(sim.commands().contains(&SimulatedCommand::Sanitize));
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::nvme(4 * MIB, 1)).unwrap();
+        run(&sim, config(Algorithm::Zero)).unwrap();
+        assert!(!sim.commands().contains(&SimulatedCommand::Sanitize));
+        assert_eq!(sim.bytes_written(), 4 * MIB);
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_overwrite_methods_run_every_pass() {
+        use crate::algorithms::custom::{PassPattern, PassSpec, PatternScheme};
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        run(&sim, config(Algorithm::DoD5220)).unwrap();
+        assert_eq!(sim.bytes_written(), 3 * 4 * MIB);
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+        let pass = |pattern| PassSpec { pattern, verify: false };
+        let scheme = PatternScheme {
+            name: "mfm".to_string(),
+            description: None,
+            passes: vec![pass(PassPattern::Bytes { bytes: vec![0x92, 0x49, 0x24] }), pass(PassPattern::Complement)],
         };
 
-        let pattern = orchestrator.generate_pattern(1024).unwrap();
-        assert_eq!(pattern.len(), 1024);
-        assert!(pattern.iter().all(|&b| b == 0));
+        // Three-byte patterns must not slip at write boundaries
+        let sim = SimulatedDevice::temporary(SimulatedProfile::smr(4 * MIB, MIB, 1)).unwrap();
+        run(&sim, config(Algorithm::Custom(scheme))).unwrap();
+        assert_eq!(sim.bytes_written(), 2 * 4 * MIB);
+        let image = std::fs::read(sim.image_path()).unwrap();
+        assert!(image.chunks(3).all(|chunk| chunk == &[0x6D, 0xB6, 0xDB][..chunk.len()]));
+    }
+
+    #[test]
+    fn test_keystream_passes_are_read_back_through_the_backend() {
+        use crate::{PassVerification, RandomMode, VerifyCoverage, VerifyPasses};
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::ufs(4 * MIB)).unwrap();
+        let checkpoints = TempDir::new().unwrap();
+        let orchestrator = orchestrator_for(&sim, &checkpoints, WipeConfig {
+            random_mode: RandomMode::AesCtr,
+            pass_verification: PassVerification { passes: VerifyPasses::EveryPass, coverage: VerifyCoverage::Full },
+            ..config(Algorithm::Random)
+        });
+        futures::executor::block_on(orchestrator.execute()).unwrap();
+
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+        assert!(orchestrator.keystream_commitment().is_some());
+        assert!(orchestrator.unwritable_sectors().is_empty());
+    }
+
+    #[test]
+    fn test_methods_the_drive_cannot_run_are_refused() {
+        for (profile, algorithm) in [
+            (SimulatedProfile::smr(4 * MIB, MIB, 1), Algorithm::SecureErase),
+            (SimulatedProfile::hybrid(4 * MIB, MIB), Algorithm::Sanitize),
+            (SimulatedProfile::ufs(4 * MIB), Algorithm::SecureErase),
+            (SimulatedProfile::hdd(4 * MIB), Algorithm::TrimOnly),
+        ] {
+            let sim = SimulatedDevice::temporary(profile).unwrap();
+            let result = run(&sim, config(algorithm.clone()));
+            assert!(matches!(result, Err(DriveError::Unsupported(_))), "{:?}: {:?}", algorithm, result);
+            assert_eq!(sim.bytes_written(), 0);
+            assert!(sim.commands().iter().all(|c| *c == SimulatedCommand::Identify || *c == SimulatedCommand::Zones));
+            assert_eq!(sim.residual_bytes().unwrap(), 4 * MIB + if algorithm == Algorithm::Sanitize { MIB } else { 0 });
+        }
+    }
+
+    #[test]
+    fn test_smr_zone_aware_wipe() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::smr(8 * MIB, MIB, 2)).unwrap();
+        run(&sim, config(Algorithm::Zero)).unwrap();
+
+        let resets = sim.commands().iter().filter(|c| **c == SimulatedCommand::ResetZone).count();
+        assert_eq!(resets, 6);
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_optane_overwrite_and_instant_secure_erase() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::optane(4 * MIB)).unwrap();
+        run(&sim, config(Algorithm::Zero)).unwrap();
+        assert_eq!(sim.bytes_written(), 4 * MIB);
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::optane(4 * MIB)).unwrap();
+        run(&sim, config(Algorithm::SecureErase)).unwrap();
+        assert!(sim.commands().contains(&SimulatedCommand::SecureErase));
+        assert_eq!(sim.bytes_written(), 0);
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_hybrid_wipe_flushes_nand_cache() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hybrid(4 * MIB, MIB)).unwrap();
+        run(&sim, config(Algorithm::Zero)).unwrap();
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+        assert!(sim.write_cache_enabled());
+
+        // With the cache left on, its copies of old data survive
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hybrid(4 * MIB, MIB)).unwrap();
+        sim.inject(Fault::Timeout(SimulatedCommand::SetWriteCache));
+        run(&sim, config(Algorithm::Zero)).unwrap();
+        assert_eq!(sim.residual_bytes().unwrap(), MIB);
+    }
+
+    #[test]
+    fn test_emmc_wipe_covers_boot_partitions() {
+        for algorithm in [Algorithm::Zero, Algorithm::Sanitize, Algorithm::SecureErase] {
+            let sim = SimulatedDevice::temporary(SimulatedProfile::emmc(4 * MIB, 512 * 1024)).unwrap();
+            run(&sim, config(algorithm.clone())).unwrap();
+            assert!(sim.commands().contains(&SimulatedCommand::HardwarePartitions));
+            assert_eq!(sim.residual_bytes().unwrap(), 0, "{:?}", algorithm);
+        }
+    }
+
+    #[test]
+    fn test_ufs_purge_and_overwrite() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::ufs(4 * MIB)).unwrap();
+        run(&sim, config(Algorithm::Sanitize)).unwrap();
+        assert!(sim.commands().contains(&SimulatedCommand::Sanitize));
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::ufs(4 * MIB)).unwrap();
+        run(&sim, config(Algorithm::Zero)).unwrap();
+        assert!(sim.commands().contains(&SimulatedCommand::Discard));
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_raid_member_requires_force() {
+        let sim = SimulatedDevice::temporary(SimulatedProfile::raid_member(4 * MIB)).unwrap();
+        assert!(matches!(run(&sim, config(Algorithm::Zero)), Err(DriveError::Unsupported(_))));
+        assert_eq!(sim.bytes_written(), 0);
+
+        run(&sim, WipeConfig { unlock_encrypted: true, ..config(Algorithm::Zero) }).unwrap();
+        assert_eq!(sim.residual_bytes().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_faults_surface_as_errors() {
+        // Bad sectors are skipped and reported, not fatal
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        sim.inject(Fault::BadSector(4000));
+        let checkpoints = TempDir::new().unwrap();
+        let orchestrator = orchestrator_for(&sim, &checkpoints, config(Algorithm::Zero));
+        futures::executor::block_on(orchestrator.execute()).unwrap();
+        assert_eq!(orchestrator.unwritable_sectors(), vec![4000]);
+        assert_eq!(sim.residual_bytes().unwrap(), 512);
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::hdd(4 * MIB)).unwrap();
+        sim.inject(Fault::SurpriseRemoval { after_bytes: MIB + 100 });
+        assert!(matches!(run(&sim, config(Algorithm::Zero)), Err(DriveError::NotFound(_))));
+        assert_eq!(sim.bytes_written(), MIB + 100);
+
+        let sim = SimulatedDevice::temporary(SimulatedProfile::sata_ssd(4 * MIB)).unwrap();
+        sim.inject(Fault::Timeout(SimulatedCommand::SecureErase));
+        assert!(matches!(run(&sim, config(Algorithm::SecureErase)), Err(DriveError::Timeout(_))));
     }
 }